@@ -0,0 +1,922 @@
+// Sent when the user has not set Accept-Encoding, these are what BodyDecoder handles
+pub const DEFAULT_ACCEPT_ENCODING: &str = "gzip, deflate, br, zstd";
+
+// Incremental decoder for a Content-Encoding, fed one network chunk at a time.
+// Anything we don't know (compress, ...) is passed through untouched.
+pub enum BodyDecoder {
+    Identity,
+    Gzip(flate2::write::GzDecoder<Vec<u8>>),
+    Brotli(Box<brotli::DecompressorWriter<Vec<u8>>>),
+    Zstd(zstd::stream::write::Decoder<'static, Vec<u8>>),
+    // `deflate` should be zlib wrapped, but some servers send raw deflate. The
+    // first two bytes decide, they're held here until both have arrived
+    DeflatePending(Vec<u8>),
+    Deflate(flate2::write::ZlibDecoder<Vec<u8>>),
+    RawDeflate(flate2::write::DeflateDecoder<Vec<u8>>),
+}
+
+impl BodyDecoder {
+    pub fn new(encoding: &str) -> Self {
+        match encoding {
+            "gzip" | "x-gzip" => BodyDecoder::Gzip(flate2::write::GzDecoder::new(Vec::new())),
+            "deflate" => BodyDecoder::DeflatePending(Vec::new()),
+            "br" => {
+                BodyDecoder::Brotli(Box::new(brotli::DecompressorWriter::new(Vec::new(), 4096)))
+            }
+            // Only fails when zstd can't allocate its context
+            "zstd" => zstd::stream::write::Decoder::new(Vec::new())
+                .map_or(BodyDecoder::Identity, BodyDecoder::Zstd),
+            _ => BodyDecoder::Identity,
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> std::io::Result<Vec<u8>> {
+        use std::io::Write;
+        match self {
+            BodyDecoder::Identity => Ok(chunk.to_vec()),
+            BodyDecoder::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            BodyDecoder::Brotli(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            // zstd keeps its output buffered until flushed
+            BodyDecoder::Zstd(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.flush()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            BodyDecoder::DeflatePending(head) => {
+                head.extend_from_slice(chunk);
+                if head.len() < 2 {
+                    return Ok(Vec::new());
+                }
+                let head = std::mem::take(head);
+                *self = Self::deflate_for(&head);
+                self.feed(&head)
+            }
+            BodyDecoder::Deflate(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            BodyDecoder::RawDeflate(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+        }
+    }
+
+    pub fn finish(self) -> std::io::Result<Vec<u8>> {
+        match self {
+            BodyDecoder::Identity => Ok(Vec::new()),
+            BodyDecoder::Gzip(decoder) => decoder.finish(),
+            BodyDecoder::Brotli(mut decoder) => {
+                decoder.close()?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            BodyDecoder::Zstd(mut decoder) => {
+                use std::io::Write;
+                decoder.flush()?;
+                Ok(decoder.into_inner())
+            }
+            // A body too short for a zlib header
+            BodyDecoder::DeflatePending(head) => {
+                let mut decoder = Self::deflate_for(&head);
+                let mut out = decoder.feed(&head)?;
+                out.extend(decoder.finish()?);
+                Ok(out)
+            }
+            BodyDecoder::Deflate(decoder) => decoder.finish(),
+            BodyDecoder::RawDeflate(decoder) => decoder.finish(),
+        }
+    }
+
+    // A zlib header is a deflate method nibble and a checksum making the pair a
+    // multiple of 31 (RFC 1950), anything else is taken as raw deflate
+    fn deflate_for(head: &[u8]) -> Self {
+        match head {
+            [cmf, flg, ..]
+                if cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0 =>
+            {
+                BodyDecoder::Deflate(flate2::write::ZlibDecoder::new(Vec::new()))
+            }
+            _ => BodyDecoder::RawDeflate(flate2::write::DeflateDecoder::new(Vec::new())),
+        }
+    }
+}
+
+// Content-Encoding of a response, lowercased, empty when there is none
+pub fn content_encoding(headers: &reqwest::header::HeaderMap) -> String {
+    headers
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+// A whole body at once, for responses that are read in full before use
+pub fn decode_body(encoding: &str, bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoder = BodyDecoder::new(encoding);
+    let mut out = decoder.feed(bytes)?;
+    out.extend(decoder.finish()?);
+    Ok(out)
+}
+
+// Sorted by name, values of a repeated header such as Set-Cookie stay in arrival order
+pub fn collect_header_list(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    let mut list: Vec<(String, String)> = headers
+        .iter()
+        .map(|(name, value)| {
+            (
+                name.to_string(),
+                String::from_utf8_lossy(value.as_bytes()).into_owned(),
+            )
+        })
+        .collect();
+    list.sort_by(|a, b| a.0.cmp(&b.0));
+    list
+}
+
+// Status line and headers close to how they came over the wire. hyper lowercases
+// names and groups repeated ones, otherwise HeaderMap keeps them in arrival order
+pub fn format_raw_head(resp: &reqwest::Response) -> String {
+    let status = resp.status();
+    let mut head = format!(
+        "{:?} {} {}",
+        resp.version(),
+        status.as_u16(),
+        status.canonical_reason().unwrap_or("")
+    )
+    .trim_end()
+    .to_string();
+    for (name, value) in resp.headers() {
+        head.push('\n');
+        head.push_str(name.as_str());
+        head.push_str(": ");
+        head.push_str(&String::from_utf8_lossy(value.as_bytes()));
+    }
+    head
+}
+
+pub fn format_header_list(list: &[(String, String)]) -> String {
+    list.iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn describe_send_error(e: reqwest::Error) -> String {
+    if e.is_timeout() {
+        "Request timed out".into()
+    } else {
+        format!("Request failed: {e}")
+    }
+}
+
+// A failed send, and whether trying again could help
+pub struct SendFailure {
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl From<reqwest::Error> for SendFailure {
+    fn from(e: reqwest::Error) -> Self {
+        Self {
+            retryable: e.is_connect() || e.is_timeout(),
+            message: describe_send_error(e),
+        }
+    }
+}
+
+impl From<String> for SendFailure {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            retryable: false,
+        }
+    }
+}
+
+pub async fn send_once(
+    request: reqwest::RequestBuilder,
+    manual_redirects: bool,
+    max_redirects: usize,
+) -> Result<(reqwest::Response, Vec<String>), SendFailure> {
+    if manual_redirects {
+        let (client, built) = request.build_split();
+        send_following_redirects(client, built?, max_redirects).await
+    } else {
+        Ok((request.send().await?, Vec::new()))
+    }
+}
+
+// Follows redirects one hop at a time so every hop can be reported back.
+// Returns the final response plus one "301 http://a → http://b" line per hop.
+async fn send_following_redirects(
+    client: reqwest::Client,
+    request: reqwest::Request,
+    max_redirects: usize,
+) -> Result<(reqwest::Response, Vec<String>), SendFailure> {
+    let mut hops = Vec::new();
+    let mut visited = std::collections::HashSet::new();
+    let mut current = request;
+
+    loop {
+        let url = current.url().clone();
+        if !visited.insert(url.clone()) {
+            return Err(format!("Redirect loop detected at {}", url).into());
+        }
+
+        let method = current.method().clone();
+        let headers = current.headers().clone();
+        let replay = current.try_clone();
+        let resp = client.execute(current).await?;
+
+        let status = resp.status();
+        let location = resp
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok());
+        let Some(location) = location.filter(|_| status.is_redirection()) else {
+            return Ok((resp, hops));
+        };
+        if hops.len() >= max_redirects {
+            return Err(format!("Too many redirects (more than {})", max_redirects).into());
+        }
+
+        let next_url = url
+            .join(location)
+            .map_err(|e| format!("Invalid redirect location `{}`: {}", location, e))?;
+        hops.push(format!("{} {} → {}", status.as_u16(), url, next_url));
+
+        current = match status.as_u16() {
+            // 307 and 308 repeat the same method and body
+            307 | 308 => {
+                let mut next = replay.ok_or_else(|| {
+                    "Cannot repeat a streamed request body for a 307/308 redirect".to_string()
+                })?;
+                if next_url.origin() != url.origin() {
+                    strip_credentials(next.headers_mut());
+                }
+                *next.url_mut() = next_url;
+                next
+            }
+            _ => {
+                let next_method = if method == reqwest::Method::HEAD {
+                    reqwest::Method::HEAD
+                } else {
+                    reqwest::Method::GET
+                };
+                let same_origin = next_url.origin() == url.origin();
+                let mut next = reqwest::Request::new(next_method, next_url);
+                for (name, value) in &headers {
+                    // The body is gone, and credentials stay with the origin they were meant for
+                    let drop = name == reqwest::header::CONTENT_TYPE
+                        || name == reqwest::header::CONTENT_LENGTH
+                        || (!same_origin
+                            && (name == reqwest::header::AUTHORIZATION
+                                || name == reqwest::header::COOKIE));
+                    if !drop {
+                        next.headers_mut().append(name, value.clone());
+                    }
+                }
+                next
+            }
+        };
+    }
+}
+
+// Credentials stay with the origin they were meant for, a request sent on to
+// another one loses them
+pub fn strip_credentials(headers: &mut reqwest::header::HeaderMap) {
+    headers.remove(reqwest::header::AUTHORIZATION);
+    headers.remove(reqwest::header::COOKIE);
+}
+
+// "Name: value" lines into a header map. Blank lines, `#` comments and lines that
+// aren't a valid header are skipped, the raw headers editor flags those itself
+pub fn parse_headers(raw: &str) -> reqwest::header::HeaderMap {
+    raw.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let (key, value) = line.split_once(':')?;
+            let name = reqwest::header::HeaderName::from_bytes(key.trim().as_bytes()).ok()?;
+            let value = reqwest::header::HeaderValue::from_str(value.trim()).ok()?;
+            Some((name, value))
+        })
+        .collect()
+}
+
+// Adds the headers of `extra` that `headers` doesn't set at all, every value of a
+// repeated one. A name already present keeps only its own values
+pub fn merge_missing(headers: &mut reqwest::header::HeaderMap, extra: &reqwest::header::HeaderMap) {
+    for name in extra.keys() {
+        if !headers.contains_key(name) {
+            for value in extra.get_all(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+    }
+}
+
+// Bodies of these types are kept as bytes and shown as an image, player or hex dump
+const BINARY_CONTENT_TYPES: &[&str] = &[
+    "image/",
+    "audio/",
+    "video/",
+    "font/",
+    "application/pdf",
+    "application/octet-stream",
+    "application/zip",
+    "application/gzip",
+    "application/x-gzip",
+    "application/x-tar",
+    "application/x-7z-compressed",
+    "application/x-rar-compressed",
+    "application/x-bzip2",
+    "application/wasm",
+    "application/msword",
+    "application/vnd.ms-",
+    "application/vnd.openxmlformats-",
+    "application/vnd.oasis.opendocument",
+    "application/x-protobuf",
+    "application/protobuf",
+    "application/msgpack",
+    "application/x-msgpack",
+    "application/cbor",
+];
+
+// Always text, streamed into the body view as it arrives
+const TEXT_CONTENT_TYPES: &[&str] = &[
+    "text/",
+    "application/json",
+    "application/xml",
+    "application/javascript",
+    "application/ecmascript",
+    "application/x-www-form-urlencoded",
+    "application/graphql",
+    "application/x-ndjson",
+    "application/yaml",
+    "application/x-yaml",
+    "application/toml",
+];
+
+// How much of an unknown body is looked at to guess whether it's text
+const SNIFF_LEN: usize = 8 * 1024;
+
+pub fn is_binary_content_type(content_type: &str) -> bool {
+    let content_type = content_type.trim().to_ascii_lowercase();
+    BINARY_CONTENT_TYPES
+        .iter()
+        .any(|prefix| content_type.starts_with(prefix))
+}
+
+// `+json` and `+xml` cover vendor types like application/problem+json, and a
+// charset parameter only makes sense on text
+pub fn is_text_content_type(content_type: &str) -> bool {
+    let content_type = content_type.trim().to_ascii_lowercase();
+    let essence = content_type.split(';').next().unwrap_or("").trim();
+    TEXT_CONTENT_TYPES
+        .iter()
+        .any(|prefix| essence.starts_with(prefix))
+        || essence.ends_with("+json")
+        || essence.ends_with("+xml")
+        || content_type.contains("charset=")
+}
+
+// Known types decide on their own, anything else (or no Content-Type at all) is
+// sniffed: a NUL, invalid UTF-8 or lots of control characters mean binary
+pub fn detect_binary(content_type: &str, bytes: &[u8]) -> bool {
+    if is_binary_content_type(content_type) {
+        return true;
+    }
+    if is_text_content_type(content_type) {
+        return false;
+    }
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN)];
+    if sample.contains(&0) {
+        return true;
+    }
+    // The sample may end inside a character, only a bad sequence before that counts
+    if let Err(e) = std::str::from_utf8(sample)
+        && e.error_len().is_some()
+    {
+        return true;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b.is_ascii_control() && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    control * 10 > sample.len()
+}
+
+// Text decoder for the charset named in a Content-Type, UTF-8 when there is none
+// or it's unknown. A BOM overrides the label, like browsers do
+pub struct CharsetDecoder(encoding_rs::Decoder);
+
+impl CharsetDecoder {
+    pub fn for_content_type(content_type: &str) -> Self {
+        let encoding = content_type
+            .split(';')
+            .skip(1)
+            .filter_map(|param| param.split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("charset"))
+            .and_then(|(_, label)| {
+                encoding_rs::Encoding::for_label(label.trim().trim_matches('"').as_bytes())
+            })
+            .unwrap_or(encoding_rs::UTF_8);
+        Self(encoding.new_decoder())
+    }
+
+    // Bytes that end mid-character are held until the next call, `last` flushes them
+    pub fn decode(&mut self, bytes: &[u8], last: bool) -> String {
+        let capacity = self
+            .0
+            .max_utf8_buffer_length(bytes.len())
+            .unwrap_or(bytes.len() * 3);
+        let mut out = String::with_capacity(capacity);
+        self.0.decode_to_string(bytes, &mut out, last);
+        out
+    }
+}
+
+// Suggested name when saving, from Content-Disposition or the URL path.
+// `filename*=UTF-8''...` (RFC 6266) wins over a plain `filename=` when both are sent
+pub fn filename_from_response(headers: &reqwest::header::HeaderMap, url: &str) -> String {
+    let disposition = headers
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
+        .unwrap_or_default();
+
+    let mut plain = None;
+    let mut extended = None;
+    for param in split_params(&disposition).into_iter().skip(1) {
+        let Some((key, value)) = param.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                // charset'language'percent-encoded, anything but UTF-8 is decoded lossily
+                let encoded = value.splitn(3, '\'').nth(2).unwrap_or(value);
+                let bytes = urlencoding::decode_binary(encoded.as_bytes());
+                extended = Some(String::from_utf8_lossy(&bytes).into_owned());
+            }
+            "filename" => plain = Some(value.trim_matches(|c| c == '"' || c == '\'').to_string()),
+            _ => {}
+        }
+    }
+
+    // Only the last path segment, a name like `../../x` must not leave the chosen folder
+    extended
+        .or(plain)
+        .and_then(|name| {
+            name.rsplit(['/', '\\'])
+                .next()
+                .filter(|n| !n.is_empty() && *n != "..")
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| {
+            // Only a path segment, a bare `https://example.com` has none to offer
+            reqwest::Url::parse(url)
+                .ok()
+                .and_then(|url| {
+                    let last = url.path_segments()?.next_back()?;
+                    let name = urlencoding::decode(last).ok()?.into_owned();
+                    (!name.is_empty() && !name.contains(['/', '\\']) && name != "..")
+                        .then_some(name)
+                })
+                .unwrap_or_else(|| "download".to_string())
+        })
+}
+
+// Content-Disposition split on `;`, except inside a quoted value like "a;b.txt"
+fn split_params(value: &str) -> Vec<&str> {
+    let mut params = Vec::new();
+    let mut quoted = false;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                params.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    params.push(&value[start..]);
+    params
+}
+
+pub fn gzip_bytes(bytes: &[u8]) -> Option<Vec<u8>> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(bytes).ok()?;
+    encoder.finish().ok()
+}
+
+// A request body as main.rs collects it from the tab, files already read
+pub enum RequestBody {
+    // Content-Type names the uncompressed body, gzip is only the encoding
+    Raw {
+        bytes: Vec<u8>,
+        content_type: String,
+        gzip: bool,
+    },
+    UrlEncoded(Vec<(String, String)>),
+    Multipart(Vec<FormPart>),
+}
+
+pub struct FormPart {
+    pub name: String,
+    pub content: PartContent,
+    pub content_type: Option<String>,
+}
+
+pub enum PartContent {
+    Text(String),
+    File { file_name: String, bytes: Vec<u8> },
+}
+
+// None when the body can't be gzipped or a part's content type isn't a valid mime type
+pub fn with_body(
+    request: reqwest::RequestBuilder,
+    body: RequestBody,
+) -> Option<reqwest::RequestBuilder> {
+    let request = match body {
+        RequestBody::Raw {
+            bytes,
+            content_type,
+            gzip,
+        } => {
+            let request = if gzip {
+                request
+                    .body(gzip_bytes(&bytes)?)
+                    .header(reqwest::header::CONTENT_ENCODING, "gzip")
+            } else {
+                request.body(bytes)
+            };
+            request.header(reqwest::header::CONTENT_TYPE, content_type)
+        }
+        RequestBody::UrlEncoded(params) => request.form(&params),
+        RequestBody::Multipart(parts) => {
+            let mut form = reqwest::multipart::Form::new();
+            for part in parts {
+                let mut built = match part.content {
+                    PartContent::Text(text) => reqwest::multipart::Part::text(text),
+                    PartContent::File { file_name, bytes } => {
+                        reqwest::multipart::Part::bytes(bytes).file_name(file_name)
+                    }
+                };
+                if let Some(content_type) = &part.content_type {
+                    built = built.mime_str(content_type).ok()?;
+                }
+                form = form.part(part.name, built);
+            }
+            request.multipart(form)
+        }
+    };
+    Some(request)
+}
+
+// GraphQL over POST. Variables that aren't valid JSON are sent as `{}`, the
+// editor flags them before a send gets this far
+pub fn graphql_body(query: &str, variables: &str, operation: &str) -> String {
+    let variables: serde_json::Value =
+        serde_json::from_str(variables).unwrap_or(serde_json::Value::Object(Default::default()));
+    let operation = if operation.is_empty() {
+        serde_json::Value::Null
+    } else {
+        serde_json::Value::String(operation.to_string())
+    };
+    serde_json::json!({
+        "query": query,
+        "variables": variables,
+        "operationName": operation,
+    })
+    .to_string()
+}
+
+// `Basic` credentials, sent even when both are empty since `Basic Og==` is still valid
+pub fn basic_auth_value(username: &str, password: &str) -> String {
+    use base64::Engine;
+    let credentials =
+        base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+    format!("Basic {credentials}")
+}
+
+// Sets a query parameter, dropping any the URL already has under that name.
+// None when the URL doesn't parse
+pub fn replace_query_param(url: &str, name: &str, value: &str) -> Option<String> {
+    let mut parsed = url::Url::parse(url).ok()?;
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| key != name)
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    parsed
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(kept)
+        .append_pair(name, value);
+    Some(parsed.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{CONTENT_DISPOSITION, HeaderMap, HeaderValue};
+
+    fn disposition(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_DISPOSITION, HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn parse_headers_skips_comments_and_invalid_lines() {
+        let headers = parse_headers(
+            "Accept: application/json\n\n# X-Off: 1\nno colon here\nBad Name: x\n  X-Trim :  padded  \nX-Multi: a\nX-Multi: b",
+        );
+        assert_eq!(headers.len(), 4);
+        assert_eq!(headers["accept"], "application/json");
+        assert_eq!(headers["x-trim"], "padded");
+        let multi: Vec<_> = headers.get_all("x-multi").iter().collect();
+        assert_eq!(multi, ["a", "b"]);
+        assert!(!headers.contains_key("x-off"));
+    }
+
+    #[test]
+    fn merge_missing_keeps_headers_already_set() {
+        let mut headers = parse_headers("Accept: text/plain");
+        merge_missing(
+            &mut headers,
+            &parse_headers("Accept: */*\nX-Env: a\nX-Env: b"),
+        );
+        assert_eq!(headers["accept"], "text/plain");
+        assert_eq!(headers.get_all("x-env").iter().count(), 2);
+    }
+
+    #[test]
+    fn is_binary_content_type_matches_prefixes() {
+        assert!(is_binary_content_type("image/png"));
+        assert!(is_binary_content_type(" IMAGE/PNG "));
+        assert!(is_binary_content_type("application/vnd.ms-excel"));
+        assert!(is_binary_content_type(
+            "application/octet-stream; charset=binary"
+        ));
+        assert!(!is_binary_content_type("application/json"));
+        assert!(!is_binary_content_type("text/html; charset=utf-8"));
+        assert!(!is_binary_content_type(""));
+    }
+
+    #[test]
+    fn charset_decoder_reads_latin1_across_chunks() {
+        // "café ñ" in ISO-8859-1, the bytes above 0x7F land on both sides of the split
+        let body = b"caf\xe9 \xf1";
+        let mut decoder = CharsetDecoder::for_content_type("text/plain; charset=\"ISO-8859-1\"");
+        let mut text = decoder.decode(&body[..4], false);
+        text += &decoder.decode(&body[4..], true);
+        assert_eq!(text, "café ñ");
+    }
+
+    #[test]
+    fn charset_decoder_holds_a_split_utf8_character() {
+        let body = "café".as_bytes();
+        let mut decoder = CharsetDecoder::for_content_type("text/plain");
+        assert_eq!(decoder.decode(&body[..4], false), "caf");
+        assert_eq!(decoder.decode(&body[4..], true), "é");
+    }
+
+    fn decode_in_chunks(encoding: &str, body: &[u8], chunk: usize) -> Vec<u8> {
+        let mut decoder = BodyDecoder::new(encoding);
+        let mut out = Vec::new();
+        for piece in body.chunks(chunk) {
+            out.extend(decoder.feed(piece).unwrap());
+        }
+        out.extend(decoder.finish().unwrap());
+        out
+    }
+
+    #[test]
+    fn deflate_accepts_zlib_and_raw_streams() {
+        use std::io::Write;
+        let text = b"deflate, with or without the zlib wrapper".repeat(20);
+
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        zlib.write_all(&text).unwrap();
+        let zlib = zlib.finish().unwrap();
+        let mut raw =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        raw.write_all(&text).unwrap();
+        let raw = raw.finish().unwrap();
+
+        // One byte at a time, so the header check has to wait for the second
+        assert_eq!(decode_in_chunks("deflate", &zlib, 1), text);
+        assert_eq!(decode_in_chunks("deflate", &raw, 1), text);
+        assert_eq!(decode_in_chunks("deflate", &raw, 4096), text);
+    }
+
+    #[test]
+    fn decode_body_reads_a_gzipped_page() {
+        use std::io::Write;
+        let page = br#"{"items":[1,2,3],"next":"/items?page=2"}"#;
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(page).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            reqwest::header::CONTENT_ENCODING,
+            HeaderValue::from_static(" GZIP "),
+        );
+        let encoding = content_encoding(&headers);
+        assert_eq!(encoding, "gzip");
+        assert_eq!(decode_body(&encoding, &gzip).unwrap(), page);
+        assert_eq!(decode_body("", page).unwrap(), page);
+    }
+
+    #[test]
+    fn brotli_and_zstd_decode_across_chunks() {
+        use std::io::Write;
+        let text = b"brotli and zstd, as CDNs send them".repeat(50);
+
+        let mut br = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+        br.write_all(&text).unwrap();
+        let br = br.into_inner();
+        let zstd = zstd::encode_all(&text[..], 3).unwrap();
+
+        assert_eq!(decode_in_chunks("br", &br, 7), text);
+        assert_eq!(decode_in_chunks("br", &br, 4096), text);
+        assert_eq!(decode_in_chunks("zstd", &zstd, 7), text);
+    }
+
+    fn built(body: RequestBody) -> Option<reqwest::Request> {
+        let request = reqwest::Client::new().post("http://localhost/");
+        with_body(request, body).map(|request| request.build().unwrap())
+    }
+
+    #[test]
+    fn raw_body_is_gzipped_only_when_asked() {
+        let json = br#"{"a": 1}"#.to_vec();
+        let plain = built(RequestBody::Raw {
+            bytes: json.clone(),
+            content_type: "application/json".to_string(),
+            gzip: false,
+        })
+        .unwrap();
+        assert_eq!(plain.headers()["content-type"], "application/json");
+        assert!(plain.headers().get("content-encoding").is_none());
+        assert_eq!(plain.body().and_then(|b| b.as_bytes()), Some(&json[..]));
+
+        let gzipped = built(RequestBody::Raw {
+            bytes: json.clone(),
+            content_type: "application/json".to_string(),
+            gzip: true,
+        })
+        .unwrap();
+        assert_eq!(gzipped.headers()["content-type"], "application/json");
+        assert_eq!(gzipped.headers()["content-encoding"], "gzip");
+        let sent = gzipped.body().and_then(|b| b.as_bytes()).unwrap();
+        assert_eq!(decode_body("gzip", sent).unwrap(), json);
+    }
+
+    #[test]
+    fn url_encoded_body_escapes_its_pairs() {
+        let request = built(RequestBody::UrlEncoded(vec![
+            ("name".to_string(), "a b&c".to_string()),
+            ("n".to_string(), "1".to_string()),
+        ]))
+        .unwrap();
+        assert_eq!(
+            request.headers()["content-type"],
+            "application/x-www-form-urlencoded"
+        );
+        assert_eq!(
+            request.body().and_then(|b| b.as_bytes()),
+            Some(&b"name=a+b%26c&n=1"[..])
+        );
+    }
+
+    #[test]
+    fn multipart_body_rejects_a_bad_part_type() {
+        let part = |content_type: Option<&str>| FormPart {
+            name: "file".to_string(),
+            content: PartContent::File {
+                file_name: "a.txt".to_string(),
+                bytes: b"hello".to_vec(),
+            },
+            content_type: content_type.map(str::to_string),
+        };
+        let request = built(RequestBody::Multipart(vec![
+            FormPart {
+                name: "note".to_string(),
+                content: PartContent::Text("hi".to_string()),
+                content_type: None,
+            },
+            part(Some("text/plain")),
+        ]))
+        .unwrap();
+        let content_type = request.headers()["content-type"].to_str().unwrap();
+        assert!(content_type.starts_with("multipart/form-data; boundary="));
+
+        assert!(built(RequestBody::Multipart(vec![part(Some("not a type"))])).is_none());
+    }
+
+    #[test]
+    fn graphql_body_falls_back_to_empty_variables() {
+        let body: serde_json::Value =
+            serde_json::from_str(&graphql_body("{ me }", "not json", "")).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"query": "{ me }", "variables": {}, "operationName": null})
+        );
+        let body: serde_json::Value =
+            serde_json::from_str(&graphql_body("query Q { me }", r#"{"id": 1}"#, "Q")).unwrap();
+        assert_eq!(body["variables"]["id"], 1);
+        assert_eq!(body["operationName"], "Q");
+    }
+
+    #[test]
+    fn basic_auth_encodes_user_and_password() {
+        assert_eq!(basic_auth_value("user", "pass"), "Basic dXNlcjpwYXNz");
+        assert_eq!(basic_auth_value("", ""), "Basic Og==");
+    }
+
+    #[test]
+    fn query_param_replaces_the_same_name_only() {
+        assert_eq!(
+            replace_query_param("https://x.io/a?key=old&b=1&key=older", "key", "n w").as_deref(),
+            Some("https://x.io/a?b=1&key=n+w")
+        );
+        assert_eq!(replace_query_param("{{base}}/a", "key", "v"), None);
+    }
+
+    #[test]
+    fn detect_binary_sniffs_unknown_types() {
+        // Known types decide without looking at the bytes
+        assert!(detect_binary("image/png", b"plain"));
+        assert!(!detect_binary("application/json", b"\0\0"));
+
+        assert!(!detect_binary("", b"{\"a\": 1}\n\tok\r\n"));
+        assert!(detect_binary("", b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"));
+        assert!(detect_binary("application/x-custom", b"ab\xff\xfecd"));
+        assert!(detect_binary("", &[0x01, 0x02, 0x03, b'a', b'b']));
+        // A character cut off by the end of the sample isn't held against the body
+        let long = format!("a{}", "é".repeat(SNIFF_LEN));
+        assert!(!detect_binary("", long.as_bytes()));
+    }
+
+    #[test]
+    fn filename_prefers_extended_over_plain() {
+        let headers = disposition(
+            "attachment; filename=\"fallback.txt\"; filename*=UTF-8''r%C3%A9sum%C3%A9.pdf",
+        );
+        assert_eq!(
+            filename_from_response(&headers, "https://example.com/x"),
+            "résumé.pdf"
+        );
+    }
+
+    #[test]
+    fn filename_keeps_semicolons_inside_quotes() {
+        let headers = disposition("attachment; filename=\"a;b.txt\"");
+        assert_eq!(
+            filename_from_response(&headers, "https://example.com/x"),
+            "a;b.txt"
+        );
+    }
+
+    #[test]
+    fn filename_drops_directories() {
+        let headers = disposition("attachment; filename=\"../../etc/passwd\"");
+        assert_eq!(
+            filename_from_response(&headers, "https://example.com/x"),
+            "passwd"
+        );
+    }
+
+    #[test]
+    fn filename_falls_back_to_the_url_path() {
+        let headers = HeaderMap::new();
+        assert_eq!(
+            filename_from_response(&headers, "https://example.com/files/report%201.csv?v=2#top"),
+            "report 1.csv"
+        );
+        assert_eq!(
+            filename_from_response(&headers, "https://example.com"),
+            "download"
+        );
+        assert_eq!(
+            filename_from_response(&headers, "https://example.com/dir/"),
+            "download"
+        );
+    }
+}
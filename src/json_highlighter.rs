@@ -1,5 +1,6 @@
 use iced::Color;
 use iced::widget::text;
+use std::borrow::Cow;
 use std::ops::Range;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,24 +19,56 @@ pub enum HighlightType {
     Syntax(Color),
     SearchMatch,
     CurrentMatch,
+    // Where a JSON body stopped parsing
+    Error,
+}
+
+// Which tokenizer colors the text, picked from the Content-Type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Json,
+    Xml,
+    Html,
+    // Unknown content, only search matches are highlighted
+    Plain,
+}
+
+impl Language {
+    pub fn from_content_type(content_type: &str) -> Self {
+        let content_type = content_type.to_ascii_lowercase();
+        if content_type.contains("json") {
+            Language::Json
+        } else if content_type.contains("html") {
+            Language::Html
+        } else if content_type.contains("xml") {
+            Language::Xml
+        } else {
+            Language::Plain
+        }
+    }
 }
 
 // Settings that include both theme and search information
 #[derive(Debug, Clone, PartialEq)]
 pub struct JsonHighlighterSettings {
     pub theme: JsonThemeWrapper,
+    pub language: Language,
     pub search_matches: Vec<(usize, usize)>,
     pub current_match: Option<(usize, usize)>,
     pub match_length: usize,
+    // Line and column (both from 0) of a parse error to mark
+    pub error_position: Option<(usize, usize)>,
 }
 
 impl JsonHighlighterSettings {
     pub fn new(theme: JsonThemeWrapper) -> Self {
         Self {
             theme,
+            language: Language::Json,
             search_matches: Vec::new(),
             current_match: None,
             match_length: 0,
+            error_position: None,
         }
     }
 
@@ -50,6 +83,16 @@ impl JsonHighlighterSettings {
         self.match_length = length;
         self
     }
+
+    pub fn with_language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    pub fn with_error(mut self, position: Option<(usize, usize)>) -> Self {
+        self.error_position = position;
+        self
+    }
 }
 
 pub struct JsonHighlighter {
@@ -76,78 +119,36 @@ impl JsonHighlighter {
         range: Range<usize>,
         highlight_type: HighlightType,
     ) {
+        // The whole range is covered, including unhighlighted gaps such as
+        // whitespace or an invalid token, the syntax colors around it are kept
         let mut new_highlights: Vec<(Range<usize>, HighlightType)> = Vec::new();
-        let mut covered = false;
 
         for (existing_range, existing_type) in highlights.drain(..) {
             if existing_range.end <= range.start || existing_range.start >= range.end {
                 // No overlap - keep existing highlight
                 new_highlights.push((existing_range, existing_type));
-            } else {
-                // There's overlap
-                covered = true;
-
-                // Part before overlap (keep original syntax highlighting)
-                if existing_range.start < range.start {
-                    new_highlights.push((existing_range.start..range.start, existing_type));
-                }
-
-                // Overlapping part gets search highlight
-                let overlap_start = existing_range.start.max(range.start);
-                let overlap_end = existing_range.end.min(range.end);
-                new_highlights.push((overlap_start..overlap_end, highlight_type));
-
-                // Part after overlap (keep original syntax highlighting)
-                if existing_range.end > range.end {
-                    new_highlights.push((range.end..existing_range.end, existing_type));
-                }
+                continue;
+            }
+            // Part before overlap (keep original syntax highlighting)
+            if existing_range.start < range.start {
+                new_highlights.push((existing_range.start..range.start, existing_type));
+            }
+            // Part after overlap (keep original syntax highlighting)
+            if existing_range.end > range.end {
+                new_highlights.push((range.end..existing_range.end, existing_type));
             }
         }
-
-        // If no existing highlight covered this range, add it with default color
-        if !covered {
-            new_highlights.push((range, highlight_type));
-        }
+        new_highlights.push((range, highlight_type));
 
         *highlights = new_highlights;
     }
-}
-
-impl text::Highlighter for JsonHighlighter {
-    type Settings = JsonHighlighterSettings;
-    type Highlight = HighlightType;
-    type Iterator<'a> = Box<dyn Iterator<Item = (Range<usize>, Self::Highlight)> + 'a>;
-
-    fn new(settings: &Self::Settings) -> Self {
-        Self {
-            current_line_number: 0,
-            settings: settings.clone(),
-        }
-    }
-
-    fn update(&mut self, new_settings: &Self::Settings) {
-        self.settings = new_settings.clone();
-    }
-
-    fn change_line(&mut self, line: usize) {
-        println!("Changed line called with line number {line}");
-        self.current_line_number = line;
-    }
-
-    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
-        let actual_line = self.current_line_number;
-
-        if line.is_empty() {
-            return Box::new(std::iter::empty());
-        }
 
+    fn json_highlights(&self, chars: &[char]) -> Vec<(Range<usize>, HighlightType)> {
         let mut highlights: Vec<(Range<usize>, HighlightType)> = Vec::new();
-        let chars: Vec<char> = line.chars().collect();
         let len = chars.len();
         let mut i = 0;
         let mut current_context_is_key = true;
 
-        // --- STEP 1: Syntax Highlighting First ---
         while i < len {
             let ch = chars[i];
             let start = i;
@@ -191,7 +192,32 @@ impl text::Highlighter for JsonHighlighter {
                     }
                     i += 1;
                 }
-                // ... (rest of your number/bool/null logic) ...
+                '-' | '0'..='9' => {
+                    i = Self::scan_number(chars, i);
+                    // A lone '-' is not a number, leave it unhighlighted
+                    if i > start + 1 || ch != '-' {
+                        highlights.push((
+                            start..i,
+                            HighlightType::Syntax(self.token_color(JsonToken::Number)),
+                        ));
+                    }
+                }
+                't' | 'f' | 'n' => {
+                    let keyword = [
+                        ("true", JsonToken::Boolean),
+                        ("false", JsonToken::Boolean),
+                        ("null", JsonToken::Null),
+                    ]
+                    .into_iter()
+                    .find(|(word, _)| Self::is_keyword_at(chars, i, word));
+
+                    if let Some((word, token)) = keyword {
+                        i += word.len();
+                        highlights.push((start..i, HighlightType::Syntax(self.token_color(token))));
+                    } else {
+                        i += 1;
+                    }
+                }
                 c if c.is_whitespace() => {
                     i += 1;
                 }
@@ -201,6 +227,196 @@ impl text::Highlighter for JsonHighlighter {
             }
         }
 
+        highlights
+    }
+
+    // Tags, attributes and comments on a single line. A tag split over several
+    // lines only gets its first line colored, the highlighter keeps no state.
+    fn markup_highlights(&self, chars: &[char]) -> Vec<(Range<usize>, HighlightType)> {
+        let mut highlights: Vec<(Range<usize>, HighlightType)> = Vec::new();
+        let len = chars.len();
+        let punctuation = HighlightType::Syntax(self.token_color(JsonToken::Punctuation));
+        let mut i = 0;
+
+        while i < len {
+            if chars[i] != '<' {
+                i += 1;
+                continue;
+            }
+            let start = i;
+
+            if chars[i..].starts_with(&['<', '!', '-', '-']) {
+                i += 4;
+                while i < len && !chars[i..].starts_with(&['-', '-', '>']) {
+                    i += 1;
+                }
+                i = (i + 3).min(len);
+                highlights.push((
+                    start..i,
+                    HighlightType::Syntax(self.token_color(JsonToken::Null)),
+                ));
+                continue;
+            }
+
+            // `<`, `</`, `<?` and `<!` open a tag
+            i += 1;
+            if i < len && matches!(chars[i], '/' | '?' | '!') {
+                i += 1;
+            }
+            highlights.push((start..i, punctuation));
+
+            let name_start = i;
+            while i < len && !chars[i].is_whitespace() && !matches!(chars[i], '>' | '/' | '?') {
+                i += 1;
+            }
+            if i > name_start {
+                highlights.push((
+                    name_start..i,
+                    HighlightType::Syntax(self.token_color(JsonToken::Key)),
+                ));
+            }
+
+            // Attributes up to the closing `>`
+            while i < len && chars[i] != '>' {
+                let start = i;
+                match chars[i] {
+                    '"' | '\'' => {
+                        let quote = chars[i];
+                        i += 1;
+                        while i < len && chars[i] != quote {
+                            i += 1;
+                        }
+                        i = (i + 1).min(len);
+                        highlights.push((
+                            start..i,
+                            HighlightType::Syntax(self.token_color(JsonToken::String)),
+                        ));
+                    }
+                    '=' | '/' | '?' => {
+                        i += 1;
+                        highlights.push((start..i, punctuation));
+                    }
+                    c if c.is_whitespace() => {
+                        i += 1;
+                    }
+                    _ => {
+                        while i < len
+                            && !chars[i].is_whitespace()
+                            && !matches!(chars[i], '=' | '>' | '/' | '"' | '\'')
+                        {
+                            i += 1;
+                        }
+                        highlights.push((
+                            start..i,
+                            HighlightType::Syntax(self.token_color(JsonToken::Number)),
+                        ));
+                    }
+                }
+            }
+            if i < len {
+                highlights.push((i..i + 1, punctuation));
+                i += 1;
+            }
+        }
+
+        highlights
+    }
+
+    // Scans a JSON number (`-1`, `0.5`, `-1.5e10`, `2E-3`) starting at `start`
+    // and returns the index just past it
+    fn scan_number(chars: &[char], start: usize) -> usize {
+        let len = chars.len();
+        let mut i = start;
+
+        if chars[i] == '-' {
+            i += 1;
+        }
+        while i < len && chars[i].is_ascii_digit() {
+            i += 1;
+        }
+
+        // Fraction, only when a digit follows the dot
+        if i + 1 < len && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+            i += 1;
+            while i < len && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+        }
+
+        // Exponent, only when it is followed by at least one digit
+        if i < len && matches!(chars[i], 'e' | 'E') {
+            let mut j = i + 1;
+            if j < len && matches!(chars[j], '+' | '-') {
+                j += 1;
+            }
+            if j < len && chars[j].is_ascii_digit() {
+                i = j;
+                while i < len && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+            }
+        }
+
+        i
+    }
+
+    // True if `word` starts at `at` and is not just the prefix of a longer identifier
+    fn is_keyword_at(chars: &[char], at: usize, word: &str) -> bool {
+        let end = at + word.len();
+        end <= chars.len()
+            && chars[at..end].iter().copied().eq(word.chars())
+            && chars
+                .get(end)
+                .is_none_or(|c| !c.is_alphanumeric() && *c != '_')
+    }
+}
+
+impl text::Highlighter for JsonHighlighter {
+    type Settings = JsonHighlighterSettings;
+    type Highlight = HighlightType;
+    type Iterator<'a> = Box<dyn Iterator<Item = (Range<usize>, Self::Highlight)> + 'a>;
+
+    fn new(settings: &Self::Settings) -> Self {
+        Self {
+            current_line_number: 0,
+            settings: settings.clone(),
+        }
+    }
+
+    fn update(&mut self, new_settings: &Self::Settings) {
+        self.settings = new_settings.clone();
+    }
+
+    fn change_line(&mut self, line: usize) {
+        self.current_line_number = line;
+    }
+
+    fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
+        // Lines are highlighted in order after `change_line`, so track the next one here
+        let actual_line = self.current_line_number;
+        self.current_line_number += 1;
+
+        if line.is_empty() {
+            return Box::new(std::iter::empty());
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let len = chars.len();
+        let mut highlights = match self.settings.language {
+            Language::Json => self.json_highlights(&chars),
+            Language::Xml | Language::Html => self.markup_highlights(&chars),
+            Language::Plain => Vec::new(),
+        };
+
+        // The error marks the rest of the line from where parsing gave up
+        if let Some((line_num, col)) = self.settings.error_position
+            && line_num == actual_line
+            && len > 0
+        {
+            let col = col.min(len - 1);
+            self.apply_search_highlight(&mut highlights, col..len, HighlightType::Error);
+        }
+
         for &(line_num, col_start) in &self.settings.search_matches {
             if line_num == actual_line {
                 let col_end = col_start + self.settings.match_length;
@@ -229,7 +445,20 @@ impl text::Highlighter for JsonHighlighter {
 
         highlights.sort_by_key(|(range, _)| range.start);
 
-        Box::new(highlights.into_iter())
+        // Everything above works on char indices, but iced expects byte ranges
+        let byte_offsets: Vec<usize> = line
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(std::iter::once(line.len()))
+            .collect();
+        let highlights = highlights.into_iter().map(move |(range, highlight)| {
+            (
+                byte_offsets[range.start]..byte_offsets[range.end],
+                highlight,
+            )
+        });
+
+        Box::new(highlights)
     }
 
     fn current_line(&self) -> usize {
@@ -237,14 +466,14 @@ impl text::Highlighter for JsonHighlighter {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum JsonThemeWrapper {
     Builtin(iced::highlighter::Theme),
     Custom(CustomJsonTheme),
 }
 
 impl JsonThemeWrapper {
-    pub const ALL: &'static [JsonThemeWrapper] = &[
+    pub const BUILTIN: &'static [JsonThemeWrapper] = &[
         // Built-in themes
         JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Eighties),
         JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Mocha),
@@ -257,6 +486,29 @@ impl JsonThemeWrapper {
         JsonThemeWrapper::Custom(CustomJsonTheme::VSCODE_DARK),
     ];
 
+    // The shipped themes followed by the ones the user made
+    pub fn all(user_themes: &[CustomJsonTheme]) -> Vec<JsonThemeWrapper> {
+        Self::BUILTIN
+            .iter()
+            .cloned()
+            .chain(user_themes.iter().cloned().map(JsonThemeWrapper::Custom))
+            .collect()
+    }
+
+    // Same colors as an editable theme, the starting point for the theme editor
+    pub fn to_custom(&self) -> CustomJsonTheme {
+        CustomJsonTheme {
+            name: Cow::Owned(self.to_string()),
+            key: self.key_color(),
+            string: self.string_color(),
+            number: self.number_color(),
+            boolean: self.boolean_color(),
+            null: self.null_color(),
+            punctuation: self.punctuation_color(),
+            text: self.text_color(),
+        }
+    }
+
     // Helper methods to extract colors
     fn key_color(&self) -> Color {
         match self {
@@ -365,8 +617,9 @@ impl std::fmt::Display for JsonThemeWrapper {
 }
 
 // Custom JSON-specific themes
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct CustomJsonTheme {
+    pub name: Cow<'static, str>,
     pub key: Color,
     pub string: Color,
     pub number: Color,
@@ -377,7 +630,19 @@ pub struct CustomJsonTheme {
 }
 
 impl CustomJsonTheme {
+    // Labels for the colors, in the order `color` and `set_color` index them
+    pub const FIELDS: [&'static str; 7] = [
+        "Key",
+        "String",
+        "Number",
+        "Boolean",
+        "Null",
+        "Punctuation",
+        "Text",
+    ];
+
     pub const DEFAULT_DARK: Self = Self {
+        name: Cow::Borrowed("Custom Dark"),
         key: Color::from_rgb(0.4, 0.76, 0.94),
         string: Color::from_rgb(0.73, 0.87, 0.53),
         number: Color::from_rgb(0.88, 0.73, 0.53),
@@ -388,6 +653,7 @@ impl CustomJsonTheme {
     };
 
     pub const DEFAULT_LIGHT: Self = Self {
+        name: Cow::Borrowed("Custom Light"),
         key: Color::from_rgb(0.0, 0.33, 0.8),
         string: Color::from_rgb(0.13, 0.54, 0.13),
         number: Color::from_rgb(0.8, 0.4, 0.0),
@@ -398,6 +664,7 @@ impl CustomJsonTheme {
     };
 
     pub const VSCODE_DARK: Self = Self {
+        name: Cow::Borrowed("VS Code Dark"),
         key: Color::from_rgb(0.61, 0.82, 0.96),
         string: Color::from_rgb(0.81, 0.71, 0.58),
         number: Color::from_rgb(0.71, 0.86, 0.65),
@@ -406,19 +673,35 @@ impl CustomJsonTheme {
         punctuation: Color::from_rgb(0.85, 0.85, 0.85),
         text: Color::from_rgb(0.85, 0.85, 0.85),
     };
+
+    pub fn color(&self, field: usize) -> Color {
+        match field {
+            0 => self.key,
+            1 => self.string,
+            2 => self.number,
+            3 => self.boolean,
+            4 => self.null,
+            5 => self.punctuation,
+            _ => self.text,
+        }
+    }
+
+    pub fn set_color(&mut self, field: usize, color: Color) {
+        match field {
+            0 => self.key = color,
+            1 => self.string = color,
+            2 => self.number = color,
+            3 => self.boolean = color,
+            4 => self.null = color,
+            5 => self.punctuation = color,
+            _ => self.text = color,
+        }
+    }
 }
 
 impl std::fmt::Display for CustomJsonTheme {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if *self == Self::DEFAULT_DARK {
-            write!(f, "Custom Dark")
-        } else if *self == Self::DEFAULT_LIGHT {
-            write!(f, "Custom Light")
-        } else if *self == Self::VSCODE_DARK {
-            write!(f, "VS Code Dark")
-        } else {
-            write!(f, "Custom")
-        }
+        write!(f, "{}", self.name)
     }
 }
 
@@ -479,3 +762,96 @@ impl text::Highlighter for LogHighlighter {
         self.current_line
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use iced::widget::text::Highlighter;
+
+    fn settings() -> JsonHighlighterSettings {
+        JsonHighlighterSettings::new(JsonThemeWrapper::Custom(CustomJsonTheme::DEFAULT_DARK))
+    }
+
+    // Each highlighted slice of the line with its color, None for the error mark
+    fn spans(settings: &JsonHighlighterSettings, line: &str) -> Vec<(String, Option<Color>)> {
+        let mut highlighter = JsonHighlighter::new(settings);
+        highlighter
+            .highlight_line(line)
+            .map(|(range, highlight)| {
+                let color = match highlight {
+                    HighlightType::Syntax(color) => Some(color),
+                    _ => None,
+                };
+                (line[range].to_string(), color)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn colors_keys_strings_and_numbers() {
+        let settings = settings();
+        let theme = &settings.theme;
+        let spans = spans(&settings, r#"{"café": "ünï", "n": -1.5e3, "ok": true}"#);
+        let color_of = |slice: &str| {
+            spans
+                .iter()
+                .find(|(s, _)| s == slice)
+                .and_then(|(_, color)| *color)
+        };
+
+        assert_eq!(color_of(r#""café""#), Some(theme.key_color()));
+        assert_eq!(color_of(r#""ünï""#), Some(theme.string_color()));
+        assert_eq!(color_of(r#""n""#), Some(theme.key_color()));
+        assert_eq!(color_of("-1.5e3"), Some(theme.number_color()));
+        assert_eq!(color_of("true"), Some(theme.boolean_color()));
+        assert_eq!(color_of("{"), Some(theme.punctuation_color()));
+    }
+
+    #[test]
+    fn colors_literals_in_arrays() {
+        let settings = settings();
+        let theme = &settings.theme;
+        let spans = spans(&settings, "  [true, false, null],");
+        let colored: Vec<_> = spans
+            .iter()
+            .filter(|(s, _)| !s.trim().is_empty())
+            .map(|(s, color)| (s.as_str(), *color))
+            .collect();
+
+        assert_eq!(
+            colored,
+            [
+                ("[", Some(theme.punctuation_color())),
+                ("true", Some(theme.boolean_color())),
+                (",", Some(theme.punctuation_color())),
+                ("false", Some(theme.boolean_color())),
+                (",", Some(theme.punctuation_color())),
+                ("null", Some(theme.null_color())),
+                ("]", Some(theme.punctuation_color())),
+                (",", Some(theme.punctuation_color())),
+            ]
+        );
+    }
+
+    #[test]
+    fn marks_the_rest_of_the_line_from_the_error() {
+        let settings = settings().with_error(Some((0, 6)));
+        let spans = spans(&settings, r#"{"a": oops}"#);
+
+        assert_eq!(spans.last(), Some(&("oops}".to_string(), None)));
+        assert_eq!(
+            spans[1],
+            (r#""a""#.to_string(), Some(settings.theme.key_color()))
+        );
+    }
+
+    #[test]
+    fn error_on_another_line_is_not_marked() {
+        let settings = settings().with_error(Some((3, 0)));
+        assert!(
+            spans(&settings, r#"{"a": 1}"#)
+                .iter()
+                .all(|(_, color)| color.is_some())
+        );
+    }
+}
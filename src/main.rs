@@ -1,8 +1,10 @@
 #![allow(unused)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use base64::Engine;
 use futures::channel::mpsc;
 use iced_aw::iced_aw_font::down_open;
+use jsonpath_rust::JsonPath;
 use reqwest_websocket::RequestBuilderExt;
 use std::sync::atomic::Ordering;
 
@@ -17,6 +19,7 @@ use iced::{
 };
 use serde::{Deserialize, Serialize};
 
+mod http;
 mod json_highlighter;
 
 //TODOS:
@@ -37,6 +40,34 @@ enum Message {
     RequestTabLoad(usize),
 
     UrlChanged(String),
+    SendAndDownload,
+    LoadTestToggle,
+    LoadTestCountChanged(String),
+    LoadTestConcurrencyChanged(String),
+    LoadTestStart,
+    LoadTestStop,
+    // One request of a load test run, the status and total time or why it failed
+    LoadTestResult {
+        tab_id: usize,
+        run: u64,
+        result: Result<(u16, std::time::Duration), String>,
+    },
+    PaginationToggle,
+    PaginationItemsPathChanged(String),
+    PaginationNextPathChanged(String),
+    PaginationCursorParamChanged(String),
+    PaginationMaxPagesChanged(String),
+    PaginationStart,
+    PaginationStop,
+    // A page fetched by an auto-paginate run, or why the run ended
+    PaginationEvent {
+        tab_id: usize,
+        run: u64,
+        event: PaginationEvent,
+    },
+    DownloadTargetChosen(std::path::PathBuf),
+    RequestNameChanged(String),
+    RequestDescriptionChanged(String),
     RequestTypeSelected(RequestType),
     MethodSelected(HttpMethod),
 
@@ -46,34 +77,154 @@ enum Message {
     HeaderKeyChanged(usize, String),
     HeaderValueChanged(usize, String),
     HeaderToggled(usize),
+    HeaderSuggestionPicked(usize, &'static str),
+    HeaderValueSuggestionPicked(usize, &'static str),
+    HeadersViewRaw,
+    HeadersViewFormatted,
+    HeadersRawAction(text_editor::Action),
 
     BodyAction(text_editor::Action),
+    BodyLintDue(usize, u64),
+    BodyFileLoad,
+    BodyTemplateSelected(&'static str),
+    BodyTemplateConfirmed,
+    BodyTemplateCancelled,
+    BinaryBodyFileSelect,
+    BinaryBodyFileSelected(String),
+    BinaryContentTypeChanged(String),
+    GzipBodyToggled(bool),
+    DeleteBodyToggled(bool),
+    InsecureTlsToggled(bool),
+    InsecureTlsConfirmed,
+    InsecureTlsCancelled,
+    // A file dialog finished in `dir`, remembered before `then` is handled
+    FileDialogDone {
+        dir: std::path::PathBuf,
+        then: Box<Message>,
+    },
+    BodyFileSelected(String),
+    BodyFileLoadConfirmed,
+    BodyFileLoadCancelled,
+    // Tab id, the read finishes after the user may have switched tabs
+    BodyLoadedFromFile(usize, Result<String, String>),
     AuthTypeSelected(AuthType),
+    AuthPresetsLoaded(Option<Vec<AuthPreset>>),
+    AuthPresetSelected(usize),
+    AuthPresetNameChanged(String),
+    AuthPresetSave,
+    AuthPresetDelete(usize),
+    AuthPresetsSaved,
     ApiKeyNameChanged(String),
     ApiKeyChanged(String),
     ApiKeyPositionChanged(ApiKeyPosition),
     BearerTokenChanged(String),
+    BasicUsernameChanged(String),
+    BasicPasswordChanged(String),
+    OAuthTokenUrlChanged(String),
+    OAuthClientIdChanged(String),
+    OAuthClientSecretChanged(String),
+    OAuthScopeChanged(String),
+    OAuthGetToken,
+    SigningToggled(bool),
+    SigningAlgorithmSelected(SigningAlgorithm),
+    SigningEncodingSelected(SignatureEncoding),
+    SigningSecretChanged(String),
+    SigningHeaderNameChanged(String),
+    SigningSignPathToggled(bool),
+    SigningSignHeaderChanged(String),
+    SigningSignBodyToggled(bool),
+    // `send_after` is set when SendRequest had to fetch a token first
+    OAuthTokenFetched {
+        tab_id: usize,
+        send_after: bool,
+        result: Result<OAuthToken, String>,
+    },
     ContentTypeSelected(ContentType),
     SendRequest,
-    ResponseReceived(HttpResponse),
+    ResponseReceived(usize, HttpResponse),
     RequestTabSelected(RequestTab),
     ResponseTabSelected(ResponseTab),
+    ToggleJsonNode(String),
+    JsonImagesToggled(bool),
+    HtmlLinkClicked(String),
+    HexDumpLoadMore,
+    ResponseShowMore,
+    ResponseShowAll,
+    ImageZoomIn,
+    ImageZoomOut,
+    ImageFitToggled,
+    ImageWheelZoom(iced::mouse::ScrollDelta),
     ResponseBodyAction(text_editor::Action),
+    ResponseFilterChanged(String),
     ResponseHeadersAction(text_editor::Action),
-    PrettifyJson,
-    JsonPrettified(Result<String, String>),
+    PrettifyBody,
+    MinifyJson,
+    PrettifyGraphqlVariables,
+    BodyPrettified(Result<String, String>),
     CopyToClipboard,
+    CopyResponseBody,
+    CopyFullResponse,
+    ExportHar,
+    CopyAsCurl,
     ResetCopied,
     JsonThemeChanged(json_highlighter::JsonThemeWrapper),
+    ThemeEditorOpen,
+    ThemeEditorNameChanged(String),
+    ThemeEditorFieldSelected(usize),
+    ThemeEditorChannelChanged(usize, f32),
+    ThemeEditorHexChanged(String),
+    ThemeEditorSave,
+    ThemeEditorCancel,
     AppThemeChanged(iced::Theme),
     SaveRequest,
+    // The request was written to a file, so its edits count as saved
+    RequestFileSaved(usize, String),
     LoadRequest,
     RequestLoaded(SavedState),
+    DiscardChangesConfirmed,
+    DiscardChangesCancelled,
     RequestLoadFailed(String),
+    SendFailed(usize, String),
+    ShowToast(Toast),
+    DismissToast(usize),
+    ToastTick,
+    CurlImportOpen,
+    CurlImportClose,
+    CurlImportAction(text_editor::Action),
+    CurlImportConfirm,
+    // Settings
+    SettingsOpen,
+    SettingsClose,
+    HttpClientRebuildDue(u64),
+    FollowRedirectsToggled(bool),
+    ShowRedirectChainToggled(bool),
+    MaxRedirectsChanged(String),
+    RetryCountChanged(String),
+    RetryBackoffChanged(String),
+    RetryNonIdempotentToggled(bool),
+    ResponseCacheToggled(bool),
+    ResponseCacheCleared,
+    ProxyUrlChanged(String),
+    ClientCertSelect,
+    ClientCertChosen(String),
+    ClientKeySelect,
+    ClientKeyChosen(String),
+    ClientCertPasswordChanged(String),
+    ClientCertCleared,
+    PinnedHeaderAdd,
+    PinnedHeaderRemove(usize),
+    PinnedHeaderKeyChanged(usize, String),
+    PinnedHeaderValueChanged(usize, String),
+    PinnedHeaderToggled(usize),
+    ProxyUsernameChanged(String),
+    ProxyPasswordChanged(String),
     CancelRequest,
     SaveBinaryResponse,
-    FileSaved(Result<String, String>),
-    ClearResponseText,
+    SaveTextResponse,
+    UseResponseAsBody,
+    // Id of the tab whose response or request was saved
+    FileSaved(usize, Result<String, String>),
+    ClearResponse,
 
     // GraphQL
     GraphqlQueryAction(text_editor::Action),
@@ -100,18 +251,45 @@ enum Message {
 
     // Query params
     QueryParamAdd,
+    ListUndo,
+    ListRedo,
     QueryParamRemove(usize),
+    QueryParamDuplicate(usize),
     QueryParamKeyChanged(usize, String),
     QueryParamValueChanged(usize, String),
     QueryParamToggled(usize),
+    QueryParamMoveUp(usize),
+    QueryParamMoveDown(usize),
 
     // streaming response
-    StreamChunk(String),
-    StreamDone,
+    StreamChunk(usize, String),
+    SseEvent(usize, SseEvent),
+    StopStream,
+    StreamDone {
+        tab_id: usize,
+        wire_size: usize,
+        total: std::time::Duration,
+    },
+    DownloadProgress {
+        tab_id: usize,
+        received: u64,
+        total: Option<u64>,
+    },
+    StreamToFileProgress {
+        tab_id: usize,
+        received: u64,
+        total: Option<u64>,
+    },
+    RetryAttempt {
+        tab_id: usize,
+        attempt: u32,
+        max: u32,
+    },
 
     // WebSocket messages
     WsConnect,
     WsDisconnect,
+    WsReconnect,
     WsEvent(WsEvent),
     WsMessageInputChanged(String),
     WsSendMessage,
@@ -122,11 +300,13 @@ enum Message {
     WsBinaryMessageTypeSelected(WsBinaryMessageType),
 
     //Subscription
-    Tick,
+    Tick(iced::time::Instant),
 
     //Video
     TogglePause,
     VideoVolume(f64),
+    ToggleMute,
+    PlaybackSpeedChanged(f64),
     ToggleLoop,
     Seek(f64),
     SeekRelease,
@@ -136,15 +316,28 @@ enum Message {
     // Form data messages
     FormFieldKeyChanged(usize, String),
     FormFieldValueChanged(usize, String),
+    FormFieldContentTypeChanged(usize, String),
     FormFieldTypeSelected(usize, FormFieldType),
     FormFieldFileSelect(usize),
     FormFieldFilesSelected(usize, Vec<String>),
+    // Field index and the index of the file within it
+    FormFieldFileRemove(usize, usize),
     FormFieldRemove(usize),
+    FormFieldDuplicate(usize),
     FormFieldAdd,
     FormFieldToggled(usize),
+    FormFieldMoveUp(usize),
+    FormFieldMoveDown(usize),
     ViewRawForm,
     ViewFormattedForm,
     FormRawAction(text_editor::Action),
+    CaptureRuleAdd,
+    CaptureRuleRemove(usize),
+    CaptureRuleToggled(usize),
+    CaptureRuleNameChanged(usize, String),
+    CaptureRulePathChanged(usize, String),
+    CapturedVarRemove(String),
+    CapturedVarsClear,
 
     // Find/Replace messages
     ToggleFindDialog,
@@ -163,6 +356,12 @@ enum Message {
     StateLoaded(Option<SessionState>, Vec<TabMetadata>),
     SaveComplete,
 
+    ToggleLayout,
+    ToggleLineNumbers,
+    ToggleWrapLines,
+    PaneDividerPressed,
+    PaneDividerReset,
+
     // Sidebar
     ToggleSidebar,
     SidebarItemSelected(usize),
@@ -170,6 +369,8 @@ enum Message {
     CollectionFolderAdd(Option<usize>),
     CollectionItemToggleExpand(usize),
     CollectionRequestOpen(usize),
+    CollectionRequestFocus(usize),
+    CollectionItemMove(usize, isize),
     CollectionItemRename(usize),
     CollectionItemRenameInput(String),
     CollectionItemRenameConfirm(usize),
@@ -183,8 +384,140 @@ enum Message {
     SaveModalConfirm,
     SaveModalCancel,
     CollectionSaved,
+    PostmanImport,
+    PostmanImported(PostmanImport),
+    PostmanImportFailed(String),
+    // History
+    HistoryLoaded(Option<Vec<HistoryEntry>>),
+    HistoryToggle,
+    HistorySelected(usize),
+    ClearHistory,
+    HistorySaved,
+    RecentUrlsLoaded(Option<Vec<String>>),
+    RecentUrlsSaved,
+    UrlSuggestionPicked(String),
+    // Moves the highlight through the URL suggestions, down when true
+    UrlSuggestionMoved(bool),
+    // Enter in the URL bar, picks the highlighted suggestion or sends
+    UrlSubmitted,
 
     EventOccurred(Event),
+    WindowMonitorChecked(iced::window::Id, Option<iced::Size>),
+}
+
+impl Message {
+    // Messages that change the request itself, which a load would throw away
+    fn edits_request(&self) -> bool {
+        match self {
+            Message::BodyAction(action)
+            | Message::HeadersRawAction(action)
+            | Message::GraphqlQueryAction(action)
+            | Message::GraphqlVariablesAction(action)
+            | Message::FormRawAction(action) => action.is_edit(),
+            Message::UrlChanged(_)
+            | Message::UrlSuggestionPicked(_)
+            | Message::RequestNameChanged(_)
+            | Message::RequestDescriptionChanged(_)
+            | Message::RequestTypeSelected(_)
+            | Message::MethodSelected(_)
+            | Message::HeaderAdd
+            | Message::HeaderRemove(_)
+            | Message::HeaderKeyChanged(..)
+            | Message::HeaderValueChanged(..)
+            | Message::HeaderToggled(_)
+            | Message::HeaderSuggestionPicked(..)
+            | Message::HeaderValueSuggestionPicked(..)
+            | Message::BodyTemplateConfirmed
+            | Message::BinaryBodyFileSelected(_)
+            | Message::BinaryContentTypeChanged(_)
+            | Message::GzipBodyToggled(_)
+            | Message::DeleteBodyToggled(_)
+            | Message::InsecureTlsToggled(_)
+            | Message::AuthTypeSelected(_)
+            | Message::AuthPresetSelected(_)
+            | Message::ApiKeyNameChanged(_)
+            | Message::ApiKeyChanged(_)
+            | Message::ApiKeyPositionChanged(_)
+            | Message::BearerTokenChanged(_)
+            | Message::BasicUsernameChanged(_)
+            | Message::BasicPasswordChanged(_)
+            | Message::OAuthTokenUrlChanged(_)
+            | Message::OAuthClientIdChanged(_)
+            | Message::OAuthClientSecretChanged(_)
+            | Message::OAuthScopeChanged(_)
+            | Message::SigningToggled(_)
+            | Message::SigningAlgorithmSelected(_)
+            | Message::SigningEncodingSelected(_)
+            | Message::SigningSecretChanged(_)
+            | Message::SigningHeaderNameChanged(_)
+            | Message::SigningSignPathToggled(_)
+            | Message::SigningSignHeaderChanged(_)
+            | Message::SigningSignBodyToggled(_)
+            | Message::PaginationItemsPathChanged(_)
+            | Message::PaginationNextPathChanged(_)
+            | Message::PaginationCursorParamChanged(_)
+            | Message::PaginationMaxPagesChanged(_)
+            | Message::ContentTypeSelected(_)
+            | Message::PrettifyBody
+            | Message::MinifyJson
+            | Message::PrettifyGraphqlVariables
+            | Message::UseResponseAsBody
+            | Message::GraphqlOperationChanged(_)
+            | Message::GraphqlFieldToggled(_)
+            | Message::GraphqlArgToggled(_)
+            | Message::QueryParamAdd
+            | Message::QueryParamRemove(_)
+            | Message::QueryParamDuplicate(_)
+            | Message::QueryParamKeyChanged(..)
+            | Message::QueryParamValueChanged(..)
+            | Message::QueryParamToggled(_)
+            | Message::QueryParamMoveUp(_)
+            | Message::QueryParamMoveDown(_)
+            | Message::ListUndo
+            | Message::ListRedo
+            | Message::FormFieldKeyChanged(..)
+            | Message::FormFieldValueChanged(..)
+            | Message::FormFieldContentTypeChanged(..)
+            | Message::FormFieldTypeSelected(..)
+            | Message::FormFieldFilesSelected(..)
+            | Message::FormFieldFileRemove(..)
+            | Message::FormFieldRemove(_)
+            | Message::FormFieldDuplicate(_)
+            | Message::FormFieldAdd
+            | Message::FormFieldToggled(_)
+            | Message::FormFieldMoveUp(_)
+            | Message::FormFieldMoveDown(_)
+            | Message::CaptureRuleAdd
+            | Message::CaptureRuleRemove(_)
+            | Message::CaptureRuleToggled(_)
+            | Message::CaptureRuleNameChanged(..)
+            | Message::CaptureRulePathChanged(..) => true,
+            _ => false,
+        }
+    }
+
+    // Messages that change what the current tab's saved file holds besides the
+    // request: its response and view state. Responses routed by tab id mark their
+    // tab where they're stored
+    fn changes_saved_tab(&self) -> bool {
+        self.edits_request()
+            || matches!(
+                self,
+                Message::RequestTabSelected(_)
+                    | Message::ResponseTabSelected(_)
+                    | Message::ViewRawForm
+                    | Message::ViewFormattedForm
+                    | Message::WsMessageTypeSelected(_)
+                    | Message::WsBinaryMessageTypeSelected(_)
+                    | Message::GraphqlSchemaFetched(_)
+                    | Message::GraphqlTypeToggled(_)
+                    | Message::GraphqlCollapseAll
+                    | Message::LoadTestToggle
+                    | Message::PaginationToggle
+                    | Message::CancelRequest
+                    | Message::ClearResponse
+            )
+    }
 }
 
 struct CrabiPie {
@@ -196,6 +529,15 @@ struct CrabiPie {
     // text_editor contents , same for all tabs,
     request_body_content: text_editor::Content,
     raw_form_content: text_editor::Content,
+    raw_headers_content: text_editor::Content,
+    // Headers are edited as "Key: Value" lines instead of rows
+    headers_raw_mode: bool,
+    // Line number and reason for each raw header line that won't be sent
+    header_errors: Vec<(usize, String)>,
+    // Row whose key input is being typed in, it shows name suggestions
+    header_suggest_idx: Option<usize>,
+    // Row whose value is being typed in, it shows values common for its header
+    header_value_suggest_idx: Option<usize>,
     graphql_query_content: text_editor::Content,
     graphql_variables_content: text_editor::Content,
     response_body_content: text_editor::Content,
@@ -203,11 +545,19 @@ struct CrabiPie {
 
     // Global UI state (shared across all tabs)
     json_theme: json_highlighter::JsonThemeWrapper,
+    // Themes made in the theme editor, kept in json_themes.json
+    user_json_themes: Vec<json_highlighter::CustomJsonTheme>,
+    theme_editor: Option<ThemeEditor>,
     app_theme: iced::Theme,
     svg_rotation: f32,
+    // When the spinner last moved, it turns by the time passed rather than per tick
+    last_tick: Option<iced::time::Instant>,
 
     // Global cookie jar
     cookie_jar_open: bool,
+    // Transient notifications, newest last, dropped by ToastTick once expired
+    toasts: Vec<Toast>,
+    next_toast_id: usize,
     cookie_jar_new_domain: String,
     cookie_jar: std::collections::HashMap<String, Vec<CookieEntry>>,
     cookie_jar_error: Option<String>,
@@ -219,6 +569,7 @@ struct CrabiPie {
     replace_text: String,
     case_sensitive: bool,
     whole_word: bool,
+    find_target: FindTarget,
     current_match: usize,
     current_match_pos: Option<usize>,
     total_matches: usize,
@@ -235,16 +586,80 @@ struct CrabiPie {
     next_collection_id: usize,
     sidebar_editing_id: Option<usize>,
     sidebar_editing_name: String,
+    // A load waiting on "Discard unsaved changes?" for the current tab
+    pending_load: Option<SavedState>,
     // Save to collection modal
     save_modal_open: bool,
     save_modal_name: String,
     save_modal_folder_id: Option<usize>,
+    // Import from cURL modal
+    curl_import_open: bool,
+    curl_import_content: text_editor::Content,
+    curl_import_error: Option<String>,
+    // A body file over BODY_FILE_WARN_SIZE waiting for the user to confirm
+    body_file_pending: Option<(String, u64)>,
+    body_file_error: Option<String>,
+    // Request history, oldest first
+    history: Vec<HistoryEntry>,
+    history_open: bool,
+    // Newest first, from responses that came back, suggested while typing a URL
+    recent_urls: Vec<String>,
+    url_suggest_open: bool,
+    url_suggest_selected: Option<usize>,
+    // Named auth configs shared by all tabs, kept in auth_presets.json
+    auth_presets: Vec<AuthPreset>,
+    auth_preset_selected: Option<usize>,
+    auth_preset_name: String,
+    // Client settings, the client is rebuilt whenever they change
+    http_settings: HttpSettings,
+    http_client: reqwest::Client,
+    // Same settings, but accepts any certificate
+    insecure_http_client: reqwest::Client,
+    // Turning verification off asks once per run, the pending toggle waits here
+    insecure_tls_confirmed: bool,
+    insecure_tls_pending: bool,
+    settings_open: bool,
+    // Why the last settings change could not be applied, the previous client stays in use
+    settings_error: Option<String>,
+    // Bumped on each typed change to the client settings, only the latest one
+    // rebuilds once typing pauses. Pending until that rebuild has run
+    client_rebuild_generation: u64,
+    client_rebuild_pending: bool,
+    // Last known inner size, restored on the next launch
+    window_size: iced::Size,
+    // Last known position, None until the window reports one
+    window_position: Option<iced::Point>,
+    // Request and response panes side by side or stacked
+    layout: Layout,
+    // Line number gutter beside the body editors
+    show_line_numbers: bool,
+    // Wrap long lines in the body editors, they never wrap while line numbers show
+    wrap_lines: bool,
+    // Share of the space given to the request pane, the response gets the rest
+    pane_ratio: f32,
+    // Cursor position and ratio when the divider drag started
+    pane_drag: Option<(iced::Point, f32)>,
+    cursor_position: iced::Point,
+    // Folder of the last file opened or saved, where the next dialog starts
+    last_dir: Option<std::path::PathBuf>,
+    // Filled by capture rules, shared by all tabs and substituted for `{{name}}`
+    captured_vars: std::collections::BTreeMap<String, String>,
+    // Last validated GET responses by "GET url", only filled while caching is on
+    response_cache: std::collections::HashMap<String, CachedResponse>,
+    // JSONPath-style paths of the open nodes in the Tree tab, reset by each response
+    json_tree_expanded: std::collections::HashSet<String>,
+    // Image responses, shared by all tabs. Fitted to the pane unless zoomed
+    image_zoom: f32,
+    image_fit: bool,
 }
 
 struct TabState {
     metadata: TabMetadata,
     id: usize,
     title: String,
+    // User given name and notes, the name shows in the window title
+    name: String,
+    description: String,
 
     // Request configuration
     url_id: iced::widget::Id,
@@ -256,14 +671,54 @@ struct TabState {
     form_view_type: FormViewType,
     auth_type: AuthType,
     bearer_token: String,
+    basic_username: String,
+    basic_password: String,
     api_key_name: String,
     api_key: String,
     api_key_position: ApiKeyPosition,
+    // OAuth2 client credentials, the secret and fetched token are never saved
+    oauth_token_url: String,
+    oauth_client_id: String,
+    oauth_client_secret: String,
+    oauth_scope: String,
+    oauth_access_token: String,
+    oauth_expires_at: Option<std::time::Instant>,
+    // Set by a fetch that sends right after, that send uses the token even if it's
+    // inside the expiry margin, a very short-lived one would otherwise be fetched forever
+    oauth_just_fetched: bool,
+    oauth_fetching: bool,
+    oauth_error: Option<String>,
+    // HMAC signing step run before send, the secret is never saved
+    signing: RequestSigning,
+    signing_secret: String,
     content_type: ContentType,
+    // Raw file body, read from disk when the request is sent
+    binary_body_path: String,
+    binary_body_size: Option<u64>,
+    binary_content_type: String,
+    binary_body_error: Option<String>,
+    // Send raw bodies with Content-Encoding: gzip
+    gzip_body: bool,
+    // DELETE goes out without a body unless asked, many servers reject one
+    delete_body: bool,
+    // Skip certificate checks, for dev servers with self-signed certs
+    insecure_tls: bool,
     query_params: Vec<QueryParam>,
     form_data: Vec<FormField>,
+    // Files of the last send that couldn't be read, shown above the form fields
+    form_data_errors: Vec<String>,
+    // A template waiting for the user to agree to replace what they wrote
+    body_template_pending: Option<&'static str>,
+    // Sizes of chosen form files by path, read when they're picked, not on every frame
+    form_file_sizes: std::collections::HashMap<String, u64>,
+    // Param and form field lists before each add, remove, move or toggle, for Ctrl+Z
+    list_undo: Vec<ListSnapshot>,
+    list_redo: Vec<ListSnapshot>,
     raw_form_content: std::sync::Arc<str>,
     cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    // Aborts the in-flight request task, dropping it closes the connection.
+    // Set to abort on drop, so a replaced request or closed tab stops its download too
+    request_handle: Option<iced::task::Handle>,
 
     // GraphQL Editors ---
     graphql_query: std::sync::Arc<str>,
@@ -272,6 +727,16 @@ struct TabState {
     graphql_schema: Option<GraphqlSchema>,
     graphql_schema_loading: bool,
     graphql_schema_error: Option<String>,
+    // Set when a send was refused because the variables aren't valid JSON
+    graphql_variables_error: Option<String>,
+    // Parse result of a JSON request body, None while empty or not yet checked
+    is_body_valid_json: Option<Result<(), String>>,
+    body_json_error_at: Option<(usize, usize)>,
+    // Bumped on every body edit, only the last scheduled lint runs
+    body_lint_generation: u64,
+    // JSONPath expression narrowing the response editor, the body itself is kept whole
+    response_filter: String,
+    response_filter_error: Option<String>,
     graphql_expanded_types: std::collections::HashSet<String>,
     graphql_search: String,
     graphql_selected_paths: std::collections::HashSet<String>,
@@ -294,6 +759,16 @@ struct TabState {
 
     //image handle
     image_handle: Option<iced::widget::image::Handle>,
+    image_size: Option<(u32, u32)>,
+    // Parsed once the body is complete, only for text/html responses
+    response_html: Vec<HtmlBlock>,
+    // Events received so far on a text/event-stream response
+    sse_event_count: usize,
+    // Set-Cookie headers of the last response, one entry per header line
+    response_cookies: Vec<ResponseCookie>,
+    // Hex view of the first hex_dump_len bytes of a binary response that isn't media
+    hex_dump: String,
+    hex_dump_len: usize,
 
     // Video response
     video_player: Option<iced_video_player::Video>,
@@ -302,20 +777,79 @@ struct TabState {
     // Response data
     response_status: String,
     response_body: std::sync::Arc<str>,
+    // The pretty or filtered text the response editor shows, only the first
+    // response_shown_len bytes of it are loaded so a huge body doesn't hang the UI
+    response_display: std::sync::Arc<str>,
+    response_shown_len: usize,
     response_headers: std::sync::Arc<str>,
+    // Structured copy of the last response's headers, not saved with the tab
+    response_header_list: Vec<(String, String)>,
+    // Status line and headers in arrival order, not saved with the tab either
+    response_raw_head: String,
+    // Raw tab text, rebuilt when the response changes rather than on every view
+    response_raw: String,
     is_response_binary: bool,
     response_filename: String,
     response_bytes: Vec<u8>,
     response_content_type: String,
     response_time: Option<std::time::Duration>,
+    response_timing: Option<ResponseTiming>,
+    // What the last send put on the wire, HAR export describes this request
+    sent_request: Option<SentRequest>,
+    // Bytes received so far and the Content-Length, while a binary body downloads
+    download_progress: Option<(u64, Option<u64>)>,
+    // Send and Download target, the body is written here instead of kept in memory
+    download_path: Option<std::path::PathBuf>,
+    // Retry number and the most retries allowed, once the first attempt failed
+    retry_attempt: Option<(u32, u32)>,
+    // Content-Encoding of the last response and its size before decoding
+    response_encoding: String,
+    response_wire_size: usize,
+    // Cache entry the request in flight may be answered from, and whether the
+    // shown body came from it after a 304
+    cache_key: Option<String>,
+    response_from_cache: bool,
+    // The text body before the last one and the line diff against it, for the Diff tab
+    previous_response_body: Option<String>,
+    response_diff: Option<Vec<DiffLine>>,
+    // Parsed body for the Tree tab, None when the response isn't JSON
+    response_json: Option<std::sync::Arc<serde_json::Value>>,
+    // Images found in response_json strings by tree path, only decoded while
+    // render_json_images is on
+    render_json_images: bool,
+    response_images: std::collections::HashMap<String, iced::widget::image::Handle>,
+    // "Send N times", shown as a response tab while open
+    load_test_open: bool,
+    load_test_count: usize,
+    load_test_concurrency: usize,
+    load_test: Option<LoadTestRun>,
+    // Bumped per run, results of a stopped run are dropped
+    load_test_run: u64,
+    // Follows next links and gathers the items of every page, shown as a response tab
+    pagination: Pagination,
+    pagination_open: bool,
+    pagination_run: Option<PaginationRun>,
+    pagination_run_id: u64,
+
+    // Values pulled out of each response into `{{name}}` variables
+    capture_rules: Vec<CaptureRule>,
+    // Rules that found nothing in the last response, by index
+    capture_errors: Vec<(usize, String)>,
 
     // Tab-specific UI state
     loading: bool,
+    // When Send was pressed, for the elapsed counter in the loading overlay
+    loading_started: Option<std::time::Instant>,
+    // Wall clock time of the last send, the HAR export's startedDateTime
+    sent_at: Option<chrono::DateTime<chrono::Local>>,
     active_request_tab: RequestTab,
     active_response_tab: ResponseTab,
     copied: bool,
     ws_connection_id: usize,
     dirty: bool,
+    // Request edits since it was last saved or loaded, unlike dirty which only
+    // tracks what the session autosave still has to write
+    unsaved_edits: bool,
 }
 
 impl TabState {
@@ -327,9 +861,12 @@ impl TabState {
                 url: "https://jsonplaceholder.typicode.com/posts".to_string(),
                 method: HttpMethod::GET,
                 request_type: RequestType::HTTP,
+                collection_request_id: None,
             },
             id,
             title: format!("Request {}", id + 1),
+            name: String::new(),
+            description: String::new(),
             url_id: iced::widget::Id::unique(),
             url: "https://jsonplaceholder.typicode.com/posts".to_string(),
             request_type: RequestType::HTTP,
@@ -339,26 +876,90 @@ impl TabState {
             form_view_type: FormViewType::Formatted,
             auth_type: AuthType::None,
             bearer_token: String::new(),
+            basic_username: String::new(),
+            basic_password: String::new(),
             api_key_name: String::new(),
             api_key: String::new(),
             api_key_position: ApiKeyPosition::Header,
+            oauth_token_url: String::new(),
+            oauth_client_id: String::new(),
+            oauth_client_secret: String::new(),
+            oauth_scope: String::new(),
+            signing: RequestSigning::default(),
+            signing_secret: String::new(),
+            oauth_access_token: String::new(),
+            oauth_expires_at: None,
+            oauth_just_fetched: false,
+            oauth_fetching: false,
+            oauth_error: None,
             content_type: ContentType::Json,
+            binary_body_path: String::new(),
+            binary_body_size: None,
+            binary_content_type: String::new(),
+            binary_body_error: None,
+            gzip_body: false,
+            delete_body: false,
+            insecure_tls: false,
             query_params: vec![QueryParam::new()],
             form_data: vec![FormField::new()],
+            form_data_errors: Vec::new(),
+            body_template_pending: None,
+            form_file_sizes: std::collections::HashMap::new(),
+            list_undo: Vec::new(),
+            list_redo: Vec::new(),
             raw_form_content: std::sync::Arc::from(""),
             image_handle: None,
+            image_size: None,
+            response_html: Vec::new(),
+            sse_event_count: 0,
+            response_cookies: Vec::new(),
+            hex_dump: String::new(),
+            hex_dump_len: 0,
             video_player: None,
             video_state: None,
             cancel_flag: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            request_handle: None,
             response_status: String::new(),
             response_headers: std::sync::Arc::from(""),
+            response_header_list: Vec::new(),
+            response_raw_head: String::new(),
+            response_raw: String::new(),
             response_body: std::sync::Arc::from(""),
+            response_display: std::sync::Arc::from(""),
+            response_shown_len: 0,
             is_response_binary: false,
             response_filename: String::new(),
             response_bytes: Vec::new(),
+            download_progress: None,
+            download_path: None,
+            retry_attempt: None,
+            response_encoding: String::new(),
+            response_wire_size: 0,
+            cache_key: None,
+            response_from_cache: false,
+            previous_response_body: None,
+            response_diff: None,
+            response_json: None,
+            render_json_images: false,
+            response_images: std::collections::HashMap::new(),
+            load_test_open: false,
+            load_test_count: 50,
+            load_test_concurrency: 10,
+            load_test: None,
+            load_test_run: 0,
+            pagination: Pagination::default(),
+            pagination_open: false,
+            pagination_run: None,
+            pagination_run_id: 0,
             response_content_type: String::new(),
             response_time: None,
+            response_timing: None,
+            sent_request: None,
             loading: false,
+            loading_started: None,
+            sent_at: None,
+            capture_rules: Vec::new(),
+            capture_errors: Vec::new(),
             active_request_tab: RequestTab::Query,
             active_response_tab: ResponseTab::Body,
             copied: false,
@@ -380,25 +981,38 @@ impl TabState {
             graphql_schema: None,
             graphql_schema_loading: false,
             graphql_schema_error: None,
+            graphql_variables_error: None,
+            is_body_valid_json: None,
+            body_json_error_at: None,
+            body_lint_generation: 0,
+            response_filter: String::new(),
+            response_filter_error: None,
             graphql_expanded_types: std::collections::HashSet::new(),
             graphql_search: String::new(),
             graphql_selected_paths: std::collections::HashSet::new(),
             manually_selected_paths: std::collections::HashSet::new(),
-            dirty: false,
+            // Not written yet, the next autosave picks it up
+            dirty: true,
+            unsaved_edits: false,
         }
     }
 
     fn from_saved(saved: SavedState) -> Self {
-        Self {
+        let response_body: std::sync::Arc<str> =
+            std::sync::Arc::from(saved.response_body.unwrap_or_default().as_str());
+        let mut tab = Self {
             metadata: TabMetadata {
                 id: saved.id,
                 title: saved.title.clone(),
                 url: saved.url.clone(),
                 method: saved.method,
                 request_type: saved.request_type,
+                collection_request_id: None,
             },
             id: saved.id,
             title: saved.title,
+            name: saved.name,
+            description: saved.description,
             url_id: iced::widget::Id::unique(),
             url: saved.url,
             request_type: saved.request_type,
@@ -408,31 +1022,107 @@ impl TabState {
             form_view_type: saved.form_view_type,
             auth_type: saved.auth_type,
             bearer_token: saved.bearer_token,
+            basic_username: saved.basic_username,
+            basic_password: saved.basic_password,
             api_key_name: saved.api_key_name,
             api_key: saved.api_key,
             api_key_position: saved.api_key_position,
+            oauth_token_url: saved.oauth_token_url,
+            oauth_client_id: saved.oauth_client_id,
+            oauth_client_secret: String::new(),
+            oauth_scope: saved.oauth_scope,
+            signing: saved.signing,
+            signing_secret: String::new(),
+            oauth_access_token: String::new(),
+            oauth_expires_at: None,
+            oauth_just_fetched: false,
+            oauth_fetching: false,
+            oauth_error: None,
             content_type: saved.content_type,
+            binary_body_size: std::fs::metadata(&saved.binary_body_path)
+                .ok()
+                .map(|m| m.len()),
+            binary_body_path: saved.binary_body_path,
+            binary_content_type: saved.binary_content_type,
+            binary_body_error: None,
+            gzip_body: saved.gzip_body,
+            delete_body: saved.delete_body,
+            insecure_tls: saved.insecure_tls,
             query_params: saved.query_params,
+            form_file_sizes: file_sizes(saved.form_data.iter().flat_map(|f| &f.files)),
             form_data: saved.form_data,
+            form_data_errors: Vec::new(),
+            body_template_pending: None,
+            list_undo: Vec::new(),
+            list_redo: Vec::new(),
             raw_form_content: std::sync::Arc::from(saved.raw_form_content.as_str()),
             image_handle: None,
+            image_size: None,
+            response_html: Vec::new(),
+            sse_event_count: 0,
+            response_cookies: Vec::new(),
+            hex_dump: String::new(),
+            hex_dump_len: 0,
             video_player: None,
             video_state: None,
             cancel_flag: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            request_handle: None,
             response_status: saved.response_status.unwrap_or_default(),
             response_headers: std::sync::Arc::from(
                 saved.response_headers.unwrap_or_default().as_str(),
             ),
+            response_header_list: Vec::new(),
+            response_raw_head: String::new(),
+            response_raw: String::new(),
 
-            response_body: std::sync::Arc::from(saved.response_body.unwrap_or_default().as_str()),
+            response_shown_len: response_display_cut(&response_body, 0, RESPONSE_DISPLAY_PAGE),
+            response_display: response_body.clone(),
+            response_body,
             is_response_binary: false,
             response_filename: String::new(),
             response_bytes: Vec::new(),
+            download_progress: None,
+            download_path: None,
+            retry_attempt: None,
+            response_encoding: String::new(),
+            response_wire_size: 0,
+            cache_key: None,
+            response_from_cache: false,
+            previous_response_body: None,
+            response_diff: None,
+            response_json: None,
+            render_json_images: false,
+            response_images: std::collections::HashMap::new(),
+            load_test_open: false,
+            load_test_count: 50,
+            load_test_concurrency: 10,
+            load_test: None,
+            load_test_run: 0,
+            pagination: saved.pagination,
+            pagination_open: false,
+            pagination_run: None,
+            pagination_run_id: 0,
             response_content_type: String::new(),
             response_time: None,
+            response_timing: None,
+            sent_request: None,
             loading: false,
+            loading_started: None,
+            sent_at: None,
+            capture_rules: saved.capture_rules,
+            capture_errors: Vec::new(),
             active_request_tab: saved.active_request_tab,
-            active_response_tab: saved.active_response_tab,
+            // The preview isn't saved, it comes back with the next response
+            active_response_tab: match saved.active_response_tab {
+                ResponseTab::Preview
+                | ResponseTab::Raw
+                | ResponseTab::Cookies
+                | ResponseTab::Diff
+                | ResponseTab::Tree
+                | ResponseTab::LoadTest
+                | ResponseTab::Paginate => ResponseTab::Body,
+                other => other,
+            },
             copied: false,
             ws_connected: false,
             ws_input: String::new(),
@@ -452,18 +1142,162 @@ impl TabState {
             graphql_schema: saved.graphql_schema,
             graphql_schema_loading: false,
             graphql_schema_error: saved.graphql_schema_error,
+            graphql_variables_error: None,
+            is_body_valid_json: None,
+            body_json_error_at: None,
+            body_lint_generation: 0,
+            response_filter: String::new(),
+            response_filter_error: None,
             graphql_expanded_types: saved.graphql_expanded_types,
             graphql_search: String::new(),
             graphql_selected_paths: saved.graphql_selected_paths,
             manually_selected_paths: saved.manually_selected_paths,
             dirty: false,
+            unsaved_edits: false,
+        };
+        tab.refresh_raw_response();
+        tab
+    }
+
+    // The request's name, or the URL host while it has none
+    fn display_name(&self) -> String {
+        let name = self.name.trim();
+        if name.is_empty() {
+            extract_domain(&self.url).unwrap_or_default()
+        } else {
+            name.to_string()
+        }
+    }
+
+    // Called before a structural edit of the param or form lists. A new edit
+    // drops whatever could have been redone
+    fn snapshot_lists(&mut self) {
+        self.list_undo.push(ListSnapshot {
+            query_params: self.query_params.clone(),
+            form_data: self.form_data.clone(),
+        });
+        if self.list_undo.len() > LIST_UNDO_LIMIT {
+            self.list_undo.remove(0);
+        }
+        self.list_redo.clear();
+    }
+
+    // Swaps the lists with the newest snapshot of one stack, the current ones go on the other
+    fn restore_lists(&mut self, redo: bool) -> bool {
+        let (from, to) = if redo {
+            (&mut self.list_redo, &mut self.list_undo)
+        } else {
+            (&mut self.list_undo, &mut self.list_redo)
+        };
+        let Some(snapshot) = from.pop() else {
+            return false;
+        };
+        to.push(ListSnapshot {
+            query_params: std::mem::replace(&mut self.query_params, snapshot.query_params),
+            form_data: std::mem::replace(&mut self.form_data, snapshot.form_data),
+        });
+        true
+    }
+
+    // Whether an HTTP request of this tab carries the body editor's content
+    fn sends_body(&self) -> bool {
+        method_has_body(self.method) && (self.method != HttpMethod::DELETE || self.delete_body)
+    }
+
+    // Every file of the enabled multipart file fields must open, otherwise
+    // request_body would fail the whole build over it
+    fn validate_form_data(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        for field in self
+            .form_data
+            .iter()
+            .filter(|f| f.enabled && !f.key.is_empty() && f.field_type == FormFieldType::File)
+        {
+            if field.files.is_empty() {
+                errors.push(format!("{}: no file chosen", field.key));
+            }
+            for fp in &field.files {
+                if let Some(error) = readable_file_error(fp) {
+                    errors.push(format!("{}: {}", field.key, error));
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Treated as expired a little early, so it doesn't run out mid-request
+    fn oauth_token_valid(&self) -> bool {
+        !self.oauth_access_token.is_empty()
+            && self
+                .oauth_expires_at
+                .is_none_or(|at| std::time::Instant::now() + OAUTH_EXPIRY_MARGIN < at)
+    }
+
+    // The pretty body, or what the response filter picks out of it
+    fn refresh_response_display(&mut self) {
+        let display = if self.response_filter.trim().is_empty() {
+            self.response_filter_error = None;
+            pretty_response_text(
+                &self.response_body,
+                &self.response_content_type,
+                self.response_json.as_deref(),
+            )
+        } else {
+            let (display, error) = filter_json_response(&self.response_body, &self.response_filter);
+            self.response_filter_error = error;
+            display
+        };
+        self.set_response_display(&display);
+    }
+
+    fn set_response_display(&mut self, text: &str) {
+        self.response_display = std::sync::Arc::from(text);
+        self.response_shown_len = response_display_cut(text, 0, RESPONSE_DISPLAY_PAGE);
+    }
+
+    // The response as it arrived, headers in order and the body without reformatting.
+    // The body is shown after content and charset decoding, a binary one only by size
+    fn raw_response(&self, limit: usize) -> String {
+        let mut out = format!("{}\n\n", self.response_raw_head);
+        if self.is_response_binary {
+            out.push_str(&format!(
+                "[{} of binary data]",
+                CrabiPie::human_size(self.response_bytes.len())
+            ));
+        } else {
+            out.push_str(
+                &self.response_body[..response_display_cut(&self.response_body, 0, limit)],
+            );
         }
+        out
+    }
+
+    fn refresh_raw_response(&mut self) {
+        self.response_raw = self.raw_response(RESPONSE_DISPLAY_PAGE);
+    }
+
+    fn response_editor_content(&self) -> text_editor::Content {
+        text_editor::Content::with_text(&self.response_display[..self.response_shown_len])
+    }
+
+    // Called when the client settings change, the old token may not match them
+    fn forget_oauth_token(&mut self) {
+        self.oauth_access_token.clear();
+        self.oauth_expires_at = None;
+        self.oauth_just_fetched = false;
+        self.oauth_error = None;
     }
 
     fn to_saved(&self, json_theme: &str, app_theme: &str) -> SavedState {
         SavedState {
             id: self.id,
             title: self.title.clone(),
+            name: self.name.clone(),
+            description: self.description.clone(),
             url: self.url.clone(),
             request_type: self.request_type.clone(),
             method: self.method.clone(),
@@ -472,10 +1306,22 @@ impl TabState {
             form_view_type: self.form_view_type,
             auth_type: self.auth_type.clone(),
             bearer_token: self.bearer_token.clone(),
+            basic_username: self.basic_username.clone(),
+            basic_password: self.basic_password.clone(),
             api_key_name: self.api_key_name.clone(),
             api_key: self.api_key.clone(),
             api_key_position: self.api_key_position,
+            oauth_token_url: self.oauth_token_url.clone(),
+            oauth_client_id: self.oauth_client_id.clone(),
+            oauth_scope: self.oauth_scope.clone(),
+            signing: self.signing.clone(),
+            pagination: self.pagination.clone(),
             content_type: self.content_type.clone(),
+            binary_body_path: self.binary_body_path.clone(),
+            binary_content_type: self.binary_content_type.clone(),
+            gzip_body: self.gzip_body,
+            delete_body: self.delete_body,
+            insecure_tls: self.insecure_tls,
             query_params: self.query_params.clone(),
             form_data: self.form_data.clone(),
             raw_form_content: self.raw_form_content.to_string(),
@@ -486,7 +1332,7 @@ impl TabState {
             } else {
                 Some(self.response_status.clone())
             },
-            response_headers: Some(self.response_body.to_string()),
+            response_headers: Some(self.response_headers.to_string()),
             response_body: Some(self.response_body.to_string()),
             ws_message_type: self.ws_message_type,
             ws_binary_message_type: self.ws_binary_message_type,
@@ -499,6 +1345,7 @@ impl TabState {
             graphql_selected_paths: self.graphql_selected_paths.clone(),
             manually_selected_paths: self.manually_selected_paths.clone(),
 
+            capture_rules: self.capture_rules.clone(),
             active_request_tab: self.active_request_tab,
             active_response_tab: self.active_response_tab,
         }
@@ -544,6 +1391,55 @@ impl TabState {
                     enabled,
                     field_type: FormFieldType::Text,
                     files: vec![],
+                    content_type: None,
+                })
+            })
+            .collect()
+    }
+
+    // Same line format as the raw form view, disabled headers are commented out
+    fn headers_to_raw(headers: &[RequestHeaders]) -> String {
+        headers
+            .iter()
+            .filter(|h| !h.key.is_empty() || !h.value.is_empty())
+            .map(|h| {
+                if h.missing_colon {
+                    return h.key.clone();
+                }
+                let line = format!("{}: {}", h.key, h.value);
+                if h.enabled {
+                    line
+                } else {
+                    format!("# {}", line)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // A `#` line without ':' is a comment and dropped, any other such line
+    // becomes a row flagged as missing its ':'
+    fn raw_to_headers(raw: &str) -> Vec<RequestHeaders> {
+        raw.lines()
+            .filter_map(|line| {
+                let line = line.trim();
+                let (enabled, content) = match line.strip_prefix('#') {
+                    Some(rest) => (false, rest),
+                    None => (true, line),
+                };
+                let Some((key, value)) = content.split_once(':') else {
+                    return (enabled && !line.is_empty()).then(|| RequestHeaders {
+                        key: line.to_string(),
+                        value: String::new(),
+                        enabled,
+                        missing_colon: true,
+                    });
+                };
+                Some(RequestHeaders {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                    enabled,
+                    missing_colon: false,
                 })
             })
             .collect()
@@ -559,14 +1455,18 @@ impl CrabiPie {
             json_theme: json_highlighter::JsonThemeWrapper::Custom(
                 json_highlighter::CustomJsonTheme::VSCODE_DARK,
             ),
+            user_json_themes: load_user_json_themes(),
+            theme_editor: None,
             app_theme: iced::Theme::CatppuccinMocha,
             svg_rotation: 0.0,
+            last_tick: None,
             find_dialog_open: false,
             find_replace_mode: false,
             find_text: String::new(),
             replace_text: String::new(),
             case_sensitive: false,
             whole_word: false,
+            find_target: FindTarget::ResponseBody,
             current_match: 0,
             current_match_pos: None,
             total_matches: 0,
@@ -579,14 +1479,60 @@ impl CrabiPie {
             next_collection_id: 1,
             sidebar_editing_id: None,
             sidebar_editing_name: String::new(),
+            pending_load: None,
             save_modal_open: false,
             save_modal_name: String::new(),
             save_modal_folder_id: None,
+            curl_import_open: false,
+            curl_import_content: text_editor::Content::new(),
+            curl_import_error: None,
+            body_file_pending: None,
+            body_file_error: None,
+            history: Vec::new(),
+            history_open: true,
+            recent_urls: Vec::new(),
+            url_suggest_open: false,
+            url_suggest_selected: None,
+            auth_presets: Vec::new(),
+            auth_preset_selected: None,
+            auth_preset_name: String::new(),
+            http_settings: HttpSettings::default(),
+            http_client: build_http_client(&HttpSettings::default(), false)
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            insecure_http_client: build_http_client(&HttpSettings::default(), true)
+                .unwrap_or_else(|_| reqwest::Client::new()),
+            insecure_tls_confirmed: false,
+            insecure_tls_pending: false,
+            settings_open: false,
+            settings_error: None,
+            client_rebuild_generation: 0,
+            client_rebuild_pending: false,
+            window_size: DEFAULT_WINDOW_SIZE,
+            window_position: None,
+            layout: Layout::Horizontal,
+            show_line_numbers: false,
+            wrap_lines: true,
+            pane_ratio: DEFAULT_PANE_RATIO,
+            pane_drag: None,
+            cursor_position: iced::Point::ORIGIN,
+            last_dir: None,
+            captured_vars: std::collections::BTreeMap::new(),
+            json_tree_expanded: std::collections::HashSet::new(),
+            response_cache: std::collections::HashMap::new(),
+            image_zoom: 1.0,
+            image_fit: true,
             cookie_jar_open: false,
+            toasts: Vec::new(),
+            next_toast_id: 0,
             cookie_jar_new_domain: String::new(),
             cookie_jar: std::collections::HashMap::new(),
             cookie_jar_error: None,
             raw_form_content: text_editor::Content::new(),
+            raw_headers_content: text_editor::Content::new(),
+            headers_raw_mode: false,
+            header_errors: Vec::new(),
+            header_suggest_idx: None,
+            header_value_suggest_idx: None,
             request_body_content: text_editor::Content::new(),
             graphql_query_content: text_editor::Content::new(),
             graphql_variables_content: text_editor::Content::new(),
@@ -599,13 +1545,19 @@ impl CrabiPie {
                 Message::StateLoaded(session, metadata)
             }),
             iced::Task::perform(load_collection(), Message::CollectionLoaded),
+            iced::Task::perform(load_history(), Message::HistoryLoaded),
+            iced::Task::perform(load_recent_urls(), Message::RecentUrlsLoaded),
+            iced::Task::perform(load_auth_presets(), Message::AuthPresetsLoaded),
         ]);
 
         (app, task)
     }
 
     fn title(&self) -> String {
-        "CrabiPie".to_string()
+        match self.current_tab().map(TabState::display_name) {
+            Some(name) if !name.is_empty() => format!("{name} - CrabiPie"),
+            _ => "CrabiPie".to_string(),
+        }
     }
 
     fn current_tab(&self) -> Option<&TabState> {
@@ -616,13 +1568,21 @@ impl CrabiPie {
         }
     }
 
+    fn is_active_tab(&self, id: usize) -> bool {
+        self.current_tab().is_some_and(|tab| tab.id == id)
+    }
+
+    fn tab_mut_by_id(&mut self, id: usize) -> Option<&mut TabState> {
+        self.tabs.iter_mut().find_map(|slot| match slot {
+            TabLoadState::Loaded(state) if state.id == id => Some(state.as_mut()),
+            _ => None,
+        })
+    }
+
     fn current_tab_mut(&mut self) -> Option<&mut TabState> {
         let slot = self.tabs.get_mut(self.active_tab)?;
         match slot {
-            TabLoadState::Loaded(state) => {
-                state.dirty = true;
-                Some(state)
-            }
+            TabLoadState::Loaded(state) => Some(state),
             _ => None,
         }
     }
@@ -651,12 +1611,21 @@ impl CrabiPie {
         }
     }
 
-    fn get_highlighter_settings(&self) -> json_highlighter::JsonHighlighterSettings {
-        json_highlighter::JsonHighlighterSettings::new(self.json_theme).with_search(
-            self.search_match_positions.clone(),
-            self.current_match_line_col,
-            self.search_match_length,
-        )
+    // Search matches are only passed to the editor the find dialog is searching
+    fn get_highlighter_settings(
+        &self,
+        target: Option<FindTarget>,
+    ) -> json_highlighter::JsonHighlighterSettings {
+        let settings = json_highlighter::JsonHighlighterSettings::new(self.json_theme.clone());
+        if self.find_dialog_open && target == Some(self.find_target) {
+            settings.with_search(
+                self.search_match_positions.clone(),
+                self.current_match_line_col,
+                self.search_match_length,
+            )
+        } else {
+            settings
+        }
     }
 
     fn view_find_replace(&self) -> Element<'_, Message> {
@@ -682,6 +1651,13 @@ impl CrabiPie {
             .into();
 
         let find_btns_row = row![
+            tooltip(
+                button(text("🔼").shaping(text::Shaping::Advanced))
+                    .style(button::text)
+                    .on_press(Message::FindPrevious),
+                "Find Previous",
+                tooltip::Position::Bottom
+            ),
             tooltip(
                 button(text("🔍").shaping(text::Shaping::Advanced))
                     .style(button::text)
@@ -712,7 +1688,7 @@ impl CrabiPie {
                 tooltip(
                     button(text("✏️").shaping(text::Shaping::Advanced))
                         .style(button::text)
-                        .on_press(Message::FindNext),
+                        .on_press(Message::Replace),
                     "Replace Next",
                     tooltip::Position::Bottom
                 ),
@@ -731,12 +1707,16 @@ impl CrabiPie {
         };
 
         let match_info: Element<'_, Message> = if !self.find_text.is_empty() {
-            text(format!(
-                "{} / {} matches",
-                self.current_match, self.total_matches,
-            ))
-            .align_y(iced::Alignment::Center)
-            .into()
+            let target = match self.find_target {
+                FindTarget::RequestBody => "request",
+                FindTarget::ResponseBody => "response",
+            };
+            let info = match (self.current_match, self.total_matches) {
+                (_, 0) => format!("No matches in {}", target),
+                (0, total) => format!("{} matches in {}", total, target),
+                (current, total) => format!("{} of {} in {}", current, total, target),
+            };
+            text(info).align_y(iced::Alignment::Center).into()
         } else {
             Space::new().into()
         };
@@ -789,159 +1769,184 @@ impl CrabiPie {
             .into()
     }
 
-    fn position_to_line_col(text: &str, byte_pos: usize) -> (usize, usize) {
-        let mut line_idx = 0;
+    // Converts ascending byte offsets into (line, char column) pairs in a single pass
+    fn positions_to_line_col(text: &str, byte_positions: &[usize]) -> Vec<(usize, usize)> {
+        let mut result = Vec::with_capacity(byte_positions.len());
+        let mut positions = byte_positions.iter().peekable();
         let mut line_start_byte = 0;
 
-        for line in text.split_inclusive('\n') {
+        for (line_idx, line) in text.split_inclusive('\n').enumerate() {
             let line_end_byte = line_start_byte + line.len();
 
-            if byte_pos >= line_start_byte && byte_pos < line_end_byte {
-                let byte_offset_in_line = byte_pos - line_start_byte;
-
+            while let Some(&&pos) = positions.peek() {
+                if pos >= line_end_byte {
+                    break;
+                }
                 // Convert byte offset → char offset
-                let char_offset = line[..byte_offset_in_line].chars().count();
-
-                return (line_idx, char_offset);
+                let char_offset = line[..pos - line_start_byte].chars().count();
+                result.push((line_idx, char_offset));
+                positions.next();
             }
 
             line_start_byte = line_end_byte;
-            line_idx += 1;
         }
 
-        (0, 0)
+        result
+    }
+
+    fn is_word_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    // Returns the byte length of the match if `rest` starts with the lowercased pattern
+    fn match_ignore_case(rest: &str, pattern_lower: &[char]) -> Option<usize> {
+        let mut idx = 0;
+        for (offset, ch) in rest.char_indices() {
+            for lc in ch.to_lowercase() {
+                if idx >= pattern_lower.len() || pattern_lower[idx] != lc {
+                    return None;
+                }
+                idx += 1;
+            }
+            if idx == pattern_lower.len() {
+                return Some(offset + ch.len_utf8());
+            }
+        }
+        None
     }
 
-    fn find_matches(&self, text: &str, pattern: &str) -> Vec<usize> {
+    // Byte ranges of every match, including overlapping ones
+    fn find_matches(&self, text: &str, pattern: &str) -> Vec<std::ops::Range<usize>> {
         if pattern.is_empty() {
             return Vec::new();
         }
 
+        let pattern_lower: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
         let mut matches = Vec::new();
 
-        if self.case_sensitive {
-            if self.whole_word {
-                // Case sensitive + whole word
-                let mut start = 0;
-                while let Some(pos) = text[start..].find(pattern) {
-                    let abs_pos = start + pos;
-                    let before =
-                        abs_pos == 0 || !text[..abs_pos].chars().last().unwrap().is_alphanumeric();
-                    let after_pos = abs_pos + pattern.len();
-                    let after = after_pos >= text.len()
-                        || !text[after_pos..].chars().next().unwrap().is_alphanumeric();
-
-                    if before && after {
-                        matches.push(abs_pos);
-                    }
-                    start = abs_pos + 1;
-                }
+        for (start, _) in text.char_indices() {
+            let rest = &text[start..];
+            let len = if self.case_sensitive {
+                rest.starts_with(pattern).then_some(pattern.len())
             } else {
-                // Case sensitive only
-                let mut start = 0;
-                while let Some(pos) = text[start..].find(pattern) {
-                    matches.push(start + pos);
-                    start += pos + 1;
-                }
-            }
-        } else {
-            let text_lower = text.to_lowercase();
-            let pattern_lower = pattern.to_lowercase();
+                Self::match_ignore_case(rest, &pattern_lower)
+            };
+            let Some(len) = len else {
+                continue;
+            };
+            let end = start + len;
 
             if self.whole_word {
-                // Case insensitive + whole word
-                let mut start = 0;
-                while let Some(pos) = text_lower[start..].find(&pattern_lower) {
-                    let abs_pos = start + pos;
-                    let before =
-                        abs_pos == 0 || !text[..abs_pos].chars().last().unwrap().is_alphanumeric();
-                    let after_pos = abs_pos + pattern.len();
-                    let after = after_pos >= text.len()
-                        || !text[after_pos..].chars().next().unwrap().is_alphanumeric();
-
-                    if before && after {
-                        matches.push(abs_pos);
-                    }
-                    start = abs_pos + 1;
-                }
-            } else {
-                // Case insensitive only
-                let mut start = 0;
-                while let Some(pos) = text_lower[start..].find(&pattern_lower) {
-                    matches.push(start + pos);
-                    start += pos + 1;
+                let before = text[..start]
+                    .chars()
+                    .next_back()
+                    .is_none_or(|c| !Self::is_word_char(c));
+                let after = text[end..]
+                    .chars()
+                    .next()
+                    .is_none_or(|c| !Self::is_word_char(c));
+                if !(before && after) {
+                    continue;
                 }
             }
+
+            matches.push(start..end);
         }
 
         matches
     }
 
-    fn find_next(&mut self) {
-        if !self.find_dialog_open {
-            return;
+    fn find_target_text(&self) -> String {
+        match self.find_target {
+            FindTarget::RequestBody => self.request_body_content.text(),
+            FindTarget::ResponseBody => self.response_body_content.text(),
         }
+    }
 
-        let text = self
-            .current_tab()
-            .map(|t| t.response_body.clone())
-            .unwrap_or_default();
+    fn set_find_target_text(&mut self, text: &str) {
+        match self.find_target {
+            FindTarget::RequestBody => {
+                self.request_body_content = text_editor::Content::with_text(text);
+                if let Some(tab) = self.current_tab_mut() {
+                    tab.request_body = std::sync::Arc::from(text);
+                }
+            }
+            FindTarget::ResponseBody => {
+                self.response_body_content = text_editor::Content::with_text(text);
+                // Only the shown text changes, the body stays what the server sent
+                if let Some(tab) = self.current_tab_mut() {
+                    // Find only sees the loaded part, the rest is kept as it was
+                    let full = format!(
+                        "{}{}",
+                        text,
+                        &tab.response_display[tab.response_shown_len..]
+                    );
+                    tab.response_display = std::sync::Arc::from(full.as_str());
+                    tab.response_shown_len = text.len();
+                }
+            }
+        }
+    }
 
-        println!("=== FIND NEXT ===");
-        println!("Text length: {}", text.len());
-        println!("Number of lines: {}", text.lines().count());
-        println!(
-            "First 200 chars: {:?}",
-            &text.chars().take(200).collect::<String>()
-        );
+    fn clear_search(&mut self) {
+        self.current_match = 0;
+        self.total_matches = 0;
+        self.current_match_pos = None;
+        self.search_match_positions = Vec::new();
+        self.current_match_line_col = None;
+        self.search_match_length = 0;
+    }
 
+    // Re-runs the search on the target editor, keeping the current index when still valid
+    fn refresh_matches(&mut self) -> Vec<std::ops::Range<usize>> {
+        let text = self.find_target_text();
         let matches = self.find_matches(&text, &self.find_text);
 
-        println!("Search text: '{}'", self.find_text);
-        println!("Found {} matches", matches.len());
-        println!("Match positions: {:?}", matches);
-
-        self.total_matches = matches.len();
-
         if matches.is_empty() {
-            self.current_match = 0;
-            self.current_match_pos = None;
-            self.search_match_positions = Vec::new();
-            self.current_match_line_col = None;
-            self.search_match_length = 0;
-            return;
+            self.clear_search();
+            return matches;
         }
 
-        // Convert all matches to line/col positions
-        let match_positions: Vec<(usize, usize)> = matches
-            .iter()
-            .map(|&pos| Self::position_to_line_col(&text, pos))
-            .collect();
-
-        // println!("Line/col positions: {:?}", match_positions);
-
-        // Update search state
-        self.search_match_positions = match_positions;
+        let starts: Vec<usize> = matches.iter().map(|m| m.start).collect();
+        self.total_matches = matches.len();
+        self.search_match_positions = Self::positions_to_line_col(&text, &starts);
         self.search_match_length = self.find_text.chars().count();
 
-        println!("Match length (chars): {}", self.search_match_length);
+        if self.current_match > matches.len() {
+            self.current_match = matches.len();
+        }
+        self.select_current_match(&matches);
+
+        matches
+    }
 
-        // Move to next match
-        if self.current_match == 0 || self.current_match >= matches.len() {
-            self.current_match = 1;
+    fn select_current_match(&mut self, matches: &[std::ops::Range<usize>]) {
+        if self.current_match == 0 {
+            self.current_match_pos = None;
+            self.current_match_line_col = None;
         } else {
-            self.current_match += 1;
+            self.current_match_pos = Some(matches[self.current_match - 1].start);
+            self.current_match_line_col = Some(self.search_match_positions[self.current_match - 1]);
         }
+    }
 
-        if self.current_match > matches.len() {
-            self.current_match = 1;
+    fn find_next(&mut self) {
+        if !self.find_dialog_open {
+            return;
         }
 
-        if self.current_match > 0 {
-            let match_pos = matches[self.current_match - 1];
-            self.current_match_pos = Some(match_pos);
-            self.current_match_line_col = Some(self.search_match_positions[self.current_match - 1]);
+        let matches = self.refresh_matches();
+        if matches.is_empty() {
+            return;
         }
+
+        // Move to next match, wrapping around at the end of the document
+        self.current_match = if self.current_match >= matches.len() {
+            1
+        } else {
+            self.current_match + 1
+        };
+        self.select_current_match(&matches);
     }
 
     fn find_previous(&mut self) {
@@ -949,45 +1954,78 @@ impl CrabiPie {
             return;
         }
 
-        let text = self
-            .current_tab()
-            .map(|t| t.response_body.clone())
-            .unwrap_or_default();
-        let matches = self.find_matches(&text, &self.find_text);
+        let matches = self.refresh_matches();
+        if matches.is_empty() {
+            return;
+        }
 
-        self.total_matches = matches.len();
+        // Move to previous match, wrapping around at the start of the document
+        self.current_match = if self.current_match <= 1 {
+            matches.len()
+        } else {
+            self.current_match - 1
+        };
+        self.select_current_match(&matches);
+    }
+
+    fn replace_current(&mut self) {
+        if !self.find_dialog_open {
+            return;
+        }
 
+        let matches = self.refresh_matches();
         if matches.is_empty() {
-            self.current_match = 0;
-            self.current_match_pos = None;
-            self.search_match_positions = Vec::new();
-            self.current_match_line_col = None;
-            self.search_match_length = 0;
             return;
         }
 
-        // Convert all matches to line/col positions
-        let match_positions: Vec<(usize, usize)> = matches
+        let index = self.current_match.max(1) - 1;
+        let range = matches[index].clone();
+        let mut text = self.find_target_text();
+        text.replace_range(range.clone(), &self.replace_text);
+        self.set_find_target_text(&text);
+
+        // Continue from just after the inserted text so a replacement that
+        // contains the search term is not matched again
+        let resume_at = range.start + self.replace_text.len();
+        let matches = self.refresh_matches();
+        if matches.is_empty() {
+            return;
+        }
+        self.current_match = matches
             .iter()
-            .map(|&pos| Self::position_to_line_col(&text, pos))
-            .collect();
+            .position(|m| m.start >= resume_at)
+            .map(|i| i + 1)
+            .unwrap_or(1);
+        self.select_current_match(&matches);
+    }
 
-        // Update search state
-        self.search_match_positions = match_positions;
-        self.search_match_length = self.find_text.chars().count();
+    fn replace_all(&mut self) {
+        if !self.find_dialog_open {
+            return;
+        }
 
-        // Move to previous match
-        if self.current_match <= 1 {
-            self.current_match = matches.len();
-        } else {
-            self.current_match -= 1;
+        let text = self.find_target_text();
+        let matches = self.find_matches(&text, &self.find_text);
+        if matches.is_empty() {
+            return;
         }
 
-        if self.current_match > 0 {
-            let match_pos = matches[self.current_match - 1];
-            self.current_match_pos = Some(match_pos);
-            self.current_match_line_col = Some(self.search_match_positions[self.current_match - 1]);
+        // Overlapping matches are skipped so each character is replaced at most once
+        let mut result = String::with_capacity(text.len());
+        let mut last_end = 0;
+        for m in matches {
+            if m.start < last_end {
+                continue;
+            }
+            result.push_str(&text[last_end..m.start]);
+            result.push_str(&self.replace_text);
+            last_end = m.end;
         }
+        result.push_str(&text[last_end..]);
+
+        self.set_find_target_text(&result);
+        self.current_match = 0;
+        self.refresh_matches();
     }
 
     fn render_websocket_panel(&self) -> Element<'_, Message> {
@@ -1016,6 +2054,12 @@ impl CrabiPie {
                 .style(button::primary)
         };
 
+        let reconnect_button = button(text("Reconnect").size(14))
+            .on_press_maybe(
+                (!tab.loading && tab.ws_connection_id > 0).then_some(Message::WsReconnect),
+            )
+            .padding(10);
+
         let clear_button = button(text("Clear").size(14))
             .on_press(Message::WsClearMessages)
             .padding(10);
@@ -1026,10 +2070,16 @@ impl CrabiPie {
             text("🔴 Disconnected").color(iced::Color::from_rgb(0.8, 0.0, 0.0))
         };
 
-        let connection_row = row![url_input, connect_button, clear_button, status_text,]
-            .spacing(10)
-            .padding(Padding::new(0.0).top(10.0))
-            .align_y(Alignment::Center);
+        let connection_row = row![
+            url_input,
+            connect_button,
+            reconnect_button,
+            clear_button,
+            status_text,
+        ]
+        .spacing(10)
+        .padding(Padding::new(0.0).top(10.0))
+        .align_y(Alignment::Center);
 
         let editor = text_editor(&tab.ws_messages_content)
             .on_action(Message::WsMessageEditorAction)
@@ -1224,7 +2274,21 @@ impl CrabiPie {
         };
 
         // ── right: query + variables ──────────
-        let operation_row = row![
+        let mut variables_row = row![text("Variables (JSON):").size(12)]
+            .spacing(8)
+            .align_y(Alignment::Center);
+        if let Some(err) = &tab.graphql_variables_error {
+            variables_row = variables_row.push(text(err).size(12).style(|_| text::Style {
+                color: Some(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+            }));
+        }
+        let variables_row = variables_row.push(space::horizontal()).push(
+            button(text("✨ Prettify").size(12))
+                .style(button::text)
+                .on_press(Message::PrettifyGraphqlVariables),
+        );
+
+        let mut operation_row = row![
             text("Operation:").size(12),
             text_input("optional", &tab.graphql_operation)
                 .on_input(Message::GraphqlOperationChanged)
@@ -1233,16 +2297,27 @@ impl CrabiPie {
         ]
         .spacing(8)
         .align_y(Alignment::Center);
+        if let Some(picker) = self.render_template_picker() {
+            operation_row = operation_row.push(picker);
+        }
+        let template_confirm = self
+            .render_template_confirm()
+            .unwrap_or_else(|| Space::new().into());
 
         let right_panel = column![
             operation_row,
+            template_confirm,
             text("Query:").size(12),
             text_editor(&self.graphql_query_content)
                 .on_action(Message::GraphqlQueryAction)
                 .height(Length::FillPortion(3)),
-            text("Variables (JSON):").size(12),
+            variables_row,
             text_editor(&self.graphql_variables_content)
                 .on_action(Message::GraphqlVariablesAction)
+                .highlight_with::<json_highlighter::JsonHighlighter>(
+                    self.get_highlighter_settings(None),
+                    Self::json_highlight_format,
+                )
                 .height(Length::FillPortion(2)),
         ]
         .spacing(8)
@@ -1315,6 +2390,15 @@ impl CrabiPie {
             app_theme: app_theme_str.clone(),
             next_tab_id: self.next_tab_id,
             cookie_jar: self.cookie_jar.clone(),
+            http_settings: self.http_settings.clone(),
+            window_size: Some((self.window_size.width, self.window_size.height)),
+            window_position: self.window_position.map(|p| (p.x, p.y)),
+            layout: self.layout,
+            show_line_numbers: self.show_line_numbers,
+            wrap_lines: self.wrap_lines,
+            pane_ratio: Some(self.pane_ratio),
+            last_dir: self.last_dir.clone(),
+            captured_vars: self.captured_vars.clone(),
         };
 
         let metadata: Vec<TabMetadata> = self.tabs.iter().map(|t| t.metadata().clone()).collect();
@@ -1329,7 +2413,7 @@ impl CrabiPie {
                 _ => None,
             })
             .collect();
-        println!("Saving {} dirty tabs", dirty_tabs.len());
+        log::debug!("Saving {} dirty tabs", dirty_tabs.len());
 
         iced::Task::perform(
             async move {
@@ -1348,19 +2432,189 @@ impl CrabiPie {
         iced::Task::perform(save_collection(collection), |_| Message::CollectionSaved)
     }
 
-    fn next_collection_id(&mut self) -> usize {
-        let id = self.next_collection_id;
-        self.next_collection_id += 1;
-        id
+    // Refill the response editor from the active tab, narrowed by its JSONPath filter
+    fn apply_response_filter(&mut self) {
+        let Some(tab) = self.current_tab_mut() else {
+            return;
+        };
+        tab.refresh_response_display();
+        self.response_body_content = tab.response_editor_content();
     }
 
-    // Find and remove an item by id, returning it
-    fn collection_remove_item(
-        items: &mut Vec<CollectionItem>,
-        id: usize,
-    ) -> Option<CollectionItem> {
-        if let Some(pos) = items.iter().position(|i| i.id() == id) {
-            return Some(items.remove(pos));
+    // The oldest entry makes room once the cache is full
+    fn store_cached_response(&mut self, key: String, entry: CachedResponse) {
+        if self.response_cache.len() >= RESPONSE_CACHE_LIMIT
+            && !self.response_cache.contains_key(&key)
+            && let Some(oldest) = self
+                .response_cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.stored_at)
+                .map(|(key, _)| key.clone())
+        {
+            self.response_cache.remove(&oldest);
+        }
+        self.response_cache.insert(key, entry);
+    }
+
+    // File dialogs start where the last one left off, in Downloads the first time
+    fn file_dialog(&self) -> rfd::AsyncFileDialog {
+        let dialog = rfd::AsyncFileDialog::new();
+        match self.last_dir.clone().or_else(downloads_dir) {
+            Some(dir) => dialog.set_directory(dir),
+            None => dialog,
+        }
+    }
+
+    // The raw headers editor is shared between tabs, refill it from the active one
+    fn sync_raw_headers(&mut self) {
+        let raw = self
+            .current_tab()
+            .map(|tab| TabState::headers_to_raw(&tab.headers))
+            .unwrap_or_default();
+        self.header_errors = validate_headers(&raw);
+        self.raw_headers_content = text_editor::Content::with_text(&raw);
+    }
+
+    // Rebuilds once typing in a client setting pauses, building on each keystroke
+    // would load the certificate and proxy again for every character
+    fn schedule_client_rebuild(&mut self) -> iced::Task<Message> {
+        self.client_rebuild_generation += 1;
+        self.client_rebuild_pending = true;
+        let generation = self.client_rebuild_generation;
+        iced::Task::perform(
+            tokio::time::sleep(std::time::Duration::from_millis(CLIENT_REBUILD_DELAY_MS)),
+            move |_| Message::HttpClientRebuildDue(generation),
+        )
+    }
+
+    fn rebuild_http_client(&mut self) {
+        self.client_rebuild_pending = false;
+        let build = |settings: &HttpSettings| {
+            build_http_client(settings, false).and_then(|client| {
+                build_http_client(settings, true).map(|insecure| (client, insecure))
+            })
+        };
+        let (clients, error) = match build(&self.http_settings) {
+            Ok(clients) => (Some(clients), None),
+            // A certificate that can't be loaded, like a .p12 whose password isn't
+            // saved across restarts, shouldn't cost the proxy and redirect settings
+            Err(err) if !self.http_settings.client_cert_path.trim().is_empty() => {
+                let without_cert = HttpSettings {
+                    client_cert_path: String::new(),
+                    client_key_path: String::new(),
+                    ..self.http_settings.clone()
+                };
+                (build(&without_cert).ok(), Some(err))
+            }
+            Err(err) => (None, Some(err)),
+        };
+        if let Some((client, insecure_client)) = clients {
+            self.http_client = client;
+            self.insecure_http_client = insecure_client;
+        }
+        self.settings_error = error;
+    }
+
+    // Requests with SSL verification turned off go through their own client
+    fn client(&self, insecure_tls: bool) -> &reqwest::Client {
+        if insecure_tls {
+            &self.insecure_http_client
+        } else {
+            &self.http_client
+        }
+    }
+
+    fn history_save_task(&self) -> iced::Task<Message> {
+        let history = self.history.clone();
+        iced::Task::perform(save_history(history), |_| Message::HistorySaved)
+    }
+
+    // Snapshot the current request into the history, skipping an identical repeat
+    fn record_history(&mut self) -> iced::Task<Message> {
+        let Some(tab) = self.current_tab() else {
+            return iced::Task::none();
+        };
+        let mut saved_state =
+            tab.to_saved(&self.json_theme.to_string(), &self.app_theme.to_string());
+        // Only the request is worth keeping, responses can be huge
+        saved_state.response_status = None;
+        saved_state.response_headers = None;
+        saved_state.response_body = None;
+
+        let is_repeat = self.history.last().is_some_and(|last| {
+            serde_json::to_value(&last.saved_state).ok() == serde_json::to_value(&saved_state).ok()
+        });
+        if is_repeat {
+            return iced::Task::none();
+        }
+
+        self.history.push(HistoryEntry {
+            sent_at: chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            saved_state,
+        });
+        if self.history.len() > HISTORY_LIMIT {
+            let overflow = self.history.len() - HISTORY_LIMIT;
+            self.history.drain(..overflow);
+        }
+        self.history_save_task()
+    }
+
+    // Moves the URL to the front of the recent ones, saving the list if it changed
+    fn record_recent_url(&mut self, url: String) -> iced::Task<Message> {
+        let url = url.trim().to_string();
+        if url.is_empty() || self.recent_urls.first() == Some(&url) {
+            return iced::Task::none();
+        }
+        self.recent_urls.retain(|u| *u != url);
+        self.recent_urls.insert(0, url);
+        self.recent_urls.truncate(RECENT_URLS_LIMIT);
+        let recent_urls = self.recent_urls.clone();
+        iced::Task::perform(save_recent_urls(recent_urls), |_| Message::RecentUrlsSaved)
+    }
+
+    // Recent URLs starting with what's typed, the typed URL itself left out
+    fn url_suggestions(&self) -> Vec<&str> {
+        let Some(tab) = self.current_tab() else {
+            return Vec::new();
+        };
+        let typed = tab.url.trim().to_lowercase();
+        if !self.url_suggest_open || typed.is_empty() {
+            return Vec::new();
+        }
+        self.recent_urls
+            .iter()
+            .filter(|url| url.to_lowercase().starts_with(&typed) && url.trim() != tab.url.trim())
+            .take(URL_SUGGESTIONS_SHOWN)
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn next_collection_id(&mut self) -> usize {
+        let id = self.next_collection_id;
+        self.next_collection_id += 1;
+        id
+    }
+
+    // Imported items come without ids, they get fresh ones before going in the tree
+    fn collection_assign_ids(&mut self, items: &mut [CollectionItem]) {
+        for item in items {
+            match item {
+                CollectionItem::Folder(f) => {
+                    f.id = self.next_collection_id();
+                    self.collection_assign_ids(&mut f.children);
+                }
+                CollectionItem::Request(r) => r.id = self.next_collection_id(),
+            }
+        }
+    }
+
+    // Find and remove an item by id, returning it
+    fn collection_remove_item(
+        items: &mut Vec<CollectionItem>,
+        id: usize,
+    ) -> Option<CollectionItem> {
+        if let Some(pos) = items.iter().position(|i| i.id() == id) {
+            return Some(items.remove(pos));
         }
         for item in items.iter_mut() {
             if let CollectionItem::Folder(f) = item {
@@ -1421,6 +2675,21 @@ impl CrabiPie {
         }
     }
 
+    // Move an item among its siblings, returns false when it is already at the edge
+    fn collection_move_item(items: &mut [CollectionItem], id: usize, offset: isize) -> bool {
+        if let Some(pos) = items.iter().position(|i| i.id() == id) {
+            let Some(target) = pos.checked_add_signed(offset).filter(|t| *t < items.len()) else {
+                return false;
+            };
+            items.swap(pos, target);
+            return true;
+        }
+        items.iter_mut().any(|item| match item {
+            CollectionItem::Folder(f) => Self::collection_move_item(&mut f.children, id, offset),
+            CollectionItem::Request(_) => false,
+        })
+    }
+
     fn collection_toggle_expand(items: &mut Vec<CollectionItem>, id: usize) {
         for item in items.iter_mut() {
             if let CollectionItem::Folder(f) = item {
@@ -1499,6 +2768,13 @@ impl CrabiPie {
                 "Save current tab",
                 tooltip::Position::Bottom
             ),
+            tooltip(
+                button(text("📥").shaping(text::Shaping::Advanced).size(12))
+                    .style(button::text)
+                    .on_press(Message::PostmanImport),
+                "Import a Postman collection (v2.1)",
+                tooltip::Position::Bottom
+            ),
         ]
         .align_y(Alignment::Center)
         .spacing(4);
@@ -1524,15 +2800,90 @@ impl CrabiPie {
             .into()
         };
 
+        let history_header = row![
+            button(
+                row![
+                    text(if self.history_open { "▾" } else { "▸" }).size(12),
+                    text("History").size(14),
+                ]
+                .spacing(4)
+                .align_y(Alignment::Center)
+            )
+            .style(button::text)
+            .on_press(Message::HistoryToggle),
+            space::horizontal(),
+            tooltip(
+                button(text("🗑").shaping(text::Shaping::Advanced).size(12))
+                    .style(button::text)
+                    .on_press_maybe(if self.history.is_empty() {
+                        None
+                    } else {
+                        Some(Message::ClearHistory)
+                    }),
+                "Clear history",
+                tooltip::Position::Bottom
+            ),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(4);
+
+        let history: Element<'_, Message> = if !self.history_open {
+            Space::new().into()
+        } else if self.history.is_empty() {
+            container(
+                text("Sent requests show up here.")
+                    .size(12)
+                    .color(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+            )
+            .padding(10)
+            .into()
+        } else {
+            // Newest first
+            let entries = self
+                .history
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(idx, entry)| {
+                    button(
+                        column![
+                            row![
+                                text(entry.saved_state.method.to_string())
+                                    .size(11)
+                                    .color(entry.saved_state.method.color()),
+                                text(&entry.saved_state.url).size(12),
+                            ]
+                            .spacing(6),
+                            text(&entry.sent_at)
+                                .size(10)
+                                .color(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+                        ]
+                        .spacing(2),
+                    )
+                    .style(button::text)
+                    .width(Length::Fill)
+                    .on_press(Message::HistorySelected(idx))
+                    .into()
+                })
+                .collect::<Vec<Element<'_, Message>>>();
+
+            scrollable(column(entries).spacing(2))
+                .height(Length::Fill)
+                .into()
+        };
+
         let content = column![
             header,
             rule::horizontal(1.0),
             scrollable(items).height(Length::Fill),
+            rule::horizontal(1.0),
+            history_header,
+            history,
         ]
         .spacing(8);
 
         container(content)
-            .width(Length::Fixed(240.0))
+            .width(Length::Fixed(SIDEBAR_WIDTH))
             .height(Length::Fill)
             .style(|theme: &iced::Theme| container::Style {
                 border: Border {
@@ -1613,6 +2964,26 @@ impl CrabiPie {
                                 .style(button::text)
                                 .width(Length::Fill)
                                 .on_press(Message::CollectionItemRename(folder.id)),
+                                button(
+                                    row![
+                                        text("⬆️").shaping(text::Shaping::Advanced).size(12),
+                                        text(" Move up").size(13)
+                                    ]
+                                    .spacing(4)
+                                )
+                                .style(button::text)
+                                .width(Length::Fill)
+                                .on_press(Message::CollectionItemMove(folder.id, -1)),
+                                button(
+                                    row![
+                                        text("⬇️").shaping(text::Shaping::Advanced).size(12),
+                                        text(" Move down").size(13)
+                                    ]
+                                    .spacing(4)
+                                )
+                                .style(button::text)
+                                .width(Length::Fill)
+                                .on_press(Message::CollectionItemMove(folder.id, 1)),
                                 button(
                                     row![
                                         text("🗑").shaping(text::Shaping::Advanced).size(12),
@@ -1663,14 +3034,7 @@ impl CrabiPie {
             }
 
             CollectionItem::Request(req) => {
-                let method_color = match req.method {
-                    HttpMethod::GET => iced::Color::from_rgb(0.27, 0.73, 0.27),
-                    HttpMethod::POST => iced::Color::from_rgb(0.98, 0.65, 0.14),
-                    HttpMethod::PUT => iced::Color::from_rgb(0.14, 0.59, 0.98),
-                    HttpMethod::DELETE => iced::Color::from_rgb(0.95, 0.26, 0.21),
-                    HttpMethod::PATCH => iced::Color::from_rgb(0.61, 0.15, 0.69),
-                    _ => iced::Color::from_rgb(0.5, 0.5, 0.5),
-                };
+                let method_color = req.method.color();
 
                 let name_or_input: Element<'_, Message> = if self.sidebar_editing_id == Some(req.id)
                 {
@@ -1705,7 +3069,7 @@ impl CrabiPie {
                         }
                     })
                     .width(Length::Fill)
-                    .on_press(Message::SidebarItemSelected(req.id));
+                    .on_press(Message::CollectionRequestFocus(req.id));
 
                     let req_id = req.id;
                     iced_aw::ContextMenu::new(row_btn, move || {
@@ -1733,6 +3097,26 @@ impl CrabiPie {
                             .style(button::text)
                             .width(Length::Fill)
                             .on_press(Message::CollectionItemDuplicate(req_id)),
+                            button(
+                                row![
+                                    text("⬆️").shaping(text::Shaping::Advanced).size(12),
+                                    text(" Move up").size(13)
+                                ]
+                                .spacing(4)
+                            )
+                            .style(button::text)
+                            .width(Length::Fill)
+                            .on_press(Message::CollectionItemMove(req_id, -1)),
+                            button(
+                                row![
+                                    text("⬇️").shaping(text::Shaping::Advanced).size(12),
+                                    text(" Move down").size(13)
+                                ]
+                                .spacing(4)
+                            )
+                            .style(button::text)
+                            .width(Length::Fill)
+                            .on_press(Message::CollectionItemMove(req_id, 1)),
                             button(
                                 row![
                                     text("🗑").shaping(text::Shaping::Advanced).size(12),
@@ -1853,63 +3237,583 @@ impl CrabiPie {
             .into()
     }
 
-    fn format_duration(dur: std::time::Duration) -> String {
-        let secs = dur.as_secs_f64();
-        if secs < 1.0 {
-            format!("{:.1}ms", secs * 1000.0)
-        } else if secs < 60.0 {
-            format!("{:.2}s", secs)
-        } else if secs < 3600.0 {
-            let m = secs as u64 / 60;
-            let s = secs as u64 % 60;
-            format!("{}m {}s", m, s)
-        } else {
-            let h = secs as u64 / 3600;
-            let m = (secs as u64 % 3600) / 60;
-            format!("{}h {}m", h, m)
-        }
+    fn render_discard_changes_modal(&self) -> Element<'_, Message> {
+        let name = self
+            .current_tab()
+            .map(|tab| tab.display_name())
+            .unwrap_or_default();
+        let modal_content = column![
+            text("Discard unsaved changes?").size(16),
+            rule::horizontal(1.0),
+            text(format!(
+                "\"{}\" has edits that haven't been saved, loading another request replaces them.",
+                name
+            ))
+            .size(13),
+            row![
+                space::horizontal(),
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::DiscardChangesCancelled)
+                    .padding(8),
+                button("Discard")
+                    .style(button::danger)
+                    .on_press(Message::DiscardChangesConfirmed)
+                    .padding(8),
+            ]
+            .spacing(8),
+        ]
+        .spacing(12);
+
+        container(modal_content)
+            .width(Length::Fixed(340.0))
+            .padding(20)
+            .style(|theme: &iced::Theme| container::Style {
+                background: Some(iced::Background::Color(theme.palette().background)),
+                border: Border {
+                    width: 1.0,
+                    color: theme.palette().primary,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            })
+            .into()
     }
 
-    pub fn build_query(&self) -> String {
-        let Some(tab) = self.current_tab() else {
-            return String::from("Loading...");
-        };
+    fn render_settings_modal(&self) -> Element<'_, Message> {
+        let settings = &self.http_settings;
 
-        let mut buf = String::from("query {\n");
+        let redirects = column![
+            text("Redirects").size(14),
+            row![
+                checkbox(settings.follow_redirects).on_toggle(Message::FollowRedirectsToggled),
+                text("Follow redirects").size(13),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            row![
+                checkbox(settings.show_redirect_chain).on_toggle_maybe(
+                    settings
+                        .follow_redirects
+                        .then_some(Message::ShowRedirectChainToggled)
+                ),
+                text("Show the redirect chain in the response headers").size(13),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            row![
+                text("Max redirects").size(13),
+                text_input("10", &settings.max_redirects.to_string())
+                    .on_input(Message::MaxRedirectsChanged)
+                    .width(60)
+                    .padding(4),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(8);
 
-        if let Some(schema) = &tab.graphql_schema {
-            // Find the "Query" type (or whatever your query_type is named)
-            if let Some(root_type) = schema
-                .types
-                .iter()
-                .find(|t| t.name.as_deref() == Some("Query"))
-            {
-                buf.push_str(&generate_selection_set(
-                    &schema.types,
-                    root_type,
-                    &tab.graphql_selected_paths,
-                    1,       // Start at indent level 1
-                    "Query", // Starting path
-                ));
-            }
-        }
+        let retries = column![
+            text("Retries").size(14),
+            row![
+                text("Retry attempts").size(13),
+                text_input("0", &settings.retry_count.to_string())
+                    .on_input(Message::RetryCountChanged)
+                    .width(60)
+                    .padding(4),
+                text("Backoff (ms)").size(13),
+                text_input("500", &settings.retry_backoff_ms.to_string())
+                    .on_input(Message::RetryBackoffChanged)
+                    .width(70)
+                    .padding(4),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            row![
+                checkbox(settings.retry_non_idempotent)
+                    .on_toggle(Message::RetryNonIdempotentToggled),
+                text("Also retry POST, PATCH and GraphQL requests").size(13),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(8);
 
-        buf.push('}');
-        buf
-    }
-}
+        let caching = column![
+            text("Caching").size(14),
+            row![
+                checkbox(settings.cache_responses).on_toggle(Message::ResponseCacheToggled),
+                text("Revalidate GET responses with ETag / Last-Modified").size(13),
+                space::horizontal(),
+                button(text(format!("Clear ({})", self.response_cache.len())).size(12))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (!self.response_cache.is_empty()).then_some(Message::ResponseCacheCleared)
+                    ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(8);
 
-enum TabLoadState {
-    Unloaded(TabMetadata),
-    Loading(TabMetadata), // background task running
-    Loaded(Box<TabState>),
-}
-impl TabLoadState {
-    fn id(&self) -> usize {
-        self.metadata().id
-    }
+        let proxy = column![
+            text("Proxy").size(14),
+            text_input("http://proxy.example.com:8080", &settings.proxy_url)
+                .on_input(Message::ProxyUrlChanged)
+                .padding(6),
+            row![
+                text_input("Username (optional)", &settings.proxy_username)
+                    .on_input(Message::ProxyUsernameChanged)
+                    .padding(6),
+                text_input("Password", &settings.proxy_password)
+                    .on_input(Message::ProxyPasswordChanged)
+                    .secure(true)
+                    .padding(6),
+            ]
+            .spacing(8),
+        ]
+        .spacing(8);
 
-    fn metadata(&self) -> &TabMetadata {
+        let file_name = |path: &str| {
+            std::path::Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string())
+        };
+        let mut client_cert = column![
+            text("Client certificate").size(14),
+            row![
+                text(if settings.client_cert_path.is_empty() {
+                    "None".to_string()
+                } else {
+                    file_name(&settings.client_cert_path)
+                })
+                .size(13)
+                .width(Length::Fill),
+                button(text("Choose...").size(12))
+                    .style(button::secondary)
+                    .on_press(Message::ClientCertSelect),
+                button(text("Clear").size(12))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (!settings.client_cert_path.is_empty())
+                            .then_some(Message::ClientCertCleared)
+                    ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(8);
+        if is_pkcs12_path(&settings.client_cert_path) {
+            client_cert = client_cert.push(
+                text_input("Certificate password", &settings.client_cert_password)
+                    .on_input(Message::ClientCertPasswordChanged)
+                    .secure(true)
+                    .padding(6),
+            );
+        } else if !settings.client_cert_path.is_empty() {
+            client_cert = client_cert.push(
+                row![
+                    text(if settings.client_key_path.is_empty() {
+                        "Key: in the certificate file".to_string()
+                    } else {
+                        format!("Key: {}", file_name(&settings.client_key_path))
+                    })
+                    .size(13)
+                    .width(Length::Fill),
+                    button(text("Choose key...").size(12))
+                        .style(button::secondary)
+                        .on_press(Message::ClientKeySelect),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        let mut pinned = column![
+            text("Pinned headers").size(14),
+            text("Sent with every request, unless the request sets the same header").size(12),
+        ]
+        .spacing(8);
+        for (idx, header) in settings.pinned_headers.iter().enumerate() {
+            pinned = pinned.push(
+                row![
+                    checkbox(header.enabled).on_toggle(move |_| Message::PinnedHeaderToggled(idx)),
+                    text_input("User-Agent", &header.key)
+                        .on_input(move |key| Message::PinnedHeaderKeyChanged(idx, key))
+                        .width(130)
+                        .padding(4),
+                    text_input("value", &header.value)
+                        .on_input(move |value| Message::PinnedHeaderValueChanged(idx, value))
+                        .padding(4),
+                    button(text("❌").shaping(text::Shaping::Advanced).size(12))
+                        .style(button::subtle)
+                        .on_press(Message::PinnedHeaderRemove(idx)),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            );
+            if header.enabled
+                && !header.key.trim().is_empty()
+                && let Err(err) = check_header(&header.key, &header.value)
+            {
+                pinned = pinned.push(Self::header_warning(format!("{err}, not sent")));
+            }
+        }
+        pinned = pinned.push(
+            button(text("➕ Add").shaping(text::Shaping::Advanced).size(12))
+                .style(button::subtle)
+                .on_press(Message::PinnedHeaderAdd),
+        );
+
+        let error: Element<'_, Message> = match &self.settings_error {
+            Some(err) => text(err.clone())
+                .size(12)
+                .style(|theme: &iced::Theme| text::Style {
+                    color: Some(theme.palette().danger),
+                })
+                .into(),
+            None => Space::new().into(),
+        };
+
+        let modal_content = column![
+            text("Settings").size(16),
+            rule::horizontal(1.0),
+            redirects,
+            rule::horizontal(1.0),
+            retries,
+            rule::horizontal(1.0),
+            caching,
+            rule::horizontal(1.0),
+            proxy,
+            rule::horizontal(1.0),
+            client_cert,
+            rule::horizontal(1.0),
+            pinned,
+            error,
+            row![
+                space::horizontal(),
+                button("Close")
+                    .style(button::primary)
+                    .on_press(Message::SettingsClose)
+                    .padding(8),
+            ],
+        ]
+        .spacing(12);
+
+        container(modal_content)
+            .width(Length::Fixed(420.0))
+            .padding(20)
+            .style(|theme: &iced::Theme| container::Style {
+                background: Some(iced::Background::Color(theme.palette().background)),
+                border: Border {
+                    width: 1.0,
+                    color: theme.palette().primary,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            })
+            .into()
+    }
+
+    fn render_theme_editor_modal(&self) -> Element<'_, Message> {
+        let Some(editor) = &self.theme_editor else {
+            return Space::new().into();
+        };
+
+        let mut fields = Column::new().spacing(4);
+        for (idx, label) in json_highlighter::CustomJsonTheme::FIELDS.iter().enumerate() {
+            let color = editor.draft.color(idx);
+            let swatch =
+                container(Space::new().width(18).height(18)).style(move |theme: &iced::Theme| {
+                    container::Style {
+                        background: Some(iced::Background::Color(color)),
+                        border: Border {
+                            width: 1.0,
+                            color: theme.extended_palette().background.strong.color,
+                            radius: 3.0.into(),
+                        },
+                        ..Default::default()
+                    }
+                });
+            let selected = idx == editor.selected;
+            fields = fields.push(
+                button(
+                    row![swatch, text(*label).size(13).color(color)]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                )
+                .style(move |theme, status| {
+                    if selected {
+                        button::secondary(theme, status)
+                    } else {
+                        button::text(theme, status)
+                    }
+                })
+                .width(Length::Fill)
+                .on_press(Message::ThemeEditorFieldSelected(idx)),
+            );
+        }
+
+        let color = editor.draft.color(editor.selected);
+        let channel = |label: &'static str, idx: usize, value: f32| {
+            row![
+                text(label).size(12).width(14),
+                iced::widget::Slider::new(0.0..=1.0, value, move |v| {
+                    Message::ThemeEditorChannelChanged(idx, v)
+                })
+                .step(1.0 / 255.0),
+                text(format!("{}", (value * 255.0).round() as u8))
+                    .size(12)
+                    .width(28),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+        };
+        let hex_valid = color_from_hex(&editor.hex_input).is_some();
+        let picker = column![
+            channel("R", 0, color.r),
+            channel("G", 1, color.g),
+            channel("B", 2, color.b),
+            text_input("#rrggbb", &editor.hex_input)
+                .on_input(Message::ThemeEditorHexChanged)
+                .padding(4)
+                .style(move |theme: &iced::Theme, status| {
+                    let mut style = text_input::default(theme, status);
+                    if !hex_valid {
+                        style.border.color = theme.palette().danger;
+                    }
+                    style
+                }),
+        ]
+        .spacing(8)
+        .width(Length::Fill);
+
+        let modal_content = column![
+            text("New JSON theme").size(16),
+            rule::horizontal(1.0),
+            text_input("Theme name", &editor.draft.name)
+                .on_input(Message::ThemeEditorNameChanged)
+                .padding(6),
+            row![fields.width(160), picker].spacing(16),
+            row![
+                space::horizontal(),
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::ThemeEditorCancel)
+                    .padding(8),
+                button("Save")
+                    .style(button::primary)
+                    .on_press(Message::ThemeEditorSave)
+                    .padding(8),
+            ]
+            .spacing(8),
+        ]
+        .spacing(12);
+
+        container(modal_content)
+            .width(Length::Fixed(460.0))
+            .padding(20)
+            .style(|theme: &iced::Theme| container::Style {
+                background: Some(iced::Background::Color(theme.palette().background)),
+                border: Border {
+                    width: 1.0,
+                    color: theme.palette().primary,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            })
+            .into()
+    }
+
+    fn render_curl_import_modal(&self) -> Element<'_, Message> {
+        let error: Element<'_, Message> = match &self.curl_import_error {
+            Some(err) => text(err.clone())
+                .size(12)
+                .style(|theme: &iced::Theme| text::Style {
+                    color: Some(theme.palette().danger),
+                })
+                .into(),
+            None => Space::new().into(),
+        };
+
+        let modal_content = column![
+            text("Import from cURL").size(16),
+            rule::horizontal(1.0),
+            text_editor(&self.curl_import_content)
+                .placeholder("curl -X POST https://api.example.com -H 'Content-Type: application/json' -d '{}'")
+                .on_action(Message::CurlImportAction)
+                .height(200)
+                .padding(8),
+            error,
+            row![
+                space::horizontal(),
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::CurlImportClose)
+                    .padding(8),
+                button("Import")
+                    .style(button::primary)
+                    .on_press(Message::CurlImportConfirm)
+                    .padding(8),
+            ]
+            .spacing(8),
+        ]
+        .spacing(12);
+
+        container(modal_content)
+            .width(Length::Fixed(520.0))
+            .padding(20)
+            .style(|theme: &iced::Theme| container::Style {
+                background: Some(iced::Background::Color(theme.palette().background)),
+                border: Border {
+                    width: 1.0,
+                    color: theme.palette().primary,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            })
+            .into()
+    }
+
+    // Picks the unit by size, e.g. 850µs, 4.2ms, 230ms, 3.25s, 45.2s, 2m 05s.
+    // The unit is chosen after rounding, so 999.6ms shows as 1.00s, not 1000ms
+    fn format_duration(dur: std::time::Duration) -> String {
+        let secs = dur.as_secs_f64();
+        let ms = secs * 1000.0;
+        let round_to = |value: f64, places: i32| {
+            let scale = 10f64.powi(places);
+            (value * scale).round() / scale
+        };
+        if dur.as_micros() < 1000 {
+            format!("{}µs", dur.as_micros())
+        } else if round_to(ms, 1) < 10.0 {
+            format!("{:.1}ms", round_to(ms, 1))
+        } else if round_to(ms, 0) < 1000.0 {
+            format!("{:.0}ms", round_to(ms, 0))
+        } else if round_to(secs, 2) < 10.0 {
+            format!("{:.2}s", round_to(secs, 2))
+        } else if round_to(secs, 1) < 60.0 {
+            format!("{:.1}s", round_to(secs, 1))
+        } else {
+            let total = secs.round() as u64;
+            if total < 3600 {
+                format!("{}m {:02}s", total / 60, total % 60)
+            } else {
+                format!("{}h {:02}m", total / 3600, (total % 3600) / 60)
+            }
+        }
+    }
+
+    // Time badge followed by a small bar per phase, the exact numbers are in the tooltip
+    fn render_timing_waterfall<'a>(
+        badge: Element<'a, Message>,
+        total: std::time::Duration,
+        timing: ResponseTiming,
+    ) -> Element<'a, Message> {
+        const WIDTH: f32 = 90.0;
+        let dns = timing.dns.unwrap_or_default();
+        let phases = [
+            ("DNS", Some(dns), iced::Color::from_rgb(0.35, 0.6, 0.95)),
+            (
+                "Connect + wait",
+                Some(timing.ttfb.saturating_sub(dns)),
+                iced::Color::from_rgb(0.95, 0.65, 0.25),
+            ),
+            (
+                "Download",
+                timing.download,
+                iced::Color::from_rgb(0.4, 0.8, 0.45),
+            ),
+        ];
+
+        let total_secs = total.as_secs_f32().max(f32::EPSILON);
+        let mut bar = iced::widget::Row::new().height(8);
+        let mut details = Column::new().spacing(2);
+        for (label, duration, color) in phases {
+            let Some(duration) = duration else {
+                continue;
+            };
+            let width = duration.as_secs_f32() / total_secs * WIDTH;
+            if width >= 1.0 {
+                bar = bar.push(container(Space::new()).width(width).height(8).style(
+                    move |_theme: &iced::Theme| container::Style {
+                        background: Some(color.into()),
+                        ..Default::default()
+                    },
+                ));
+            }
+            if label != "DNS" || timing.dns.is_some() {
+                details = details
+                    .push(text(format!("{}: {}", label, Self::format_duration(duration))).size(12));
+            }
+        }
+        if timing.dns.is_none() {
+            details = details.push(text("No DNS lookup, connection reused or cached").size(12));
+        }
+
+        tooltip(
+            row![badge, bar].spacing(6).align_y(Alignment::Center),
+            container(details).padding(6).style(container::rounded_box),
+            tooltip::Position::Bottom,
+        )
+        .into()
+    }
+
+    fn human_size(bytes: usize) -> String {
+        const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+        if bytes < 1024 {
+            return format!("{} B", bytes);
+        }
+        let mut size = bytes as f64 / 1024.0;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+
+    pub fn build_query(&self) -> String {
+        let Some(tab) = self.current_tab() else {
+            return String::from("Loading...");
+        };
+
+        let mut buf = String::from("query {\n");
+
+        if let Some(schema) = &tab.graphql_schema {
+            // Find the "Query" type (or whatever your query_type is named)
+            if let Some(root_type) = schema
+                .types
+                .iter()
+                .find(|t| t.name.as_deref() == Some("Query"))
+            {
+                buf.push_str(&generate_selection_set(
+                    &schema.types,
+                    root_type,
+                    &tab.graphql_selected_paths,
+                    1,       // Start at indent level 1
+                    "Query", // Starting path
+                ));
+            }
+        }
+
+        buf.push('}');
+        buf
+    }
+}
+
+enum TabLoadState {
+    Unloaded(TabMetadata),
+    Loading(TabMetadata), // background task running
+    Loaded(Box<TabState>),
+}
+impl TabLoadState {
+    fn id(&self) -> usize {
+        self.metadata().id
+    }
+
+    fn metadata(&self) -> &TabMetadata {
         match self {
             TabLoadState::Unloaded(m) => m,
             TabLoadState::Loading(m) => m,
@@ -1925,6 +3829,9 @@ struct TabMetadata {
     url: String,
     method: HttpMethod,
     request_type: RequestType,
+    // The collection request this tab was opened from, clicking it again focuses the tab
+    #[serde(default)]
+    collection_request_id: Option<usize>,
 }
 impl TabMetadata {
     async fn save_metadata(tabs: Vec<TabMetadata>) {
@@ -1941,7 +3848,7 @@ impl TabMetadata {
             .ok()
             .and_then(|b| serde_json::from_slice(&b).ok())
             .unwrap_or_default();
-        println!(
+        log::debug!(
             "metadata ids: {:?}",
             result.iter().map(|m| m.id).collect::<Vec<_>>()
         );
@@ -1964,6 +3871,29 @@ struct SessionState {
     app_theme: String,
     next_tab_id: usize,
     cookie_jar: std::collections::HashMap<String, Vec<CookieEntry>>,
+    #[serde(default)]
+    http_settings: HttpSettings,
+    #[serde(default)]
+    window_size: Option<(f32, f32)>,
+    #[serde(default)]
+    window_position: Option<(f32, f32)>,
+    #[serde(default)]
+    layout: Layout,
+    #[serde(default)]
+    show_line_numbers: bool,
+    #[serde(default = "default_wrap_lines")]
+    wrap_lines: bool,
+    #[serde(default)]
+    pane_ratio: Option<f32>,
+    #[serde(default)]
+    last_dir: Option<std::path::PathBuf>,
+    #[serde(default)]
+    captured_vars: std::collections::BTreeMap<String, String>,
+}
+
+// Sessions saved before the setting existed keep wrapping
+fn default_wrap_lines() -> bool {
+    true
 }
 
 impl SessionState {
@@ -1972,6 +3902,27 @@ impl SessionState {
         serde_json::from_slice(&bytes).ok()
     }
 
+    // The window is created before the runtime exists, so its size and position are read synchronously
+    fn load_window_geometry() -> (Option<iced::Size>, Option<iced::Point>) {
+        let Some(session) = std::fs::read(state_file_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<SessionState>(&bytes).ok())
+        else {
+            return (None, None);
+        };
+        let size = session
+            .window_size
+            .filter(|&(width, height)| width > 0.0 && height > 0.0)
+            .map(|(width, height)| iced::Size::new(width, height));
+        // Keep at least part of the title bar on screen if a monitor left of or above was unplugged,
+        // the rest is checked once the window is open
+        let position = session.window_position.map(|(x, y)| {
+            let width = size.unwrap_or(DEFAULT_WINDOW_SIZE).width;
+            iced::Point::new(x.max(WINDOW_MIN_VISIBLE - width), y.max(0.0))
+        });
+        (size, position)
+    }
+
     async fn save(&self) {
         if let Ok(json) = serde_json::to_vec(self) {
             tokio::fs::write(state_file_path(), json).await.ok();
@@ -1983,6 +3934,10 @@ impl SessionState {
 struct SavedState {
     id: usize,
     title: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
     url: String,
     request_type: RequestType,
     method: HttpMethod,
@@ -1991,10 +3946,35 @@ struct SavedState {
     form_view_type: FormViewType,
     auth_type: AuthType,
     bearer_token: String,
+    #[serde(default)]
+    basic_username: String,
+    #[serde(default)]
+    basic_password: String,
     api_key_name: String,
     api_key: String,
     api_key_position: ApiKeyPosition,
-    content_type: ContentType,
+    #[serde(default)]
+    oauth_token_url: String,
+    #[serde(default)]
+    oauth_client_id: String,
+    #[serde(default)]
+    oauth_scope: String,
+    #[serde(default)]
+    signing: RequestSigning,
+    #[serde(default)]
+    pagination: Pagination,
+    content_type: ContentType,
+    // Only the path of a raw file body is saved, not its contents
+    #[serde(default)]
+    binary_body_path: String,
+    #[serde(default)]
+    binary_content_type: String,
+    #[serde(default)]
+    gzip_body: bool,
+    #[serde(default)]
+    delete_body: bool,
+    #[serde(default)]
+    insecure_tls: bool,
     query_params: Vec<QueryParam>,
     form_data: Vec<FormField>,
     raw_form_content: String,
@@ -2019,6 +3999,8 @@ struct SavedState {
     graphql_selected_paths: std::collections::HashSet<String>,
     manually_selected_paths: std::collections::HashSet<String>,
 
+    #[serde(default)]
+    capture_rules: Vec<CaptureRule>,
     active_request_tab: RequestTab,
     active_response_tab: ResponseTab,
 }
@@ -2029,6 +4011,8 @@ impl Default for SavedState {
         Self {
             id: 0,
             title: "Request-1".into(),
+            name: String::new(),
+            description: String::new(),
             url: base.clone(),
             request_type: RequestType::HTTP,
             method: HttpMethod::GET,
@@ -2038,9 +4022,21 @@ impl Default for SavedState {
             auth_type: AuthType::None,
             api_key_position: ApiKeyPosition::Header,
             bearer_token: String::new(),
+            basic_username: String::new(),
+            basic_password: String::new(),
             api_key_name: String::new(),
             api_key: String::new(),
+            oauth_token_url: String::new(),
+            oauth_client_id: String::new(),
+            oauth_scope: String::new(),
+            signing: RequestSigning::default(),
+            pagination: Pagination::default(),
             content_type: ContentType::Json,
+            binary_body_path: String::new(),
+            binary_content_type: String::new(),
+            gzip_body: false,
+            delete_body: false,
+            insecure_tls: false,
             query_params: vec![QueryParam::new()],
             form_data: vec![FormField::new()],
             raw_form_content: String::new(),
@@ -2059,6 +4055,7 @@ impl Default for SavedState {
             graphql_expanded_types: std::collections::HashSet::new(),
             graphql_selected_paths: std::collections::HashSet::new(),
             manually_selected_paths: std::collections::HashSet::new(),
+            capture_rules: Vec::new(),
             active_request_tab: RequestTab::Query,
             active_response_tab: ResponseTab::Body,
         }
@@ -2160,24 +4157,103 @@ impl CrabiPie {
 
     fn render_active_tab_content(&self) -> Element<'_, Message> {
         // Render the content using the active tab's data
-        column![
-            self.render_request_row(),
-            row![
-                self.render_request_section(),
-                self.render_response_section()
+        let request_portion = (self.pane_ratio * 1000.0).round() as u16;
+        let response_portion = 1000 - request_portion;
+        let sections: Element<'_, Message> = match self.layout {
+            Layout::Horizontal => row![
+                container(self.render_request_section())
+                    .width(Length::FillPortion(request_portion)),
+                self.render_pane_divider(),
+                container(self.render_response_section())
+                    .width(Length::FillPortion(response_portion)),
+            ]
+            .into(),
+            Layout::Vertical => column![
+                container(self.render_request_section())
+                    .height(Length::FillPortion(request_portion)),
+                self.render_pane_divider(),
+                container(self.render_response_section())
+                    .height(Length::FillPortion(response_portion)),
             ]
+            .into(),
+        };
+        column![self.render_request_row(), sections]
             .spacing(10)
-        ]
-        .spacing(10)
-        .into()
+            .into()
+    }
+
+    // Drag to resize the panes, double-click to split them evenly again
+    fn render_pane_divider(&self) -> Element<'_, Message> {
+        let (line, interaction): (Element<'_, Message>, _) = match self.layout {
+            Layout::Horizontal => (
+                container(rule::vertical(1.0))
+                    .width(10)
+                    .height(Length::Fill)
+                    .center_x(10)
+                    .into(),
+                iced::mouse::Interaction::ResizingHorizontally,
+            ),
+            Layout::Vertical => (
+                container(rule::horizontal(1.0))
+                    .height(10)
+                    .width(Length::Fill)
+                    .center_y(10)
+                    .into(),
+                iced::mouse::Interaction::ResizingVertically,
+            ),
+        };
+        iced::widget::mouse_area(line)
+            .interaction(interaction)
+            .on_press(Message::PaneDividerPressed)
+            .on_double_click(Message::PaneDividerReset)
+            .into()
+    }
+
+    // The panes span roughly the window minus the sidebar and the rows above them
+    fn dragged_pane_ratio(&self, start: iced::Point, ratio: f32, position: iced::Point) -> f32 {
+        let (moved, extent) = match self.layout {
+            Layout::Horizontal => {
+                let sidebar = if self.sidebar_open {
+                    SIDEBAR_WIDTH
+                } else {
+                    0.0
+                };
+                (position.x - start.x, self.window_size.width - sidebar)
+            }
+            Layout::Vertical => (
+                position.y - start.y,
+                self.window_size.height - PANES_TOP_OFFSET,
+            ),
+        };
+        if extent <= 0.0 {
+            return ratio;
+        }
+        (ratio + moved / extent).clamp(MIN_PANE_RATIO, 1.0 - MIN_PANE_RATIO)
     }
 
     fn render_title_row(&self) -> Element<'_, Message> {
+        let name_inputs: Element<'_, Message> = match self.current_tab() {
+            Some(tab) => row![
+                text_input("Request name", &tab.name)
+                    .on_input(Message::RequestNameChanged)
+                    .size(14)
+                    .width(200),
+                text_input("Description", &tab.description)
+                    .on_input(Message::RequestDescriptionChanged)
+                    .size(14)
+                    .width(280),
+            ]
+            .spacing(8)
+            .into(),
+            None => space().into(),
+        };
+
         row![
             button(text(if self.sidebar_open { "◀" } else { "▶" }).size(14))
                 .style(button::text)
                 .on_press(Message::ToggleSidebar),
             text("CrabiPie HTTP Client").size(16),
+            name_inputs,
             space::horizontal(),
             text("App theme"),
             pick_list(
@@ -2185,6 +4261,61 @@ impl CrabiPie {
                 Some(&self.app_theme),
                 Message::AppThemeChanged,
             ),
+            tooltip(
+                button(
+                    text(match self.layout {
+                        Layout::Horizontal => "↕️",
+                        Layout::Vertical => "↔️",
+                    })
+                    .shaping(text::Shaping::Advanced)
+                    .size(14)
+                )
+                .style(button::text)
+                .on_press(Message::ToggleLayout),
+                match self.layout {
+                    Layout::Horizontal => "Stack request and response",
+                    Layout::Vertical => "Show request and response side by side",
+                },
+                tooltip::Position::Bottom,
+            ),
+            tooltip(
+                button(text("🔢").shaping(text::Shaping::Advanced).size(14))
+                    .style(if self.show_line_numbers {
+                        button::secondary
+                    } else {
+                        button::text
+                    })
+                    .on_press(Message::ToggleLineNumbers),
+                if self.show_line_numbers {
+                    "Hide line numbers"
+                } else {
+                    "Show line numbers, long lines aren't wrapped while shown"
+                },
+                tooltip::Position::Bottom,
+            ),
+            tooltip(
+                button(text("↩️").shaping(text::Shaping::Advanced).size(14))
+                    .style(if self.wrap_lines {
+                        button::secondary
+                    } else {
+                        button::text
+                    })
+                    .on_press_maybe((!self.show_line_numbers).then_some(Message::ToggleWrapLines)),
+                if self.show_line_numbers {
+                    "Lines aren't wrapped while line numbers are shown"
+                } else if self.wrap_lines {
+                    "Stop wrapping long lines in the body editors"
+                } else {
+                    "Wrap long lines in the body editors"
+                },
+                tooltip::Position::Bottom,
+            ),
+            button(text("⚙️").shaping(text::Shaping::Advanced).size(14))
+                .style(button::text)
+                .on_press(Message::SettingsOpen),
+            button(text("📥").shaping(text::Shaping::Advanced).size(14))
+                .style(button::text)
+                .on_press(Message::CurlImportOpen),
             button(text("📂").shaping(text::Shaping::Advanced).size(14))
                 .style(button::text)
                 .on_press(Message::LoadRequest),
@@ -2235,11 +4366,18 @@ impl CrabiPie {
             .padding(8)
             .width(100)
         } else {
+            // Invalid JSON is still sent, the warning is only a hint
+            let body_invalid = tab.content_type == ContentType::Json
+                && matches!(tab.is_body_valid_json, Some(Err(_)));
             button(
-                text("📤 Send")
-                    .shaping(text::Shaping::Advanced)
-                    .align_x(alignment::Horizontal::Center)
-                    .width(Length::Fill),
+                text(if body_invalid {
+                    "⚠️ Send"
+                } else {
+                    "📤 Send"
+                })
+                .shaping(text::Shaping::Advanced)
+                .align_x(alignment::Horizontal::Center)
+                .width(Length::Fill),
             )
             .on_press_maybe(if !tab.url.trim().is_empty() {
                 Some(Message::SendRequest)
@@ -2250,7 +4388,157 @@ impl CrabiPie {
             .width(100)
         };
 
-        container(row![req_type, method_picker, url_input, send_button].spacing(10))
+        let download_button = tooltip(
+            button(text("⬇️").shaping(text::Shaping::Advanced))
+                .style(button::text)
+                .on_press_maybe(
+                    (tab.request_type == RequestType::HTTP
+                        && !tab.loading
+                        && !tab.url.trim().is_empty())
+                    .then_some(Message::SendAndDownload),
+                )
+                .padding(8),
+            "Send and save the response straight to a file",
+            tooltip::Position::Bottom,
+        );
+
+        let load_test_button = tooltip(
+            button(text("⏱️").shaping(text::Shaping::Advanced))
+                .style(if tab.load_test_open {
+                    button::secondary
+                } else {
+                    button::text
+                })
+                .on_press_maybe(
+                    (tab.request_type != RequestType::WebSocket).then_some(Message::LoadTestToggle),
+                )
+                .padding(8),
+            "Send this request many times and compare latencies",
+            tooltip::Position::Bottom,
+        );
+
+        let paginate_button = tooltip(
+            button(text("⏭️").shaping(text::Shaping::Advanced))
+                .style(if tab.pagination_open {
+                    button::secondary
+                } else {
+                    button::text
+                })
+                .on_press_maybe(
+                    (tab.request_type == RequestType::HTTP).then_some(Message::PaginationToggle),
+                )
+                .padding(8),
+            "Follow next links and combine the items of every page",
+            tooltip::Position::Bottom,
+        );
+
+        let curl_button = tooltip(
+            button(text("📋").shaping(text::Shaping::Advanced))
+                .style(button::text)
+                .on_press_maybe(if !tab.url.trim().is_empty() {
+                    Some(Message::CopyAsCurl)
+                } else {
+                    None
+                })
+                .padding(8),
+            "Copy as cURL",
+            tooltip::Position::Bottom,
+        );
+
+        // Stands out while verification is off, so it isn't left on by accident
+        let tls_button = if tab.insecure_tls {
+            tooltip(
+                button(text("⚠️ SSL off").shaping(text::Shaping::Advanced).size(13))
+                    .style(button::danger)
+                    .on_press(Message::InsecureTlsToggled(false))
+                    .padding(8),
+                "Certificates are not verified for this request, click to verify them again",
+                tooltip::Position::Bottom,
+            )
+        } else {
+            tooltip(
+                button(text("🔒").shaping(text::Shaping::Advanced))
+                    .style(button::text)
+                    .on_press(Message::InsecureTlsToggled(true))
+                    .padding(8),
+                "Certificates are verified, click to accept self-signed ones",
+                tooltip::Position::Bottom,
+            )
+        };
+
+        let mut section = column![
+            row![
+                req_type,
+                method_picker,
+                url_input,
+                send_button,
+                download_button,
+                load_test_button,
+                paginate_button,
+                curl_button,
+                tls_button
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+        ]
+        .spacing(6);
+        let suggestions = self.url_suggestions();
+        if !suggestions.is_empty() {
+            let mut list = Column::new().spacing(2);
+            for (idx, url) in suggestions.into_iter().enumerate() {
+                list = list.push(
+                    button(text(url.to_string()).size(13))
+                        .style(if self.url_suggest_selected == Some(idx) {
+                            button::primary
+                        } else {
+                            button::text
+                        })
+                        .width(Length::Fill)
+                        .padding([4, 8])
+                        .on_press(Message::UrlSuggestionPicked(url.to_string())),
+                );
+            }
+            section = section.push(
+                row![
+                    space::horizontal().width(220),
+                    container(list)
+                        .padding(4)
+                        .width(Length::Fill)
+                        .style(|theme: &iced::Theme| container::Style {
+                            background: Some(theme.extended_palette().background.weak.color.into()),
+                            border: Border {
+                                width: 1.0,
+                                color: theme.palette().primary,
+                                radius: 6.0.into(),
+                            },
+                            ..Default::default()
+                        }),
+                ]
+                .spacing(10),
+            );
+        }
+        if self.insecure_tls_pending {
+            section = section.push(
+                row![
+                    text(
+                        "⚠️ Turning off SSL verification lets anyone on the network read and alter this request. Only use it for servers you trust."
+                    )
+                    .shaping(text::Shaping::Advanced)
+                    .size(12),
+                    space::horizontal(),
+                    button(text("Turn off").size(12))
+                        .style(button::danger)
+                        .on_press(Message::InsecureTlsConfirmed),
+                    button(text("Cancel").size(12))
+                        .style(button::secondary)
+                        .on_press(Message::InsecureTlsCancelled),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        container(section)
             .padding(Padding::new(0.0).top(10.0))
             .into()
     }
@@ -2271,7 +4559,13 @@ impl CrabiPie {
                 )
                 .push(
                     RequestTab::Body,
-                    iced_aw::TabLabel::Text("Body".into()),
+                    iced_aw::TabLabel::Text(
+                        if tab.request_type == RequestType::HTTP && !tab.sends_body() {
+                            "Body (none)".into()
+                        } else {
+                            "Body".into()
+                        },
+                    ),
                     container({
                         match tab.request_type {
                             RequestType::GraphQL => self.render_graphql_tab(),
@@ -2285,7 +4579,10 @@ impl CrabiPie {
                 )
                 .push(
                     RequestTab::Headers,
-                    iced_aw::TabLabel::Text("Headers".into()),
+                    iced_aw::TabLabel::Text(match self.active_pinned_headers().count() {
+                        0 => "Headers".into(),
+                        count => format!("Headers (+{count} pinned)"),
+                    }),
                     container(self.render_headers_tab()).padding(Padding {
                         top: 10.0,
                         ..Default::default()
@@ -2299,13 +4596,25 @@ impl CrabiPie {
                         ..Default::default()
                     }),
                 )
+                .push(
+                    RequestTab::Captures,
+                    iced_aw::TabLabel::Text("Captures".into()),
+                    container(self.render_captures_tab()).padding(Padding {
+                        top: 10.0,
+                        ..Default::default()
+                    }),
+                )
                 .height(Length::Fill)
                 .set_active_tab(&tab.active_request_tab)
                 .tab_bar_position(iced_aw::TabBarPosition::Top)
                 .into();
 
         let req_title = row![
-            text("Request").height(20),
+            tooltip(
+                text("Request").height(20),
+                "Ctrl+1…5 switch tabs, Ctrl+Shift+[ and ] cycle through them",
+                tooltip::Position::Bottom,
+            ),
             space::horizontal(),
             button(text("🍪 Add Cookie").shaping(text::Shaping::Advanced))
                 .height(20)
@@ -2332,31 +4641,53 @@ impl CrabiPie {
         let Some(tab) = self.current_tab() else {
             return iced::widget::text("Loading...").into();
         };
-        if !matches!(
-            tab.method,
-            HttpMethod::POST | HttpMethod::PUT | HttpMethod::PATCH
-        ) {
-            return text("Select POST, PUT, or PATCH to edit body.").into();
+        if tab.method == HttpMethod::DELETE && !tab.delete_body {
+            return column![
+                text("DELETE requests are sent without a body."),
+                row![
+                    checkbox(false).on_toggle(Message::DeleteBodyToggled),
+                    text("Send a body with DELETE"),
+                ]
+                .spacing(6)
+                .align_y(Alignment::Center),
+            ]
+            .spacing(8)
+            .into();
+        }
+        // The type picker and form editors only matter once there's a body to send
+        if !method_has_body(tab.method) {
+            return text(format!(
+                "{} requests don't have a body, select POST, PUT, PATCH or DELETE to edit one.",
+                tab.method
+            ))
+            .into();
         }
 
-        let toggle_format_or_prettify_btn =
-            button(text(if tab.content_type == ContentType::Json {
+        // Multi-line JSON is taken as already pretty, so the button offers to collapse it
+        let json_is_pretty = tab.request_body.trim().contains('\n');
+        let toggle_format_or_prettify_btn = button(text(
+            if tab.content_type == ContentType::Json && json_is_pretty {
+                "🗜 Minify"
+            } else if tab.content_type == ContentType::Json {
                 "✨ Prettify"
             } else if tab.form_view_type == FormViewType::Formatted {
                 "View raw"
             } else {
                 "View Formatted"
-            }))
-            .style(button::text)
-            .on_press(if tab.content_type == ContentType::Json {
-                Message::PrettifyJson
-            } else if tab.form_view_type == FormViewType::Formatted {
-                Message::ViewRawForm
-            } else {
-                Message::ViewFormattedForm
-            });
+            },
+        ))
+        .style(button::text)
+        .on_press(if tab.content_type == ContentType::Json && json_is_pretty {
+            Message::MinifyJson
+        } else if tab.content_type == ContentType::Json {
+            Message::PrettifyBody
+        } else if tab.form_view_type == FormViewType::Formatted {
+            Message::ViewRawForm
+        } else {
+            Message::ViewFormattedForm
+        });
 
-        let type_selector = row![
+        let mut type_selector = row![
             text("Type:"),
             pick_list(
                 &ContentType::ALL[..],
@@ -2364,39 +4695,124 @@ impl CrabiPie {
                 Message::ContentTypeSelected
             ),
             space::horizontal(),
-            toggle_format_or_prettify_btn,
         ]
         .height(20)
         .spacing(10)
         .align_y(Alignment::Center);
+        if let Some(picker) = self.render_template_picker() {
+            type_selector = type_selector.push(picker);
+        }
+        if tab.content_type == ContentType::Json {
+            type_selector = type_selector.push(
+                button(text("📂 Load from file").shaping(text::Shaping::Advanced))
+                    .style(button::text)
+                    .on_press(Message::BodyFileLoad),
+            );
+        }
+        // Form bodies are built by reqwest, only the raw ones can be compressed
+        if matches!(tab.content_type, ContentType::Json | ContentType::Binary) {
+            type_selector = type_selector.push(
+                row![
+                    checkbox(tab.gzip_body).on_toggle(Message::GzipBodyToggled),
+                    text("Gzip"),
+                ]
+                .spacing(6)
+                .align_y(Alignment::Center),
+            );
+        }
+        if tab.content_type != ContentType::Binary {
+            type_selector = type_selector.push(toggle_format_or_prettify_btn);
+        }
+        if tab.method == HttpMethod::DELETE {
+            type_selector = type_selector.push(
+                row![
+                    checkbox(tab.delete_body).on_toggle(Message::DeleteBodyToggled),
+                    text("Send with DELETE"),
+                ]
+                .spacing(6)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        let mut header = Column::new().spacing(6).push(type_selector);
+        if let Some(confirm) = self.render_template_confirm() {
+            header = header.push(confirm);
+        }
+        if let Some((path, size)) = &self.body_file_pending {
+            let name = std::path::Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.clone());
+            header = header.push(
+                row![
+                    text(format!(
+                        "⚠️ {} is {}, loading it may make the editor slow.",
+                        name,
+                        Self::human_size(*size as usize)
+                    ))
+                    .shaping(text::Shaping::Advanced)
+                    .size(12),
+                    space::horizontal(),
+                    button(text("Load anyway").size(12))
+                        .style(button::primary)
+                        .on_press(Message::BodyFileLoadConfirmed),
+                    button(text("Cancel").size(12))
+                        .style(button::secondary)
+                        .on_press(Message::BodyFileLoadCancelled),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            );
+        }
+        if let Some(err) = &self.body_file_error {
+            header =
+                header.push(
+                    text(err.clone())
+                        .size(12)
+                        .style(|theme: &iced::Theme| text::Style {
+                            color: Some(theme.palette().danger),
+                        }),
+                );
+        }
 
         let editor_content = match tab.content_type {
-            ContentType::Json => scrollable(
-                text_editor(&self.request_body_content)
+            ContentType::Json => {
+                let editor = text_editor(&self.request_body_content)
                     .on_action(Message::BodyAction)
                     .highlight_with::<json_highlighter::JsonHighlighter>(
-                        self.get_highlighter_settings(),
-                        |highlight, _theme| {
-                            let color = match highlight {
-                                json_highlighter::HighlightType::Syntax(color) => *color,
-                                json_highlighter::HighlightType::SearchMatch => {
-                                    iced::Color::from_rgb(1.0, 1.0, 0.0)
-                                }
-                                json_highlighter::HighlightType::CurrentMatch => {
-                                    iced::Color::from_rgb(1.0, 0.5, 0.0)
-                                }
-                            };
-
-                            iced::advanced::text::highlighter::Format {
-                                color: Some(color),
-                                font: None,
-                            }
-                        },
+                        self.get_highlighter_settings(Some(FindTarget::RequestBody))
+                            .with_error(tab.body_json_error_at),
+                        Self::json_highlight_format,
                     )
-                    .style(Self::get_editor_style),
-            )
-            .height(Length::Fill)
-            .into(),
+                    .style(Self::get_editor_style);
+                if self.wrap_lines && !self.show_line_numbers {
+                    scrollable(editor).height(Length::Fill).into()
+                } else {
+                    // Unwrapped lines scroll sideways instead of being cut off
+                    let editor = editor
+                        .wrapping(iced::advanced::text::Wrapping::None)
+                        .width(unwrapped_editor_width(&tab.request_body));
+                    let editor: Element<'_, Message> = if self.show_line_numbers {
+                        row![
+                            self.line_number_gutter(
+                                self.request_body_content.line_count(),
+                                FindTarget::RequestBody
+                            ),
+                            editor
+                        ]
+                        .into()
+                    } else {
+                        editor.into()
+                    };
+                    scrollable(editor)
+                        .direction(scrollable::Direction::Both {
+                            vertical: scrollable::Scrollbar::default(),
+                            horizontal: scrollable::Scrollbar::default(),
+                        })
+                        .height(Length::Fill)
+                        .into()
+                }
+            }
             ContentType::FormData | ContentType::XWWWFormUrlEncoded => match tab.form_view_type {
                 FormViewType::Formatted => self.render_form_data(),
                 FormViewType::Raw => scrollable(
@@ -2408,26 +4824,208 @@ impl CrabiPie {
                 )
                 .into(),
             },
+            ContentType::Binary => self.render_binary_body(tab),
         };
 
-        column![type_selector, editor_content]
+        let mut body = column![header, editor_content]
             .spacing(10)
-            .height(Length::Fill)
-            .into()
+            .height(Length::Fill);
+        if tab.content_type == ContentType::Json
+            && let Some(Err(err)) = &tab.is_body_valid_json
+        {
+            body = body.push(
+                text(format!("⚠️ {}", err))
+                    .shaping(text::Shaping::Advanced)
+                    .size(12)
+                    .style(|theme: &iced::Theme| text::Style {
+                        color: Some(theme.palette().danger),
+                    }),
+            );
+        }
+        body.into()
     }
 
-    fn render_form_data(&self) -> Element<'_, Message> {
-        let Some(tab) = self.current_tab() else {
-            return iced::widget::text("Loading...").into();
-        };
-        let is_url_encoded = matches!(tab.content_type, ContentType::XWWWFormUrlEncoded);
+    fn render_template_picker(&self) -> Option<Element<'_, Message>> {
+        let templates = body_templates(self.current_tab()?);
+        if templates.is_empty() {
+            return None;
+        }
+        let names: Vec<&'static str> = templates.iter().map(|(name, _)| *name).collect();
+        Some(
+            pick_list(names, None::<&'static str>, Message::BodyTemplateSelected)
+                .placeholder("Insert template")
+                .text_size(13)
+                .into(),
+        )
+    }
 
-        let mut fields_col = Column::new().spacing(10);
+    fn render_template_confirm(&self) -> Option<Element<'_, Message>> {
+        let name = self.current_tab()?.body_template_pending?;
+        Some(
+            row![
+                text(format!("⚠️ Replace the current body with \"{}\"?", name))
+                    .shaping(text::Shaping::Advanced)
+                    .size(12),
+                space::horizontal(),
+                button(text("Replace").size(12))
+                    .style(button::primary)
+                    .on_press(Message::BodyTemplateConfirmed),
+                button(text("Cancel").size(12))
+                    .style(button::secondary)
+                    .on_press(Message::BodyTemplateCancelled),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into(),
+        )
+    }
 
-        for (idx, field) in tab.form_data.iter().enumerate() {
-            // Force text type if URL-encoded
-            let effective_type = if is_url_encoded {
-                FormFieldType::Text
+    // What a template would replace, as text, to tell edits apart from a starter body
+    fn template_target_text(&self) -> String {
+        let Some(tab) = self.current_tab() else {
+            return String::new();
+        };
+        if tab.request_type == RequestType::GraphQL {
+            return tab.graphql_query.to_string();
+        }
+        match tab.content_type {
+            ContentType::Json => tab.request_body.to_string(),
+            ContentType::FormData | ContentType::XWWWFormUrlEncoded => {
+                if tab.form_view_type == FormViewType::Raw {
+                    tab.raw_form_content.to_string()
+                } else {
+                    TabState::form_data_to_raw(&tab.form_data)
+                }
+            }
+            ContentType::Binary => String::new(),
+        }
+    }
+
+    fn load_body_file(&self, path: String) -> iced::Task<Message> {
+        let Some(tab) = self.current_tab() else {
+            return iced::Task::none();
+        };
+        let tab_id = tab.id;
+        iced::Task::perform(read_body_file(path), move |result| {
+            Message::BodyLoadedFromFile(tab_id, result)
+        })
+    }
+
+    fn apply_body_template(&mut self, body: &str) {
+        let Some(tab) = self.current_tab_mut() else {
+            return;
+        };
+        // Applied straight from BodyTemplateSelected too, which isn't an edit by itself
+        tab.dirty = true;
+        tab.unsaved_edits = true;
+        if tab.request_type == RequestType::GraphQL {
+            tab.graphql_query = std::sync::Arc::from(body);
+            self.graphql_query_content = text_editor::Content::with_text(body);
+            return;
+        }
+        match tab.content_type {
+            ContentType::Json => {
+                tab.request_body = std::sync::Arc::from(body);
+                tab.is_body_valid_json = None;
+                self.request_body_content = text_editor::Content::with_text(body);
+            }
+            ContentType::FormData | ContentType::XWWWFormUrlEncoded => {
+                tab.form_data = TabState::raw_to_form_data(body);
+                if tab.form_data.is_empty() {
+                    tab.form_data.push(FormField::new());
+                }
+                tab.raw_form_content = std::sync::Arc::from(body);
+                self.raw_form_content = text_editor::Content::with_text(body);
+            }
+            ContentType::Binary => {}
+        }
+    }
+
+    fn render_binary_body<'a>(&self, tab: &'a TabState) -> Element<'a, Message> {
+        let file_label = if tab.binary_body_path.is_empty() {
+            text("No file selected").size(13)
+        } else {
+            let name = std::path::Path::new(&tab.binary_body_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| tab.binary_body_path.clone());
+            text(match tab.binary_body_size {
+                Some(size) => format!("{} ({})", name, Self::human_size(size as usize)),
+                None => name,
+            })
+            .size(13)
+        };
+
+        let mut col = column![
+            row![
+                button(text("📁 Choose file").shaping(text::Shaping::Advanced))
+                    .on_press(Message::BinaryBodyFileSelect),
+                file_label,
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            row![
+                text("Content-Type:"),
+                text_input("application/octet-stream", &tab.binary_content_type)
+                    .on_input(Message::BinaryContentTypeChanged)
+                    .width(300),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10);
+        if let Some(err) = &tab.binary_body_error {
+            col = col.push(
+                text(format!("⚠️ {}", err))
+                    .shaping(text::Shaping::Advanced)
+                    .size(12)
+                    .style(|theme: &iced::Theme| text::Style {
+                        color: Some(theme.palette().danger),
+                    }),
+            );
+        }
+        col.height(Length::Fill).into()
+    }
+
+    fn render_form_data(&self) -> Element<'_, Message> {
+        let Some(tab) = self.current_tab() else {
+            return iced::widget::text("Loading...").into();
+        };
+        let is_url_encoded = matches!(tab.content_type, ContentType::XWWWFormUrlEncoded);
+
+        let mut fields_col = Column::new().spacing(10);
+        if !tab.form_data_errors.is_empty() {
+            let danger = |theme: &iced::Theme| text::Style {
+                color: Some(theme.palette().danger),
+            };
+            let mut errors_col = Column::new().spacing(2).push(
+                text("⚠️ Not sent, these files are missing or can't be read:")
+                    .shaping(text::Shaping::Advanced)
+                    .size(12)
+                    .style(danger),
+            );
+            for error in &tab.form_data_errors {
+                errors_col = errors_col.push(text(format!(" • {error}")).size(12).style(danger));
+            }
+            fields_col = fields_col.push(errors_col);
+        }
+        // Their type is kept but a URL-encoded body only carries text
+        if is_url_encoded
+            && tab
+                .form_data
+                .iter()
+                .any(|f| f.enabled && !f.key.is_empty() && f.field_type == FormFieldType::File)
+        {
+            fields_col = fields_col.push(Self::header_warning(
+                "File fields are left out of a URL-encoded body, switch to form data to upload them"
+                    .to_string(),
+            ));
+        }
+
+        for (idx, field) in tab.form_data.iter().enumerate() {
+            // Force text type if URL-encoded
+            let effective_type = if is_url_encoded {
+                FormFieldType::Text
             } else {
                 field.field_type.clone()
             };
@@ -2478,23 +5076,81 @@ impl CrabiPie {
                 ));
             }
 
-            field_row = field_row.push(value_or_file).push(
-                button(text("❌").shaping(text::Shaping::Advanced))
-                    .style(button::subtle)
-                    .on_press(Message::FormFieldRemove(idx)),
-            );
+            field_row = field_row.push(value_or_file);
+            // Only multipart bodies have parts that can carry their own type
+            if !is_url_encoded {
+                field_row = field_row.push(
+                    text_input("type (auto)", field.content_type.as_deref().unwrap_or(""))
+                        .on_input(move |ct| Message::FormFieldContentTypeChanged(idx, ct))
+                        .width(140),
+                );
+            }
+            field_row = field_row
+                .push(
+                    button(text("▲").size(12))
+                        .style(button::subtle)
+                        .on_press_maybe((idx > 0).then_some(Message::FormFieldMoveUp(idx))),
+                )
+                .push(
+                    button(text("▼").size(12))
+                        .style(button::subtle)
+                        .on_press_maybe(
+                            (idx + 1 < tab.form_data.len())
+                                .then_some(Message::FormFieldMoveDown(idx)),
+                        ),
+                )
+                .push(
+                    button(text("⧉").shaping(text::Shaping::Advanced))
+                        .style(button::subtle)
+                        .on_press(Message::FormFieldDuplicate(idx)),
+                )
+                .push(
+                    button(text("❌").shaping(text::Shaping::Advanced))
+                        .style(button::subtle)
+                        .on_press(Message::FormFieldRemove(idx)),
+                );
 
             fields_col = fields_col.push(field_row);
+            if !is_url_encoded
+                && let Some(ct) = field.content_type.as_deref()
+                && field.part_content_type().is_none()
+                && !ct.trim().is_empty()
+            {
+                fields_col = fields_col.push(Self::header_warning(format!(
+                    "\"{}\" isn't a valid content type, reqwest picks one instead",
+                    ct.trim()
+                )));
+            }
 
             // Show selected files (only for File type and not urlencoded)
             if effective_type == FormFieldType::File && !field.files.is_empty() && !is_url_encoded {
                 let mut files_col = Column::new().spacing(4);
-                for file in &field.files {
+                for (file_idx, file) in field.files.iter().enumerate() {
                     let filename = std::path::Path::new(file)
                         .file_name()
                         .and_then(|n| n.to_str())
                         .unwrap_or(file);
-                    files_col = files_col.push(text(format!(" • {filename}")).size(13));
+                    let label = match tab.form_file_sizes.get(file) {
+                        Some(&size) => {
+                            format!(" • {filename} ({})", Self::human_size(size as usize))
+                        }
+                        None => format!(" • {filename}"),
+                    };
+                    files_col = files_col.push(
+                        row![
+                            tooltip(
+                                text(label).size(13),
+                                text(file.as_str()).size(12),
+                                tooltip::Position::Bottom,
+                            ),
+                            button(text("✕").size(11))
+                                .style(button::subtle)
+                                .padding([2, 6])
+                                .on_press(Message::FormFieldFileRemove(idx, file_idx)),
+                        ]
+                        .spacing(6)
+                        .align_y(Alignment::Center),
+                    );
                 }
                 fields_col = fields_col.push(container(files_col).padding(Padding {
                     left: 20.0,
@@ -2530,6 +5186,19 @@ impl CrabiPie {
                 .on_input(move |val| Message::QueryParamValueChanged(idx, val))
                 .width(300);
 
+            let move_up_btn = button(text("▲").size(12))
+                .style(button::text)
+                .on_press_maybe((idx > 0).then_some(Message::QueryParamMoveUp(idx)));
+            let move_down_btn = button(text("▼").size(12))
+                .style(button::text)
+                .on_press_maybe(
+                    (idx + 1 < tab.query_params.len()).then_some(Message::QueryParamMoveDown(idx)),
+                );
+
+            let duplicate_btn = button(text("⧉").shaping(text::Shaping::Advanced))
+                .style(button::text)
+                .on_press(Message::QueryParamDuplicate(idx));
+
             let remove_btn = button(text("❌").shaping(text::Shaping::Advanced))
                 .style(button::text)
                 .on_press(Message::QueryParamRemove(idx));
@@ -2540,6 +5209,9 @@ impl CrabiPie {
                 key_input,
                 text("Value:"),
                 value_input,
+                move_up_btn,
+                move_down_btn,
+                duplicate_btn,
                 remove_btn,
             ]
             .spacing(8)
@@ -2560,20 +5232,191 @@ impl CrabiPie {
             .into()
     }
 
-    fn build_query_string(&self) -> String {
+    fn render_captures_tab(&self) -> Element<'_, Message> {
         let Some(tab) = self.current_tab() else {
-            return "Loading...".into();
+            return iced::widget::text("Loading...").into();
+        };
+        let mut rules_col = Column::new().spacing(10).push(
+            text("Store values from the JSON response, use them in any request as {{name}}")
+                .size(12),
+        );
+
+        for (idx, rule) in tab.capture_rules.iter().enumerate() {
+            rules_col = rules_col.push(
+                row![
+                    checkbox(rule.enabled).on_toggle(move |_| Message::CaptureRuleToggled(idx)),
+                    text("Name:"),
+                    text_input("token", &rule.name)
+                        .on_input(move |name| Message::CaptureRuleNameChanged(idx, name))
+                        .width(160),
+                    text("JSONPath:"),
+                    text_input("$.access_token", &rule.path)
+                        .on_input(move |path| Message::CaptureRulePathChanged(idx, path))
+                        .width(Length::Fill),
+                    button(text("❌").shaping(text::Shaping::Advanced))
+                        .style(button::text)
+                        .on_press(Message::CaptureRuleRemove(idx)),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            );
+            if let Some((_, err)) = tab.capture_errors.iter().find(|(i, _)| *i == idx) {
+                rules_col = rules_col.push(Self::header_warning(err.clone()));
+            }
+        }
+
+        rules_col = rules_col.push(
+            button(text("➕ Add").shaping(text::Shaping::Advanced))
+                .style(button::subtle)
+                .on_press(Message::CaptureRuleAdd),
+        );
+
+        let mut vars_col = Column::new().spacing(6).push(
+            row![
+                text("Variables").size(14),
+                space::horizontal(),
+                button(text("Clear all").size(12))
+                    .style(button::secondary)
+                    .on_press_maybe(
+                        (!self.captured_vars.is_empty()).then_some(Message::CapturedVarsClear)
+                    ),
+            ]
+            .align_y(Alignment::Center),
+        );
+        if self.captured_vars.is_empty() {
+            vars_col = vars_col.push(text("Nothing captured yet").size(12));
+        }
+        for (name, value) in &self.captured_vars {
+            // Long values such as JWTs are cut, the full value is still substituted
+            let shown: String = if value.chars().count() > 80 {
+                format!("{}…", value.chars().take(80).collect::<String>())
+            } else {
+                value.clone()
+            };
+            vars_col = vars_col.push(
+                row![
+                    text(format!("{{{{{}}}}}", name)).size(13).width(160),
+                    text(shown).size(13).width(Length::Fill),
+                    button(text("❌").shaping(text::Shaping::Advanced))
+                        .style(button::text)
+                        .on_press(Message::CapturedVarRemove(name.clone())),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        scrollable(column![rules_col, rule::horizontal(1.0), vars_col].spacing(12))
+            .height(Length::Fill)
+            .width(Length::Fill)
+            .into()
+    }
+
+    // Shows the items gathered by an auto-paginate run as the tab's response,
+    // so the Body and Tree tabs can be used on them
+    fn show_paginated_items(&mut self, tab_id: usize) {
+        let is_active = self.is_active_tab(tab_id);
+        let Some(tab) = self.tab_mut_by_id(tab_id) else {
+            return;
+        };
+        let Some(run) = &tab.pagination_run else {
+            return;
+        };
+        if run.pages == 0 {
+            return;
+        }
+        let combined = serde_json::Value::Array(run.items.clone());
+        let body = serde_json::to_string_pretty(&combined).unwrap_or_default();
+        tab.is_response_binary = false;
+        tab.response_bytes.clear();
+        tab.response_html.clear();
+        tab.response_diff = None;
+        tab.dirty = true;
+        tab.response_content_type = "application/json".to_string();
+        tab.response_body = std::sync::Arc::from(body.as_str());
+        tab.response_images = if tab.render_json_images {
+            json_image_handles(&combined)
+        } else {
+            std::collections::HashMap::new()
+        };
+        tab.response_json = Some(std::sync::Arc::new(combined));
+        tab.refresh_response_display();
+        tab.refresh_raw_response();
+        if is_active {
+            self.response_body_content = tab.response_editor_content();
+            self.json_tree_expanded.clear();
+            self.json_tree_expanded.insert("$".to_string());
+        }
+    }
+
+    // Runs the tab's capture rules against a finished response. A rule that
+    // matches nothing keeps the variable's old value and is flagged in the tab
+    fn apply_captures(&mut self, tab_id: usize, body: &str) {
+        let Some(tab) = self.tab_mut_by_id(tab_id) else {
+            return;
         };
-        let params: Vec<String> = tab
-            .query_params
+        tab.capture_errors.clear();
+        let rules: Vec<(usize, CaptureRule)> = tab
+            .capture_rules
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(_, r)| r.enabled && !r.name.trim().is_empty() && !r.path.trim().is_empty())
+            .collect();
+        if rules.is_empty() {
+            return;
+        }
+
+        let json = serde_json::from_str::<serde_json::Value>(body);
+        let mut errors = Vec::new();
+        for (idx, rule) in rules {
+            let json = match &json {
+                Ok(json) => json,
+                Err(e) => {
+                    errors.push((idx, format!("Response is not JSON: {e}")));
+                    continue;
+                }
+            };
+            match json.query(rule.path.trim()) {
+                Ok(matches) => match matches.first() {
+                    // Strings are stored without their quotes, anything else as JSON
+                    Some(serde_json::Value::String(s)) => {
+                        self.captured_vars
+                            .insert(rule.name.trim().to_string(), s.clone());
+                    }
+                    Some(value) => {
+                        self.captured_vars
+                            .insert(rule.name.trim().to_string(), value.to_string());
+                    }
+                    None => errors.push((idx, format!("{} matched nothing", rule.path.trim()))),
+                },
+                Err(e) => errors.push((idx, e.to_string())),
+            }
+        }
+        if let Some(tab) = self.tab_mut_by_id(tab_id) {
+            tab.capture_errors = errors;
+        }
+    }
+
+    fn substitute_vars(&self, input: &str) -> String {
+        substitute_vars(input, &self.captured_vars)
+    }
+
+    // Inverse of parse_url_query, a param without a value is written as a bare key
+    fn build_query_string(query_params: &[QueryParam]) -> String {
+        let params: Vec<String> = query_params
             .iter()
             .filter(|p| p.enabled && !p.key.is_empty())
             .map(|p| {
-                format!(
-                    "{}={}",
-                    urlencoding::encode(&p.key),
-                    urlencoding::encode(&p.value)
-                )
+                if p.value.is_empty() {
+                    urlencoding::encode(&p.key).into_owned()
+                } else {
+                    format!(
+                        "{}={}",
+                        urlencoding::encode(&p.key),
+                        urlencoding::encode(&p.value)
+                    )
+                }
             })
             .collect();
 
@@ -2584,15 +5427,136 @@ impl CrabiPie {
         }
     }
 
+    fn header_warning<'a>(message: String) -> Element<'a, Message> {
+        text(format!("⚠️ {message}"))
+            .shaping(text::Shaping::Advanced)
+            .size(12)
+            .style(|_| text::Style {
+                color: Some(iced::Color::from_rgb(1.0, 0.65, 0.0)),
+            })
+            .into()
+    }
+
+    // Enabled pinned headers from the settings that go out with every request
+    fn active_pinned_headers(&self) -> impl Iterator<Item = &RequestHeaders> {
+        self.http_settings
+            .pinned_headers
+            .iter()
+            .filter(|h| h.enabled && !h.key.trim().is_empty())
+    }
+
+    // Names the pinned headers this request sends, so one showing up in the
+    // sent request is never a surprise
+    fn pinned_headers_note(&self, tab: &TabState) -> Option<Element<'_, Message>> {
+        let names: Vec<String> = self
+            .active_pinned_headers()
+            .map(|pinned| {
+                let overridden = tab
+                    .headers
+                    .iter()
+                    .any(|h| h.enabled && h.key.trim().eq_ignore_ascii_case(pinned.key.trim()));
+                if overridden {
+                    format!("{} (overridden here)", pinned.key.trim())
+                } else {
+                    pinned.key.trim().to_string()
+                }
+            })
+            .collect();
+        if names.is_empty() {
+            return None;
+        }
+        Some(
+            row![
+                text(format!("📌 Pinned in Settings: {}", names.join(", ")))
+                    .shaping(text::Shaping::Advanced)
+                    .size(12)
+                    .width(Length::Fill),
+                button(text("Edit").size(12))
+                    .style(button::text)
+                    .on_press(Message::SettingsOpen),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into(),
+        )
+    }
+
     fn render_headers_tab(&self) -> Element<'_, Message> {
         let Some(tab) = self.current_tab() else {
             return iced::widget::text("Loading...").into();
         };
 
-        let mut headers_col = Column::new().spacing(10);
+        let view_toggle = row![
+            space::horizontal(),
+            button(text(if self.headers_raw_mode {
+                "View Formatted"
+            } else {
+                "View raw"
+            }))
+            .style(button::text)
+            .on_press(if self.headers_raw_mode {
+                Message::HeadersViewFormatted
+            } else {
+                Message::HeadersViewRaw
+            }),
+        ]
+        .height(20)
+        .align_y(Alignment::Center);
+
+        if self.headers_raw_mode {
+            let editor = text_editor(&self.raw_headers_content)
+                .placeholder("Content-Type: application/json\n# Disabled-Header: value")
+                .on_action(Message::HeadersRawAction)
+                .style(Self::get_editor_style)
+                .min_height(200.0);
+            let mut errors_col = Column::new().spacing(2);
+            for (line, err) in &self.header_errors {
+                errors_col = errors_col.push(Self::header_warning(format!(
+                    "Line {line}: {err}, not sent"
+                )));
+            }
+            // One switch per header line, flipping it adds or removes the leading `#`.
+            // A line without ':' can't be commented back in, so it gets none
+            let mut toggles_col = Column::new().spacing(4);
+            for (idx, header) in tab.headers.iter().enumerate() {
+                if header.missing_colon {
+                    continue;
+                }
+                toggles_col = toggles_col.push(
+                    row![
+                        checkbox(header.enabled).on_toggle(move |_| Message::HeaderToggled(idx)),
+                        text(header.key.as_str()).size(12),
+                    ]
+                    .spacing(6)
+                    .align_y(Alignment::Center),
+                );
+            }
+            let mut raw_col = column![view_toggle].spacing(10).height(Length::Fill);
+            if let Some(note) = self.pinned_headers_note(tab) {
+                raw_col = raw_col.push(note);
+            }
+            return raw_col
+                .push(
+                    row![
+                        scrollable(editor).height(Length::Fill).width(Length::Fill),
+                        scrollable(toggles_col).height(Length::Fill).width(180),
+                    ]
+                    .spacing(10)
+                    .height(Length::Fill),
+                )
+                .push(errors_col)
+                .into();
+        }
+
+        let mut headers_col = Column::new().spacing(10).push(view_toggle);
+        if let Some(note) = self.pinned_headers_note(tab) {
+            headers_col = headers_col.push(note);
+        }
 
         for (idx, header) in tab.headers.iter().enumerate() {
-            let checkbox = checkbox(header.enabled).on_toggle(move |_| Message::HeaderToggled(idx));
+            let checkbox = checkbox(header.enabled).on_toggle_maybe(
+                (!header.missing_colon).then_some(move |_| Message::HeaderToggled(idx)),
+            );
 
             let key_input = text_input("key", &header.key)
                 .on_input(move |key| Message::HeaderKeyChanged(idx, key))
@@ -2618,6 +5582,48 @@ impl CrabiPie {
             .align_y(Alignment::Center);
 
             headers_col = headers_col.push(param_row);
+            if header.missing_colon {
+                headers_col = headers_col.push(Self::header_warning(
+                    "missing ':' between name and value, not sent until it has a value".to_string(),
+                ));
+            } else if header.enabled && !header.key.trim().is_empty() {
+                if let Err(err) = check_header(&header.key, &header.value) {
+                    headers_col =
+                        headers_col.push(Self::header_warning(format!("{err}, not sent")));
+                }
+            }
+
+            if self.header_suggest_idx == Some(idx) {
+                let suggestions = header_name_suggestions(&header.key);
+                if !suggestions.is_empty() {
+                    let mut suggestion_row = row![space::horizontal().width(70)].spacing(4);
+                    for name in suggestions {
+                        suggestion_row = suggestion_row.push(
+                            button(text(name).size(12))
+                                .style(button::secondary)
+                                .padding([2, 6])
+                                .on_press(Message::HeaderSuggestionPicked(idx, name)),
+                        );
+                    }
+                    headers_col = headers_col.push(suggestion_row);
+                }
+            }
+
+            if self.header_value_suggest_idx == Some(idx) {
+                let suggestions = header_value_suggestions(&header.key, &header.value);
+                if !suggestions.is_empty() {
+                    let mut suggestion_row = row![space::horizontal().width(330)].spacing(4);
+                    for value in suggestions {
+                        suggestion_row = suggestion_row.push(
+                            button(text(value).size(12))
+                                .style(button::secondary)
+                                .padding([2, 6])
+                                .on_press(Message::HeaderValueSuggestionPicked(idx, value)),
+                        );
+                    }
+                    headers_col = headers_col.push(suggestion_row);
+                }
+            }
         }
 
         headers_col = headers_col.push(
@@ -2657,6 +5663,24 @@ impl CrabiPie {
             .spacing(10)
             .align_y(Alignment::Center)
             .into(),
+            AuthType::Basic => row![
+                column![text("Username:"), text("Password:"),]
+                    .align_x(Alignment::Center)
+                    .spacing(10),
+                column![
+                    text_input("", &tab.basic_username)
+                        .on_input(Message::BasicUsernameChanged)
+                        .width(Length::Fill),
+                    text_input("", &tab.basic_password)
+                        .on_input(Message::BasicPasswordChanged)
+                        .secure(true)
+                        .width(Length::Fill),
+                ]
+                .spacing(10),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10)
+            .into(),
             AuthType::ApiKey => row![
                 column![text("Key:"), text("Value:"), text("Add to:"),]
                     .align_x(Alignment::Center)
@@ -2679,12 +5703,199 @@ impl CrabiPie {
             .align_y(Alignment::Center)
             .spacing(10)
             .into(),
-        };
-
-        column![type_selector, auth_form].spacing(10).into()
-    }
-
-    fn render_cookie_jar_modal(&self) -> Element<'_, Message> {
+            AuthType::OAuth2ClientCredentials => {
+                let status: Element<'_, Message> = if let Some(err) = &tab.oauth_error {
+                    text(format!("⚠️ {}", err))
+                        .shaping(text::Shaping::Advanced)
+                        .size(12)
+                        .style(|theme: &iced::Theme| text::Style {
+                            color: Some(theme.palette().danger),
+                        })
+                        .into()
+                } else if tab.oauth_access_token.is_empty() {
+                    text("No token yet, one is fetched on send").size(12).into()
+                } else {
+                    let status = match tab.oauth_expires_at {
+                        Some(_) if !tab.oauth_token_valid() => "Token expired".to_string(),
+                        Some(at) => format!(
+                            "Token expires in {}s",
+                            at.saturating_duration_since(std::time::Instant::now())
+                                .as_secs()
+                        ),
+                        None => "Token has no expiry".to_string(),
+                    };
+                    text(status).size(12).into()
+                };
+                let get_token = button(
+                    text(if tab.oauth_fetching {
+                        "Fetching..."
+                    } else {
+                        "Get Token"
+                    })
+                    .size(14),
+                )
+                .on_press_maybe(
+                    (!tab.oauth_fetching && !tab.oauth_token_url.trim().is_empty())
+                        .then_some(Message::OAuthGetToken),
+                );
+                column![
+                    row![
+                        column![
+                            text("Token URL:"),
+                            text("Client ID:"),
+                            text("Client Secret:"),
+                            text("Scope:"),
+                        ]
+                        .spacing(10),
+                        column![
+                            text_input(
+                                "https://auth.example.com/oauth/token",
+                                &tab.oauth_token_url
+                            )
+                            .on_input(Message::OAuthTokenUrlChanged)
+                            .width(Length::Fill),
+                            text_input("", &tab.oauth_client_id)
+                                .on_input(Message::OAuthClientIdChanged)
+                                .width(Length::Fill),
+                            text_input("Not saved with the request", &tab.oauth_client_secret)
+                                .on_input(Message::OAuthClientSecretChanged)
+                                .secure(true)
+                                .width(Length::Fill),
+                            text_input("Optional, space separated", &tab.oauth_scope)
+                                .on_input(Message::OAuthScopeChanged)
+                                .width(Length::Fill),
+                        ]
+                        .spacing(10),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                    row![get_token, status]
+                        .spacing(10)
+                        .align_y(Alignment::Center),
+                ]
+                .spacing(10)
+                .into()
+            }
+        };
+
+        let names: Vec<String> = self.auth_presets.iter().map(|p| p.name.clone()).collect();
+        let selected = self
+            .auth_preset_selected
+            .and_then(|idx| names.get(idx).cloned());
+        let lookup = names.clone();
+        let presets = column![
+            row![
+                text("Preset:"),
+                pick_list(names, selected, move |name: String| {
+                    Message::AuthPresetSelected(
+                        lookup.iter().position(|n| *n == name).unwrap_or_default(),
+                    )
+                })
+                .placeholder("Choose a preset")
+                .width(180),
+                button(text("❌").shaping(text::Shaping::Advanced))
+                    .style(button::text)
+                    .on_press_maybe(self.auth_preset_selected.map(Message::AuthPresetDelete)),
+                space::horizontal(),
+                text_input("Preset name", &self.auth_preset_name)
+                    .on_input(Message::AuthPresetNameChanged)
+                    .on_submit(Message::AuthPresetSave)
+                    .width(180),
+                button(text("💾 Save as preset").shaping(text::Shaping::Advanced))
+                    .style(button::text)
+                    .on_press_maybe(
+                        (!self.auth_preset_name.trim().is_empty())
+                            .then_some(Message::AuthPresetSave)
+                    ),
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center),
+            text("⚠️ Presets, tokens and passwords included, are stored unencrypted in ~/.crabipie/auth_presets.json")
+            .shaping(text::Shaping::Advanced)
+            .size(12),
+        ]
+        .spacing(6);
+
+        let signing = &tab.signing;
+        let mut signing_col = column![
+            row![
+                checkbox(signing.enabled).on_toggle(Message::SigningToggled),
+                text("Sign the request, the signature is added as a header"),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(10);
+        if signing.enabled {
+            signing_col = signing_col.push(
+                column![
+                    row![
+                        text("Algorithm:"),
+                        pick_list(
+                            &SigningAlgorithm::ALL[..],
+                            Some(signing.algorithm),
+                            Message::SigningAlgorithmSelected
+                        ),
+                        text("Encoding:"),
+                        pick_list(
+                            &SignatureEncoding::ALL[..],
+                            Some(signing.encoding),
+                            Message::SigningEncodingSelected
+                        ),
+                    ]
+                    .spacing(10)
+                    .align_y(Alignment::Center),
+                    row![
+                        column![
+                            text("Secret:"),
+                            text("Send in header:"),
+                            text("Also sign header:")
+                        ]
+                        .spacing(10),
+                        column![
+                            text_input("Not saved with the request", &tab.signing_secret)
+                                .on_input(Message::SigningSecretChanged)
+                                .secure(true)
+                                .width(Length::Fill),
+                            text_input("X-Signature", &signing.header_name)
+                                .on_input(Message::SigningHeaderNameChanged)
+                                .width(Length::Fill),
+                            text_input("Optional, e.g. X-Timestamp", &signing.sign_header)
+                                .on_input(Message::SigningSignHeaderChanged)
+                                .width(Length::Fill),
+                        ]
+                        .spacing(10),
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                    row![
+                        checkbox(signing.sign_path).on_toggle(Message::SigningSignPathToggled),
+                        text("Sign the path and query"),
+                        checkbox(signing.sign_body).on_toggle(Message::SigningSignBodyToggled),
+                        text("Sign the body"),
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+                    text("Signed parts are joined with newlines in the order path, header, body")
+                        .size(12),
+                ]
+                .spacing(10),
+            );
+        }
+
+        column![
+            presets,
+            rule::horizontal(1.0),
+            type_selector,
+            auth_form,
+            rule::horizontal(1.0),
+            signing_col
+        ]
+        .spacing(10)
+        .into()
+    }
+
+    fn render_cookie_jar_modal(&self) -> Element<'_, Message> {
         if !self.cookie_jar_open {
             return Space::new().into();
         }
@@ -2847,28 +6058,88 @@ impl CrabiPie {
         .into()
     }
 
-    fn render_response_section(&self) -> Element<'_, Message> {
-        fn status_color(status: &str) -> iced::Color {
-            let code = status
-                .split_whitespace()
-                .next()
-                .and_then(|s| s.parse::<u16>().ok());
+    // Sizes and types of the current request and response, along the bottom
+    fn render_status_bar(&self) -> Element<'_, Message> {
+        let Some(tab) = self.current_tab() else {
+            return Space::new().into();
+        };
+        if tab.request_type == RequestType::WebSocket {
+            return Space::new().into();
+        }
 
-            match code {
-                Some(200..=299) => iced::Color::from_rgb(0.2, 0.8, 0.2), // green
-                Some(300..=399) => iced::Color::from_rgb(0.2, 0.6, 0.9), // blue
-                Some(400..=499) => iced::Color::from_rgb(0.9, 0.6, 0.2), // orange
-                Some(500..=599) => iced::Color::from_rgb(0.9, 0.2, 0.2), // red
-                _ => iced::Color::WHITE,
-            }
+        let has_body = tab.request_type == RequestType::GraphQL || tab.sends_body();
+        let request = if !has_body {
+            "Request: no body".to_string()
+        } else if tab.request_type == RequestType::GraphQL {
+            format!(
+                "Request: GraphQL, {}",
+                Self::human_size(tab.graphql_query.len() + tab.graphql_variables.len())
+            )
+        } else {
+            let size = match tab.content_type {
+                ContentType::Json => Self::human_size(tab.request_body.len()),
+                ContentType::Binary => tab
+                    .binary_body_size
+                    .map(|size| Self::human_size(size as usize))
+                    .unwrap_or_else(|| "no file".to_string()),
+                ContentType::FormData | ContentType::XWWWFormUrlEncoded => {
+                    let fields = tab
+                        .form_data
+                        .iter()
+                        .filter(|f| f.enabled && !f.key.is_empty())
+                        .count();
+                    format!("{} field{}", fields, if fields == 1 { "" } else { "s" })
+                }
+            };
+            format!("Request: {}, {}", tab.content_type, size)
+        };
+
+        let response = if tab.is_response_binary {
+            Some(format!(
+                "Response: {}, {} bytes",
+                Self::human_size(tab.response_bytes.len()),
+                tab.response_bytes.len()
+            ))
+        } else if !tab.response_body.is_empty() {
+            let lines = self.response_body_content.line_count();
+            Some(format!(
+                "Response: {}, {} line{}",
+                Self::human_size(tab.response_body.len()),
+                lines,
+                if lines == 1 { "" } else { "s" }
+            ))
+        } else {
+            None
+        };
+
+        let mut bar = row![text(request).size(12)].spacing(16);
+        if let Some(response) = response {
+            bar = bar.push(text(response).size(12));
+        }
+        if !tab.response_content_type.is_empty() {
+            bar = bar.push(text(tab.response_content_type.clone()).size(12));
         }
 
+        container(bar)
+            .width(Length::Fill)
+            .padding(Padding::from([2, 8]))
+            .style(|theme: &iced::Theme| container::Style {
+                text_color: Some(theme.extended_palette().background.strong.text),
+                background: Some(iced::Background::Color(
+                    theme.extended_palette().background.weak.color,
+                )),
+                ..Default::default()
+            })
+            .into()
+    }
+
+    fn render_response_section(&self) -> Element<'_, Message> {
         let Some(tab) = self.current_tab() else {
             return iced::widget::text("Loading...").into();
         };
 
         let status_view: Element<'_, Message> = if tab.loading {
-            text("Loading...").into()
+            text("Loading...").color(status_color("Loading...")).into()
         } else if !tab.response_status.is_empty() {
             text(&tab.response_status)
                 .color(status_color(&tab.response_status))
@@ -2884,72 +6155,246 @@ impl CrabiPie {
 
         header_row = header_row.push(text("Response"));
         header_row = header_row.push(status_view);
-
-        if let Some(resp_time) = tab.response_time {
+        if tab.response_from_cache && !tab.loading {
             header_row = header_row.push(
-                text(format!("⏱️ {}", Self::format_duration(resp_time)))
-                    .shaping(text::Shaping::Advanced),
+                text("(from cache, 304)")
+                    .size(12)
+                    .color(iced::Color::from_rgb(0.5, 0.5, 0.5)),
             );
         }
-        if tab.is_response_binary {
-            header_row = header_row.push(
-                text(format!(
-                    "🗃️ {:.2} KB",
-                    tab.response_bytes.len() as f32 / 1024.0
-                ))
-                .shaping(text::Shaping::Advanced),
-            );
+
+        if tab.is_streaming && tab.response_content_type.starts_with("text/event-stream") {
+            header_row = header_row
+                .push(
+                    text(format!("📡 {} events", tab.sse_event_count))
+                        .shaping(text::Shaping::Advanced),
+                )
+                .push(
+                    button(text("⏹ Stop").shaping(text::Shaping::Advanced).size(12))
+                        .style(button::danger)
+                        .on_press(Message::StopStream),
+                );
+        }
+
+        if let Some(resp_time) = tab.response_time {
+            let badge = text(format!("⏱️ {}", Self::format_duration(resp_time)))
+                .shaping(text::Shaping::Advanced);
+            header_row = match tab.response_timing {
+                Some(timing) if !tab.loading => header_row.push(Self::render_timing_waterfall(
+                    badge.into(),
+                    resp_time,
+                    timing,
+                )),
+                _ => header_row.push(badge),
+            };
+        }
+        let decoded_size = if tab.is_response_binary {
+            tab.response_bytes.len()
+        } else {
+            tab.response_body.len()
+        };
+        if !tab.loading && decoded_size > 0 {
+            let size =
+                if !tab.response_encoding.is_empty() && tab.response_wire_size != decoded_size {
+                    format!(
+                        "🗃️ {} → {} ({})",
+                        Self::human_size(tab.response_wire_size),
+                        Self::human_size(decoded_size),
+                        tab.response_encoding
+                    )
+                } else {
+                    format!("🗃️ {}", Self::human_size(decoded_size))
+                };
+            let size = match tab.image_size {
+                Some((width, height)) if tab.response_content_type.starts_with("image/") => {
+                    format!("{} · {}×{} px", size, width, height)
+                }
+                _ => size,
+            };
+            header_row = header_row.push(text(size).shaping(text::Shaping::Advanced));
         }
         header_row = header_row.push(space::horizontal());
         header_row = header_row.push(text("Json Theme:"));
         header_row = header_row.push(pick_list(
-            &json_highlighter::JsonThemeWrapper::ALL[..],
+            json_highlighter::JsonThemeWrapper::all(&self.user_json_themes),
             Some(&self.json_theme),
             Message::JsonThemeChanged,
         ));
+        header_row = header_row.push(tooltip(
+            button(text("🎨").shaping(text::Shaping::Advanced))
+                .style(button::text)
+                .on_press(Message::ThemeEditorOpen),
+            "Create a JSON theme",
+            tooltip::Position::Bottom,
+        ));
         if !tab.response_body.is_empty() || !tab.response_headers.is_empty() {
-            header_row = header_row.push(tooltip(
+            let copy_button = tooltip(
                 button(text(if tab.copied { "✅" } else { "📋" }).shaping(text::Shaping::Advanced))
                     .on_press(Message::CopyToClipboard)
                     .style(button::text),
                 if tab.copied {
                     "Copied"
                 } else {
-                    "Copy to Clipboard"
+                    "Copy to Clipboard (right-click for more)"
                 },
                 tooltip::Position::Bottom,
-            ));
+            );
+            // Streamed media never lands in memory, so there is no body to copy
+            let can_copy_body = !tab.is_response_binary || !tab.response_bytes.is_empty();
+            let body_label = if tab.is_response_binary {
+                " Copy body (base64)"
+            } else {
+                " Copy body"
+            };
+            header_row = header_row.push(iced_aw::ContextMenu::new(copy_button, move || {
+                let body_item: Element<'_, Message> = if can_copy_body {
+                    button(
+                        row![
+                            text("📄").shaping(text::Shaping::Advanced).size(12),
+                            text(body_label).size(13)
+                        ]
+                        .spacing(4),
+                    )
+                    .style(button::text)
+                    .width(Length::Fill)
+                    .on_press(Message::CopyResponseBody)
+                    .into()
+                } else {
+                    tooltip(
+                        button(
+                            row![
+                                text("📄").shaping(text::Shaping::Advanced).size(12),
+                                text(body_label).size(13)
+                            ]
+                            .spacing(4),
+                        )
+                        .style(button::text)
+                        .width(Length::Fill),
+                        "Streamed media has no body to copy",
+                        tooltip::Position::Bottom,
+                    )
+                    .into()
+                };
+                let context_items = column![
+                    body_item,
+                    button(
+                        row![
+                            text("🧾").shaping(text::Shaping::Advanced).size(12),
+                            text(" Copy full response").size(13)
+                        ]
+                        .spacing(4)
+                    )
+                    .style(button::text)
+                    .width(Length::Fill)
+                    .on_press(Message::CopyFullResponse),
+                    button(
+                        row![
+                            text("📦").shaping(text::Shaping::Advanced).size(12),
+                            text(" Export as HAR").size(13)
+                        ]
+                        .spacing(4)
+                    )
+                    .style(button::text)
+                    .width(Length::Fill)
+                    .on_press(Message::ExportHar),
+                ]
+                .padding(4);
+
+                container(context_items)
+                    .style(|theme: &iced::Theme| container::Style {
+                        background: Some(iced::Background::Color(theme.palette().background)),
+                        border: Border {
+                            width: 1.0,
+                            color: theme.extended_palette().background.weak.color,
+                            radius: 4.0.into(),
+                        },
+                        ..Default::default()
+                    })
+                    .width(Length::Fixed(190.0))
+                    .into()
+            }));
         }
         header_row = header_row.push(tooltip(
             button(text("🧹").shaping(text::Shaping::Advanced))
-                .on_press(Message::ClearResponseText)
+                .on_press(Message::ClearResponse)
                 .style(button::text),
-            "Clear",
+            "Clear response",
             tooltip::Position::Bottom,
         ));
 
-        let res_tabs: iced_aw::Tabs<Message, ResponseTab, iced::Theme, iced::Renderer> =
-            iced_aw::Tabs::new(Message::ResponseTabSelected)
-                .push(
-                    ResponseTab::Body,
-                    iced_aw::TabLabel::Text("Body".into()),
-                    container(self.with_overlay(self.render_response_body())).padding(Padding {
-                        top: 10.0,
-                        ..Default::default()
-                    }),
-                )
-                .push(
-                    ResponseTab::Headers,
-                    iced_aw::TabLabel::Text("Header".into()),
-                    container(self.with_overlay(self.render_response_headers())).padding(Padding {
+        // Tabs other than Body and Headers only show up when they have something to show
+        let pages: [(ResponseTab, String, Option<Element<'_, Message>>); 9] = [
+            (
+                ResponseTab::Body,
+                "Body".into(),
+                Some(self.with_overlay(self.render_response_body())),
+            ),
+            (
+                ResponseTab::Headers,
+                "Header".into(),
+                Some(self.with_overlay(self.render_response_headers())),
+            ),
+            (
+                ResponseTab::Raw,
+                "Raw".into(),
+                (!tab.response_raw_head.is_empty())
+                    .then(|| self.with_overlay(self.render_response_raw())),
+            ),
+            (
+                ResponseTab::Preview,
+                "Preview".into(),
+                (!tab.response_html.is_empty())
+                    .then(|| self.with_overlay(self.render_html_preview())),
+            ),
+            (
+                ResponseTab::Cookies,
+                format!("Cookies ({})", tab.response_cookies.len()),
+                (!tab.response_cookies.is_empty())
+                    .then(|| self.with_overlay(self.render_response_cookies())),
+            ),
+            (
+                ResponseTab::Diff,
+                "Diff".into(),
+                tab.response_diff
+                    .is_some()
+                    .then(|| self.with_overlay(self.render_response_diff())),
+            ),
+            (
+                ResponseTab::Tree,
+                "Tree".into(),
+                tab.response_json
+                    .is_some()
+                    .then(|| self.with_overlay(self.render_json_tree())),
+            ),
+            (
+                ResponseTab::LoadTest,
+                "Load Test".into(),
+                tab.load_test_open.then(|| self.render_load_test()),
+            ),
+            (
+                ResponseTab::Paginate,
+                "Paginate".into(),
+                tab.pagination_open.then(|| self.render_pagination()),
+            ),
+        ];
+        let mut res_tabs: iced_aw::Tabs<Message, ResponseTab, iced::Theme, iced::Renderer> =
+            iced_aw::Tabs::new(Message::ResponseTabSelected);
+        for (id, label, page) in pages {
+            if let Some(page) = page {
+                res_tabs = res_tabs.push(
+                    id,
+                    iced_aw::TabLabel::Text(label),
+                    container(page).padding(Padding {
                         top: 10.0,
                         ..Default::default()
                     }),
-                )
-                .height(Length::Fill)
-                .set_active_tab(&tab.active_response_tab)
-                .tab_bar_position(iced_aw::TabBarPosition::Top)
-                .into();
+                );
+            }
+        }
+        let res_tabs = res_tabs
+            .height(Length::Fill)
+            .set_active_tab(&tab.active_response_tab)
+            .tab_bar_position(iced_aw::TabBarPosition::Top);
 
         container(column![header_row, rule::horizontal(1.0), res_tabs].spacing(10))
             .style(|theme: &iced::Theme| container::Style {
@@ -2965,6 +6410,153 @@ impl CrabiPie {
             .into()
     }
 
+    // Seek bar and transport row, shared by the video and audio players
+    fn render_media_controls<'a>(
+        video: &'a iced_video_player::Video,
+        vs: &'a VideoState,
+    ) -> Column<'a, Message> {
+        Column::new()
+            .push(
+                container::Container::new(
+                    iced::widget::Slider::new(
+                        0.0..=video.duration().as_secs_f64(),
+                        vs.position,
+                        Message::Seek,
+                    )
+                    .step(0.1)
+                    .on_release(Message::SeekRelease),
+                )
+                .padding(Padding {
+                    right: 10.0,
+                    left: 10.0,
+                    top: 5.0,
+                    bottom: 5.0,
+                }),
+            )
+            .spacing(4)
+            .push(
+                iced::widget::Row::new()
+                    .spacing(2)
+                    .align_y(iced::alignment::Vertical::Center)
+                    .padding(iced::Padding::new(10.0).top(0.0))
+                    .push(
+                        button::Button::new(
+                            text::Text::new(if video.paused() { "▶️" } else { "⏸️" })
+                                .shaping(text::Shaping::Advanced),
+                        )
+                        .style(button::text)
+                        .on_press(Message::TogglePause),
+                    )
+                    .push(
+                        button::Button::new(
+                            text::Text::new(if video.looping() { "🔁❌" } else { "🔁" })
+                                .shaping(text::Shaping::Advanced),
+                        )
+                        .style(button::text)
+                        .on_press(Message::ToggleLoop),
+                    )
+                    .push(
+                        button::Button::new(
+                            text::Text::new(if vs.volume == 0.0 { "🔇" } else { "🔊" })
+                                .shaping(text::Shaping::Advanced),
+                        )
+                        .style(button::text)
+                        .on_press(Message::ToggleMute),
+                    )
+                    .push(
+                        iced::widget::Slider::new(0.0..=1.0, vs.volume, Message::VideoVolume)
+                            .step(0.01)
+                            .width(100),
+                    )
+                    .push(
+                        pick_list(
+                            &PlaybackRate::ALL[..],
+                            Some(PlaybackRate(vs.speed)),
+                            |rate| Message::PlaybackSpeedChanged(rate.0),
+                        )
+                        .text_size(12),
+                    )
+                    .push(
+                        text::Text::new(format!(
+                            "{}:{:02}s / {}:{:02}s ({})",
+                            vs.position as u64 / 60,
+                            vs.position as u64 % 60,
+                            video.duration().as_secs() / 60,
+                            video.duration().as_secs() % 60,
+                            PlaybackRate(vs.speed),
+                        ))
+                        .width(iced::Length::Fill)
+                        .align_x(iced::alignment::Horizontal::Right),
+                    ),
+            )
+    }
+
+    fn render_html_preview(&self) -> Element<'_, Message> {
+        let Some(tab) = self.current_tab() else {
+            return iced::widget::text("Loading...").into();
+        };
+
+        let mut content = Column::new().spacing(8).padding(Padding {
+            right: 12.0,
+            ..Default::default()
+        });
+        for block in &tab.response_html {
+            let is_heading = matches!(block.kind, HtmlBlockKind::Heading(_));
+            let spans: Vec<iced::widget::text::Span<'_, String>> = block
+                .spans
+                .iter()
+                .map(|s| {
+                    let mut font = iced::Font::DEFAULT;
+                    if s.bold || is_heading {
+                        font.weight = iced::font::Weight::Bold;
+                    }
+                    if s.italic {
+                        font.style = iced::font::Style::Italic;
+                    }
+                    if s.code {
+                        font.family = iced::font::Family::Monospace;
+                    }
+                    let span = iced::widget::span(s.text.as_str()).font(font);
+                    match &s.link {
+                        Some(href) => span
+                            .link(href.clone())
+                            .underline(true)
+                            .color(iced::Color::from_rgb(0.35, 0.6, 0.95)),
+                        None => span,
+                    }
+                })
+                .collect();
+            let rich = iced::widget::rich_text(spans).on_link_click(Message::HtmlLinkClicked);
+
+            let element: Element<'_, Message> = match &block.kind {
+                HtmlBlockKind::Heading(level) => rich.size(30.0 - *level as f32 * 3.0).into(),
+                HtmlBlockKind::Paragraph => rich.size(14).into(),
+                HtmlBlockKind::ListItem { depth, marker } => row![
+                    space::horizontal().width(*depth as f32 * 16.0 + 8.0),
+                    text(marker).size(14),
+                    rich.size(14),
+                ]
+                .spacing(6)
+                .into(),
+                HtmlBlockKind::Preformatted => container(rich.size(13))
+                    .padding(8)
+                    .width(Length::Fill)
+                    .style(container::rounded_box)
+                    .into(),
+                HtmlBlockKind::Quote => container(rich.size(14))
+                    .padding(Padding {
+                        left: 16.0,
+                        ..Default::default()
+                    })
+                    .into(),
+                HtmlBlockKind::Rule => rule::horizontal(1.0).into(),
+            };
+            content = content.push(element);
+        }
+
+        scrollable(content).height(Length::Fill).into()
+    }
+
     fn render_response_body(&self) -> Element<'_, Message> {
         let Some(tab) = self.current_tab() else {
             return iced::widget::text("Loading...").into();
@@ -2984,14 +6576,52 @@ impl CrabiPie {
 
             if tab.response_content_type.starts_with("image/") {
                 if let Some(handle) = &tab.image_handle {
+                    // Zooming needs the natural size, without it the image stays fitted
+                    let can_zoom = tab.image_size.is_some();
                     body_column = body_column.push(
-                        scrollable(
+                        row![
+                            button(text("➖").shaping(text::Shaping::Advanced))
+                                .style(button::text)
+                                .on_press_maybe(can_zoom.then_some(Message::ImageZoomOut)),
+                            text(if self.image_fit || !can_zoom {
+                                "Fit".to_string()
+                            } else {
+                                format!("{:.0}%", self.image_zoom * 100.0)
+                            })
+                            .size(12),
+                            button(text("➕").shaping(text::Shaping::Advanced))
+                                .style(button::text)
+                                .on_press_maybe(can_zoom.then_some(Message::ImageZoomIn)),
+                            button(text(if self.image_fit { "100%" } else { "Fit" }).size(12))
+                                .style(button::text)
+                                .on_press_maybe(can_zoom.then_some(Message::ImageFitToggled)),
+                        ]
+                        .spacing(4)
+                        .align_y(Alignment::Center),
+                    );
+
+                    let image: Element<'_, Message> = match tab.image_size {
+                        Some((width, height)) if !self.image_fit => scrollable(
                             iced::widget::image(handle.clone())
-                                .content_fit(iced::ContentFit::Contain),
+                                .width(width as f32 * self.image_zoom)
+                                .height(height as f32 * self.image_zoom)
+                                .content_fit(iced::ContentFit::Fill),
                         )
+                        .direction(scrollable::Direction::Both {
+                            vertical: scrollable::Scrollbar::default(),
+                            horizontal: scrollable::Scrollbar::default(),
+                        })
                         .height(Length::Fill)
-                        .width(Length::Fill),
-                    );
+                        .width(Length::Fill)
+                        .into(),
+                        _ => iced::widget::image(handle.clone())
+                            .content_fit(iced::ContentFit::Contain)
+                            .height(Length::Fill)
+                            .width(Length::Fill)
+                            .into(),
+                    };
+                    body_column = body_column
+                        .push(iced::widget::mouse_area(image).on_scroll(Message::ImageWheelZoom));
                 }
             } else if tab.response_content_type.starts_with("video/") {
                 // Video playback
@@ -3012,68 +6642,31 @@ impl CrabiPie {
                             .width(iced::Length::Fill)
                             .height(iced::Length::Fill),
                         )
-                        .push(
-                            container::Container::new(
-                                iced::widget::Slider::new(
-                                    0.0..=video.duration().as_secs_f64(),
-                                    vs.position,
-                                    Message::Seek,
-                                )
-                                .step(0.1)
-                                .on_release(Message::SeekRelease),
-                            )
-                            .padding(Padding {
-                                right: 10.0,
-                                left: 10.0,
-                                top: 5.0,
-                                bottom: 5.0,
+                        .push(Self::render_media_controls(video, vs));
+                } else {
+                    body_column = body_column.push(
+                        text("🎬 Loading video...")
+                            .shaping(text::Shaping::Advanced)
+                            .style(|_| text::Style {
+                                color: Some(iced::Color::from_rgb(1.0, 0.65, 0.0)),
                             }),
-                        )
-                        .spacing(4)
+                    );
+                }
+            } else if tab.response_content_type.starts_with("audio/")
+                && (tab.video_player.is_some() || tab.response_bytes.is_empty())
+            {
+                // Audio playback, no picture so only the transport row is shown
+                if let (Some(video), Some(vs)) = (&tab.video_player, &tab.video_state) {
+                    body_column = body_column
                         .push(
-                            iced::widget::Row::new()
-                                .spacing(2)
-                                .align_y(iced::alignment::Vertical::Center)
-                                .padding(iced::Padding::new(10.0).top(0.0))
-                                .push(
-                                    button::Button::new(
-                                        text::Text::new(if video.paused() {
-                                            "▶️"
-                                        } else {
-                                            "⏸️"
-                                        })
-                                        .shaping(text::Shaping::Advanced),
-                                    )
-                                    .style(button::text)
-                                    .on_press(Message::TogglePause),
-                                )
-                                .push(
-                                    button::Button::new(
-                                        text::Text::new(if video.looping() {
-                                            "🔁❌"
-                                        } else {
-                                            "🔁"
-                                        })
-                                        .shaping(text::Shaping::Advanced),
-                                    )
-                                    .style(button::text)
-                                    .on_press(Message::ToggleLoop),
-                                )
-                                .push(
-                                    text::Text::new(format!(
-                                        "{}:{:02}s / {}:{:02}s",
-                                        vs.position as u64 / 60,
-                                        vs.position as u64 % 60,
-                                        video.duration().as_secs() / 60,
-                                        video.duration().as_secs() % 60,
-                                    ))
-                                    .width(iced::Length::Fill)
-                                    .align_x(iced::alignment::Horizontal::Right),
-                                ),
-                        );
+                            text(format!("🎵 {}", tab.response_filename))
+                                .shaping(text::Shaping::Advanced)
+                                .size(16),
+                        )
+                        .push(Self::render_media_controls(video, vs));
                 } else {
                     body_column = body_column.push(
-                        text("🎬 Loading video...")
+                        text("🎵 Audio could not be played")
                             .shaping(text::Shaping::Advanced)
                             .style(|_| text::Style {
                                 color: Some(iced::Color::from_rgb(1.0, 0.65, 0.0)),
@@ -3091,8 +6684,32 @@ impl CrabiPie {
                         color: Some(iced::Color::from_rgb(1.0, 0.65, 0.0)),
                     }),
                 );
-                body_column =
-                    body_column.push(text(format!("Size: {} bytes", tab.response_bytes.len())));
+                body_column = body_column.push(text(format!(
+                    "Size: {}",
+                    Self::human_size(tab.response_bytes.len())
+                )));
+                body_column = body_column.push(
+                    scrollable(text(&tab.hex_dump).font(iced::Font::MONOSPACE).size(12))
+                        .height(Length::Fill)
+                        .width(Length::Fill),
+                );
+                if tab.hex_dump_len < tab.response_bytes.len() {
+                    body_column = body_column.push(
+                        row![
+                            text(format!(
+                                "Showing {} of {}",
+                                Self::human_size(tab.hex_dump_len),
+                                Self::human_size(tab.response_bytes.len())
+                            ))
+                            .size(12),
+                            button(text("Load more").size(12))
+                                .style(button::text)
+                                .on_press(Message::HexDumpLoadMore),
+                        ]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                    );
+                }
             }
             body_column.into()
         } else {
@@ -3108,35 +6725,181 @@ impl CrabiPie {
             } else if tab.response_body.is_empty() {
                 space().into()
             } else {
-                text_editor(&self.response_body_content)
+                let save_row = row![
+                    button(text("💾 Save").shaping(text::Shaping::Advanced))
+                        .on_press(Message::SaveTextResponse)
+                        .style(button::text),
+                    tooltip(
+                        button(text("📝 Use as body").shaping(text::Shaping::Advanced))
+                            .on_press(Message::UseResponseAsBody)
+                            .style(button::text),
+                        "Copy this response into the request body to edit and resend it",
+                        tooltip::Position::Bottom,
+                    ),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center);
+
+                let mut filter_col = Column::new().spacing(4);
+                if text_extension_for(&tab.response_content_type) == "json" {
+                    filter_col = filter_col.push(
+                        text_input(
+                            "Filter with JSONPath, e.g. $.data[0].name",
+                            &tab.response_filter,
+                        )
+                        .on_input(Message::ResponseFilterChanged)
+                        .size(13),
+                    );
+                    if let Some(err) = &tab.response_filter_error {
+                        filter_col = filter_col.push(
+                            text(format!("⚠️ {err}"))
+                                .shaping(text::Shaping::Advanced)
+                                .size(12)
+                                .style(|_| text::Style {
+                                    color: Some(iced::Color::from_rgb(1.0, 0.3, 0.3)),
+                                }),
+                        );
+                    }
+                }
+
+                let editor = text_editor(&self.response_body_content)
                     .on_action(Message::ResponseBodyAction)
                     .highlight_with::<json_highlighter::JsonHighlighter>(
-                        self.get_highlighter_settings(),
-                        |highlight, _theme| {
-                            let color = match highlight {
-                                json_highlighter::HighlightType::Syntax(color) => *color,
-                                json_highlighter::HighlightType::SearchMatch => {
-                                    iced::Color::from_rgb(1.0, 1.0, 0.0)
-                                }
-                                json_highlighter::HighlightType::CurrentMatch => {
-                                    iced::Color::from_rgb(1.0, 0.0, 1.0)
-                                }
-                            };
-
-                            iced::advanced::text::highlighter::Format {
-                                color: Some(color),
-                                font: None,
-                            }
-                        },
+                        self.get_highlighter_settings(Some(FindTarget::ResponseBody))
+                            .with_language(json_highlighter::Language::from_content_type(
+                                &tab.response_content_type,
+                            )),
+                        Self::json_highlight_format,
                     )
-                    .wrapping(iced::advanced::text::Wrapping::Glyph)
-                    .style(Self::get_editor_style)
-                    .into()
+                    .style(Self::get_editor_style);
+                let editor: Element<'_, Message> = if self.wrap_lines && !self.show_line_numbers {
+                    editor
+                        .wrapping(iced::advanced::text::Wrapping::Glyph)
+                        .into()
+                } else {
+                    // Unwrapped lines scroll sideways instead of being cut off
+                    let shown = tab
+                        .response_display
+                        .get(..tab.response_shown_len)
+                        .unwrap_or(&tab.response_display);
+                    let editor = editor
+                        .wrapping(iced::advanced::text::Wrapping::None)
+                        .width(unwrapped_editor_width(shown));
+                    let editor: Element<'_, Message> = if self.show_line_numbers {
+                        row![
+                            self.line_number_gutter(
+                                self.response_body_content.line_count(),
+                                FindTarget::ResponseBody
+                            ),
+                            editor
+                        ]
+                        .into()
+                    } else {
+                        editor.into()
+                    };
+                    scrollable(editor)
+                        .direction(scrollable::Direction::Horizontal(
+                            scrollable::Scrollbar::default(),
+                        ))
+                        .into()
+                };
+
+                let mut body_col = column![save_row, filter_col].spacing(5);
+                if tab.response_shown_len < tab.response_display.len() {
+                    body_col = body_col.push(
+                        row![
+                            text(format!(
+                                "Showing {} of {}, the rest isn't loaded to keep the editor responsive",
+                                Self::human_size(tab.response_shown_len),
+                                Self::human_size(tab.response_display.len())
+                            ))
+                            .size(12),
+                            button(text("Show more").size(12))
+                                .style(button::text)
+                                .on_press(Message::ResponseShowMore),
+                            button(text("Show all").size(12))
+                                .style(button::text)
+                                .on_press(Message::ResponseShowAll),
+                            button(text("Save to file").size(12))
+                                .style(button::text)
+                                .on_press(Message::SaveTextResponse),
+                        ]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                    );
+                }
+                body_col.push(editor).into()
             };
             scrollable(content).height(Length::FillPortion(1)).into()
         }
     }
 
+    // Numbers for each editor line, placed beside the editor in the same scrollable so
+    // the two scroll together. The editor isn't wrapped while it's shown, a wrapped
+    // line would push the numbers out of step. Lines with find matches are colored
+    // like the matches themselves
+    fn line_number_gutter(&self, line_count: usize, target: FindTarget) -> Element<'_, Message> {
+        let searching = self.find_dialog_open && self.find_target == target;
+        let current_line = self
+            .current_match_line_col
+            .filter(|_| searching)
+            .map(|(line, _)| line);
+        let match_lines: std::collections::HashSet<usize> = if searching {
+            self.search_match_positions
+                .iter()
+                .map(|(line, _)| *line)
+                .collect()
+        } else {
+            std::collections::HashSet::new()
+        };
+
+        let width = line_count.max(1).to_string().len();
+        let spans: Vec<iced::widget::text::Span<'_>> = (0..line_count.max(1))
+            .map(|line| {
+                let number = if line + 1 < line_count {
+                    format!("{:>width$}\n", line + 1)
+                } else {
+                    format!("{:>width$}", line + 1)
+                };
+                let color = if Some(line) == current_line {
+                    iced::Color::from_rgb(1.0, 0.5, 0.0)
+                } else if match_lines.contains(&line) {
+                    iced::Color::from_rgb(0.85, 0.75, 0.0)
+                } else {
+                    iced::Color::from_rgb(0.5, 0.5, 0.5)
+                };
+                iced::widget::span(number).color(color)
+            })
+            .collect();
+
+        // Same top padding as the editor, so the first number lines up with the first line
+        container(iced::widget::rich_text(spans).font(iced::Font::MONOSPACE))
+            .padding(Padding {
+                top: 5.0,
+                right: 6.0,
+                bottom: 5.0,
+                left: 4.0,
+            })
+            .into()
+    }
+
+    // Colors for the JsonHighlighter spans, search matches drawn over the syntax colors
+    fn json_highlight_format(
+        highlight: &json_highlighter::HighlightType,
+        _theme: &iced::Theme,
+    ) -> iced::advanced::text::highlighter::Format<iced::Font> {
+        let color = match highlight {
+            json_highlighter::HighlightType::Syntax(color) => *color,
+            json_highlighter::HighlightType::SearchMatch => iced::Color::from_rgb(1.0, 1.0, 0.0),
+            json_highlighter::HighlightType::CurrentMatch => iced::Color::from_rgb(1.0, 0.5, 0.0),
+            json_highlighter::HighlightType::Error => iced::Color::from_rgb(1.0, 0.3, 0.3),
+        };
+        iced::advanced::text::highlighter::Format {
+            color: Some(color),
+            font: None,
+        }
+    }
+
     fn get_editor_style(theme: &iced::Theme, status: text_editor::Status) -> text_editor::Style {
         let mut style = text_editor::Catalog::style(
             theme,
@@ -3149,170 +6912,699 @@ impl CrabiPie {
         style
     }
 
-    fn render_response_headers(&self) -> Element<'_, Message> {
+    fn render_toasts(&self) -> Element<'_, Message> {
+        let mut list = Column::new().spacing(8).align_x(Alignment::End);
+        for toast in &self.toasts {
+            // Fully opaque until the last second, then fades out
+            let remaining = TOAST_DURATION.saturating_sub(toast.shown_at.elapsed());
+            let alpha = (remaining.as_secs_f32() / TOAST_FADE.as_secs_f32()).min(1.0);
+            let kind = toast.kind;
+            list = list.push(
+                container(
+                    row![
+                        text(format!(
+                            "{} {}",
+                            match kind {
+                                ToastKind::Success => "✅",
+                                ToastKind::Error => "⚠️",
+                            },
+                            toast.message
+                        ))
+                        .shaping(text::Shaping::Advanced)
+                        .size(13)
+                        .color(iced::Color::WHITE.scale_alpha(alpha)),
+                        button(text("✕").size(12))
+                            .style(button::text)
+                            .on_press(Message::DismissToast(toast.id)),
+                    ]
+                    .spacing(8)
+                    .align_y(Alignment::Center),
+                )
+                .padding(Padding::from([6, 12]))
+                .max_width(420)
+                .style(move |theme: &iced::Theme| {
+                    let palette = theme.extended_palette();
+                    let color = match kind {
+                        ToastKind::Success => palette.success.strong.color,
+                        ToastKind::Error => palette.danger.strong.color,
+                    };
+                    container::Style {
+                        background: Some(iced::Background::Color(color.scale_alpha(alpha))),
+                        border: Border {
+                            radius: 6.0.into(),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }
+                }),
+            );
+        }
+        container(list)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::End)
+            .align_y(Alignment::End)
+            .padding(20)
+            .into()
+    }
+
+    fn render_pagination(&self) -> Element<'_, Message> {
         let Some(tab) = self.current_tab() else {
             return iced::widget::text("Loading...").into();
         };
+        let running = tab
+            .pagination_run
+            .as_ref()
+            .is_some_and(PaginationRun::is_running);
+        let config = &tab.pagination;
 
-        let content: Element<'_, Message> = if tab.response_headers.is_empty() {
-            space().into()
-        } else {
-            text_editor(&self.response_headers_content)
-                .on_action(Message::ResponseHeadersAction)
-                .height(Length::FillPortion(1))
-                .highlight_with::<json_highlighter::JsonHighlighter>(
-                    self.get_highlighter_settings(),
-                    |highlight, _theme| {
-                        let color = match highlight {
-                            json_highlighter::HighlightType::Syntax(color) => *color,
-                            json_highlighter::HighlightType::SearchMatch => {
-                                iced::Color::from_rgb(1.0, 1.0, 0.0)
-                            }
-                            json_highlighter::HighlightType::CurrentMatch => {
-                                iced::Color::from_rgb(1.0, 0.5, 0.0)
-                            }
-                        };
-                        iced::advanced::text::highlighter::Format {
-                            color: Some(color),
-                            font: None,
-                        }
-                    },
-                )
-                .style(|theme: &iced::Theme, status| Self::get_editor_style(theme, status))
-                .into()
+        let label = |label: &str| text(label.to_string()).size(13).width(110);
+        let controls = column![
+            row![
+                label("Items"),
+                text_input("$.items, empty for the whole body", &config.items_path)
+                    .on_input_maybe((!running).then_some(Message::PaginationItemsPathChanged))
+                    .padding(4),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            row![
+                label("Next"),
+                text_input("$.next, empty for the Link header", &config.next_path)
+                    .on_input_maybe((!running).then_some(Message::PaginationNextPathChanged))
+                    .padding(4),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            row![
+                label("Cursor param"),
+                text_input("Empty when next is a URL", &config.cursor_param)
+                    .on_input_maybe((!running).then_some(Message::PaginationCursorParamChanged))
+                    .padding(4),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            row![
+                label("Page limit"),
+                text_input("10", &config.max_pages.to_string())
+                    .on_input_maybe((!running).then_some(Message::PaginationMaxPagesChanged))
+                    .width(70)
+                    .padding(4),
+                if running {
+                    button(text("🛑 Stop").shaping(text::Shaping::Advanced))
+                        .style(button::danger)
+                        .on_press(Message::PaginationStop)
+                } else {
+                    button(text("▶ Start").shaping(text::Shaping::Advanced))
+                        .style(button::primary)
+                        .on_press_maybe(
+                            (config.max_pages > 0 && !tab.url.trim().is_empty())
+                                .then_some(Message::PaginationStart),
+                        )
+                },
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ]
+        .spacing(8);
+
+        let Some(run) = &tab.pagination_run else {
+            return column![
+                controls,
+                text("Starts from this request, the combined items open in the Body and Tree tabs")
+                    .size(12),
+            ]
+            .spacing(12)
+            .into();
         };
 
-        content
+        let mut status = column![
+            iced::widget::progress_bar(0.0..=run.max_pages as f32, run.pages as f32).girth(8),
+            text(run.summary()).size(12),
+        ]
+        .spacing(8);
+        if let Some(reason) = &run.stop_reason {
+            let failed = run.failed;
+            status = status.push(text(reason.clone()).size(12).style(
+                move |theme: &iced::Theme| text::Style {
+                    color: failed.then_some(theme.palette().danger),
+                },
+            ));
+        }
+
+        scrollable(column![controls, status].spacing(12))
+            .height(Length::Fill)
+            .width(Length::Fill)
+            .into()
     }
 
-    fn loading_overlay(&self) -> Option<Element<'_, Message>> {
+    fn render_load_test(&self) -> Element<'_, Message> {
         let Some(tab) = self.current_tab() else {
-            return Some(iced::widget::text("Loading...").into());
+            return iced::widget::text("Loading...").into();
         };
+        let running = tab.load_test.as_ref().is_some_and(LoadTestRun::is_running);
+
+        let controls = row![
+            text("Requests").size(13),
+            text_input("50", &tab.load_test_count.to_string())
+                .on_input_maybe((!running).then_some(Message::LoadTestCountChanged))
+                .width(70)
+                .padding(4),
+            text("At once").size(13),
+            text_input("10", &tab.load_test_concurrency.to_string())
+                .on_input_maybe((!running).then_some(Message::LoadTestConcurrencyChanged))
+                .width(60)
+                .padding(4),
+            if running {
+                button(text("🛑 Stop").shaping(text::Shaping::Advanced))
+                    .style(button::danger)
+                    .on_press(Message::LoadTestStop)
+            } else {
+                button(text("▶ Start").shaping(text::Shaping::Advanced))
+                    .style(button::primary)
+                    .on_press_maybe(
+                        (tab.load_test_count > 0 && !tab.url.trim().is_empty())
+                            .then_some(Message::LoadTestStart),
+                    )
+            },
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
 
-        if !tab.loading {
-            return None;
-        }
+        let Some(run) = &tab.load_test else {
+            return column![
+                controls,
+                text("Requests are sent as the request tabs describe them, run one to see its latencies")
+                    .size(12),
+            ]
+            .spacing(12)
+            .into();
+        };
 
-        Some(
-            container(column![
-                iced::widget::svg(iced::advanced::svg::Handle::from_memory(include_bytes!(
-                    "./assets/ring-with-bg.svg"
-                )))
-                .width(80)
-                .height(80)
-                .rotation(iced::Radians::from(
-                    self.svg_rotation * std::f32::consts::PI / 180.0,
-                )),
-                text("📤 Sending...").shaping(text::Shaping::Advanced)
-            ])
-            .width(iced::Length::Fill)
-            .height(iced::Length::Fill)
-            .align_x(iced::Alignment::Center)
-            .align_y(iced::Alignment::Center)
-            .style(|theme: &iced::Theme| {
-                container::Style {
-                    background: Some(iced::Background::Color(
-                        iced::Color::from_rgba(0.0, 0.0, 0.0, 0.5), // Semi-transparent overlay
-                    )),
-                    ..Default::default()
-                }
-            })
-            .into(),
-        )
-    }
+        let done = run.done();
+        let mut stats = column![
+            iced::widget::progress_bar(0.0..=run.total as f32, done as f32).girth(8),
+            text(format!(
+                "{} / {} done in {:.2}s{}",
+                done,
+                run.total,
+                run.elapsed().as_secs_f64(),
+                if run.stopped { ", stopped" } else { "" }
+            ))
+            .size(12),
+        ]
+        .spacing(8);
 
-    fn with_overlay<'a>(
-        &'a self,
-        content: impl Into<Element<'a, Message>>,
-    ) -> Element<'a, Message> {
-        let content = content.into();
-        if let Some(overlay) = self.loading_overlay() {
-            iced::widget::stack![content, overlay].into()
-        } else {
-            content
-        }
-    }
+        let stat = |label: &str, value: String| {
+            column![text(label.to_string()).size(11), text(value).size(16)]
+                .spacing(2)
+                .width(Length::FillPortion(1))
+        };
+        stats = stats.push(
+            row![
+                stat("Min", run.percentile_text(0.0)),
+                stat("Median", run.percentile_text(0.5)),
+                stat("p95", run.percentile_text(0.95)),
+                stat("Max", run.percentile_text(1.0)),
+                stat("Succeeded", run.successes().to_string()),
+                stat("Failed", run.failures().to_string()),
+            ]
+            .spacing(12),
+        );
 
-    fn update_query<F: FnOnce(&mut QueryParam)>(&mut self, idx: usize, f: F) {
-        if let Some(tab) = self.current_tab_mut() {
-            if let Some(param) = tab.query_params.get_mut(idx) {
-                f(param);
-            }
+        let mut statuses = Column::new().spacing(4).push(text("Status codes").size(13));
+        for (status, count) in &run.statuses {
+            statuses = statuses.push(text(format!("{}: {}", status, count)).size(12));
+        }
+        if run.errors > 0 {
+            statuses = statuses.push(
+                text(format!(
+                    "Errors: {}{}",
+                    run.errors,
+                    run.last_error
+                        .as_ref()
+                        .map(|e| format!(" (last: {})", e))
+                        .unwrap_or_default()
+                ))
+                .size(12)
+                .style(|theme: &iced::Theme| text::Style {
+                    color: Some(theme.palette().danger),
+                }),
+            );
         }
-        self.rebuild_url();
-    }
 
-    fn rebuild_url(&mut self) {
-        use reqwest::Url;
+        scrollable(column![controls, stats, statuses].spacing(12))
+            .height(Length::Fill)
+            .width(Length::Fill)
+            .into()
+    }
 
+    fn render_json_tree(&self) -> Element<'_, Message> {
         let Some(tab) = self.current_tab() else {
-            return;
+            return iced::widget::text("Loading...").into();
         };
+        let Some(json) = tab.response_json.as_ref() else {
+            // Not JSON after all, show the plain body instead
+            return self.render_response_body();
+        };
+        let mut rows = Vec::new();
+        json_tree_rows(
+            json,
+            "$",
+            None,
+            0,
+            &self.json_tree_expanded,
+            &tab.response_images,
+            &mut rows,
+        );
+        let truncated = rows.len() >= JSON_TREE_MAX_ROWS;
 
-        let current = tab.url.clone();
-        let mut url = Url::parse(&current).expect("invalid URL");
-
-        // wipe existing query completely
-        url.set_query(None);
-
-        {
-            let mut pairs = url.query_pairs_mut();
-            for p in &tab.query_params {
-                if p.enabled && !p.key.is_empty() {
-                    pairs.append_pair(&p.key, &p.value);
-                }
-            }
+        let mut col = Column::new().spacing(0);
+        for row in rows {
+            col = col.push(row);
         }
-
-        if let Some(tab) = self.current_tab_mut() {
-            tab.url = url.to_string();
+        if truncated {
+            col = col.push(
+                text(format!(
+                    "… only the first {JSON_TREE_MAX_ROWS} rows are shown, collapse a node or use the filter"
+                ))
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+                }),
+            );
+        }
+        let mut images_row = row![
+            checkbox(tab.render_json_images).on_toggle(Message::JsonImagesToggled),
+            text("Render images").size(13),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+        if tab.render_json_images {
+            images_row = images_row.push(
+                text(match tab.response_images.len() {
+                    0 => "none found, looks for data URLs and base64 image strings".to_string(),
+                    n if n >= JSON_IMAGE_MAX_COUNT => format!("first {n} shown"),
+                    n => format!("{n} found"),
+                })
+                .size(12)
+                .style(|_| text::Style {
+                    color: Some(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+                }),
+            );
         }
+
+        column![
+            images_row,
+            scrollable(col)
+                .direction(scrollable::Direction::Both {
+                    vertical: scrollable::Scrollbar::default(),
+                    horizontal: scrollable::Scrollbar::default(),
+                })
+                .height(Length::Fill)
+                .width(Length::Fill)
+        ]
+        .spacing(5)
+        .into()
     }
 
-    fn parse_url_query(&mut self) {
-        let Some(tab) = self.current_tab_mut() else {
-            return;
+    fn render_response_diff(&self) -> Element<'_, Message> {
+        let Some(lines) = self.current_tab().and_then(|t| t.response_diff.as_ref()) else {
+            return Space::new().into();
         };
+        if lines.is_empty() {
+            return text("Same as the previous response").size(13).into();
+        }
 
-        tab.query_params.clear();
-
-        if let Some(q_index) = tab.url.find('?') {
-            let query = &tab.url[q_index + 1..];
-
-            for pair in query.split('&') {
-                if pair.is_empty() {
-                    continue;
+        let (added, removed) = lines.iter().fold((0, 0), |(a, r), line| match line {
+            DiffLine::Added(_) => (a + 1, r),
+            DiffLine::Removed(_) => (a, r + 1),
+            _ => (a, r),
+        });
+        // Only the first lines are drawn, a huge diff would make every frame slow
+        let mut col = Column::new();
+        for line in lines.iter().take(DIFF_MAX_LINES) {
+            let line_text = text(line.to_unified())
+                .font(iced::Font::MONOSPACE)
+                .size(12)
+                .width(Length::Fill);
+            col = col.push(match line {
+                DiffLine::Added(_) | DiffLine::Removed(_) => {
+                    let added = matches!(line, DiffLine::Added(_));
+                    container(line_text)
+                        .width(Length::Fill)
+                        .style(move |theme: &iced::Theme| {
+                            let palette = theme.extended_palette();
+                            let pair = if added {
+                                palette.success.weak
+                            } else {
+                                palette.danger.weak
+                            };
+                            container::Style {
+                                background: Some(iced::Background::Color(pair.color)),
+                                text_color: Some(pair.text),
+                                ..Default::default()
+                            }
+                        })
+                }
+                DiffLine::Context(_) | DiffLine::Skipped(_) => container(line_text),
+            });
+        }
+        if lines.len() > DIFF_MAX_LINES {
+            col = col.push(
+                text(format!(
+                    "… {} more lines, copy the diff to see all of it",
+                    lines.len() - DIFF_MAX_LINES
+                ))
+                .size(12),
+            );
+        }
+
+        column![
+            text(format!(
+                "+{} -{} lines against the previous response{}",
+                added,
+                removed,
+                if self
+                    .current_tab()
+                    .is_some_and(|t| t.response_content_type.contains("json"))
+                {
+                    ", both prettified"
+                } else {
+                    ""
                 }
+            ))
+            .size(12),
+            scrollable(col).height(Length::Fill).width(Length::Fill),
+        ]
+        .spacing(8)
+        .into()
+    }
 
-                let mut parts = pair.splitn(2, '=');
-                let key = parts.next().unwrap_or("").to_string();
-                let value = parts.next().unwrap_or("").to_string();
+    fn render_response_cookies(&self) -> Element<'_, Message> {
+        let Some(tab) = self.current_tab() else {
+            return iced::widget::text("Loading...").into();
+        };
 
-                tab.query_params.push(QueryParam {
-                    key,
-                    value,
-                    enabled: true,
-                });
+        let cell = |s: &str, portion: u16| {
+            text(s.to_string())
+                .size(12)
+                .width(Length::FillPortion(portion))
+        };
+        let col_headers = row![
+            cell("Name", 2),
+            cell("Value", 3),
+            cell("Domain", 2),
+            cell("Path", 1),
+            cell("Expires", 2),
+            cell("Flags", 2),
+        ]
+        .spacing(8);
+
+        let mut rows = Column::new().spacing(6);
+        for cookie in &tab.response_cookies {
+            rows = rows.push(
+                row![
+                    cell(&cookie.name, 2),
+                    cell(&cookie.value, 3),
+                    cell(&cookie.domain, 2),
+                    cell(&cookie.path, 1),
+                    cell(cookie.expires.as_deref().unwrap_or("session"), 2),
+                    cell(&cookie.flags.join(", "), 2),
+                ]
+                .spacing(8),
+            );
+        }
+
+        column![
+            row![
+                text("Stored in the cookie jar for the response's domain").size(12),
+                space::horizontal(),
+                button(text("🍪 Edit in cookie jar").shaping(text::Shaping::Advanced))
+                    .style(button::text)
+                    .on_press(Message::CookieJarOpen),
+            ]
+            .align_y(Alignment::Center),
+            col_headers,
+            rule::horizontal(1.0),
+            scrollable(rows).height(Length::Fill),
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn render_response_headers(&self) -> Element<'_, Message> {
+        let Some(tab) = self.current_tab() else {
+            return iced::widget::text("Loading...").into();
+        };
+
+        let content: Element<'_, Message> = if tab.response_headers.is_empty() {
+            space().into()
+        } else {
+            text_editor(&self.response_headers_content)
+                .on_action(Message::ResponseHeadersAction)
+                .height(Length::FillPortion(1))
+                .highlight_with::<json_highlighter::JsonHighlighter>(
+                    self.get_highlighter_settings(None),
+                    Self::json_highlight_format,
+                )
+                .style(|theme: &iced::Theme, status| Self::get_editor_style(theme, status))
+                .into()
+        };
+
+        content
+    }
+
+    fn render_response_raw(&self) -> Element<'_, Message> {
+        let Some(tab) = self.current_tab() else {
+            return iced::widget::text("Loading...").into();
+        };
+
+        let mut content = column![].spacing(5);
+        if !tab.is_response_binary && tab.response_body.len() > RESPONSE_DISPLAY_PAGE {
+            content = content.push(
+                text(format!(
+                    "Showing the first {} of the body, Copy takes all of it",
+                    Self::human_size(RESPONSE_DISPLAY_PAGE)
+                ))
+                .size(12),
+            );
+        }
+        content
+            .push(
+                scrollable(text(&tab.response_raw).font(iced::Font::MONOSPACE).size(13))
+                    .height(Length::Fill)
+                    .width(Length::Fill),
+            )
+            .into()
+    }
+
+    fn loading_overlay(&self) -> Option<Element<'_, Message>> {
+        let Some(tab) = self.current_tab() else {
+            return Some(iced::widget::text("Loading...").into());
+        };
+
+        if !tab.loading {
+            return None;
+        }
+
+        let mut overlay = column![
+            iced::widget::svg(iced::advanced::svg::Handle::from_memory(include_bytes!(
+                "./assets/ring-with-bg.svg"
+            )))
+            .width(80)
+            .height(80)
+            .rotation(iced::Radians::from(
+                self.svg_rotation * std::f32::consts::PI / 180.0,
+            )),
+            text(match (tab.retry_attempt, &tab.download_path) {
+                (Some((attempt, max)), _) => format!("📤 Sending... (retry {}/{})", attempt, max),
+                (None, Some(path)) => format!("💾 Downloading to {}...", path.display()),
+                (None, None) => "📤 Sending...".to_string(),
+            })
+            .shaping(text::Shaping::Advanced)
+        ]
+        .spacing(6)
+        .align_x(iced::Alignment::Center);
+
+        // Redrawn by the spinner tick, so it counts up without a timer of its own
+        if let Some(started) = tab.loading_started {
+            overlay = overlay
+                .push(text(format!("Elapsed: {:.1}s", started.elapsed().as_secs_f32())).size(12));
+        }
+
+        if let Some((received, total)) = tab.download_progress {
+            overlay = match total {
+                Some(total) if total > 0 => overlay
+                    .push(
+                        iced::widget::progress_bar(0.0..=total as f32, received as f32)
+                            .length(240)
+                            .girth(8),
+                    )
+                    .push(
+                        text(format!(
+                            "{} / {}",
+                            Self::human_size(received as usize),
+                            Self::human_size(total as usize)
+                        ))
+                        .size(12),
+                    ),
+                // No Content-Length, all we can show is what arrived
+                _ => overlay.push(
+                    text(format!("{} received", Self::human_size(received as usize))).size(12),
+                ),
+            };
+        }
+
+        Some(
+            container(overlay)
+                .width(iced::Length::Fill)
+                .height(iced::Length::Fill)
+                .align_x(iced::Alignment::Center)
+                .align_y(iced::Alignment::Center)
+                .style(|theme: &iced::Theme| {
+                    container::Style {
+                        background: Some(iced::Background::Color(
+                            iced::Color::from_rgba(0.0, 0.0, 0.0, 0.5), // Semi-transparent overlay
+                        )),
+                        ..Default::default()
+                    }
+                })
+                .into(),
+        )
+    }
+
+    fn with_overlay<'a>(
+        &'a self,
+        content: impl Into<Element<'a, Message>>,
+    ) -> Element<'a, Message> {
+        let content = content.into();
+        if let Some(overlay) = self.loading_overlay() {
+            iced::widget::stack![content, overlay].into()
+        } else {
+            content
+        }
+    }
+
+    fn update_query<F: FnOnce(&mut QueryParam)>(&mut self, idx: usize, f: F) {
+        if let Some(tab) = self.current_tab_mut() {
+            if let Some(param) = tab.query_params.get_mut(idx) {
+                f(param);
             }
         }
+        self.rebuild_url();
+    }
+
+    // The url is sent as it is in the input, so the params are written back into it.
+    // Works on the raw text, a half typed url must not be rejected or reformatted
+    fn rebuild_url(&mut self) {
+        let Some(tab) = self.current_tab_mut() else {
+            return;
+        };
+
+        let (without_fragment, fragment) = match tab.url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (tab.url.as_str(), None),
+        };
+        let base = without_fragment.split('?').next().unwrap_or("");
+
+        let mut url = format!("{}{}", base, Self::build_query_string(&tab.query_params));
+        if let Some(fragment) = fragment {
+            url.push('#');
+            url.push_str(fragment);
+        }
+        tab.url = url;
+    }
+
+    fn parse_url_query(&mut self) {
+        let Some(tab) = self.current_tab_mut() else {
+            return;
+        };
+
+        tab.query_params = merge_query(std::mem::take(&mut tab.query_params), &tab.url);
+    }
+
+    // Re-checks the current tab's form files before a request is built from it,
+    // Some with a toast when one is missing or unreadable
+    fn check_form_files(&mut self) -> Option<iced::Task<Message>> {
+        let tab = self.current_tab_mut()?;
+        if tab.request_type != RequestType::HTTP
+            || !tab.sends_body()
+            || tab.content_type != ContentType::FormData
+        {
+            return None;
+        }
+        tab.form_data_errors = tab.validate_form_data().err().unwrap_or_default();
+        let count = tab.form_data_errors.len();
+        (count > 0).then(|| {
+            iced::Task::done(Message::ShowToast(Toast::error(format!(
+                "Not sent, {} form {} missing or unreadable, see the Body tab",
+                count,
+                if count == 1 { "file" } else { "files" }
+            ))))
+        })
+    }
+
+    // The current tab's signer, None when signing is off
+    fn request_signer(&self) -> Result<Option<RequestSigner>, String> {
+        let Some(tab) = self.current_tab().filter(|t| t.signing.enabled) else {
+            return Ok(None);
+        };
+        let header_name =
+            reqwest::header::HeaderName::from_bytes(tab.signing.header_name.trim().as_bytes())
+                .map_err(|_| {
+                    format!(
+                        "\"{}\" isn't a valid signature header name",
+                        tab.signing.header_name.trim()
+                    )
+                })?;
+        Ok(Some(RequestSigner {
+            signing: tab.signing.clone(),
+            header_name,
+            secret: self.substitute_vars(&tab.signing_secret),
+        }))
+    }
+
+    // Signs a request built from the current tab, a no-op when signing is off
+    fn sign_request(&self, request: &mut reqwest::Request) -> Result<(), String> {
+        match self.request_signer()? {
+            Some(signer) => signer.sign(request),
+            None => Ok(()),
+        }
+    }
+
+    // Some with a toast when signing is on but can't produce a signature, an
+    // unsigned request would only be rejected by the server
+    fn check_signing(&self) -> Option<iced::Task<Message>> {
+        let tab = self.current_tab()?;
+        (tab.signing.enabled
+            && (tab.signing_secret.is_empty() || tab.signing.header_name.trim().is_empty()))
+        .then(|| {
+            iced::Task::done(Message::ShowToast(Toast::error(
+                "Not sent, signing is on but the secret or header name is empty".to_string(),
+            )))
+        })
     }
 
     fn build_request(&self) -> Option<(reqwest::RequestBuilder, String)> {
         let tab = self.current_tab()?;
-        let mut url = tab.url.clone();
+        let mut url = self.substitute_vars(&tab.url);
 
         // ── headers ──────────────────────────
-        let mut header_map: reqwest::header::HeaderMap = tab
-            .headers
-            .iter()
-            .filter(|h| h.enabled)
-            .filter_map(|h| {
-                let name = reqwest::header::HeaderName::from_bytes(h.key.trim().as_bytes()).ok()?;
-                let value = reqwest::header::HeaderValue::from_str(h.value.trim()).ok()?;
-                Some((name, value))
-            })
-            .collect();
+        let mut header_map =
+            http::parse_headers(&self.substitute_vars(&TabState::headers_to_raw(&tab.headers)));
+        // Pinned headers fill in what the request doesn't set itself
+        let pinned = http::parse_headers(&self.substitute_vars(&TabState::headers_to_raw(
+            &self.http_settings.pinned_headers,
+        )));
+        http::merge_missing(&mut header_map, &pinned);
+        // Bodies are decoded by hand in send_request, so only ask for what we can decode
+        if !header_map.contains_key(reqwest::header::ACCEPT_ENCODING) {
+            header_map.insert(
+                reqwest::header::ACCEPT_ENCODING,
+                reqwest::header::HeaderValue::from_static(http::DEFAULT_ACCEPT_ENCODING),
+            );
+        }
 
         // ── auth ──────────────────────────────
         match tab.auth_type {
@@ -3320,12 +7612,21 @@ impl CrabiPie {
                 if !tab.bearer_token.is_empty() {
                     if let Ok(hv) = reqwest::header::HeaderValue::from_str(&format!(
                         "Bearer {}",
-                        tab.bearer_token
+                        self.substitute_vars(&tab.bearer_token)
                     )) {
                         header_map.insert(reqwest::header::AUTHORIZATION, hv);
                     }
                 }
             }
+            AuthType::Basic => {
+                if let Ok(hv) = reqwest::header::HeaderValue::from_str(&http::basic_auth_value(
+                    &tab.basic_username,
+                    &tab.basic_password,
+                )) {
+                    header_map.insert(reqwest::header::AUTHORIZATION, hv);
+                }
+            }
+            // The auth tab takes precedence, a same-named header or query param is replaced
             AuthType::ApiKey => {
                 if !tab.api_key.is_empty() && !tab.api_key_name.is_empty() {
                     if tab.api_key_position == ApiKeyPosition::Header {
@@ -3335,11 +7636,21 @@ impl CrabiPie {
                         ) {
                             header_map.insert(hn, hv);
                         }
-                    } else if let Ok(mut parsed) = url::Url::parse(&url) {
-                        parsed
-                            .query_pairs_mut()
-                            .append_pair(&tab.api_key_name, &tab.api_key);
-                        url = parsed.to_string();
+                    } else if let Some(with_key) =
+                        http::replace_query_param(&url, &tab.api_key_name, &tab.api_key)
+                    {
+                        url = with_key;
+                    }
+                }
+            }
+            // SendRequest fetches or refreshes the token before getting here
+            AuthType::OAuth2ClientCredentials => {
+                if !tab.oauth_access_token.is_empty() {
+                    if let Ok(hv) = reqwest::header::HeaderValue::from_str(&format!(
+                        "Bearer {}",
+                        tab.oauth_access_token
+                    )) {
+                        header_map.insert(reqwest::header::AUTHORIZATION, hv);
                     }
                 }
             }
@@ -3347,14 +7658,28 @@ impl CrabiPie {
         }
 
         // ── cookie jar ───────────────────────
-        if let Some(domain) = extract_domain(&url) {
-            if let Some(cookies) = self.cookie_jar.get(&domain) {
-                let cookie_str = cookies
-                    .iter()
-                    .filter(|c| c.enabled && !c.name.is_empty())
-                    .map(|c| format!("{}={}", c.name, c.value))
-                    .collect::<Vec<_>>()
-                    .join("; ");
+        if let Ok(parsed) = url::Url::parse(&url) {
+            if let Some(host) = parsed.host_str() {
+                // Cookies stored for this host, plus ones set with a Domain attribute covering it
+                let mut sent: Vec<&str> = Vec::new();
+                let mut pairs: Vec<String> = Vec::new();
+                for (jar_domain, cookies) in &self.cookie_jar {
+                    for c in cookies {
+                        let applies = jar_domain == host
+                            || (!c.domain.is_empty() && domain_matches(host, &c.domain));
+                        if applies
+                            && c.enabled
+                            && !c.name.is_empty()
+                            && !c.is_expired()
+                            && path_matches(parsed.path(), &c.path)
+                            && !sent.contains(&c.name.as_str())
+                        {
+                            sent.push(&c.name);
+                            pairs.push(format!("{}={}", c.name, c.value));
+                        }
+                    }
+                }
+                let cookie_str = pairs.join("; ");
                 if !cookie_str.is_empty() {
                     if let Ok(hv) = reqwest::header::HeaderValue::from_str(&cookie_str) {
                         header_map.insert(reqwest::header::COOKIE, hv);
@@ -3364,25 +7689,17 @@ impl CrabiPie {
         }
 
         // ── body ─────────────────────────────
-        let client = &HTTP_CLIENT;
+        let client = self.client(tab.insecure_tls);
         if tab.request_type == RequestType::GraphQL {
-            let variables: serde_json::Value = serde_json::from_str(&tab.graphql_variables)
-                .unwrap_or(serde_json::Value::Object(Default::default()));
-
-            let body = serde_json::json!({
-                "query": *tab.graphql_query,
-                "variables": variables,
-                "operationName": if tab.graphql_operation.is_empty() {
-                    serde_json::Value::Null
-                } else {
-                    serde_json::Value::String(tab.graphql_operation.clone())
-                }
-            });
-
+            let body = http::graphql_body(
+                &tab.graphql_query,
+                &self.substitute_vars(&tab.graphql_variables),
+                &tab.graphql_operation,
+            );
             return Some((
                 client
                     .post(&url)
-                    .body(body.to_string())
+                    .body(body)
                     .header("Content-Type", "application/json")
                     .headers(header_map),
                 url,
@@ -3391,217 +7708,555 @@ impl CrabiPie {
 
         let builder = match tab.method {
             HttpMethod::GET => client.get(&url),
+            HttpMethod::POST => client.post(&url),
+            HttpMethod::PUT => client.put(&url),
+            HttpMethod::PATCH => client.patch(&url),
             HttpMethod::DELETE => client.delete(&url),
-            HttpMethod::POST | HttpMethod::PUT | HttpMethod::PATCH => {
-                let req = match tab.method {
-                    HttpMethod::POST => client.post(&url),
-                    HttpMethod::PUT => client.put(&url),
-                    HttpMethod::PATCH => client.patch(&url),
-                    _ => unreachable!(),
-                };
-                match tab.content_type {
-                    ContentType::Json => req
-                        .body(tab.request_body.to_string())
-                        .header("Content-Type", "application/json"),
-                    ContentType::XWWWFormUrlEncoded => {
-                        let params: Vec<_> = tab
-                            .form_data
-                            .iter()
-                            .filter(|f| {
-                                f.enabled
-                                    && !f.key.is_empty()
-                                    && f.field_type == FormFieldType::Text
-                            })
-                            .map(|f| (f.key.clone(), f.value.clone()))
-                            .collect();
-                        req.form(&params)
-                    }
-                    ContentType::FormData => {
-                        let mut form = reqwest::multipart::Form::new();
-                        for field in &tab.form_data {
-                            if field.enabled && !field.key.is_empty() {
-                                match field.field_type {
-                                    FormFieldType::Text => {
-                                        form = form.text(field.key.clone(), field.value.clone());
-                                    }
-                                    FormFieldType::File => {
-                                        for fp in &field.files {
-                                            if let Ok(fc) = std::fs::read(fp) {
-                                                let fname = std::path::Path::new(fp)
-                                                    .file_name()
-                                                    .and_then(|n| n.to_str())
-                                                    .unwrap_or("file")
-                                                    .to_string();
-                                                let part = reqwest::multipart::Part::bytes(fc)
-                                                    .file_name(fname);
-                                                form = form.part(field.key.clone(), part);
-                                            }
-                                        }
-                                    }
-                                }
+            HttpMethod::HEAD => client.head(&url),
+            HttpMethod::OPTIONS => client.request(reqwest::Method::OPTIONS, &url),
+        };
+        let builder = if tab.sends_body() {
+            http::with_body(builder, self.request_body(tab)?)?
+        } else {
+            builder
+        };
+
+        Some((builder.headers(header_map), url))
+    }
+
+    // The tab's body for its content type. None when a file that has to be sent
+    // can't be read, http::with_body turns it into the request body
+    fn request_body(&self, tab: &TabState) -> Option<http::RequestBody> {
+        let body = match tab.content_type {
+            ContentType::Json => http::RequestBody::Raw {
+                bytes: self.substitute_vars(&tab.request_body).into_bytes(),
+                content_type: "application/json".to_string(),
+                gzip: tab.gzip_body,
+            },
+            // Unreadable files are caught before sending, see Message::SendRequest
+            ContentType::Binary => http::RequestBody::Raw {
+                bytes: std::fs::read(&tab.binary_body_path).ok()?,
+                content_type: match tab.binary_content_type.trim() {
+                    "" => "application/octet-stream".to_string(),
+                    ct => ct.to_string(),
+                },
+                gzip: tab.gzip_body,
+            },
+            ContentType::XWWWFormUrlEncoded => http::RequestBody::UrlEncoded(
+                tab.form_data
+                    .iter()
+                    .filter(|f| {
+                        f.enabled && !f.key.is_empty() && f.field_type == FormFieldType::Text
+                    })
+                    .map(|f| (f.key.clone(), self.substitute_vars(&f.value)))
+                    .collect(),
+            ),
+            ContentType::FormData => {
+                let mut parts = Vec::new();
+                for field in tab
+                    .form_data
+                    .iter()
+                    .filter(|f| f.enabled && !f.key.is_empty())
+                {
+                    // Checked by part_content_type, so with_body can't fail on it
+                    let content_type = field.part_content_type().map(str::to_string);
+                    match field.field_type {
+                        FormFieldType::Text => parts.push(http::FormPart {
+                            name: field.key.clone(),
+                            content: http::PartContent::Text(self.substitute_vars(&field.value)),
+                            content_type,
+                        }),
+                        // Checked by validate_form_data before anything is built, a file
+                        // that still can't be read fails the build rather than going missing
+                        FormFieldType::File => {
+                            for fp in &field.files {
+                                let file_name = std::path::Path::new(fp)
+                                    .file_name()
+                                    .and_then(|n| n.to_str())
+                                    .unwrap_or("file")
+                                    .to_string();
+                                parts.push(http::FormPart {
+                                    name: field.key.clone(),
+                                    content: http::PartContent::File {
+                                        file_name,
+                                        bytes: std::fs::read(fp).ok()?,
+                                    },
+                                    content_type: content_type.clone(),
+                                });
                             }
                         }
-                        req.multipart(form)
                     }
                 }
+                http::RequestBody::Multipart(parts)
             }
         };
+        Some(body)
+    }
 
-        Some((builder.headers(header_map), url))
+    // Response body for the clipboard, binary bodies as base64
+    fn response_body_text(&self) -> Option<String> {
+        let tab = self.current_tab()?;
+        if !tab.is_response_binary {
+            return Some(tab.response_body.to_string());
+        }
+        if tab.response_bytes.is_empty() {
+            return None;
+        }
+        Some(base64::engine::general_purpose::STANDARD.encode(&tab.response_bytes))
     }
 
-    fn send_request(&mut self) -> iced::Task<Message> {
-        let Some((request, _url)) = self.build_request() else {
-            return iced::Task::none();
-        };
+    // Status line, headers, blank line and body, laid out like an HTTP message
+    fn format_full_response(&self) -> String {
         let Some(tab) = self.current_tab() else {
-            return iced::Task::none();
+            return String::new();
         };
-        let cancel_flag = tab.cancel_flag.clone();
-        tab.cancel_flag.store(false, Ordering::Relaxed);
-        if let Some(mut_tab) = self.current_tab_mut() {
-            mut_tab.response_time = None;
-            mut_tab.stream_buffer = String::new();
+        let mut out = format!("HTTP/1.1 {}\n", tab.response_status);
+        if !tab.response_header_list.is_empty() {
+            out.push_str(&http::format_header_list(&tab.response_header_list));
+            out.push('\n');
+        } else {
+            // Restored tabs only have the text, where shown redirect hops come before the blank line
+            let headers = match tab.response_headers.split_once("\n\n") {
+                Some((hops, rest)) if hops.starts_with("Redirects:") => rest,
+                _ => tab.response_headers.as_ref(),
+            };
+            for line in headers.lines().filter(|line| line.contains(": ")) {
+                out.push_str(line);
+                out.push('\n');
+            }
         }
+        out.push('\n');
+        out.push_str(&self.response_body_text().unwrap_or_default());
+        out
+    }
 
-        iced::Task::run(
-            async_stream::stream! {
-                let start_time = tokio::time::Instant::now();
-                let resp = match request.send().await {
-                    Ok(r)  => r,
-                    Err(e) => {
-                        let msg = if e.is_timeout() { "Request timed out".into() }
-                                  else { format!("Request failed: {e}") };
-                        yield Message::ResponseReceived(HttpResponse {
-                            status: "Error".to_string(),
-                            body: msg,
-                            response_time: Some(start_time.elapsed()),
-                            ..Default::default()
-                        });
-                        return;
-                    }
-                };
-
-                // ── emit status + headers immediately ───────────────────────────
-                let status = format!(
-                    "{} {}",
-                    resp.status().as_u16(),
-                    resp.status().canonical_reason().unwrap_or("")
-                );
-
-                let hm = resp.headers().clone();
-
-                // Cookies
-                let set_cookies: Vec<String> = hm.get_all("set-cookie")
+    // The current request and its response as a one entry HAR 1.2 log. Phases
+    // reqwest doesn't report are -1, which the spec uses for "not available"
+    fn to_har(&self) -> serde_json::Value {
+        let Some(tab) = self.current_tab() else {
+            return serde_json::Value::Null;
+        };
+        let pairs = |list: Vec<(String, String)>| {
+            list.into_iter()
+                .map(|(name, value)| serde_json::json!({ "name": name, "value": value }))
+                .collect::<Vec<_>>()
+        };
+        let millis = |d: std::time::Duration| d.as_secs_f64() * 1000.0;
+
+        let request_json = match &tab.sent_request {
+            Some(request) => {
+                let query = request
+                    .url
+                    .query_pairs()
+                    .map(|(name, value)| (name.into_owned(), value.into_owned()))
+                    .collect();
+                let cookies = request
+                    .headers
                     .iter()
-                    .filter_map(|v| v.to_str().ok().map(|s| s.to_string()))
+                    .filter(|(name, _)| name == "cookie")
+                    .flat_map(|(_, value)| value.split(';'))
+                    .filter_map(|pair| pair.trim().split_once('='))
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
                     .collect();
-
-                // Headers all
-                let headers_text = format!("{:#?}", hm);
-
-                // Content Type
-                let ct = hm.get("content-type")
-                    .and_then(|v| v.to_str().ok())
-                    .unwrap_or("").to_string();
-
-                // Binary / video: fall back to old buffered path
-                let is_binary = ct.starts_with("image/")
-                    || ct.starts_with("application/pdf")
-                    || ct.starts_with("application/octet-stream")
-                    || ct.starts_with("video/")
-                    || ct.starts_with("audio/");
-
-                if is_binary {
-                    let accepts_range = hm.get("accept-ranges").and_then(|h| h.to_str().ok()).is_some();
-                    let filename = hm.get("content-disposition")
-                        .and_then(|v| v.to_str().ok())
-                        .and_then(|s| s.split("filename=").nth(1)
-                            .map(|f| f.trim_matches(|c| c == '"' || c == '\'').to_string()))
-                        .unwrap_or_else(|| _url.split('/').last().unwrap_or("download").to_string());
-
-                    if accepts_range && ct.starts_with("video/") {
-                        yield Message::ResponseReceived(HttpResponse {
-                            status, headers: headers_text, is_binary: true,
-                            filename, content_type: ct,
-                            response_time: Some(start_time.elapsed()),
-                            accepts_range: true, ..Default::default()
-                        });
-                        return;
-                    }
-
-                    let (body, bytes) = match resp.bytes().await {
-                        Ok(b)  => (format!("Binary file ({} bytes)\n\nContent-Type: {}", b.len(), ct), b.to_vec()),
-                        Err(e) => (format!("Error reading binary data: {e}"), vec![]),
-                    };
-                    yield Message::ResponseReceived(HttpResponse {
-                        status, headers: headers_text, body, is_binary: true,
-                        filename, bytes, content_type: ct,
-                        response_time: Some(start_time.elapsed()),
-                        set_cookies,
-                        accepts_range,
+                let mut json = serde_json::json!({
+                    "method": request.method,
+                    "url": request.url.as_str(),
+                    "httpVersion": "HTTP/1.1",
+                    "cookies": pairs(cookies),
+                    "headers": pairs(request.headers.clone()),
+                    "queryString": pairs(query),
+                    "headersSize": -1,
+                    "bodySize": -1,
+                });
+                // Streamed bodies such as multipart files have no bytes to write out
+                if let Some(body) = &request.body {
+                    let mime_type = request
+                        .headers
+                        .iter()
+                        .find(|(name, _)| name == "content-type")
+                        .map_or("", |(_, value)| value.as_str());
+                    json["postData"] = serde_json::json!({
+                        "mimeType": mime_type,
+                        "text": String::from_utf8_lossy(body),
                     });
-                    return;
+                    json["bodySize"] = body.len().into();
                 }
-
-                // ── TEXT: stream chunks ─────────────────────────────────────────
-                // Emit a "headers ready" snapshot so the UI can show status immediately
-                yield Message::ResponseReceived(HttpResponse {
-                    status: status.clone(),
-                    headers: headers_text,
-                    body: String::new(),
-                    content_type: ct.clone(),
-                    response_time: Some(start_time.elapsed()),
-                    set_cookies,
-                    ..Default::default()
+                json
+            }
+            // Not sent yet, only what the tab itself says
+            None => serde_json::json!({
+                "method": tab.method.to_string(),
+                "url": self.substitute_vars(&tab.url),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": [],
+                "queryString": [],
+                "headersSize": -1,
+                "bodySize": -1,
+            }),
+        };
+
+        let (status, status_text) = tab
+            .response_status
+            .split_once(' ')
+            .unwrap_or((tab.response_status.as_str(), ""));
+        let http_version = tab
+            .response_raw_head
+            .split_whitespace()
+            .next()
+            .filter(|v| v.starts_with("HTTP/"))
+            .unwrap_or("HTTP/1.1");
+        let header = |name: &str| {
+            tab.response_header_list
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.clone())
+                .unwrap_or_default()
+        };
+        // Binary bodies go in as base64, which the spec marks with `encoding`
+        let content = if tab.is_response_binary {
+            serde_json::json!({
+                "size": tab.response_bytes.len(),
+                "mimeType": tab.response_content_type,
+                "text": base64::engine::general_purpose::STANDARD.encode(&tab.response_bytes),
+                "encoding": "base64",
+            })
+        } else {
+            serde_json::json!({
+                "size": tab.response_body.len(),
+                "mimeType": tab.response_content_type,
+                "text": tab.response_body.as_ref(),
+            })
+        };
+        let cookies = tab
+            .response_cookies
+            .iter()
+            .map(|cookie| {
+                let flag = |name: &str| cookie.flags.iter().any(|f| f.eq_ignore_ascii_case(name));
+                let mut json = serde_json::json!({
+                    "name": cookie.name,
+                    "value": cookie.value,
+                    "path": cookie.path,
+                    "domain": cookie.domain,
+                    "httpOnly": flag("HttpOnly"),
+                    "secure": flag("Secure"),
                 });
+                if let Some(expires) = &cookie.expires {
+                    json["expires"] = expires.as_str().into();
+                }
+                json
+            })
+            .collect::<Vec<_>>();
+        let response_json = serde_json::json!({
+            "status": status.parse::<u16>().unwrap_or(0),
+            "statusText": status_text,
+            "httpVersion": http_version,
+            "cookies": cookies,
+            "headers": pairs(tab.response_header_list.clone()),
+            "content": content,
+            "redirectURL": header("location"),
+            "headersSize": -1,
+            "bodySize": if tab.response_wire_size > 0 {
+                tab.response_wire_size as i64
+            } else {
+                -1
+            },
+        });
 
-            use futures_util::StreamExt;
-            let mut byte_stream = resp.bytes_stream();
-            let mut buf: Vec<u8> = Vec::new();
+        let timing = tab.response_timing.unwrap_or_default();
+        let timings = serde_json::json!({
+            "blocked": -1,
+            "dns": timing.dns.map_or(-1.0, millis),
+            "connect": -1,
+            "ssl": -1,
+            "send": 0,
+            "wait": millis(timing.ttfb),
+            "receive": timing.download.map_or(0.0, millis),
+        });
 
-            while let Some(chunk_result) = byte_stream.next().await {
-                if cancel_flag.load(Ordering::Relaxed) {
-                    yield Message::StreamChunk("…[cancelled]".to_string());
-                    break;
-                }
-                match chunk_result {
-                    Ok(bytes) => {
-                        buf.extend_from_slice(bytes.as_ref());
-                        match std::str::from_utf8(buf.as_slice()) {
-                            Ok(s) => {
-                                yield Message::StreamChunk(s.to_string());
-                                buf.clear();
-                            }
-                            Err(e) => {
-                                let valid_up_to = e.valid_up_to();
-                                if valid_up_to > 0 {
-                                    let s = unsafe {
-                                        std::str::from_utf8_unchecked(&buf.as_slice()[..valid_up_to])
-                                    }.to_string();
-                                    let remaining = buf.as_slice()[valid_up_to..].to_vec();
-                                    buf = remaining;
-                                    yield Message::StreamChunk(s);
-                                }
-                            }
-                        }
+        serde_json::json!({
+            "log": {
+                "version": "1.2",
+                "creator": {
+                    "name": "CrabiPie",
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+                "entries": [{
+                    "startedDateTime": tab
+                        .sent_at
+                        .unwrap_or_else(chrono::Local::now)
+                        .to_rfc3339(),
+                    "time": tab.response_time.map_or(0.0, millis),
+                    "request": request_json,
+                    "response": response_json,
+                    "cache": {},
+                    "timings": timings,
+                }],
+            }
+        })
+    }
+
+    fn to_curl(&self) -> String {
+        let Some(tab) = self.current_tab() else {
+            return String::new();
+        };
+        // Build and sign the real request so auth, cookies, api key query params and
+        // the signature match what Send does
+        let Some(mut request) = self
+            .build_request()
+            .and_then(|(builder, _)| builder.build().ok())
+        else {
+            return String::new();
+        };
+        if self.check_signing().is_some() || self.sign_request(&mut request).is_err() {
+            return String::new();
+        }
+
+        let is_multipart = tab.request_type == RequestType::HTTP
+            && tab.sends_body()
+            && tab.content_type == ContentType::FormData;
+        let has_body = tab.request_type == RequestType::HTTP && tab.sends_body();
+        let is_raw_file = has_body && tab.content_type == ContentType::Binary;
+
+        let mut parts = vec!["curl".to_string()];
+        if tab.insecure_tls {
+            parts.push("--insecure".to_string());
+        }
+        match *request.method() {
+            reqwest::Method::GET => {}
+            // `-X HEAD` makes curl wait for a body that never comes
+            reqwest::Method::HEAD => parts.push("--head".to_string()),
+            ref method => parts.push(format!("-X {}", method)),
+        }
+        parts.push(shell_quote(request.url().as_str()));
+
+        for (name, value) in request.headers() {
+            // curl generates its own multipart boundary
+            if is_multipart && name == reqwest::header::CONTENT_TYPE {
+                continue;
+            }
+            // Our own Accept-Encoding, let curl negotiate and decode instead
+            if name == reqwest::header::ACCEPT_ENCODING
+                && value.as_bytes() == http::DEFAULT_ACCEPT_ENCODING.as_bytes()
+            {
+                parts.push("--compressed".to_string());
+                continue;
+            }
+            let header = format!("{}: {}", name, String::from_utf8_lossy(value.as_bytes()));
+            parts.push(format!("-H {}", shell_quote(&header)));
+        }
+
+        if is_multipart {
+            for field in tab
+                .form_data
+                .iter()
+                .filter(|f| f.enabled && !f.key.is_empty())
+            {
+                let part_type = field
+                    .part_content_type()
+                    .map(|ct| format!(";type={ct}"))
+                    .unwrap_or_default();
+                match field.field_type {
+                    FormFieldType::Text => {
+                        let pair = format!("{}={}{}", field.key, field.value, part_type);
+                        parts.push(format!("-F {}", shell_quote(&pair)));
                     }
-                    Err(e) => {
-                        yield Message::StreamChunk(format!("\n[stream error: {e}]"));
-                        break;
+                    FormFieldType::File => {
+                        for fp in &field.files {
+                            let pair = format!("{}=@{}{}", field.key, fp, part_type);
+                            parts.push(format!("-F {}", shell_quote(&pair)));
+                        }
                     }
                 }
             }
+        } else if is_raw_file && tab.gzip_body {
+            parts.push(format!(
+                "--data-binary @<(gzip -c {})",
+                shell_quote(&tab.binary_body_path)
+            ));
+        } else if is_raw_file {
+            parts.push(format!(
+                "--data-binary {}",
+                shell_quote(&format!("@{}", tab.binary_body_path))
+            ));
+        } else if tab.gzip_body && tab.content_type == ContentType::Json && has_body {
+            // The compressed bytes can't be pasted into a shell, gzip them on the way instead
+            parts.push(format!(
+                "--data-binary @<(printf %s {} | gzip)",
+                shell_quote(&tab.request_body)
+            ));
+        } else if let Some(body) = request.body().and_then(|b| b.as_bytes()) {
+            if !body.is_empty() {
+                let body = String::from_utf8_lossy(body);
+                parts.push(format!("--data-raw {}", shell_quote(&body)));
+            }
+        }
 
-            yield Message::StreamDone;
-                },
+        parts.join(" \\\n  ")
+    }
+
+    fn fetch_oauth_token(&mut self, send_after: bool) -> iced::Task<Message> {
+        let Some(insecure_tls) = self.current_tab().map(|t| t.insecure_tls) else {
+            return iced::Task::none();
+        };
+        let client = self.client(insecure_tls).clone();
+        let Some(tab) = self.current_tab_mut() else {
+            return iced::Task::none();
+        };
+        tab.oauth_fetching = true;
+        tab.oauth_error = None;
+        let tab_id = tab.id;
+        iced::Task::perform(
+            request_oauth_token(
+                client,
+                tab.oauth_token_url.trim().to_string(),
+                tab.oauth_client_id.clone(),
+                tab.oauth_client_secret.clone(),
+                tab.oauth_scope.trim().to_string(),
+            ),
+            move |result| Message::OAuthTokenFetched {
+                tab_id,
+                send_after,
+                result,
+            },
+        )
+    }
+
+    // `download_to` streams the body into that file instead of the response view
+    fn send_request(&mut self, download_to: Option<std::path::PathBuf>) -> iced::Task<Message> {
+        let Some((request, url)) = self.build_request() else {
+            // Nothing goes out, so the tab mustn't stay on "Sending..."
+            let Some(tab) = self.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.loading = false;
+            tab.loading_started = None;
+            if tab.content_type == ContentType::Binary
+                && let Err(e) = std::fs::read(&tab.binary_body_path)
+            {
+                tab.binary_body_error = Some(format!("Can't read {}: {}", tab.binary_body_path, e));
+            }
+            return iced::Task::done(Message::ShowToast(Toast::error(
+                "The request couldn't be built, nothing was sent".to_string(),
+            )));
+        };
+        let Some(tab) = self.current_tab() else {
+            return iced::Task::none();
+        };
+        // Responses are routed back by id, the user may switch tabs while this runs
+        let tab_id = tab.id;
+        let cancel_flag = tab.cancel_flag.clone();
+        tab.cancel_flag.store(false, Ordering::Relaxed);
+
+        // POST and PATCH may not be safe to repeat, so they only retry when allowed
+        let idempotent = tab.request_type != RequestType::GraphQL
+            && !matches!(tab.method, HttpMethod::POST | HttpMethod::PATCH);
+        let max_retries = if idempotent || self.http_settings.retry_non_idempotent {
+            self.http_settings.retry_count
+        } else {
+            0
+        };
+        let retry_backoff = std::time::Duration::from_millis(self.http_settings.retry_backoff_ms);
+
+        // Only plain GETs are revalidated, downloads go to disk and never hit the cache
+        let cache_key = (self.http_settings.cache_responses
+            && tab.request_type == RequestType::HTTP
+            && tab.method == HttpMethod::GET
+            && download_to.is_none())
+        .then(|| format!("GET {url}"));
+        let mut request = request;
+        if let Some(cached) = cache_key
+            .as_ref()
+            .and_then(|key| self.response_cache.get(key))
+        {
+            // Conditions the user wrote themselves win over the cached ones
+            let user_set = |name: &str| {
+                tab.headers
+                    .iter()
+                    .any(|h| h.enabled && h.key.trim().eq_ignore_ascii_case(name))
+            };
+            if let Some(etag) = &cached.etag
+                && !user_set("If-None-Match")
+            {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified
+                && !user_set("If-Modified-Since")
+            {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let (client, built) = request.build_split();
+        let mut request = match built {
+            Ok(request) => request,
+            Err(e) => {
+                if let Some(mut_tab) = self.current_tab_mut() {
+                    mut_tab.loading = false;
+                    mut_tab.loading_started = None;
+                }
+                return iced::Task::done(Message::ShowToast(Toast::error(format!(
+                    "Not sent: {}",
+                    e
+                ))));
+            }
+        };
+        if let Err(e) = self.sign_request(&mut request) {
+            if let Some(mut_tab) = self.current_tab_mut() {
+                mut_tab.loading = false;
+                mut_tab.loading_started = None;
+            }
+            return iced::Task::done(Message::ShowToast(Toast::error(format!(
+                "Not sent, the request couldn't be signed: {}",
+                e
+            ))));
+        }
+        let sent_request = SentRequest::of(&request);
+        let request = reqwest::RequestBuilder::from_parts(client, request);
+
+        if let Some(mut_tab) = self.current_tab_mut() {
+            mut_tab.cache_key = cache_key;
+            mut_tab.sent_request = Some(sent_request);
+            mut_tab.sent_at = Some(chrono::Local::now());
+            mut_tab.response_time = None;
+            mut_tab.response_timing = None;
+            mut_tab.stream_buffer = String::new();
+            mut_tab.download_progress = None;
+            mut_tab.retry_attempt = None;
+            mut_tab.download_path = download_to.clone();
+        }
+
+        let (task, handle) = iced::Task::run(
+            send_stream(SendJob {
+                tab_id,
+                request,
+                url,
+                cancel_flag,
+                max_retries,
+                retry_backoff,
+                manual_redirects: self.http_settings.follow_redirects
+                    && self.http_settings.show_redirect_chain,
+                max_redirects: self.http_settings.max_redirects,
+                download_to,
+            }),
             std::convert::identity, // stream already yields Message
         )
+        .abortable();
+        if let Some(tab) = self.tab_mut_by_id(tab_id) {
+            tab.request_handle = Some(handle.abort_on_drop());
+        }
+        task
     }
 
     fn subscription(&self) -> iced::Subscription<Message> {
         let mut subscriptions = vec![self.svg_rotation_subscription(), self.event_subscription()];
 
+        // Drives the fade and expiry, idle while nothing is shown
+        if !self.toasts.is_empty() {
+            subscriptions.push(
+                iced::time::every(std::time::Duration::from_millis(50)).map(|_| Message::ToastTick),
+            );
+        }
+
         if let Some(tab) = self.current_tab()
             && tab.request_type == RequestType::WebSocket
             && tab.ws_connection_id > 0
@@ -3610,6 +8265,16 @@ impl CrabiPie {
             subscriptions.push(Self::websocket_subscription(tab.ws_connection_id, url));
         }
 
+        // Audio has no frames to drive NewFrame, so poll the position instead
+        if let Some(tab) = self.current_tab()
+            && tab.response_content_type.starts_with("audio/")
+            && tab.video_player.as_ref().is_some_and(|vp| !vp.paused())
+        {
+            subscriptions.push(
+                iced::time::every(std::time::Duration::from_millis(250)).map(|_| Message::NewFrame),
+            );
+        }
+
         iced::Subscription::batch(subscriptions)
     }
 
@@ -3624,7 +8289,8 @@ impl CrabiPie {
         if let Some(tab) = self.current_tab()
             && tab.loading
         {
-            iced::time::every(std::time::Duration::from_millis(5)).map(|_| Message::Tick)
+            // About 60 frames a second is plenty for a spinner
+            iced::time::every(std::time::Duration::from_millis(16)).map(Message::Tick)
         } else {
             iced::Subscription::none()
         }
@@ -3654,6 +8320,16 @@ fn update(app: &mut CrabiPie, message: Message) -> iced::Task<Message> {
             app.sidebar_editing_name = String::new();
         }
     }
+    // Only changes the autosave has to write mark a tab, looking one up doesn't.
+    // Replace edits the request only when find is on the request body
+    let replaces_in_request = matches!(message, Message::Replace | Message::ReplaceAll)
+        && app.find_target == FindTarget::RequestBody;
+    if (message.changes_saved_tab() || replaces_in_request)
+        && let Some(tab) = app.current_tab_mut()
+    {
+        tab.dirty = true;
+        tab.unsaved_edits |= message.edits_request() || replaces_in_request;
+    }
     match message {
         Message::NoOp => iced::Task::none(),
         Message::TabSelected(index) => {
@@ -3680,9 +8356,13 @@ fn update(app: &mut CrabiPie, message: Message) -> iced::Task<Message> {
                     app.graphql_query_content = text_editor::Content::with_text(&tab.graphql_query);
                     app.graphql_variables_content =
                         text_editor::Content::with_text(&tab.graphql_variables);
-                    app.response_body_content = text_editor::Content::with_text(&tab.response_body);
+                    app.response_body_content = tab.response_editor_content();
                     app.response_headers_content =
                         text_editor::Content::with_text(&tab.response_headers);
+                    if !tab.response_filter.is_empty() {
+                        app.apply_response_filter();
+                    }
+                    app.sync_raw_headers();
                     iced::Task::none()
                 }
                 TabLoadState::Unloaded(meta) => {
@@ -3702,15 +8382,20 @@ fn update(app: &mut CrabiPie, message: Message) -> iced::Task<Message> {
             app.next_tab_id += 1;
             app.tabs
                 .push(TabLoadState::Loaded(Box::new(TabState::new(new_id))));
-            app.active_tab = app.tabs.len() - 1;
-            iced::Task::none()
+            // Goes through TabSelected so the shared editors are refilled for the new tab
+            update(app, Message::TabSelected(app.tabs.len() - 1))
         }
         Message::TabBodyLoaded { id, saved } => {
-            println!("Loaded tab {}", id);
+            log::debug!("Loaded tab {}", id);
             // find the slot and hydrate it
             if let Some(slot) = app.tabs.iter_mut().find(|t| t.id() == id) {
+                let collection_request_id = slot.metadata().collection_request_id;
                 *slot = match saved {
-                    Some(s) => TabLoadState::Loaded(Box::new(TabState::from_saved(s))),
+                    Some(s) => {
+                        let mut tab = TabState::from_saved(s);
+                        tab.metadata.collection_request_id = collection_request_id;
+                        TabLoadState::Loaded(Box::new(tab))
+                    }
                     None => TabLoadState::Unloaded(slot.metadata().clone()), // fallback
                 };
             }
@@ -3721,16 +8406,17 @@ fn update(app: &mut CrabiPie, message: Message) -> iced::Task<Message> {
                     let raw_form = tab.raw_form_content.clone();
                     let gql_query = tab.graphql_query.clone();
                     let gql_vars = tab.graphql_variables.clone();
-                    let res_body = tab.response_body.clone();
+                    let res_content = tab.response_editor_content();
                     let res_headers = tab.response_headers.clone();
 
                     app.request_body_content = text_editor::Content::with_text(&req_body);
                     app.raw_form_content = text_editor::Content::with_text(&raw_form);
                     app.graphql_query_content = text_editor::Content::with_text(&gql_query);
                     app.graphql_variables_content = text_editor::Content::with_text(&gql_vars);
-                    app.response_body_content = text_editor::Content::with_text(&res_body);
+                    app.response_body_content = res_content;
                     app.response_headers_content = text_editor::Content::with_text(&res_headers);
                 }
+                app.sync_raw_headers();
             }
 
             // after active tab done, schedule background loading for others
@@ -3792,6 +8478,7 @@ fn update(app: &mut CrabiPie, message: Message) -> iced::Task<Message> {
                 }
                 _ => {
                     tab.response_body = std::sync::Arc::from("Ops! Sorry. Not implemented yet!");
+                    tab.refresh_raw_response();
                 }
             }
             iced::Task::none()
@@ -3803,2216 +8490,8087 @@ fn update(app: &mut CrabiPie, message: Message) -> iced::Task<Message> {
             tab.method = method;
             iced::Task::none()
         }
+        Message::RequestNameChanged(name) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.name = name;
+            }
+            iced::Task::none()
+        }
+        Message::RequestDescriptionChanged(description) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.description = description;
+            }
+            iced::Task::none()
+        }
         Message::UrlChanged(url) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
             tab.url = url;
             app.parse_url_query();
+            app.url_suggest_open = true;
+            app.url_suggest_selected = None;
             iced::Task::none()
         }
-        Message::SendRequest => {
+        Message::UrlSuggestionPicked(url) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            if !tab.loading && !tab.url.trim().is_empty() {
-                tab.loading = true;
-                app.send_request()
-            } else {
-                iced::Task::none()
+            tab.url = url;
+            app.parse_url_query();
+            app.url_suggest_open = false;
+            app.url_suggest_selected = None;
+            iced::Task::none()
+        }
+        Message::UrlSuggestionMoved(down) => {
+            let count = app.url_suggestions().len();
+            if count == 0 {
+                return iced::Task::none();
             }
+            app.url_suggest_selected = Some(match (app.url_suggest_selected, down) {
+                (None, true) => 0,
+                (None, false) => count - 1,
+                (Some(idx), true) => (idx + 1) % count,
+                (Some(idx), false) => (idx + count - 1) % count,
+            });
+            iced::Task::none()
         }
-        Message::BodyAction(action) => {
-            match action {
-                text_editor::Action::Edit(_) => {
-                    app.request_body_content.perform(action);
-                    let updated_text = app.request_body_content.text();
-                    if let Some(tab) = app.current_tab_mut() {
-                        tab.request_body = std::sync::Arc::from(updated_text.as_str());
-                    }
+        Message::UrlSubmitted => {
+            let picked = app
+                .url_suggest_selected
+                .and_then(|idx| app.url_suggestions().get(idx).map(|url| url.to_string()));
+            match picked {
+                Some(url) => update(app, Message::UrlSuggestionPicked(url)),
+                None => update(app, Message::SendRequest),
+            }
+        }
+        Message::LoadTestToggle => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.load_test_open = !tab.load_test_open;
+            if tab.load_test_open {
+                tab.active_response_tab = ResponseTab::LoadTest;
+            } else {
+                // Closing the tab also stops a run still going
+                tab.load_test = None;
+                if tab.active_response_tab == ResponseTab::LoadTest {
+                    tab.active_response_tab = ResponseTab::Body;
                 }
-                _ => app.request_body_content.perform(action),
             }
             iced::Task::none()
         }
-        Message::AuthTypeSelected(auth_type) => {
+        Message::LoadTestCountChanged(value) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.auth_type = auth_type;
+            if value.is_empty() {
+                tab.load_test_count = 0;
+            } else if let Ok(count) = value.parse::<usize>() {
+                tab.load_test_count = count.min(LOAD_TEST_MAX_REQUESTS);
+            }
             iced::Task::none()
         }
-        Message::BearerTokenChanged(token) => {
+        Message::LoadTestConcurrencyChanged(value) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.bearer_token = token;
+            if value.is_empty() {
+                tab.load_test_concurrency = 1;
+            } else if let Ok(concurrency) = value.parse::<usize>() {
+                tab.load_test_concurrency = concurrency.clamp(1, LOAD_TEST_MAX_CONCURRENCY);
+            }
             iced::Task::none()
         }
-        Message::ContentTypeSelected(content_type) => {
+        Message::LoadTestStart => {
+            if let Some(refused) = app.check_form_files() {
+                return refused;
+            }
+            if let Some(refused) = app.check_signing() {
+                return refused;
+            }
+            let Some((builder, _)) = app.build_request() else {
+                return iced::Task::done(Message::ShowToast(Toast::error(
+                    "The request couldn't be built, nothing was sent".to_string(),
+                )));
+            };
+            let (client, request) = builder.build_split();
+            let mut request = match request {
+                Ok(request) => request,
+                Err(e) => {
+                    return iced::Task::done(Message::ShowToast(Toast::error(format!(
+                        "Can't build the request: {}",
+                        e
+                    ))));
+                }
+            };
+            // Every run sends the same bytes, so one signature covers them all
+            if let Err(e) = app.sign_request(&mut request) {
+                return iced::Task::done(Message::ShowToast(Toast::error(format!(
+                    "Not sent, the request couldn't be signed: {}",
+                    e
+                ))));
+            }
+            // Streamed bodies can only be sent once
+            if request.try_clone().is_none() {
+                return iced::Task::done(Message::ShowToast(Toast::error(
+                    "This request's body can't be sent more than once".to_string(),
+                )));
+            }
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.content_type = content_type;
-            iced::Task::none()
+            tab.load_test_run += 1;
+            let (tab_id, run) = (tab.id, tab.load_test_run);
+            let total = tab.load_test_count;
+            let (task, handle) = iced::Task::run(
+                load_test_stream(client, request, total, tab.load_test_concurrency),
+                move |result| Message::LoadTestResult {
+                    tab_id,
+                    run,
+                    result,
+                },
+            )
+            .abortable();
+            tab.load_test = Some(LoadTestRun::new(total, handle.abort_on_drop()));
+            task
         }
-        Message::CancelRequest => {
+        Message::LoadTestStop => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.cancel_flag.store(true, Ordering::Relaxed);
-            tab.loading = false;
-            tab.response_body = std::sync::Arc::from("Request cancelled by user");
-            tab.response_status = "Cancelled".to_string();
-            app.response_body_content =
-                text_editor::Content::with_text("Request cancelled by user");
+            tab.load_test_run += 1;
+            let Some(run) = tab.load_test.as_mut() else {
+                return iced::Task::none();
+            };
+            // Dropping the handle aborts the requests still in flight, results
+            // already on their way are ignored by the bumped run number
+            run.handle = None;
+            run.stopped = true;
+            run.finished_at = Some(std::time::Instant::now());
             iced::Task::none()
         }
-        Message::SaveBinaryResponse => {
-            let Some(tab) = app.current_tab() else {
+        Message::LoadTestResult {
+            tab_id,
+            run,
+            result,
+        } => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
                 return iced::Task::none();
             };
-            if !tab.is_response_binary {
+            if tab.load_test_run != run {
                 return iced::Task::none();
             }
-
-            let file_name = tab.response_filename.clone();
-            let response_bytes = tab.response_bytes.clone();
-
-            iced::Task::perform(
-                async move {
-                    match rfd::AsyncFileDialog::new()
-                        .set_file_name(&file_name)
-                        .save_file()
-                        .await
-                    {
-                        Some(file) => match file.write(&response_bytes).await {
-                            Ok(_) => Message::FileSaved(Ok(file.file_name().to_string())),
-                            Err(e) => Message::FileSaved(Err(format!("Failed to save: {}", e))),
-                        },
-                        None => Message::FileSaved(Err("Save dialog cancelled".to_string())),
-                    }
-                },
-                |message| message,
-            )
+            if let Some(load_test) = tab.load_test.as_mut() {
+                load_test.record(result);
+            }
+            iced::Task::none()
         }
-        Message::FileSaved(result) => {
+        Message::PaginationToggle => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            match result {
-                Ok(filename) => {
-                    tab.response_body =
-                        std::sync::Arc::from(format!("File saved successfully: {}", filename))
-                }
-                Err(error) => {
-                    tab.response_body =
-                        std::sync::Arc::from(format!("Error saving file: {}", error))
+            tab.pagination_open = !tab.pagination_open;
+            if tab.pagination_open {
+                tab.active_response_tab = ResponseTab::Paginate;
+            } else {
+                tab.pagination_run = None;
+                if tab.active_response_tab == ResponseTab::Paginate {
+                    tab.active_response_tab = ResponseTab::Body;
                 }
             }
-            app.response_body_content = text_editor::Content::with_text(&tab.response_body);
             iced::Task::none()
         }
-        Message::ClearResponseText => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            tab.response_body = std::sync::Arc::from("");
-            tab.response_headers = std::sync::Arc::from("");
-            app.response_body_content = text_editor::Content::new();
-            app.response_headers_content = text_editor::Content::new();
+        Message::PaginationItemsPathChanged(value) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.pagination.items_path = value;
+            }
             iced::Task::none()
         }
-        Message::GraphqlQueryAction(action) => {
-            app.graphql_query_content.perform(action);
-            let query_text = app.graphql_query_content.text();
+        Message::PaginationNextPathChanged(value) => {
             if let Some(tab) = app.current_tab_mut() {
-                tab.graphql_query = std::sync::Arc::from(query_text.as_str());
+                tab.pagination.next_path = value;
             }
             iced::Task::none()
         }
-        Message::GraphqlVariablesAction(action) => {
-            app.graphql_variables_content.perform(action);
-            let vars_text = app.graphql_variables_content.text();
+        Message::PaginationCursorParamChanged(value) => {
             if let Some(tab) = app.current_tab_mut() {
-                tab.graphql_variables = std::sync::Arc::from(vars_text.as_str());
+                tab.pagination.cursor_param = value;
             }
             iced::Task::none()
         }
-        Message::GraphqlOperationChanged(val) => {
+        Message::PaginationMaxPagesChanged(value) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.graphql_operation = val;
+            if value.is_empty() {
+                tab.pagination.max_pages = 0;
+            } else if let Ok(pages) = value.parse::<usize>() {
+                tab.pagination.max_pages = pages.min(PAGINATION_MAX_PAGES);
+            }
             iced::Task::none()
         }
-        Message::FetchGraphqlSchema => {
+        Message::PaginationStart => {
+            if let Some(refused) = app.check_form_files() {
+                return refused;
+            }
+            if let Some(refused) = app.check_signing() {
+                return refused;
+            }
+            // Each page has its own path, pagination_stream signs them one by one
+            let signer = match app.request_signer() {
+                Ok(signer) => signer,
+                Err(e) => {
+                    return iced::Task::done(Message::ShowToast(Toast::error(format!(
+                        "Not sent, the request couldn't be signed: {}",
+                        e
+                    ))));
+                }
+            };
+            let Some((builder, _)) = app.build_request() else {
+                return iced::Task::done(Message::ShowToast(Toast::error(
+                    "The request couldn't be built, nothing was sent".to_string(),
+                )));
+            };
+            let (client, request) = builder.build_split();
+            let request = match request {
+                Ok(request) => request,
+                Err(e) => {
+                    return iced::Task::done(Message::ShowToast(Toast::error(format!(
+                        "Can't build the request: {}",
+                        e
+                    ))));
+                }
+            };
+            if request.try_clone().is_none() {
+                return iced::Task::done(Message::ShowToast(Toast::error(
+                    "This request's body can't be sent more than once".to_string(),
+                )));
+            }
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            let url = tab.url.clone();
-            if url.is_empty() {
+            tab.pagination_run_id += 1;
+            let (tab_id, run) = (tab.id, tab.pagination_run_id);
+            let config = tab.pagination.clone();
+            let max_pages = config.max_pages;
+            let (task, handle) = iced::Task::run(
+                pagination_stream(client, request, config, signer),
+                move |event| Message::PaginationEvent { tab_id, run, event },
+            )
+            .abortable();
+            tab.pagination_run = Some(PaginationRun::new(max_pages, handle.abort_on_drop()));
+            task
+        }
+        Message::PaginationStop => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            let Some(run) = tab.pagination_run.as_mut() else {
+                return iced::Task::none();
+            };
+            if !run.is_running() {
                 return iced::Task::none();
             }
-
-            tab.graphql_schema_loading = true;
-            tab.graphql_schema_error = None;
-
-            iced::Task::perform(
-                async move {
-                    let body = serde_json::json!({ "query": INTROSPECTION_QUERY });
-                    let resp = HTTP_CLIENT
-                        .post(&url)
-                        .header("Content-Type", "application/json")
-                        .body(body.to_string())
-                        .send()
-                        .await
-                        .map_err(|e| e.to_string())?;
-
-                    let text = resp.text().await.map_err(|e| e.to_string())?;
-
-                    // This function should now return your new GraphqlSchema struct
-                    parse_graphql_schema(&text)
+            // Dropping the handle aborts the page in flight
+            run.handle = None;
+            run.stop_reason = Some("Cancelled".to_string());
+            let tab_id = tab.id;
+            app.show_paginated_items(tab_id);
+            iced::Task::none()
+        }
+        Message::PaginationEvent { tab_id, run, event } => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            if tab.pagination_run_id != run {
+                return iced::Task::none();
+            }
+            let Some(pagination) = tab.pagination_run.as_mut() else {
+                return iced::Task::none();
+            };
+            match event {
+                PaginationEvent::Page(items) => {
+                    pagination.pages += 1;
+                    pagination.items.extend(items);
+                    iced::Task::none()
+                }
+                PaginationEvent::Done { reason, failed } => {
+                    pagination.handle = None;
+                    pagination.stop_reason = Some(reason);
+                    pagination.failed = failed;
+                    app.show_paginated_items(tab_id);
+                    iced::Task::none()
+                }
+            }
+        }
+        Message::SendAndDownload => {
+            let Some(tab) = app.current_tab() else {
+                return iced::Task::none();
+            };
+            if tab.loading || tab.url.trim().is_empty() {
+                return iced::Task::none();
+            }
+            let file_name = tab
+                .url
+                .split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit('/').next())
+                .filter(|s| !s.is_empty() && !s.contains(':'))
+                .unwrap_or("download")
+                .to_string();
+            iced::Task::perform(
+                app.file_dialog()
+                    .set_title("Download Response To")
+                    .set_file_name(&file_name)
+                    .save_file(),
+                |handle| match handle {
+                    Some(handle) => remember_dir(
+                        handle.path(),
+                        Message::DownloadTargetChosen(handle.path().to_path_buf()),
+                    ),
+                    None => Message::NoOp,
                 },
-                Message::GraphqlSchemaFetched,
             )
         }
-
-        Message::GraphqlSchemaFetched(result) => {
+        Message::DownloadTargetChosen(path) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.download_path = Some(path);
+            }
+            update(app, Message::SendRequest)
+        }
+        Message::SendRequest => {
+            app.url_suggest_open = false;
+            app.url_suggest_selected = None;
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.graphql_schema_loading = false;
-            match result {
-                Ok(schema) => {
-                    tab.graphql_schema = Some(schema);
-                    tab.graphql_schema_error = None;
-                    // Optional: Auto-expand the root query type
-                    // tab.graphql_expanded_types.insert(schema.query_type.name.clone());
+            // A missing or expired token is fetched first, OAuthTokenFetched sends afterwards
+            let just_fetched = std::mem::take(&mut tab.oauth_just_fetched);
+            if tab.auth_type == AuthType::OAuth2ClientCredentials
+                && !just_fetched
+                && !tab.oauth_token_valid()
+            {
+                if tab.oauth_fetching || tab.loading {
+                    return iced::Task::none();
                 }
-                Err(e) => {
-                    tab.graphql_schema_error = Some(e);
+                if tab.oauth_token_url.trim().is_empty() {
+                    tab.oauth_error = Some("Set a token URL in the Auth tab".to_string());
+                    return iced::Task::none();
                 }
+                return app.fetch_oauth_token(true);
+            }
+            // Set by DownloadTargetChosen, taken here so a refused send doesn't leave it behind
+            let download_to = tab.download_path.take();
+            if tab.request_type == RequestType::GraphQL && !tab.graphql_variables.trim().is_empty()
+            {
+                if let Err(e) = serde_json::from_str::<serde_json::Value>(&tab.graphql_variables) {
+                    tab.graphql_variables_error = Some(format!("Invalid JSON: {}", e));
+                    return iced::Task::none();
+                }
+            }
+            // A missing file would otherwise go out as an empty body
+            if tab.request_type == RequestType::HTTP
+                && tab.sends_body()
+                && tab.content_type == ContentType::Binary
+            {
+                tab.binary_body_error = if tab.binary_body_path.is_empty() {
+                    Some("Choose a file to send".to_string())
+                } else {
+                    readable_file_error(&tab.binary_body_path)
+                };
+                if tab.binary_body_error.is_some() {
+                    return iced::Task::none();
+                }
+            }
+            if let Some(refused) = app.check_form_files() {
+                return refused;
+            }
+            if let Some(refused) = app.check_signing() {
+                return refused;
             }
-            iced::Task::none()
-        }
-        Message::GraphqlFieldToggled(path) | Message::GraphqlArgToggled(path) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            let selected = &mut tab.graphql_selected_paths;
-            let manual = &mut tab.manually_selected_paths;
-
-            if selected.contains(&path) {
-                // --- TOGGLE OFF ---
-                // 4. Recursive uncheck (Downwards)
-                let child_prefix = format!("{}.", path);
-                selected.retain(|p| p != &path && !p.starts_with(&child_prefix));
-                manual.retain(|p| p != &path && !p.starts_with(&child_prefix));
-
-                // 3. Walk backwards (Upwards)
-                let mut parts: Vec<String> = path.split('.').map(|s| s.to_string()).collect();
-                while parts.len() > 1 {
-                    parts.pop();
-                    let parent_path = parts.join(".");
-
-                    let sibling_prefix = format!("{}.", parent_path);
-                    let has_active_children =
-                        selected.iter().any(|p| p.starts_with(&sibling_prefix));
-
-                    if !has_active_children && !manual.contains(&parent_path) {
-                        selected.remove(&parent_path);
-                    } else {
-                        break;
-                    }
-                }
+            if !tab.loading && !tab.url.trim().is_empty() {
+                tab.loading = true;
+                tab.loading_started = Some(std::time::Instant::now());
+                let history_task = app.record_history();
+                iced::Task::batch([app.send_request(download_to), history_task])
             } else {
-                // --- TOGGLE ON ---
-                manual.insert(path.clone()); // Track this as a manual click
-
-                // 1. Bubbling check (Upwards)
-                let parts: Vec<&str> = path.split('.').collect();
-                let mut current_path = String::new();
-                for (i, part) in parts.iter().enumerate() {
-                    if i > 0 {
-                        current_path.push('.');
+                iced::Task::none()
+            }
+        }
+        Message::BodyAction(action) => {
+            let retarget = app.find_target != FindTarget::RequestBody;
+            app.find_target = FindTarget::RequestBody;
+            let mut lint = iced::Task::none();
+            match action {
+                text_editor::Action::Edit(_) => {
+                    app.request_body_content.perform(action);
+                    let updated_text = app.request_body_content.text();
+                    if let Some(tab) = app.current_tab_mut() {
+                        tab.request_body = std::sync::Arc::from(updated_text.as_str());
+                        tab.body_lint_generation += 1;
+                        let (tab_id, generation) = (tab.id, tab.body_lint_generation);
+                        lint = iced::Task::perform(
+                            tokio::time::sleep(std::time::Duration::from_millis(
+                                BODY_LINT_DELAY_MS,
+                            )),
+                            move |_| Message::BodyLintDue(tab_id, generation),
+                        );
+                    }
+                    if app.find_dialog_open {
+                        app.refresh_matches();
                     }
-                    current_path.push_str(part);
-                    selected.insert(current_path.clone());
                 }
+                _ => app.request_body_content.perform(action),
             }
-            let query = app.build_query();
-            app.graphql_query_content = text_editor::Content::with_text(&query);
-            if let Some(tab) = app.current_tab_mut() {
-                tab.graphql_query = std::sync::Arc::from(query);
+            if retarget && app.find_dialog_open {
+                app.current_match = 0;
+                app.find_next();
             }
-            iced::Task::none()
+            lint
         }
-
-        Message::GraphqlTypeToggled(name) => {
-            let Some(tab) = app.current_tab_mut() else {
+        Message::BodyLintDue(tab_id, generation) => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
                 return iced::Task::none();
             };
-
-            let expanded = &mut tab.graphql_expanded_types;
-            if !expanded.remove(&name) {
-                expanded.insert(name);
+            // A newer edit has its own lint on the way
+            if tab.body_lint_generation != generation {
+                return iced::Task::none();
             }
+            tab.body_json_error_at = None;
+            tab.is_body_valid_json = if tab.request_body.trim().is_empty() {
+                None
+            } else {
+                match serde_json::from_str::<serde_json::Value>(&tab.request_body) {
+                    Ok(_) => Some(Ok(())),
+                    Err(e) => {
+                        // serde_json counts both from 1
+                        tab.body_json_error_at =
+                            Some((e.line().saturating_sub(1), e.column().saturating_sub(1)));
+                        Some(Err(format!("Invalid JSON: {}", e)))
+                    }
+                }
+            };
             iced::Task::none()
         }
-
-        Message::GraphqlSearchChanged(val) => {
+        Message::OAuthTokenUrlChanged(value) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.graphql_search = val;
+            tab.oauth_token_url = value;
+            tab.forget_oauth_token();
             iced::Task::none()
         }
-        Message::GraphqlCollapseAll => {
+        Message::OAuthClientIdChanged(value) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            // Clear the set to collapse everything
-            tab.graphql_expanded_types.clear();
+            tab.oauth_client_id = value;
+            tab.forget_oauth_token();
             iced::Task::none()
         }
-        Message::CookieJarOpen => {
-            app.cookie_jar_open = true;
+        Message::OAuthClientSecretChanged(value) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.oauth_client_secret = value;
+            tab.forget_oauth_token();
             iced::Task::none()
         }
-        Message::CookieJarClose => {
-            for cookies in app.cookie_jar.values_mut() {
-                cookies.retain(|c| !c.name.trim().is_empty());
-            }
-            app.cookie_jar.retain(|_, cookies| !cookies.is_empty());
-            app.cookie_jar_open = false;
+        Message::OAuthScopeChanged(value) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.oauth_scope = value;
+            tab.forget_oauth_token();
             iced::Task::none()
         }
-        Message::CookieJarAdd(domain) => {
-            if domain.trim().is_empty() {
-                app.cookie_jar_error = Some("Domain cannot be empty".to_string());
-            } else {
-                app.cookie_jar.entry(domain).or_default().push(CookieEntry {
-                    name: String::new(),
-                    value: String::new(),
-                    enabled: true,
-                    domain: String::new(),
-                    path: "/".to_string(),
-                    expires: None,
-                });
-                app.cookie_jar_new_domain = String::new();
-                app.cookie_jar_error = None;
+        Message::OAuthGetToken => app.fetch_oauth_token(false),
+        Message::SigningToggled(enabled) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.signing.enabled = enabled;
             }
             iced::Task::none()
         }
-        Message::CookieJarRemove(domain, idx) => {
-            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
-                if idx < cookies.len() {
-                    cookies.remove(idx);
-                }
-                if cookies.is_empty() {
-                    app.cookie_jar.remove(&domain);
-                }
+        Message::SigningAlgorithmSelected(algorithm) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.signing.algorithm = algorithm;
             }
             iced::Task::none()
         }
-        Message::CookieJarToggled(domain, idx) => {
-            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
-                if let Some(c) = cookies.get_mut(idx) {
-                    c.enabled = !c.enabled;
-                }
+        Message::SigningEncodingSelected(encoding) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.signing.encoding = encoding;
             }
             iced::Task::none()
         }
-        Message::CookieJarNameChanged(domain, idx, val) => {
-            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
-                if let Some(c) = cookies.get_mut(idx) {
-                    c.name = val;
-                }
+        Message::SigningSecretChanged(value) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.signing_secret = value;
             }
             iced::Task::none()
         }
-        Message::CookieJarValueChanged(domain, idx, val) => {
-            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
-                if let Some(c) = cookies.get_mut(idx) {
-                    c.value = val;
-                }
+        Message::SigningHeaderNameChanged(value) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.signing.header_name = value;
             }
             iced::Task::none()
         }
-        Message::CookieJarDomainChanged(val) => {
-            app.cookie_jar_new_domain = val;
-            app.cookie_jar_error = None;
-            iced::Task::none()
-        }
-        Message::CookieJarClearDomain(domain) => {
-            app.cookie_jar.remove(&domain);
-            iced::Task::none()
-        }
-        Message::Tick => {
-            app.svg_rotation = (app.svg_rotation + 4.0) % 360.0;
-            iced::Task::none()
-        }
-        Message::TogglePause => {
+        Message::SigningSignPathToggled(enabled) => {
             if let Some(tab) = app.current_tab_mut() {
-                if let Some(vp) = tab.video_player.as_mut() {
-                    vp.set_paused(!vp.paused());
-                }
+                tab.signing.sign_path = enabled;
             }
             iced::Task::none()
         }
-        Message::ToggleLoop => {
+        Message::SigningSignHeaderChanged(value) => {
             if let Some(tab) = app.current_tab_mut() {
-                if let Some(vp) = tab.video_player.as_mut() {
-                    vp.set_looping(!vp.looping());
-                }
+                tab.signing.sign_header = value;
             }
             iced::Task::none()
         }
-        Message::VideoVolume(vol) => {
+        Message::SigningSignBodyToggled(enabled) => {
             if let Some(tab) = app.current_tab_mut() {
-                if let Some(vp) = tab.video_player.as_mut() {
-                    vp.set_volume(vol);
-                }
+                tab.signing.sign_body = enabled;
             }
             iced::Task::none()
         }
-        Message::Seek(secs) => {
-            if let Some(tab) = app.current_tab_mut() {
-                if let Some(vs) = tab.video_state.as_mut() {
-                    vs.dragging = true;
-                    vs.position = secs;
+        Message::OAuthTokenFetched {
+            tab_id,
+            send_after,
+            result,
+        } => {
+            let is_current = app.current_tab().map(|t| t.id) == Some(tab_id);
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            tab.oauth_fetching = false;
+            match result {
+                Ok(token) => {
+                    tab.oauth_access_token = token.access_token;
+                    // An expiry too far out to represent is as good as none
+                    tab.oauth_expires_at = token.expires_in.and_then(|secs| {
+                        std::time::Instant::now().checked_add(std::time::Duration::from_secs(secs))
+                    });
+                    tab.oauth_error = None;
+                    // SendRequest works on the current tab, don't send another one's request
+                    if send_after && is_current {
+                        tab.oauth_just_fetched = true;
+                        return update(app, Message::SendRequest);
+                    }
+                    iced::Task::none()
                 }
-                if let Some(vp) = tab.video_player.as_mut() {
-                    vp.set_paused(true);
+                Err(err) => {
+                    tab.oauth_error = Some(err.clone());
+                    iced::Task::done(Message::ShowToast(Toast::error(format!(
+                        "Couldn't get OAuth2 token: {}",
+                        err
+                    ))))
                 }
             }
-            iced::Task::none()
         }
-        Message::SeekRelease => {
-            if let Some(tab) = app.current_tab_mut() {
-                if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut())
-                {
-                    vs.dragging = false;
-                    vp.seek(std::time::Duration::from_secs_f64(vs.position), false)
-                        .expect("seek");
-                    vp.set_paused(false);
-                }
+        Message::AuthPresetsLoaded(presets) => {
+            if let Some(presets) = presets {
+                app.auth_presets = presets;
             }
             iced::Task::none()
         }
-        Message::EndOfStream => {
-            println!("end of stream");
-            iced::Task::none()
-        }
-        Message::NewFrame => {
+        Message::AuthPresetSelected(idx) => {
+            let Some(preset) = app.auth_presets.get(idx).cloned() else {
+                return iced::Task::none();
+            };
+            app.auth_preset_selected = Some(idx);
+            app.auth_preset_name = preset.name.clone();
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut()) {
-                if !vs.dragging {
-                    vs.position = vp.position().as_secs_f64();
-                }
-            }
+            preset.apply(tab);
             iced::Task::none()
         }
-        Message::ResponseReceived(resp) => {
-            let url = {
-                let Some(tab) = app.current_tab_mut() else {
-                    return iced::Task::none();
-                };
-
-                tab.loading = false;
-                tab.is_streaming = true;
-                tab.active_response_tab = ResponseTab::Body;
-                tab.is_response_binary = resp.is_binary;
-                tab.response_status = resp.status;
-                tab.response_content_type = resp.content_type.clone();
-                tab.response_time = resp.response_time;
-                tab.response_headers = std::sync::Arc::from(resp.headers.as_str());
-
-                let url = tab.url.clone();
-
-                if resp.is_binary && resp.content_type.starts_with("video/") && resp.accepts_range {
-                    let parsed_url = url::Url::parse(&url).unwrap();
-                    match iced_video_player::Video::new(&parsed_url) {
-                        Ok(video) => {
-                            tab.video_player = Some(video);
-                            tab.video_state = Some(VideoState {
-                                playing: true,
-                                buffering: true,
-                                position: 0.0,
-                                duration: 0.0,
-                                volume: 0.8,
-                                dragging: false,
-                            });
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to load video: {e:?}");
-                            tab.video_player = None;
-                        }
-                    }
-                } else if resp.is_binary && resp.content_type.starts_with("image/") {
-                    tab.image_handle =
-                        Some(iced::widget::image::Handle::from_bytes(resp.bytes.clone()));
-                } else {
-                    tab.video_player = None;
-                    tab.video_state = None;
-                    tab.response_headers = std::sync::Arc::from(resp.headers.as_str());
-                    tab.response_body = std::sync::Arc::from(resp.body.as_str());
-                    app.response_body_content = text_editor::Content::with_text(&resp.body);
-                }
-
-                url
+        Message::AuthPresetNameChanged(name) => {
+            app.auth_preset_name = name;
+            iced::Task::none()
+        }
+        Message::AuthPresetSave => {
+            let name = app.auth_preset_name.trim().to_string();
+            if name.is_empty() {
+                return iced::Task::none();
+            }
+            let Some(tab) = app.current_tab() else {
+                return iced::Task::none();
             };
-
-            app.response_headers_content = text_editor::Content::with_text(&resp.headers);
-
-            if let Some(domain) = extract_domain(&url) {
-                for raw in &resp.set_cookies {
-                    if let Some(cookie) = parse_set_cookie(raw) {
-                        let jar = app.cookie_jar.entry(domain.clone()).or_default();
-                        if let Some(existing) = jar.iter_mut().find(|c| c.name == cookie.name) {
-                            *existing = cookie;
-                        } else {
-                            jar.push(cookie);
-                        }
-                    }
+            let preset = AuthPreset::from_tab(name, tab);
+            // Saving under an existing name updates that preset
+            let idx = match app.auth_presets.iter().position(|p| p.name == preset.name) {
+                Some(idx) => {
+                    app.auth_presets[idx] = preset;
+                    idx
+                }
+                None => {
+                    app.auth_presets.push(preset);
+                    app.auth_presets.len() - 1
                 }
+            };
+            app.auth_preset_selected = Some(idx);
+            iced::Task::perform(save_auth_presets(app.auth_presets.clone()), |_| {
+                Message::AuthPresetsSaved
+            })
+        }
+        Message::AuthPresetDelete(idx) => {
+            if idx >= app.auth_presets.len() {
+                return iced::Task::none();
             }
-
+            app.auth_presets.remove(idx);
+            app.auth_preset_selected = None;
+            iced::Task::perform(save_auth_presets(app.auth_presets.clone()), |_| {
+                Message::AuthPresetsSaved
+            })
+        }
+        Message::AuthPresetsSaved => iced::Task::none(),
+        Message::AuthTypeSelected(auth_type) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.auth_type = auth_type;
             iced::Task::none()
         }
-        Message::ResponseBodyAction(action) => {
-            match action {
-                text_editor::Action::Edit(_) => {}
-                _ => app.response_body_content.perform(action),
-            }
+        Message::BearerTokenChanged(token) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.bearer_token = token;
             iced::Task::none()
         }
-        Message::ResponseHeadersAction(action) => {
-            match action {
-                text_editor::Action::Edit(_) => {}
-                _ => app.response_headers_content.perform(action),
-            }
+        Message::BasicUsernameChanged(username) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.basic_username = username;
             iced::Task::none()
         }
-        Message::ResponseTabSelected(response_tab) => {
+        Message::BasicPasswordChanged(password) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.active_response_tab = response_tab;
+            tab.basic_password = password;
+            iced::Task::none()
+        }
+        Message::ContentTypeSelected(content_type) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.content_type = content_type;
             iced::Task::none()
         }
-        Message::PrettifyJson => {
+        Message::CancelRequest => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            // The flag covers work between awaits, aborting drops a read that's still waiting
+            tab.cancel_flag.store(true, Ordering::Relaxed);
+            if let Some(handle) = tab.request_handle.take() {
+                handle.abort();
+            }
+            tab.loading = false;
+            tab.loading_started = None;
+            // An aborted download can't clean up after itself
+            let partial_file = tab.download_path.take();
+            tab.response_body = std::sync::Arc::from("Request cancelled by user");
+            tab.set_response_display("Request cancelled by user");
+            tab.refresh_raw_response();
+            tab.response_status = "Cancelled".to_string();
+            app.response_body_content =
+                text_editor::Content::with_text("Request cancelled by user");
+            match partial_file {
+                Some(path) => iced::Task::perform(
+                    async move { tokio::fs::remove_file(path).await.ok() },
+                    |_| Message::NoOp,
+                ),
+                None => iced::Task::none(),
+            }
+        }
+        Message::SaveBinaryResponse => {
             let Some(tab) = app.current_tab() else {
                 return iced::Task::none();
             };
-            let body_text = tab.request_body.to_string();
+            if !tab.is_response_binary {
+                return iced::Task::none();
+            }
+
+            let tab_id = tab.id;
+            let file_name = tab.response_filename.clone();
+            let response_bytes = tab.response_bytes.clone();
+            let dialog = app.file_dialog();
 
             iced::Task::perform(
                 async move {
-                    tokio::task::spawn_blocking(move || {
-                        let json: serde_json::Value =
-                            serde_json::from_str(&body_text).map_err(|e| e.to_string())?;
-
-                        serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
-                    })
-                    .await
-                    .map_err(|e| e.to_string())?
+                    match dialog.set_file_name(&file_name).save_file().await {
+                        Some(file) => match file.write(&response_bytes).await {
+                            Ok(_) => remember_dir(
+                                file.path(),
+                                Message::FileSaved(tab_id, Ok(file.file_name().to_string())),
+                            ),
+                            Err(e) => {
+                                Message::FileSaved(tab_id, Err(format!("Failed to save: {}", e)))
+                            }
+                        },
+                        None => Message::NoOp,
+                    }
                 },
-                Message::JsonPrettified,
+                |message| message,
             )
         }
-        Message::JsonPrettified(Ok(pretty)) => {
-            if let Some(tab) = app.current_tab_mut() {
-                tab.request_body = std::sync::Arc::from(pretty.as_str());
-            }
-            app.request_body_content
-                .perform(text_editor::Action::SelectAll);
-            app.request_body_content
-                .perform(text_editor::Action::Edit(text_editor::Edit::Paste(
-                    std::sync::Arc::new(pretty),
-                )));
-            iced::Task::none()
-        }
-        Message::JsonPrettified(Err(err)) => {
-            eprintln!("Prettify failed: {err}");
-            iced::Task::none()
-        }
-        Message::CopyToClipboard => {
-            let Some(tab) = app.current_tab_mut() else {
+        Message::SaveTextResponse => {
+            let Some(tab) = app.current_tab() else {
                 return iced::Task::none();
             };
-            if tab.is_response_binary {
+            if tab.is_response_binary || tab.response_body.is_empty() {
                 return iced::Task::none();
             }
-            let is_binary = tab.is_response_binary;
-            let active_response_tab = tab.active_response_tab.clone();
-            tab.copied = true;
 
-            let text = match active_response_tab {
-                ResponseTab::Body => app.response_body_content.text(),
-                ResponseTab::Headers => app.response_headers_content.text(),
+            let extension = text_extension_for(&tab.response_content_type);
+            let file_name = if tab.response_filename.contains('.') {
+                tab.response_filename.clone()
+            } else {
+                let stem = if tab.response_filename.is_empty() {
+                    "response"
+                } else {
+                    tab.response_filename.as_str()
+                };
+                format!("{}.{}", stem, extension)
             };
+            let tab_id = tab.id;
+            // The whole body, not just the part loaded into the editor
+            let body = tab.response_display.to_string();
+            let dialog = app.file_dialog();
 
-            iced::Task::batch([
-                iced::clipboard::write(text),
-                iced::Task::perform(
-                    async {
-                        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                    },
-                    |_| Message::ResetCopied,
-                ),
-            ])
+            iced::Task::perform(
+                async move {
+                    match dialog
+                        .set_file_name(&file_name)
+                        .add_filter(extension, &[extension])
+                        .save_file()
+                        .await
+                    {
+                        Some(file) => match file.write(body.as_bytes()).await {
+                            Ok(_) => remember_dir(
+                                file.path(),
+                                Message::FileSaved(tab_id, Ok(file.file_name().to_string())),
+                            ),
+                            Err(e) => {
+                                Message::FileSaved(tab_id, Err(format!("Failed to save: {}", e)))
+                            }
+                        },
+                        None => Message::NoOp,
+                    }
+                },
+                |message| message,
+            )
         }
-        Message::ResetCopied => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
+        Message::FileSaved(tab_id, result) => {
+            // The dialog may outlive the switch to another tab, name the one it was for then
+            let from = if app.is_active_tab(tab_id) {
+                String::new()
+            } else {
+                app.tab_mut_by_id(tab_id)
+                    .map(|tab| format!(" from \"{}\"", tab.display_name()))
+                    .unwrap_or_default()
             };
-            tab.copied = false;
-            iced::Task::none()
+            iced::Task::done(Message::ShowToast(match result {
+                Ok(filename) => Toast::success(format!("Saved{} to {}", from, filename)),
+                Err(error) => Toast::error(format!("Error saving file{}: {}", from, error)),
+            }))
         }
-        Message::QueryParamAdd => {
-            let Some(tab) = app.current_tab_mut() else {
+        Message::SendFailed(tab_id, error) => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
                 return iced::Task::none();
             };
-            tab.query_params.push(QueryParam::new());
-            app.rebuild_url();
-            iced::Task::none()
+            // The previous response stays on screen, the failure only gets a toast
+            tab.loading = false;
+            tab.loading_started = None;
+            tab.retry_attempt = None;
+            tab.download_progress = None;
+            tab.download_path = None;
+            iced::Task::done(Message::ShowToast(Toast::error(error)))
         }
-        Message::QueryParamRemove(idx) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            if idx < tab.query_params.len() {
-                tab.query_params.remove(idx);
+        Message::ShowToast(mut toast) => {
+            toast.id = app.next_toast_id;
+            toast.shown_at = std::time::Instant::now();
+            app.next_toast_id += 1;
+            app.toasts.push(toast);
+            // Only the newest few stay, a burst of errors shouldn't fill the window
+            if app.toasts.len() > MAX_TOASTS {
+                app.toasts.remove(0);
             }
-            app.rebuild_url();
             iced::Task::none()
         }
-        Message::QueryParamKeyChanged(idx, key) => {
-            app.update_query(idx, |p| p.key = key);
-            app.rebuild_url();
-            iced::Task::none()
-        }
-        Message::QueryParamValueChanged(idx, value) => {
-            app.update_query(idx, |p| p.value = value);
-            app.rebuild_url();
+        Message::DismissToast(id) => {
+            app.toasts.retain(|t| t.id != id);
             iced::Task::none()
         }
-        Message::QueryParamToggled(idx) => {
-            app.update_query(idx, |p| p.enabled = !p.enabled);
-            app.rebuild_url();
+        Message::ToastTick => {
+            app.toasts.retain(|t| t.shown_at.elapsed() < TOAST_DURATION);
             iced::Task::none()
         }
-        Message::FormFieldKeyChanged(index, key) => {
+        Message::ClearResponse => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-
-            if let Some(field) = tab.form_data.get_mut(index) {
-                field.key = key;
+            // Pause before dropping so the pipeline stops playing sound right away
+            if let Some(video) = tab.video_player.as_mut() {
+                video.set_paused(true);
             }
+            tab.video_player = None;
+            tab.video_state = None;
+            tab.response_status = String::new();
+            tab.response_body = std::sync::Arc::from("");
+            tab.set_response_display("");
+            tab.response_headers = std::sync::Arc::from("");
+            tab.response_header_list.clear();
+            tab.response_raw_head = String::new();
+            tab.response_raw.clear();
+            tab.response_cookies.clear();
+            tab.response_bytes = Vec::new();
+            tab.is_response_binary = false;
+            tab.is_streaming = false;
+            tab.stream_buffer = String::new();
+            tab.sse_event_count = 0;
+            tab.response_filename = String::new();
+            tab.response_content_type = String::new();
+            tab.response_html = Vec::new();
+            tab.image_handle = None;
+            tab.image_size = None;
+            tab.hex_dump = String::new();
+            tab.hex_dump_len = 0;
+            tab.response_time = None;
+            tab.response_timing = None;
+            tab.response_encoding = String::new();
+            tab.response_wire_size = 0;
+            tab.response_from_cache = false;
+            tab.previous_response_body = None;
+            tab.response_diff = None;
+            tab.response_json = None;
+            tab.response_images.clear();
+            tab.response_filter_error = None;
+            tab.active_response_tab = ResponseTab::Body;
+            app.response_body_content = text_editor::Content::new();
+            app.response_headers_content = text_editor::Content::new();
             iced::Task::none()
         }
-        Message::FormFieldValueChanged(index, value) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            if let Some(field) = tab.form_data.get_mut(index) {
-                field.value = value;
+        Message::GraphqlQueryAction(action) => {
+            app.graphql_query_content.perform(action);
+            let query_text = app.graphql_query_content.text();
+            if let Some(tab) = app.current_tab_mut() {
+                tab.graphql_query = std::sync::Arc::from(query_text.as_str());
             }
             iced::Task::none()
         }
-        Message::FormFieldTypeSelected(idx, form_field_type) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            if let Some(field) = tab.form_data.get_mut(idx) {
-                field.field_type = form_field_type;
-                field.value.clear();
-                field.files.clear();
+        Message::GraphqlVariablesAction(action) => {
+            app.graphql_variables_content.perform(action);
+            let vars_text = app.graphql_variables_content.text();
+            if let Some(tab) = app.current_tab_mut() {
+                tab.graphql_variables = std::sync::Arc::from(vars_text.as_str());
+                tab.graphql_variables_error = None;
             }
             iced::Task::none()
         }
-        Message::FormFieldToggled(idx) => {
+        Message::PrettifyGraphqlVariables => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            if let Some(field) = tab.form_data.get_mut(idx) {
-                field.enabled = !field.enabled;
+            match serde_json::from_str::<serde_json::Value>(&tab.graphql_variables)
+                .and_then(|json| serde_json::to_string_pretty(&json))
+            {
+                Ok(pretty) => {
+                    tab.graphql_variables = std::sync::Arc::from(pretty.as_str());
+                    tab.graphql_variables_error = None;
+                    app.graphql_variables_content = text_editor::Content::with_text(&pretty);
+                }
+                Err(e) => tab.graphql_variables_error = Some(format!("Invalid JSON: {}", e)),
             }
             iced::Task::none()
         }
-        Message::JsonThemeChanged(theme) => {
-            app.json_theme = theme;
-            iced::Task::none()
-        }
-        Message::AppThemeChanged(theme) => {
-            app.app_theme = theme;
+        Message::GraphqlOperationChanged(val) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.graphql_operation = val;
             iced::Task::none()
         }
-        Message::SaveRequest => {
-            let Some(tab) = app.current_tab() else {
+        Message::FetchGraphqlSchema => {
+            let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            let state = tab.to_saved(&app.json_theme.to_string(), &app.app_theme.to_string());
-
-            iced::Task::perform(
-                async move {
-                    match rfd::AsyncFileDialog::new()
-                        .set_title("Save CrabiPie State")
-                        .set_file_name("crabipie_state.json")
-                        .save_file()
-                        .await
-                    {
-                        Some(file_handle) => {
-                            // Serialize JSON
-                            let json = serde_json::to_string_pretty(&state)
-                                .map_err(|e| format!("Serialization error: {}", e))?;
+            let url = tab.url.clone();
+            if url.is_empty() {
+                return iced::Task::none();
+            }
 
-                            // Async write
-                            tokio::fs::write(file_handle.path(), json)
-                                .await
-                                .map_err(|e| format!("Failed to write file: {}", e))?;
+            tab.graphql_schema_loading = true;
+            tab.graphql_schema_error = None;
+            let insecure_tls = tab.insecure_tls;
+            let client = app.client(insecure_tls).clone();
 
-                            Ok::<_, String>(file_handle.file_name().to_string())
-                        }
-                        None => Err("Save dialog cancelled".to_string()),
-                    }
-                },
-                |result| match result {
-                    Ok(filename) => Message::FileSaved(Ok(filename)),
-                    Err(err) => Message::FileSaved(Err(err)),
-                },
-            )
-        }
-        Message::LoadRequest => {
             iced::Task::perform(
                 async move {
-                    match rfd::AsyncFileDialog::new()
-                        .set_title("Open CrabiPie State")
-                        .pick_file()
+                    let body = serde_json::json!({ "query": INTROSPECTION_QUERY });
+                    let resp = client
+                        .post(&url)
+                        .header("Content-Type", "application/json")
+                        .body(body.to_string())
+                        .send()
                         .await
-                    {
-                        Some(file_handle) => {
-                            // Async read file
-                            let bytes = tokio::fs::read(file_handle.path())
-                                .await
-                                .map_err(|e| format!("Failed to read file: {}", e))?;
-
-                            // Convert bytes to string
-                            let content = String::from_utf8(bytes)
-                                .map_err(|e| format!("Invalid UTF-8 in file: {}", e))?;
+                        .map_err(|e| e.to_string())?;
 
-                            // Deserialize JSON into SavedState
-                            let saved_state: SavedState = serde_json::from_str(&content)
-                                .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+                    let text = resp.text().await.map_err(|e| e.to_string())?;
 
-                            Ok::<_, String>(saved_state)
-                        }
-                        None => Err("Open file dialog cancelled".to_string()),
-                    }
-                },
-                |result| match result {
-                    Ok(saved_state) => Message::RequestLoaded(saved_state),
-                    Err(err) => Message::RequestLoadFailed(err),
+                    // This function should now return your new GraphqlSchema struct
+                    parse_graphql_schema(&text)
                 },
+                Message::GraphqlSchemaFetched,
             )
         }
-        Message::RequestLoaded(saved_state) => {
-            if let Some(slot) = app.tabs.get_mut(app.active_tab) {
-                *slot = TabLoadState::Loaded(Box::new(TabState::from_saved(saved_state)));
-            }
-            iced::Task::none()
-        }
-        Message::RequestLoadFailed(err) => iced::Task::none(),
-        Message::FormFieldFileSelect(idx) => {
-            let future = async move {
-                let files = rfd::AsyncFileDialog::new()
-                    .set_directory("~/Downloads")
-                    .pick_files()
-                    .await;
-
-                // Extract file paths as Strings
-                let paths = files
-                    .map(|handles| {
-                        handles
-                            .into_iter()
-                            .filter_map(|handle| handle.path().to_str().map(String::from))
-                            .collect()
-                    })
-                    .unwrap_or_default();
 
-                Message::FormFieldFilesSelected(idx, paths)
+        Message::GraphqlSchemaFetched(result) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
             };
-
-            iced::Task::perform(future, std::convert::identity)
+            tab.graphql_schema_loading = false;
+            match result {
+                Ok(schema) => {
+                    tab.graphql_schema = Some(schema);
+                    tab.graphql_schema_error = None;
+                    // Optional: Auto-expand the root query type
+                    // tab.graphql_expanded_types.insert(schema.query_type.name.clone());
+                }
+                Err(e) => {
+                    tab.graphql_schema_error = Some(e);
+                }
+            }
+            iced::Task::none()
         }
-        Message::FormFieldFilesSelected(index, files) => {
+        Message::GraphqlFieldToggled(path) | Message::GraphqlArgToggled(path) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            if let Some(field) = tab.form_data.get_mut(index) {
-                field.files = files;
+            let selected = &mut tab.graphql_selected_paths;
+            let manual = &mut tab.manually_selected_paths;
+
+            if selected.contains(&path) {
+                // --- TOGGLE OFF ---
+                // 4. Recursive uncheck (Downwards)
+                let child_prefix = format!("{}.", path);
+                selected.retain(|p| p != &path && !p.starts_with(&child_prefix));
+                manual.retain(|p| p != &path && !p.starts_with(&child_prefix));
+
+                // 3. Walk backwards (Upwards)
+                let mut parts: Vec<String> = path.split('.').map(|s| s.to_string()).collect();
+                while parts.len() > 1 {
+                    parts.pop();
+                    let parent_path = parts.join(".");
+
+                    let sibling_prefix = format!("{}.", parent_path);
+                    let has_active_children =
+                        selected.iter().any(|p| p.starts_with(&sibling_prefix));
+
+                    if !has_active_children && !manual.contains(&parent_path) {
+                        selected.remove(&parent_path);
+                    } else {
+                        break;
+                    }
+                }
+            } else {
+                // --- TOGGLE ON ---
+                manual.insert(path.clone()); // Track this as a manual click
+
+                // 1. Bubbling check (Upwards)
+                let parts: Vec<&str> = path.split('.').collect();
+                let mut current_path = String::new();
+                for (i, part) in parts.iter().enumerate() {
+                    if i > 0 {
+                        current_path.push('.');
+                    }
+                    current_path.push_str(part);
+                    selected.insert(current_path.clone());
+                }
+            }
+            let query = app.build_query();
+            app.graphql_query_content = text_editor::Content::with_text(&query);
+            if let Some(tab) = app.current_tab_mut() {
+                tab.graphql_query = std::sync::Arc::from(query);
             }
             iced::Task::none()
         }
-        Message::FormFieldRemove(index) => {
+
+        Message::GraphqlTypeToggled(name) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            if index < tab.form_data.len() {
-                tab.form_data.remove(index);
+
+            let expanded = &mut tab.graphql_expanded_types;
+            if !expanded.remove(&name) {
+                expanded.insert(name);
             }
             iced::Task::none()
         }
-        Message::FormFieldAdd => {
+
+        Message::GraphqlSearchChanged(val) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.form_data.push(FormField::new());
+            tab.graphql_search = val;
             iced::Task::none()
         }
-        Message::ViewRawForm => {
+        Message::GraphqlCollapseAll => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            if matches!(
-                tab.content_type,
-                ContentType::FormData | ContentType::XWWWFormUrlEncoded
-            ) {
-                let raw = TabState::form_data_to_raw(&tab.form_data);
-                tab.raw_form_content = std::sync::Arc::from(raw.as_str());
-                tab.form_view_type = FormViewType::Raw;
-                app.raw_form_content = text_editor::Content::with_text(&raw);
-            }
+            // Clear the set to collapse everything
+            tab.graphql_expanded_types.clear();
             iced::Task::none()
         }
-        Message::ViewFormattedForm => {
-            let raw = app.raw_form_content.text();
-            if let Some(tab) = app.current_tab_mut() {
-                if matches!(
-                    tab.content_type,
-                    ContentType::FormData | ContentType::XWWWFormUrlEncoded
-                ) {
-                    tab.raw_form_content = std::sync::Arc::from(raw.as_str());
-                    tab.form_data = TabState::raw_to_form_data(&raw);
-                    tab.form_view_type = FormViewType::Formatted;
-                }
-            }
+        Message::CookieJarOpen => {
+            app.cookie_jar_open = true;
             iced::Task::none()
         }
-        Message::FormRawAction(action) => {
-            app.raw_form_content.perform(action);
-            let raw_text = app.raw_form_content.text();
-            if let Some(tab) = app.current_tab_mut() {
-                tab.raw_form_content = std::sync::Arc::from(raw_text.as_str());
-            };
+        Message::CookieJarClose => {
+            for cookies in app.cookie_jar.values_mut() {
+                cookies.retain(|c| !c.name.trim().is_empty());
+            }
+            app.cookie_jar.retain(|_, cookies| !cookies.is_empty());
+            app.cookie_jar_open = false;
             iced::Task::none()
         }
-        Message::ToggleFindDialog => {
-            app.find_dialog_open = !app.find_dialog_open;
-            iced::widget::operation::focus("find_input")
-        }
-        Message::ToggleFindReplaceDialog => {
-            app.find_replace_mode = !app.find_replace_mode;
-            iced::widget::operation::focus(if app.find_replace_mode {
-                "replace_input"
+        Message::CookieJarAdd(domain) => {
+            if domain.trim().is_empty() {
+                app.cookie_jar_error = Some("Domain cannot be empty".to_string());
             } else {
-                "find_input"
-            })
-        }
-        Message::CloseFindDialog => {
-            app.find_dialog_open = false;
-
-            // Clear search highlights
-            app.search_match_positions = Vec::new();
-            app.current_match_line_col = None;
-            app.search_match_length = 0;
-            app.current_match = 0;
-            app.total_matches = 0;
-
-            iced::Task::none()
-        }
-        Message::FindTextChanged(find_text) => {
-            app.current_match = 0;
-            app.total_matches = 0;
-            app.current_match_pos = None;
-            app.search_match_positions = Vec::new();
-            app.current_match_line_col = None;
-            app.search_match_length = 0;
-            app.find_text = find_text;
-
-            // Automatically find first match when text changes
-            if !app.find_text.is_empty() {
-                app.find_next();
+                app.cookie_jar.entry(domain).or_default().push(CookieEntry {
+                    name: String::new(),
+                    value: String::new(),
+                    enabled: true,
+                    domain: String::new(),
+                    path: "/".to_string(),
+                    expires: None,
+                });
+                app.cookie_jar_new_domain = String::new();
+                app.cookie_jar_error = None;
             }
-
             iced::Task::none()
         }
-        Message::ReplaceTextChanged(replace_text) => {
-            app.replace_text = replace_text;
+        Message::CookieJarRemove(domain, idx) => {
+            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
+                if idx < cookies.len() {
+                    cookies.remove(idx);
+                }
+                if cookies.is_empty() {
+                    app.cookie_jar.remove(&domain);
+                }
+            }
             iced::Task::none()
         }
-        Message::ToggleCaseSensitive => {
-            app.case_sensitive = !app.case_sensitive;
-
-            // Re-search with new settings
-            if !app.find_text.is_empty() {
-                app.current_match = 0;
-                app.find_next();
+        Message::CookieJarToggled(domain, idx) => {
+            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
+                if let Some(c) = cookies.get_mut(idx) {
+                    c.enabled = !c.enabled;
+                }
             }
-
             iced::Task::none()
         }
-        Message::ToggleWholeWord => {
-            app.whole_word = !app.whole_word;
-
-            // Re-search with new settings
-            if !app.find_text.is_empty() {
-                app.current_match = 0;
-                app.find_next();
+        Message::CookieJarNameChanged(domain, idx, val) => {
+            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
+                if let Some(c) = cookies.get_mut(idx) {
+                    c.name = val;
+                }
             }
-
             iced::Task::none()
         }
-        Message::FindNext => {
-            app.find_next();
-
+        Message::CookieJarValueChanged(domain, idx, val) => {
+            if let Some(cookies) = app.cookie_jar.get_mut(&domain) {
+                if let Some(c) = cookies.get_mut(idx) {
+                    c.value = val;
+                }
+            }
             iced::Task::none()
         }
-        Message::FindPrevious => iced::Task::none(),
-        Message::Replace => {
-            println!("Event fired");
+        Message::CookieJarDomainChanged(val) => {
+            app.cookie_jar_new_domain = val;
+            app.cookie_jar_error = None;
             iced::Task::none()
         }
-        Message::ReplaceAll => {
-            println!("Event fired");
+        Message::CookieJarClearDomain(domain) => {
+            app.cookie_jar.remove(&domain);
             iced::Task::none()
         }
-        Message::EventOccurred(event) => {
-            use iced::keyboard::{Event as KeyEvent, Key, Modifiers};
-            if let Event::Window(window_event) = &event {
-                match window_event {
-                    iced::window::Event::CloseRequested => {
-                        // Save first, then close
-                        return app.save_task().chain(iced::exit());
-                    }
-                    iced::window::Event::Unfocused => {
-                        println!("window was unfocused");
-                        return iced::Task::none();
-                    }
-                    _ => {}
+        Message::Tick(now) => {
+            // The first tick after a pause only sets the clock, or the spinner would jump
+            let elapsed = app
+                .last_tick
+                .map(|last| now.saturating_duration_since(last))
+                .filter(|d| *d < std::time::Duration::from_millis(250))
+                .unwrap_or_default();
+            app.last_tick = Some(now);
+            app.svg_rotation =
+                (app.svg_rotation + SPINNER_DEGREES_PER_SEC * elapsed.as_secs_f32()) % 360.0;
+            iced::Task::none()
+        }
+        Message::TogglePause => {
+            if let Some(tab) = app.current_tab_mut() {
+                if let Some(vp) = tab.video_player.as_mut() {
+                    vp.set_paused(!vp.paused());
                 }
             }
-            if let Event::Mouse(iced::mouse::Event::ButtonPressed(
-                iced::mouse::Button::Left | iced::mouse::Button::Right,
-            )) = &event
-            {
-                if let Some(editing_id) = app.sidebar_editing_id {
-                    return iced::widget::operation::is_focused("sidebar_rename").then(
-                        move |focused| {
-                            if !focused {
-                                iced::Task::done(Message::CollectionItemRenameConfirm(editing_id))
-                            } else {
-                                iced::Task::none()
-                            }
-                        },
-                    );
+            iced::Task::none()
+        }
+        Message::ToggleLoop => {
+            if let Some(tab) = app.current_tab_mut() {
+                if let Some(vp) = tab.video_player.as_mut() {
+                    vp.set_looping(!vp.looping());
                 }
             }
-            if let Event::Keyboard(key_event) = event {
-                let Some(tab) = app.current_tab() else {
-                    return iced::Task::none();
-                };
-                match key_event {
-                    KeyEvent::KeyPressed { key, modifiers, .. } if modifiers.control() => {
-                        if let Key::Character(c) = &key {
-                            if c.as_str() == "l" {
-                                return iced::widget::operation::focus(tab.url_id.clone()).chain(
-                                    iced::widget::operation::select_all(tab.url_id.clone()),
-                                );
-                            } else if c.as_str() == "f" {
-                                return iced::Task::done(Message::ToggleFindDialog);
-                            } else if c.as_str() == "h" {
-                                return iced::Task::done(Message::ToggleFindReplaceDialog);
-                            }
-                        }
-                        if matches!(key, Key::Named(iced::keyboard::key::Named::Enter)) {
-                            println!("Key event: ctrl + Enter");
-                            return iced::Task::done(Message::SendRequest);
-                        }
-                    }
-                    KeyEvent::KeyPressed {
-                        key: Key::Named(iced::keyboard::key::Named::Enter),
-                        ..
-                    } => {
-                        let tasks = vec![
-                            iced::widget::operation::is_focused("find_input").then(|f| {
-                                if f {
-                                    iced::Task::done(Message::FindNext)
-                                } else {
-                                    iced::Task::none()
-                                }
-                            }),
-                            iced::widget::operation::is_focused("replace_input").then(|f| {
-                                if f {
-                                    iced::Task::done(Message::Replace)
-                                } else {
-                                    iced::Task::none()
-                                }
-                            }),
-                            iced::widget::operation::is_focused(tab.url_id.clone()).then(|f| {
-                                if f {
-                                    iced::Task::done(Message::SendRequest)
-                                } else {
-                                    iced::Task::none()
-                                }
-                            }),
-                        ];
-                        return iced::Task::batch(tasks);
-                    }
-                    KeyEvent::KeyPressed {
-                        key: Key::Named(iced::keyboard::key::Named::Tab),
-                        modifiers,
-                        ..
-                    } if modifiers.shift() => {
-                        println!("Key event: shift + tab");
-                        return iced::widget::operation::focus_previous();
-                    }
-                    KeyEvent::KeyPressed {
-                        key: Key::Named(iced::keyboard::key::Named::Tab),
-                        ..
-                    } => {
-                        println!("Key event: Tab");
-                        return iced::widget::operation::focus_next();
-                    }
-                    KeyEvent::KeyPressed {
-                        key: Key::Named(iced::keyboard::key::Named::Escape),
-                        ..
-                    } => {
-                        if app.sidebar_editing_id.is_some() {
-                            app.sidebar_editing_id = None;
-                            app.sidebar_editing_name = String::new();
-                            return iced::Task::none();
-                        }
-                        if app.save_modal_open {
-                            app.save_modal_open = false;
-                            app.save_modal_name = String::new();
-                            app.save_modal_folder_id = None;
-                            return iced::Task::none();
-                        }
+            iced::Task::none()
+        }
+        Message::VideoVolume(vol) => {
+            if let Some(tab) = app.current_tab_mut() {
+                if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut())
+                {
+                    vp.set_volume(vol);
+                    vs.volume = vol;
+                    // Moving the slider off zero ends a mute
+                    if vol > 0.0 {
+                        vs.muted_volume = None;
                     }
-                    _ => {}
                 }
             }
             iced::Task::none()
         }
-        Message::RequestTabSelected(request_tab) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-
-            tab.active_request_tab = request_tab;
-            iced::Task::none()
-        }
-        Message::ApiKeyPositionChanged(position) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            tab.api_key_position = position;
-            iced::Task::none()
-        }
-        Message::ApiKeyNameChanged(key) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            tab.api_key_name = key;
-            iced::Task::none()
-        }
-        Message::ApiKeyChanged(key) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            tab.api_key = key;
+        Message::ToggleMute => {
+            if let Some(tab) = app.current_tab_mut() {
+                if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut())
+                {
+                    let volume = match vs.muted_volume.take() {
+                        Some(previous) => previous,
+                        None if vs.volume > 0.0 => {
+                            vs.muted_volume = Some(vs.volume);
+                            0.0
+                        }
+                        // Already silent through the slider, unmute to the default
+                        None => 0.8,
+                    };
+                    vp.set_volume(volume);
+                    vs.volume = volume;
+                }
+            }
             iced::Task::none()
         }
-        Message::HeaderAdd => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            tab.headers.push(RequestHeaders::new());
+        Message::PlaybackSpeedChanged(speed) => {
+            if let Some(tab) = app.current_tab_mut() {
+                if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut())
+                {
+                    match vp.set_speed(speed) {
+                        Ok(()) => vs.speed = speed,
+                        Err(e) => log::warn!("Failed to set playback speed: {e:?}"),
+                    }
+                }
+            }
             iced::Task::none()
         }
-        Message::HeaderRemove(id) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            if id < tab.headers.len() {
-                tab.headers.remove(id);
+        Message::Seek(secs) => {
+            if let Some(tab) = app.current_tab_mut() {
+                if let Some(vs) = tab.video_state.as_mut() {
+                    vs.dragging = true;
+                    vs.position = secs;
+                }
+                if let Some(vp) = tab.video_player.as_mut() {
+                    vp.set_paused(true);
+                }
             }
             iced::Task::none()
         }
-        Message::HeaderKeyChanged(id, key) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-
-            if let Some(header) = tab.headers.get_mut(id) {
-                header.key = key;
+        Message::SeekRelease => {
+            if let Some(tab) = app.current_tab_mut() {
+                if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut())
+                {
+                    vs.dragging = false;
+                    vp.seek(std::time::Duration::from_secs_f64(vs.position), false)
+                        .expect("seek");
+                    // A seek resets the pipeline rate, put the chosen speed back
+                    if vs.speed != 1.0 {
+                        vp.set_speed(vs.speed).ok();
+                    }
+                    vp.set_paused(false);
+                }
             }
             iced::Task::none()
         }
-        Message::HeaderValueChanged(id, value) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            if let Some(header) = tab.headers.get_mut(id) {
-                header.value = value;
+        Message::EndOfStream => {
+            log::debug!("end of stream");
+            // Looping restarts with a seek, which drops back to normal speed
+            if let Some(tab) = app.current_tab_mut() {
+                if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut())
+                {
+                    if vp.looping() && vs.speed != 1.0 {
+                        vp.set_speed(vs.speed).ok();
+                    }
+                }
             }
             iced::Task::none()
         }
-        Message::HeaderToggled(id) => {
+        Message::NewFrame => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            if let Some(header) = tab.headers.get_mut(id) {
-                header.enabled = !header.enabled;
+            if let (Some(vs), Some(vp)) = (tab.video_state.as_mut(), tab.video_player.as_mut()) {
+                if !vs.dragging {
+                    vs.position = vp.position().as_secs_f64();
+                }
             }
             iced::Task::none()
         }
-        Message::StreamChunk(chunk) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            tab.stream_buffer.push_str(&chunk);
+        Message::ResponseReceived(tab_id, resp) => {
+            let is_active = app.is_active_tab(tab_id);
+            let url = {
+                let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                    return iced::Task::none();
+                };
 
-            iced::Task::none()
-        }
-        Message::StreamDone => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
+                tab.loading = false;
+                tab.loading_started = None;
+                tab.dirty = true;
+                // The body being replaced is what the next Diff compares against
+                if !tab.is_response_binary && !tab.is_streaming && !tab.response_body.is_empty() {
+                    tab.previous_response_body = Some(tab.response_body.to_string());
+                }
+                tab.response_diff = None;
+                tab.response_json = None;
+                tab.response_images.clear();
+                tab.is_streaming = true;
+                // HEAD responses never carry a body, the headers are the whole answer
+                tab.active_response_tab =
+                    if tab.request_type == RequestType::HTTP && tab.method == HttpMethod::HEAD {
+                        ResponseTab::Headers
+                    } else {
+                        ResponseTab::Body
+                    };
+                tab.is_response_binary = resp.is_binary;
+                tab.response_status = resp.status;
+                tab.response_content_type = resp.content_type.clone();
+                tab.response_html = Vec::new();
+                tab.sse_event_count = 0;
+                tab.response_time = resp.response_time;
+                tab.response_timing = resp.timing;
+                tab.response_headers = std::sync::Arc::from(resp.headers.as_str());
+                tab.response_header_list = resp.header_list.clone();
+                tab.response_raw_head = resp.raw_head.clone();
+                tab.response_filename = resp.filename.clone();
+                tab.response_bytes = resp.bytes.clone();
+                tab.hex_dump = String::new();
+                tab.hex_dump_len = 0;
+                tab.image_size = None;
+                if resp.is_binary
+                    && !resp.content_type.starts_with("image/")
+                    && !resp.content_type.starts_with("video/")
+                {
+                    tab.hex_dump_len = tab.response_bytes.len().min(HEX_DUMP_PAGE);
+                    tab.hex_dump = format_hex_dump(&tab.response_bytes[..tab.hex_dump_len]);
+                }
+                tab.response_encoding = resp.content_encoding.clone();
+                tab.response_wire_size = resp.wire_size;
+                tab.response_from_cache = false;
+                tab.download_progress = None;
+                tab.download_path = None;
 
-            tab.is_streaming = false;
-            tab.loading = false;
-            let body = std::mem::take(&mut tab.stream_buffer);
-            tab.response_body = std::sync::Arc::from(body.as_str());
-            let display = if let Ok(j) = serde_json::from_str::<serde_json::Value>(&body) {
-                serde_json::to_string_pretty(&j).unwrap_or_else(|_| body.clone())
-            } else {
-                body.clone()
+                let url = tab.url.clone();
+
+                if resp.is_binary
+                    && (resp.content_type.starts_with("video/")
+                        || resp.content_type.starts_with("audio/"))
+                    && resp.accepts_range
+                {
+                    // The URL that went out, the tab's own may still hold {{variables}}
+                    let parsed_url = match &tab.sent_request {
+                        Some(sent) => Ok(sent.url.clone()),
+                        None => url::Url::parse(&url).map_err(|e| e.to_string()),
+                    };
+                    let video = parsed_url.and_then(|parsed_url| {
+                        iced_video_player::Video::new(&parsed_url).map_err(|e| format!("{e:?}"))
+                    });
+                    match video {
+                        Ok(mut video) => {
+                            let video_state = VideoState {
+                                playing: true,
+                                buffering: true,
+                                position: 0.0,
+                                duration: 0.0,
+                                volume: 0.8,
+                                muted_volume: None,
+                                speed: 1.0,
+                                dragging: false,
+                            };
+                            video.set_volume(video_state.volume);
+                            tab.video_player = Some(video);
+                            tab.video_state = Some(video_state);
+                        }
+                        Err(e) => {
+                            log::warn!("Failed to load video: {e}");
+                            tab.video_player = None;
+                        }
+                    }
+                } else if resp.is_binary && resp.content_type.starts_with("image/") {
+                    tab.image_handle =
+                        Some(iced::widget::image::Handle::from_bytes(resp.bytes.clone()));
+                    tab.image_size = image_dimensions(&resp.bytes);
+                } else {
+                    tab.video_player = None;
+                    tab.video_state = None;
+                    tab.response_headers = std::sync::Arc::from(resp.headers.as_str());
+                    tab.response_body = std::sync::Arc::from(resp.body.as_str());
+                    tab.set_response_display(&resp.body);
+                    if is_active {
+                        app.response_body_content = tab.response_editor_content();
+                    }
+                }
+                tab.refresh_raw_response();
+
+                url
             };
-            if let Some(tab) = app.current_tab_mut() {
-                tab.response_body = std::sync::Arc::from(body.as_str());
+
+            if is_active {
+                app.response_headers_content = text_editor::Content::with_text(&resp.headers);
+                if app
+                    .current_tab()
+                    .is_some_and(|tab| !tab.response_filter.is_empty())
+                {
+                    app.apply_response_filter();
+                }
             }
-            app.response_body_content = text_editor::Content::with_text(&display);
-            iced::Task::none()
-        }
-        Message::WsConnect => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            let url = tab.url.clone();
 
-            // Validate URL
-            if !url.starts_with("ws://") && !url.starts_with("wss://") {
-                app.add_ws_system_message("Error: WebSocket URL must start with ws:// or wss://");
-                return iced::Task::none();
+            if let Some(tab) = app.tab_mut_by_id(tab_id) {
+                let host = extract_domain(&url).unwrap_or_default();
+                tab.response_cookies = resp
+                    .set_cookies
+                    .iter()
+                    .map(|raw| ResponseCookie::parse(raw, &host))
+                    .collect();
             }
 
-            tab.loading = true;
-            tab.ws_connection_id += 1;
-            app.add_ws_system_message(&format!("Connecting to {}...", url));
+            if let Some(domain) = extract_domain(&url) {
+                for raw in &resp.set_cookies {
+                    if let Some(cookie) = parse_set_cookie(raw) {
+                        let jar = app.cookie_jar.entry(domain.clone()).or_default();
+                        // Servers delete cookies by sending them already expired
+                        if cookie.is_expired() {
+                            jar.retain(|c| c.name != cookie.name);
+                        } else if let Some(existing) =
+                            jar.iter_mut().find(|c| c.name == cookie.name)
+                        {
+                            *existing = cookie;
+                        } else {
+                            jar.push(cookie);
+                        }
+                    }
+                }
+            }
 
-            iced::Task::none()
+            app.record_recent_url(url)
         }
-        Message::WsDisconnect => {
+        Message::ResponseFilterChanged(filter) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.ws_connection_id = 0;
-            tab.ws_connection = None;
-            tab.ws_connected = false;
-            tab.loading = false;
-            app.add_ws_system_message("Disconnected");
+            tab.response_filter = filter;
+            app.apply_response_filter();
             iced::Task::none()
         }
-        Message::WsEvent(event) => {
-            let Some(tab) = app.current_tab_mut() else {
-                return iced::Task::none();
-            };
-            match event {
-                WsEvent::Connected(connection) => {
-                    tab.loading = false;
-                    tab.ws_connected = true;
-                    tab.ws_connection = Some(connection);
-                    app.add_ws_system_message("Connected successfully");
-                }
-                WsEvent::Disconnected(reason) => {
-                    tab.ws_connection = None;
-                    tab.loading = false;
-                    tab.ws_connected = false;
-                    app.add_ws_system_message(&format!("Disconnected: {}", reason));
-                }
-                WsEvent::MessageReceived(content) => {
-                    app.add_ws_received_message(&content);
-                }
-                WsEvent::Error(error) => {
-                    tab.loading = false;
-                    app.add_ws_system_message(&format!("Error: {}", error));
-                }
+        Message::ResponseBodyAction(action) => {
+            let retarget = app.find_target != FindTarget::ResponseBody;
+            app.find_target = FindTarget::ResponseBody;
+            match action {
+                // The response stays read-only so find, filters and the diff keep
+                // matching what the server sent; edit a copy via UseResponseAsBody
+                text_editor::Action::Edit(_) => {}
+                _ => app.response_body_content.perform(action),
+            }
+            if retarget && app.find_dialog_open {
+                app.current_match = 0;
+                app.find_next();
             }
             iced::Task::none()
         }
-        Message::WsMessageInputChanged(text) => {
+        Message::UseResponseAsBody => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.ws_input = text;
+            // The editor may only hold the first page of it
+            let body = tab.response_display.to_string();
+            if text_extension_for(&tab.response_content_type) == "json" {
+                tab.content_type = ContentType::Json;
+            }
+            tab.request_body = std::sync::Arc::from(body.as_str());
+            tab.is_body_valid_json = None;
+            tab.active_request_tab = RequestTab::Body;
+            app.request_body_content = text_editor::Content::with_text(&body);
+            if app.find_dialog_open && app.find_target == FindTarget::RequestBody {
+                app.refresh_matches();
+            }
+            iced::Task::done(Message::ShowToast(Toast::success(
+                "Response copied into the request body".to_string(),
+            )))
+        }
+        Message::ResponseHeadersAction(action) => {
+            match action {
+                text_editor::Action::Edit(_) => {}
+                _ => app.response_headers_content.perform(action),
+            }
             iced::Task::none()
         }
-        Message::WsSendMessage => {
-            // 1. Isolate the mutable borrow of 'app' (via 'tab') in a block
-            let message = {
-                let Some(tab) = app.current_tab_mut() else {
-                    return iced::Task::none();
-                };
-
-                let msg = tab.ws_input.clone();
-                if msg.is_empty() {
-                    return iced::Task::none();
-                }
-
-                tab.ws_input.clear();
-
-                // If you need to send the message, do it while you have the tab
-                if let Some(conn) = tab.ws_connection.as_mut() {
-                    let _ = conn.send(msg.clone());
-                }
-
-                msg // Return the msg so we can use it outside the block
-            }; // <--- 'tab' goes out of scope here, 'app' is no longer borrowed
-
-            // 2. Now 'app' is free to be borrowed again here
-            app.add_ws_sent_message(&message);
-
+        Message::ImageZoomIn => {
+            app.image_fit = false;
+            app.image_zoom = (app.image_zoom * IMAGE_ZOOM_STEP).min(IMAGE_ZOOM_MAX);
             iced::Task::none()
         }
-        Message::WsClearMessages => {
+        Message::ImageZoomOut => {
+            app.image_fit = false;
+            app.image_zoom = (app.image_zoom / IMAGE_ZOOM_STEP).max(IMAGE_ZOOM_MIN);
+            iced::Task::none()
+        }
+        Message::ImageFitToggled => {
+            // Leaving fit goes to the natural size
+            app.image_fit = !app.image_fit;
+            if !app.image_fit {
+                app.image_zoom = 1.0;
+            }
+            iced::Task::none()
+        }
+        Message::ImageWheelZoom(delta) => {
+            let can_zoom = app
+                .current_tab()
+                .is_some_and(|tab| tab.image_size.is_some());
+            let y = match delta {
+                iced::mouse::ScrollDelta::Lines { y, .. } => y,
+                iced::mouse::ScrollDelta::Pixels { y, .. } => y,
+            };
+            match y {
+                _ if !can_zoom => iced::Task::none(),
+                y if y > 0.0 => update(app, Message::ImageZoomIn),
+                y if y < 0.0 => update(app, Message::ImageZoomOut),
+                _ => iced::Task::none(),
+            }
+        }
+        Message::HexDumpLoadMore => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-            tab.ws_messages_content = text_editor::Content::new();
-            tab.ws_count_sent = 0;
-            tab.ws_count_received = 0;
-
+            tab.hex_dump_len = (tab.hex_dump_len + HEX_DUMP_PAGE).min(tab.response_bytes.len());
+            tab.hex_dump = format_hex_dump(&tab.response_bytes[..tab.hex_dump_len]);
             iced::Task::none()
         }
-        Message::WsToggleAutoScroll => {
+        shown @ (Message::ResponseShowMore | Message::ResponseShowAll) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-
-            tab.ws_auto_scroll = !tab.ws_auto_scroll;
+            tab.response_shown_len = if matches!(shown, Message::ResponseShowAll) {
+                tab.response_display.len()
+            } else {
+                response_display_cut(
+                    &tab.response_display,
+                    tab.response_shown_len,
+                    tab.response_shown_len + RESPONSE_DISPLAY_PAGE,
+                )
+            };
+            app.response_body_content = tab.response_editor_content();
             iced::Task::none()
         }
-        Message::WsMessageEditorAction(action) => {
+        Message::HtmlLinkClicked(href) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-
-            tab.ws_messages_content.perform(action);
+            // Relative links resolve against the request that produced the page
+            let target = url::Url::parse(&tab.url)
+                .and_then(|base| base.join(&href))
+                .map(|url| url.to_string())
+                .unwrap_or(href);
+            tab.url = target;
+            app.parse_url_query();
             iced::Task::none()
         }
-        Message::WsMessageTypeSelected(msg_type) => {
+        Message::ToggleJsonNode(path) => {
+            if !app.json_tree_expanded.remove(&path) {
+                app.json_tree_expanded.insert(path);
+            }
+            iced::Task::none()
+        }
+        Message::JsonImagesToggled(enabled) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-
-            tab.ws_message_type = msg_type;
+            tab.render_json_images = enabled;
+            tab.response_images = match &tab.response_json {
+                Some(json) if enabled => json_image_handles(json),
+                _ => std::collections::HashMap::new(),
+            };
             iced::Task::none()
         }
-        Message::WsBinaryMessageTypeSelected(msg_type) => {
+        Message::ResponseTabSelected(response_tab) => {
             let Some(tab) = app.current_tab_mut() else {
                 return iced::Task::none();
             };
-
-            tab.ws_binary_message_type = msg_type;
+            tab.active_response_tab = response_tab;
             iced::Task::none()
         }
-        Message::StateLoaded(maybe_session, metadata) => {
-            if let Some(session) = maybe_session {
-                app.json_theme = json_theme_from_str(&session.json_theme);
-                app.app_theme = theme_from_str(&session.app_theme);
-                app.next_tab_id = session.next_tab_id;
-                app.cookie_jar = session.cookie_jar;
-                app.active_tab = session.active_tab.min(metadata.len().saturating_sub(1));
-            }
-
-            // build tab list — all Unloaded first
-            app.tabs = metadata
-                .into_iter()
-                .map(|m| TabLoadState::Unloaded(m))
-                .collect();
+        Message::PrettifyBody => {
+            let Some(tab) = app.current_tab() else {
+                return iced::Task::none();
+            };
+            let body_text = tab.request_body.to_string();
+            // The body type picker has no XML, so go by the Content-Type header or the body itself
+            let is_xml = tab.headers.iter().any(|h| {
+                h.enabled
+                    && h.key.trim().eq_ignore_ascii_case("content-type")
+                    && text_extension_for(&h.value) == "xml"
+            }) || body_text.trim_start().starts_with('<');
 
-            // no saved tabs — create a fresh one
-            if app.tabs.is_empty() {
-                app.tabs
-                    .push(TabLoadState::Loaded(Box::new(TabState::new(0))));
-                app.next_tab_id = 1;
-                app.active_tab = 0;
+            iced::Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || {
+                        if is_xml {
+                            return prettify_xml(&body_text).ok_or("Malformed XML".to_string());
+                        }
+                        reformat_json(&body_text, true)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())?
+                },
+                Message::BodyPrettified,
+            )
+        }
+        Message::MinifyJson => {
+            let Some(tab) = app.current_tab() else {
                 return iced::Task::none();
-            }
+            };
+            let body_text = tab.request_body.to_string();
 
-            // load active tab immediately
-            let active = app.active_tab;
-            if let Some(TabLoadState::Unloaded(meta)) = app.tabs.get(active) {
-                let id = meta.id;
-                app.tabs[active] = TabLoadState::Loading(meta.clone());
-                return iced::Task::perform(SavedState::load(id), move |s| {
-                    Message::TabBodyLoaded { id, saved: s }
-                });
-            }
-            iced::Task::none()
+            iced::Task::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || reformat_json(&body_text, false))
+                        .await
+                        .map_err(|e| e.to_string())?
+                },
+                // Same editor update as prettify, only the text differs
+                Message::BodyPrettified,
+            )
         }
-        Message::SaveComplete => {
-            println!("SaveComplete received");
+        Message::BodyPrettified(Ok(pretty)) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.request_body = std::sync::Arc::from(pretty.as_str());
+            }
+            app.request_body_content
+                .perform(text_editor::Action::SelectAll);
+            app.request_body_content
+                .perform(text_editor::Action::Edit(text_editor::Edit::Paste(
+                    std::sync::Arc::new(pretty),
+                )));
             iced::Task::none()
         }
-        Message::CollectionLoaded(maybe_collection) => {
-            if let Some(collection) = maybe_collection {
-                app.collection = collection;
-            }
+        Message::BodyPrettified(Err(err)) => {
+            log::debug!("Prettify failed: {err}");
             iced::Task::none()
         }
-        Message::ToggleSidebar => {
-            app.sidebar_open = !app.sidebar_open;
+        Message::CopyToClipboard => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if tab.is_response_binary {
+                return iced::Task::none();
+            }
+            let is_binary = tab.is_response_binary;
+            let active_response_tab = tab.active_response_tab.clone();
+            tab.copied = true;
+
+            let text = match active_response_tab {
+                ResponseTab::Body | ResponseTab::Preview | ResponseTab::Tree => app
+                    .current_tab()
+                    .map(|tab| tab.response_display.to_string())
+                    .unwrap_or_default(),
+                ResponseTab::Headers => app.response_headers_content.text(),
+                ResponseTab::Raw => app
+                    .current_tab()
+                    .map(|tab| tab.raw_response(usize::MAX))
+                    .unwrap_or_default(),
+                ResponseTab::LoadTest => app
+                    .current_tab()
+                    .and_then(|tab| tab.load_test.as_ref())
+                    .map(LoadTestRun::summary)
+                    .unwrap_or_default(),
+                ResponseTab::Paginate => app
+                    .current_tab()
+                    .and_then(|tab| tab.pagination_run.as_ref())
+                    .map(PaginationRun::summary)
+                    .unwrap_or_default(),
+                ResponseTab::Diff => app
+                    .current_tab()
+                    .and_then(|tab| tab.response_diff.as_ref())
+                    .map(|lines| {
+                        lines
+                            .iter()
+                            .map(DiffLine::to_unified)
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default(),
+                ResponseTab::Cookies => app
+                    .current_tab()
+                    .map(|tab| {
+                        tab.response_cookies
+                            .iter()
+                            .map(|c| c.raw.as_str())
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    })
+                    .unwrap_or_default(),
+            };
+
+            copy_with_feedback(text)
+        }
+        Message::CopyResponseBody => {
+            let Some(text) = app.response_body_text() else {
+                return iced::Task::none();
+            };
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.copied = true;
+            copy_with_feedback(text)
+        }
+        Message::CopyFullResponse => {
+            let text = app.format_full_response();
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.copied = true;
+            copy_with_feedback(text)
+        }
+        Message::CopyAsCurl => iced::clipboard::write(app.to_curl()),
+        Message::ExportHar => {
+            let Some(tab) = app.current_tab() else {
+                return iced::Task::none();
+            };
+            let file_name = match slugify(&tab.display_name()) {
+                slug if slug.is_empty() => "request.har".to_string(),
+                slug => format!("{slug}.har"),
+            };
+            let tab_id = tab.id;
+            let har = app.to_har();
+            let dialog = app.file_dialog();
+
+            iced::Task::perform(
+                async move {
+                    match dialog
+                        .set_title("Export as HAR")
+                        .set_file_name(&file_name)
+                        .add_filter("HAR", &["har"])
+                        .save_file()
+                        .await
+                    {
+                        Some(file_handle) => {
+                            let json = serde_json::to_string_pretty(&har)
+                                .map_err(|e| format!("Serialization error: {}", e))?;
+                            tokio::fs::write(file_handle.path(), json)
+                                .await
+                                .map_err(|e| format!("Failed to write file: {}", e))?;
+                            Ok::<_, String>(remember_dir(
+                                file_handle.path(),
+                                Message::FileSaved(tab_id, Ok(file_handle.file_name())),
+                            ))
+                        }
+                        None => Ok(Message::NoOp),
+                    }
+                },
+                move |result| match result {
+                    Ok(message) => message,
+                    Err(err) => Message::FileSaved(tab_id, Err(err)),
+                },
+            )
+        }
+        Message::ResetCopied => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.copied = false;
             iced::Task::none()
         }
-        Message::SidebarItemSelected(id) => {
-            app.sidebar_selected_id = Some(id);
+        undo @ (Message::ListUndo | Message::ListRedo) => {
+            let redo = matches!(undo, Message::ListRedo);
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if tab.restore_lists(redo) {
+                app.rebuild_url();
+            }
             iced::Task::none()
         }
-        Message::CollectionFolderAdd(parent_id) => {
-            let id = app.next_collection_id();
-            let folder = CollectionItem::Folder(CollectionFolder {
-                id,
-                name: "New Folder".to_string(),
-                expanded: true,
-                children: Vec::new(),
-            });
-            CrabiPie::collection_insert_into(&mut app.collection.items, parent_id, folder);
-            // immediately enter rename mode
-            app.sidebar_editing_id = Some(id);
-            app.sidebar_editing_name = "New Folder".to_string();
-            app.collection_save_task()
+        Message::QueryParamAdd => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            tab.query_params.push(QueryParam::new());
+            app.rebuild_url();
+            iced::Task::none()
         }
-        Message::CollectionItemToggleExpand(id) => {
-            CrabiPie::collection_toggle_expand(&mut app.collection.items, id);
+        Message::QueryParamRemove(idx) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            if idx < tab.query_params.len() {
+                tab.query_params.remove(idx);
+            }
+            app.rebuild_url();
             iced::Task::none()
         }
-        Message::CollectionRequestOpen(id) => {
-            app.sidebar_selected_id = Some(id);
-            if let Some(req) = CrabiPie::collection_find_request(&app.collection.items, id) {
-                let saved = req.saved_state.clone();
-                let new_tab = TabLoadState::Loaded(Box::new(TabState::from_saved(saved)));
-                app.tabs.push(new_tab);
-                app.active_tab = app.tabs.len() - 1;
-                app.next_tab_id += 1;
+        Message::QueryParamDuplicate(idx) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            // The copy goes right below its source so the two stay side by side
+            if let Some(param) = tab.query_params.get(idx).cloned() {
+                tab.query_params.insert(idx + 1, param);
             }
+            app.rebuild_url();
             iced::Task::none()
         }
-        Message::CollectionItemRename(id) => {
-            // find current name
-            fn find_name(items: &[CollectionItem], id: usize) -> Option<String> {
-                for item in items {
-                    if item.id() == id {
-                        return Some(item.name().to_string());
-                    }
-                    if let CollectionItem::Folder(f) = item {
-                        if let Some(n) = find_name(&f.children, id) {
-                            return Some(n);
-                        }
-                    }
-                }
-                None
+        Message::QueryParamMoveUp(idx) | Message::QueryParamMoveDown(idx) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            // Up swaps with the row above, down with the row below
+            let (a, b) = match message {
+                Message::QueryParamMoveUp(_) => (idx.wrapping_sub(1), idx),
+                _ => (idx, idx + 1),
+            };
+            if a < b && b < tab.query_params.len() {
+                tab.snapshot_lists();
+                tab.query_params.swap(a, b);
+                app.rebuild_url();
             }
-            if let Some(name) = find_name(&app.collection.items, id) {
-                app.sidebar_editing_id = Some(id);
-                app.sidebar_editing_name = name;
+            iced::Task::none()
+        }
+        Message::QueryParamKeyChanged(idx, key) => {
+            app.update_query(idx, |p| p.key = key);
+            app.rebuild_url();
+            iced::Task::none()
+        }
+        Message::QueryParamValueChanged(idx, value) => {
+            app.update_query(idx, |p| p.value = value);
+            app.rebuild_url();
+            iced::Task::none()
+        }
+        Message::QueryParamToggled(idx) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.snapshot_lists();
             }
+            app.update_query(idx, |p| p.enabled = !p.enabled);
+            app.rebuild_url();
             iced::Task::none()
         }
-        Message::CollectionItemRenameInput(text) => {
-            app.sidebar_editing_name = text;
+        Message::CaptureRuleAdd => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.capture_rules.push(CaptureRule::new());
             iced::Task::none()
         }
-        Message::CollectionItemRenameConfirm(id) => {
-            let new_name = app.sidebar_editing_name.trim().to_string();
-            if !new_name.is_empty() {
-                CrabiPie::collection_rename_item(&mut app.collection.items, id, new_name);
+        Message::CaptureRuleRemove(idx) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if idx < tab.capture_rules.len() {
+                tab.capture_rules.remove(idx);
+                // Errors are by index, they'd point at the wrong rules now
+                tab.capture_errors.clear();
             }
-            app.sidebar_editing_id = None;
-            app.sidebar_editing_name = String::new();
-            app.collection_save_task()
+            iced::Task::none()
         }
-        Message::CollectionItemRenameCancel => {
-            app.sidebar_editing_id = None;
-            app.sidebar_editing_name = String::new();
+        Message::CaptureRuleToggled(idx) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(rule) = tab.capture_rules.get_mut(idx) {
+                rule.enabled = !rule.enabled;
+            }
             iced::Task::none()
         }
-        Message::CollectionItemDelete(id) => {
-            CrabiPie::collection_remove_item(&mut app.collection.items, id);
-            app.collection_save_task()
+        Message::CaptureRuleNameChanged(idx, name) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(rule) = tab.capture_rules.get_mut(idx) {
+                rule.name = name;
+            }
+            iced::Task::none()
         }
-        Message::CollectionItemDuplicate(id) => {
-            let new_id = app.next_collection_id();
-            CrabiPie::collection_duplicate_item(&mut app.collection.items, id, new_id);
-            app.collection_save_task()
+        Message::CaptureRulePathChanged(idx, path) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(rule) = tab.capture_rules.get_mut(idx) {
+                rule.path = path;
+            }
+            iced::Task::none()
         }
-        Message::OpenSaveModal => {
-            // Scope the borrow so it ends immediately after we get the title
-            let title = {
-                let Some(tab) = app.current_tab() else {
-                    return iced::Task::none();
-                };
-                tab.title.clone()
-            }; // The borrow of 'app' ends right here because 'tab' is gone
+        Message::CapturedVarRemove(name) => {
+            app.captured_vars.remove(&name);
+            iced::Task::none()
+        }
+        Message::CapturedVarsClear => {
+            app.captured_vars.clear();
+            iced::Task::none()
+        }
+        Message::FormFieldKeyChanged(index, key) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+
+            if let Some(field) = tab.form_data.get_mut(index) {
+                field.key = key;
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldContentTypeChanged(index, content_type) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(field) = tab.form_data.get_mut(index) {
+                field.content_type = (!content_type.is_empty()).then_some(content_type);
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldValueChanged(index, value) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(field) = tab.form_data.get_mut(index) {
+                field.value = value;
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldTypeSelected(idx, form_field_type) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            if let Some(field) = tab.form_data.get_mut(idx) {
+                field.field_type = form_field_type;
+                field.value.clear();
+                field.files.clear();
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldToggled(idx) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            if let Some(field) = tab.form_data.get_mut(idx) {
+                field.enabled = !field.enabled;
+            }
+            iced::Task::none()
+        }
+        Message::JsonThemeChanged(theme) => {
+            app.json_theme = theme;
+            iced::Task::none()
+        }
+        Message::ThemeEditorOpen => {
+            let mut draft = app.json_theme.to_custom();
+            draft.name = std::borrow::Cow::Owned(format!("{} (copy)", draft.name));
+            app.theme_editor = Some(ThemeEditor {
+                hex_input: color_to_hex(draft.color(0)),
+                draft,
+                selected: 0,
+                previous: app.json_theme.clone(),
+            });
+            iced::Task::none()
+        }
+        Message::ThemeEditorNameChanged(name) => {
+            if let Some(editor) = app.theme_editor.as_mut() {
+                editor.draft.name = std::borrow::Cow::Owned(name);
+            }
+            iced::Task::none()
+        }
+        Message::ThemeEditorFieldSelected(field) => {
+            if let Some(editor) = app.theme_editor.as_mut() {
+                editor.selected = field;
+                editor.hex_input = color_to_hex(editor.draft.color(field));
+            }
+            iced::Task::none()
+        }
+        Message::ThemeEditorChannelChanged(channel, value) => {
+            let Some(editor) = app.theme_editor.as_mut() else {
+                return iced::Task::none();
+            };
+            let mut color = editor.draft.color(editor.selected);
+            match channel {
+                0 => color.r = value,
+                1 => color.g = value,
+                _ => color.b = value,
+            }
+            editor.draft.set_color(editor.selected, color);
+            editor.hex_input = color_to_hex(color);
+            // Live preview in the editors while picking
+            app.json_theme = json_highlighter::JsonThemeWrapper::Custom(editor.draft.clone());
+            iced::Task::none()
+        }
+        Message::ThemeEditorHexChanged(value) => {
+            let Some(editor) = app.theme_editor.as_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(color) = color_from_hex(&value) {
+                editor.draft.set_color(editor.selected, color);
+                app.json_theme = json_highlighter::JsonThemeWrapper::Custom(editor.draft.clone());
+            }
+            editor.hex_input = value;
+            iced::Task::none()
+        }
+        Message::ThemeEditorSave => {
+            let Some(editor) = app.theme_editor.take() else {
+                return iced::Task::none();
+            };
+            let mut theme = editor.draft;
+            if theme.name.trim().is_empty() {
+                theme.name = std::borrow::Cow::Borrowed("My theme");
+            }
+            // Saving under an existing name replaces that theme
+            match app
+                .user_json_themes
+                .iter_mut()
+                .find(|t| t.name == theme.name)
+            {
+                Some(existing) => *existing = theme.clone(),
+                None => app.user_json_themes.push(theme.clone()),
+            }
+            app.json_theme = json_highlighter::JsonThemeWrapper::Custom(theme);
+            iced::Task::perform(save_user_json_themes(app.user_json_themes.clone()), |_| {
+                Message::NoOp
+            })
+        }
+        Message::ThemeEditorCancel => {
+            if let Some(editor) = app.theme_editor.take() {
+                app.json_theme = editor.previous;
+            }
+            iced::Task::none()
+        }
+        Message::AppThemeChanged(theme) => {
+            app.app_theme = theme;
+            iced::Task::none()
+        }
+        Message::FileDialogDone { dir, then } => {
+            app.last_dir = Some(dir);
+            update(app, *then)
+        }
+        Message::BodyFileLoad => iced::Task::perform(
+            app.file_dialog()
+                .set_title("Load Request Body")
+                .add_filter("JSON", &["json"])
+                .add_filter("All files", &["*"])
+                .pick_file(),
+            |handle| {
+                let Some(handle) = handle else {
+                    return Message::BodyFileLoadCancelled;
+                };
+                match handle.path().to_str() {
+                    Some(path) => {
+                        remember_dir(handle.path(), Message::BodyFileSelected(path.to_string()))
+                    }
+                    None => Message::BodyFileLoadCancelled,
+                }
+            },
+        ),
+        Message::BodyFileSelected(path) => {
+            app.body_file_error = None;
+            match std::fs::metadata(&path) {
+                Ok(meta) if meta.len() > BODY_FILE_WARN_SIZE => {
+                    app.body_file_pending = Some((path, meta.len()));
+                    iced::Task::none()
+                }
+                Ok(_) => app.load_body_file(path),
+                Err(e) => {
+                    app.body_file_error = Some(format!("Failed to read file: {}", e));
+                    iced::Task::none()
+                }
+            }
+        }
+        Message::BodyFileLoadConfirmed => match app.body_file_pending.take() {
+            Some((path, _)) => app.load_body_file(path),
+            None => iced::Task::none(),
+        },
+        Message::InsecureTlsToggled(enabled) => {
+            app.insecure_tls_pending = enabled && !app.insecure_tls_confirmed;
+            if app.insecure_tls_pending {
+                return iced::Task::none();
+            }
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.insecure_tls = enabled;
+            iced::Task::none()
+        }
+        Message::InsecureTlsConfirmed => {
+            app.insecure_tls_pending = false;
+            app.insecure_tls_confirmed = true;
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.insecure_tls = true;
+            iced::Task::none()
+        }
+        Message::InsecureTlsCancelled => {
+            app.insecure_tls_pending = false;
+            iced::Task::none()
+        }
+        Message::BodyTemplateSelected(name) => {
+            let Some(tab) = app.current_tab() else {
+                return iced::Task::none();
+            };
+            let templates = body_templates(tab);
+            let current = app.template_target_text();
+            // Empty bodies and untouched templates are replaced without asking
+            let untouched = current.trim().is_empty()
+                || current.trim() == BODY_DEFAULT
+                || templates
+                    .iter()
+                    .any(|(_, body)| body.trim() == current.trim());
+            if untouched {
+                if let Some((_, body)) = templates.iter().find(|(n, _)| *n == name) {
+                    app.apply_body_template(body);
+                }
+            } else if let Some(tab) = app.current_tab_mut() {
+                tab.body_template_pending = Some(name);
+            }
+            iced::Task::none()
+        }
+        Message::BodyTemplateConfirmed => {
+            let Some(name) = app
+                .current_tab_mut()
+                .and_then(|tab| tab.body_template_pending.take())
+            else {
+                return iced::Task::none();
+            };
+            // The mode may have changed since, look the name up in the current one
+            let body = app
+                .current_tab()
+                .and_then(|tab| body_templates(tab).iter().find(|(n, _)| *n == name))
+                .map(|(_, body)| *body);
+            if let Some(body) = body {
+                app.apply_body_template(body);
+            }
+            iced::Task::none()
+        }
+        Message::BodyTemplateCancelled => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.body_template_pending = None;
+            }
+            iced::Task::none()
+        }
+        Message::BodyFileLoadCancelled => {
+            app.body_file_pending = None;
+            iced::Task::none()
+        }
+        Message::BodyLoadedFromFile(tab_id, result) => {
+            let is_current = app.current_tab().map(|t| t.id) == Some(tab_id);
+            match result {
+                Ok(body) => {
+                    let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                        return iced::Task::none();
+                    };
+                    // Loaded as-is, prettifying a large file is left to the user
+                    tab.request_body = std::sync::Arc::from(body.as_str());
+                    tab.dirty = true;
+                    tab.unsaved_edits = true;
+                    if is_current {
+                        app.body_file_error = None;
+                        app.request_body_content = text_editor::Content::with_text(&body);
+                        if app.find_dialog_open && app.find_target == FindTarget::RequestBody {
+                            app.refresh_matches();
+                        }
+                    }
+                    iced::Task::none()
+                }
+                Err(err) if is_current => {
+                    app.body_file_error = Some(err);
+                    iced::Task::none()
+                }
+                Err(err) => iced::Task::done(Message::ShowToast(Toast::error(err))),
+            }
+        }
+        Message::SaveRequest => {
+            let Some(tab) = app.current_tab() else {
+                return iced::Task::none();
+            };
+            let tab_id = tab.id;
+            let state = tab.to_saved(&app.json_theme.to_string(), &app.app_theme.to_string());
+            let file_name = match slugify(&tab.display_name()) {
+                slug if slug.is_empty() => "crabipie_state.json".to_string(),
+                slug => format!("{slug}.json"),
+            };
+            let dialog = app.file_dialog();
+
+            iced::Task::perform(
+                async move {
+                    match dialog
+                        .set_title("Save CrabiPie State")
+                        .set_file_name(&file_name)
+                        .save_file()
+                        .await
+                    {
+                        Some(file_handle) => {
+                            // Serialize JSON
+                            let json = serde_json::to_string_pretty(&state)
+                                .map_err(|e| format!("Serialization error: {}", e))?;
+
+                            // Async write
+                            tokio::fs::write(file_handle.path(), json)
+                                .await
+                                .map_err(|e| format!("Failed to write file: {}", e))?;
+
+                            Ok::<_, String>(remember_dir(
+                                file_handle.path(),
+                                Message::RequestFileSaved(tab_id, file_handle.file_name()),
+                            ))
+                        }
+                        None => Ok(Message::NoOp),
+                    }
+                },
+                move |result| match result {
+                    Ok(message) => message,
+                    Err(err) => Message::FileSaved(tab_id, Err(err)),
+                },
+            )
+        }
+        Message::RequestFileSaved(tab_id, file_name) => {
+            if let Some(tab) = app.tab_mut_by_id(tab_id) {
+                tab.unsaved_edits = false;
+            }
+            iced::Task::done(Message::FileSaved(tab_id, Ok(file_name)))
+        }
+        Message::LoadRequest => {
+            let dialog = app.file_dialog();
+            iced::Task::perform(
+                async move {
+                    match dialog.set_title("Open CrabiPie State").pick_file().await {
+                        Some(file_handle) => {
+                            // Async read file
+                            let bytes = tokio::fs::read(file_handle.path())
+                                .await
+                                .map_err(|e| format!("Failed to read file: {}", e))?;
+
+                            // Convert bytes to string
+                            let content = String::from_utf8(bytes)
+                                .map_err(|e| format!("Invalid UTF-8 in file: {}", e))?;
+
+                            // Deserialize JSON into SavedState
+                            let saved_state: SavedState = serde_json::from_str(&content)
+                                .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+                            Ok::<_, String>(remember_dir(
+                                file_handle.path(),
+                                Message::RequestLoaded(saved_state),
+                            ))
+                        }
+                        None => Ok(Message::NoOp),
+                    }
+                },
+                |result| match result {
+                    Ok(message) => message,
+                    Err(err) => Message::RequestLoadFailed(err),
+                },
+            )
+        }
+        Message::RequestLoaded(mut saved_state) => {
+            if app.current_tab().is_some_and(|tab| tab.unsaved_edits) {
+                app.pending_load = Some(saved_state);
+                return iced::Task::none();
+            }
+            // A file keeps the id it was saved with, which another open tab may have now
+            if let Some(tab) = app.current_tab() {
+                saved_state.id = tab.id;
+            }
+            if let Some(slot) = app.tabs.get_mut(app.active_tab) {
+                let mut tab = TabState::from_saved(saved_state);
+                tab.dirty = true;
+                *slot = TabLoadState::Loaded(Box::new(tab));
+            }
+            // The shared editors still hold the replaced request's text
+            if let Some(TabLoadState::Loaded(tab)) = app.tabs.get(app.active_tab) {
+                app.request_body_content = text_editor::Content::with_text(&tab.request_body);
+                app.raw_form_content = text_editor::Content::with_text(&tab.raw_form_content);
+                app.graphql_query_content = text_editor::Content::with_text(&tab.graphql_query);
+                app.graphql_variables_content =
+                    text_editor::Content::with_text(&tab.graphql_variables);
+                app.response_body_content = tab.response_editor_content();
+                app.response_headers_content =
+                    text_editor::Content::with_text(&tab.response_headers);
+            }
+            app.sync_raw_headers();
+            iced::Task::none()
+        }
+        Message::DiscardChangesConfirmed => {
+            let Some(saved_state) = app.pending_load.take() else {
+                return iced::Task::none();
+            };
+            if let Some(tab) = app.current_tab_mut() {
+                tab.unsaved_edits = false;
+            }
+            iced::Task::done(Message::RequestLoaded(saved_state))
+        }
+        Message::DiscardChangesCancelled => {
+            app.pending_load = None;
+            iced::Task::none()
+        }
+        Message::RequestLoadFailed(err) => iced::Task::done(Message::ShowToast(Toast::error(
+            format!("Couldn't open request: {}", err),
+        ))),
+        Message::SettingsOpen => {
+            app.settings_open = true;
+            iced::Task::none()
+        }
+        Message::SettingsClose => {
+            app.settings_open = false;
+            if app.client_rebuild_pending {
+                app.rebuild_http_client();
+            }
+            iced::Task::none()
+        }
+        Message::HttpClientRebuildDue(generation) => {
+            if app.client_rebuild_pending && generation == app.client_rebuild_generation {
+                app.rebuild_http_client();
+            }
+            iced::Task::none()
+        }
+        Message::FollowRedirectsToggled(follow) => {
+            app.http_settings.follow_redirects = follow;
+            app.rebuild_http_client();
+            iced::Task::none()
+        }
+        Message::ShowRedirectChainToggled(show) => {
+            app.http_settings.show_redirect_chain = show;
+            app.rebuild_http_client();
+            iced::Task::none()
+        }
+        Message::MaxRedirectsChanged(value) => {
+            if value.is_empty() {
+                app.http_settings.max_redirects = 0;
+            } else if let Ok(max) = value.parse::<usize>() {
+                app.http_settings.max_redirects = max.min(100);
+            }
+            app.schedule_client_rebuild()
+        }
+        Message::RetryCountChanged(value) => {
+            if value.is_empty() {
+                app.http_settings.retry_count = 0;
+            } else if let Ok(count) = value.parse::<u32>() {
+                app.http_settings.retry_count = count.min(10);
+            }
+            iced::Task::none()
+        }
+        Message::RetryBackoffChanged(value) => {
+            if value.is_empty() {
+                app.http_settings.retry_backoff_ms = 0;
+            } else if let Ok(ms) = value.parse::<u64>() {
+                app.http_settings.retry_backoff_ms = ms.min(60_000);
+            }
+            iced::Task::none()
+        }
+        Message::RetryNonIdempotentToggled(allow) => {
+            app.http_settings.retry_non_idempotent = allow;
+            iced::Task::none()
+        }
+        Message::ResponseCacheToggled(enabled) => {
+            app.http_settings.cache_responses = enabled;
+            if !enabled {
+                app.response_cache.clear();
+            }
+            iced::Task::none()
+        }
+        Message::ResponseCacheCleared => {
+            app.response_cache.clear();
+            iced::Task::none()
+        }
+        Message::ProxyUrlChanged(value) => {
+            app.http_settings.proxy_url = value;
+            app.schedule_client_rebuild()
+        }
+        Message::ProxyUsernameChanged(value) => {
+            app.http_settings.proxy_username = value;
+            app.schedule_client_rebuild()
+        }
+        Message::ProxyPasswordChanged(value) => {
+            app.http_settings.proxy_password = value;
+            app.schedule_client_rebuild()
+        }
+        Message::ClientCertSelect => iced::Task::perform(
+            app.file_dialog()
+                .set_title("Choose Client Certificate")
+                .add_filter("Certificate", &["pem", "crt", "cer", "p12", "pfx"])
+                .add_filter("All files", &["*"])
+                .pick_file(),
+            |handle| {
+                let Some(handle) = handle else {
+                    return Message::NoOp;
+                };
+                match handle.path().to_str() {
+                    Some(path) => {
+                        remember_dir(handle.path(), Message::ClientCertChosen(path.to_string()))
+                    }
+                    None => Message::NoOp,
+                }
+            },
+        ),
+        Message::ClientCertChosen(path) => {
+            app.http_settings.client_cert_path = path;
+            app.http_settings.client_key_path.clear();
+            app.http_settings.client_cert_password.clear();
+            app.rebuild_http_client();
+            iced::Task::none()
+        }
+        Message::ClientKeySelect => iced::Task::perform(
+            app.file_dialog()
+                .set_title("Choose Client Key")
+                .add_filter("Private key", &["pem", "key"])
+                .add_filter("All files", &["*"])
+                .pick_file(),
+            |handle| {
+                let Some(handle) = handle else {
+                    return Message::NoOp;
+                };
+                match handle.path().to_str() {
+                    Some(path) => {
+                        remember_dir(handle.path(), Message::ClientKeyChosen(path.to_string()))
+                    }
+                    None => Message::NoOp,
+                }
+            },
+        ),
+        Message::ClientKeyChosen(path) => {
+            app.http_settings.client_key_path = path;
+            app.rebuild_http_client();
+            iced::Task::none()
+        }
+        Message::ClientCertPasswordChanged(value) => {
+            app.http_settings.client_cert_password = value;
+            app.schedule_client_rebuild()
+        }
+        Message::PinnedHeaderAdd => {
+            app.http_settings.pinned_headers.push(RequestHeaders::new());
+            iced::Task::none()
+        }
+        Message::PinnedHeaderRemove(idx) => {
+            if idx < app.http_settings.pinned_headers.len() {
+                app.http_settings.pinned_headers.remove(idx);
+            }
+            iced::Task::none()
+        }
+        Message::PinnedHeaderKeyChanged(idx, key) => {
+            if let Some(header) = app.http_settings.pinned_headers.get_mut(idx) {
+                header.key = key;
+            }
+            iced::Task::none()
+        }
+        Message::PinnedHeaderValueChanged(idx, value) => {
+            if let Some(header) = app.http_settings.pinned_headers.get_mut(idx) {
+                header.value = value;
+            }
+            iced::Task::none()
+        }
+        Message::PinnedHeaderToggled(idx) => {
+            if let Some(header) = app.http_settings.pinned_headers.get_mut(idx) {
+                header.enabled = !header.enabled;
+            }
+            iced::Task::none()
+        }
+        Message::ClientCertCleared => {
+            app.http_settings.client_cert_path.clear();
+            app.http_settings.client_key_path.clear();
+            app.http_settings.client_cert_password.clear();
+            app.rebuild_http_client();
+            iced::Task::none()
+        }
+        Message::CurlImportOpen => {
+            app.curl_import_open = true;
+            app.curl_import_error = None;
+            app.curl_import_content = text_editor::Content::new();
+            iced::Task::none()
+        }
+        Message::CurlImportClose => {
+            app.curl_import_open = false;
+            app.curl_import_error = None;
+            iced::Task::none()
+        }
+        Message::CurlImportAction(action) => {
+            app.curl_import_content.perform(action);
+            iced::Task::none()
+        }
+        Message::CurlImportConfirm => match parse_curl(&app.curl_import_content.text()) {
+            Ok(mut saved_state) => {
+                // Keep the tab identity, only the request itself is replaced
+                if let Some(tab) = app.current_tab() {
+                    saved_state.id = tab.id;
+                    saved_state.title = tab.title.clone();
+                }
+                app.curl_import_open = false;
+                app.curl_import_error = None;
+                iced::Task::done(Message::RequestLoaded(saved_state))
+            }
+            Err(err) => {
+                app.curl_import_error = Some(err.clone());
+                iced::Task::done(Message::RequestLoadFailed(err))
+            }
+        },
+        Message::BinaryBodyFileSelect => iced::Task::perform(
+            app.file_dialog()
+                .set_title("Choose Request Body File")
+                .pick_file(),
+            |handle| {
+                let Some(handle) = handle else {
+                    return Message::NoOp;
+                };
+                match handle.path().to_str() {
+                    Some(path) => remember_dir(
+                        handle.path(),
+                        Message::BinaryBodyFileSelected(path.to_string()),
+                    ),
+                    None => Message::NoOp,
+                }
+            },
+        ),
+        Message::BinaryBodyFileSelected(path) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            match std::fs::metadata(&path) {
+                Ok(meta) => {
+                    tab.binary_body_size = Some(meta.len());
+                    tab.binary_body_error = None;
+                }
+                Err(e) => {
+                    tab.binary_body_size = None;
+                    tab.binary_body_error = Some(format!("Can't read {}: {}", path, e));
+                }
+            }
+            tab.binary_body_path = path;
+            iced::Task::none()
+        }
+        Message::GzipBodyToggled(enabled) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.gzip_body = enabled;
+            }
+            iced::Task::none()
+        }
+        Message::DeleteBodyToggled(enabled) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.delete_body = enabled;
+            }
+            iced::Task::none()
+        }
+        Message::BinaryContentTypeChanged(content_type) => {
+            if let Some(tab) = app.current_tab_mut() {
+                tab.binary_content_type = content_type;
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldFileSelect(idx) => {
+            let dialog = app.file_dialog();
+            let future = async move {
+                let files = dialog.pick_files().await;
+
+                // Extract file paths as Strings
+                let paths: Vec<String> = files
+                    .map(|handles| {
+                        handles
+                            .into_iter()
+                            .filter_map(|handle| handle.path().to_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                let first = paths.first().map(std::path::PathBuf::from);
+                let message = Message::FormFieldFilesSelected(idx, paths);
+                match first {
+                    Some(first) => remember_dir(&first, message),
+                    None => message,
+                }
+            };
+
+            iced::Task::perform(future, std::convert::identity)
+        }
+        Message::FormFieldFilesSelected(index, files) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(field) = tab.form_data.get_mut(index) {
+                tab.form_file_sizes.extend(file_sizes(&files));
+                field.files = files;
+            }
+            // Recheck a shown list, so a file picked to fix it drops off
+            if !tab.form_data_errors.is_empty() {
+                tab.form_data_errors = tab.validate_form_data().err().unwrap_or_default();
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldFileRemove(index, file_index) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if tab
+                .form_data
+                .get(index)
+                .is_some_and(|field| file_index < field.files.len())
+            {
+                tab.snapshot_lists();
+                tab.form_data[index].files.remove(file_index);
+            }
+            if !tab.form_data_errors.is_empty() {
+                tab.form_data_errors = tab.validate_form_data().err().unwrap_or_default();
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldRemove(index) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            if index < tab.form_data.len() {
+                tab.form_data.remove(index);
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldDuplicate(index) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            if let Some(field) = tab.form_data.get(index).cloned() {
+                tab.form_data.insert(index + 1, field);
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldMoveUp(idx) | Message::FormFieldMoveDown(idx) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            let (a, b) = match message {
+                Message::FormFieldMoveUp(_) => (idx.wrapping_sub(1), idx),
+                _ => (idx, idx + 1),
+            };
+            if a < b && b < tab.form_data.len() {
+                tab.snapshot_lists();
+                tab.form_data.swap(a, b);
+            }
+            iced::Task::none()
+        }
+        Message::FormFieldAdd => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.snapshot_lists();
+            tab.form_data.push(FormField::new());
+            iced::Task::none()
+        }
+        Message::ViewRawForm => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if matches!(
+                tab.content_type,
+                ContentType::FormData | ContentType::XWWWFormUrlEncoded
+            ) {
+                let raw = TabState::form_data_to_raw(&tab.form_data);
+                tab.raw_form_content = std::sync::Arc::from(raw.as_str());
+                tab.form_view_type = FormViewType::Raw;
+                app.raw_form_content = text_editor::Content::with_text(&raw);
+            }
+            iced::Task::none()
+        }
+        Message::ViewFormattedForm => {
+            let raw = app.raw_form_content.text();
+            if let Some(tab) = app.current_tab_mut() {
+                if matches!(
+                    tab.content_type,
+                    ContentType::FormData | ContentType::XWWWFormUrlEncoded
+                ) {
+                    tab.raw_form_content = std::sync::Arc::from(raw.as_str());
+                    tab.form_data = TabState::raw_to_form_data(&raw);
+                    tab.form_view_type = FormViewType::Formatted;
+                }
+            }
+            iced::Task::none()
+        }
+        Message::FormRawAction(action) => {
+            app.raw_form_content.perform(action);
+            let raw_text = app.raw_form_content.text();
+            if let Some(tab) = app.current_tab_mut() {
+                tab.raw_form_content = std::sync::Arc::from(raw_text.as_str());
+            };
+            iced::Task::none()
+        }
+        Message::ToggleFindDialog => {
+            app.find_dialog_open = !app.find_dialog_open;
+            if app.find_dialog_open && !app.find_text.is_empty() {
+                app.current_match = 0;
+                app.find_next();
+            }
+            iced::widget::operation::focus("find_input")
+        }
+        Message::ToggleFindReplaceDialog => {
+            app.find_replace_mode = !app.find_replace_mode;
+            iced::widget::operation::focus(if app.find_replace_mode {
+                "replace_input"
+            } else {
+                "find_input"
+            })
+        }
+        Message::CloseFindDialog => {
+            app.find_dialog_open = false;
+
+            // Clear search highlights
+            app.clear_search();
+
+            iced::Task::none()
+        }
+        Message::FindTextChanged(find_text) => {
+            app.clear_search();
+            app.find_text = find_text;
+
+            // Automatically find first match when text changes
+            if !app.find_text.is_empty() {
+                app.find_next();
+            }
+
+            iced::Task::none()
+        }
+        Message::ReplaceTextChanged(replace_text) => {
+            app.replace_text = replace_text;
+            iced::Task::none()
+        }
+        Message::ToggleCaseSensitive => {
+            app.case_sensitive = !app.case_sensitive;
+
+            // Re-search with new settings
+            if !app.find_text.is_empty() {
+                app.current_match = 0;
+                app.find_next();
+            }
+
+            iced::Task::none()
+        }
+        Message::ToggleWholeWord => {
+            app.whole_word = !app.whole_word;
+
+            // Re-search with new settings
+            if !app.find_text.is_empty() {
+                app.current_match = 0;
+                app.find_next();
+            }
+
+            iced::Task::none()
+        }
+        Message::FindNext => {
+            app.find_next();
+
+            iced::Task::none()
+        }
+        Message::FindPrevious => {
+            app.find_previous();
+            iced::Task::none()
+        }
+        Message::Replace => {
+            app.replace_current();
+            iced::Task::none()
+        }
+        Message::ReplaceAll => {
+            app.replace_all();
+            iced::Task::none()
+        }
+        Message::EventOccurred(event) => {
+            use iced::keyboard::{Event as KeyEvent, Key, Modifiers};
+            if let Event::Window(window_event) = &event {
+                match window_event {
+                    iced::window::Event::CloseRequested => {
+                        // Save first, then close
+                        return app.save_task().chain(iced::exit());
+                    }
+                    iced::window::Event::Resized(size) => {
+                        // Stack the panes once the window gets too narrow for both
+                        if app.window_size.width >= STACKED_LAYOUT_WIDTH
+                            && size.width < STACKED_LAYOUT_WIDTH
+                        {
+                            app.layout = Layout::Vertical;
+                        }
+                        app.window_size = *size;
+                        return iced::Task::none();
+                    }
+                    iced::window::Event::Moved(position) => {
+                        app.window_position = Some(*position);
+                        return iced::Task::none();
+                    }
+                    iced::window::Event::Opened { .. } => {
+                        return iced::window::latest().and_then(|id| {
+                            iced::window::monitor_size(id)
+                                .map(move |monitor| Message::WindowMonitorChecked(id, monitor))
+                        });
+                    }
+                    iced::window::Event::Unfocused => {
+                        log::trace!("window was unfocused");
+                        return iced::Task::none();
+                    }
+                    _ => {}
+                }
+            }
+            match &event {
+                Event::Mouse(iced::mouse::Event::CursorMoved { position }) => {
+                    app.cursor_position = *position;
+                    if let Some((start, ratio)) = app.pane_drag {
+                        app.pane_ratio = app.dragged_pane_ratio(start, ratio, *position);
+                        return iced::Task::none();
+                    }
+                }
+                Event::Mouse(iced::mouse::Event::ButtonReleased(iced::mouse::Button::Left))
+                    if app.pane_drag.is_some() =>
+                {
+                    app.pane_drag = None;
+                    return iced::Task::none();
+                }
+                _ => {}
+            }
+            if let Event::Mouse(iced::mouse::Event::ButtonPressed(
+                iced::mouse::Button::Left | iced::mouse::Button::Right,
+            )) = &event
+            {
+                if let Some(editing_id) = app.sidebar_editing_id {
+                    return iced::widget::operation::is_focused("sidebar_rename").then(
+                        move |focused| {
+                            if !focused {
+                                iced::Task::done(Message::CollectionItemRenameConfirm(editing_id))
+                            } else {
+                                iced::Task::none()
+                            }
+                        },
+                    );
+                }
+            }
+            if let Event::Keyboard(key_event) = event {
+                let Some(tab) = app.current_tab() else {
+                    return iced::Task::none();
+                };
+                // Dialogs and the rename field keep their own keys, the shortcuts
+                // that open pickers or rewrite the body stay out of their way
+                let dialog_open = app.save_modal_open
+                    || app.pending_load.is_some()
+                    || app.settings_open
+                    || app.curl_import_open
+                    || app.theme_editor.is_some()
+                    || app.sidebar_editing_id.is_some();
+                // Ctrl+Z undoes list edits only where the lists are on screen
+                let lists_active = tab.active_request_tab == RequestTab::Query
+                    || (tab.active_request_tab == RequestTab::Body
+                        && tab.sends_body()
+                        && tab.form_view_type == FormViewType::Formatted
+                        && matches!(
+                            tab.content_type,
+                            ContentType::FormData | ContentType::XWWWFormUrlEncoded
+                        ));
+                match key_event {
+                    // `command` is Cmd on macOS and Ctrl everywhere else
+                    KeyEvent::KeyPressed { key, modifiers, .. } if modifiers.command() => {
+                        if let Key::Character(c) = &key {
+                            let c = c.to_ascii_lowercase();
+                            if modifiers.shift() {
+                                // Only while the request body editor was the last one used
+                                let body_active = app.find_target == FindTarget::RequestBody
+                                    && tab.active_request_tab == RequestTab::Body;
+                                if c == "f" && body_active && !dialog_open {
+                                    return iced::Task::done(Message::PrettifyBody);
+                                }
+                                if c == "z" && lists_active && !dialog_open {
+                                    return iced::Task::done(Message::ListRedo);
+                                }
+                                // Shift turns the brackets into braces on most layouts
+                                let forward = match c.as_str() {
+                                    "[" | "{" => Some(false),
+                                    "]" | "}" => Some(true),
+                                    _ => None,
+                                };
+                                if let Some(forward) = forward
+                                    && !dialog_open
+                                {
+                                    return iced::Task::done(Message::RequestTabSelected(
+                                        tab.active_request_tab.cycled(forward),
+                                    ));
+                                }
+                                return iced::Task::none();
+                            }
+                            if c == "z" && lists_active && !dialog_open {
+                                return iced::Task::done(Message::ListUndo);
+                            }
+                            // Ctrl+digit types nothing, so this can't steal a digit from a field
+                            if let Some(request_tab) = c
+                                .parse::<usize>()
+                                .ok()
+                                .and_then(|n| n.checked_sub(1))
+                                .and_then(|i| RequestTab::ALL.get(i))
+                                && !dialog_open
+                            {
+                                return iced::Task::done(Message::RequestTabSelected(*request_tab));
+                            }
+                            if (c == "s" || c == "o") && dialog_open {
+                                return iced::Task::none();
+                            }
+                            if c == "s" {
+                                return iced::Task::done(Message::SaveRequest);
+                            } else if c == "o" {
+                                return iced::Task::done(Message::LoadRequest);
+                            }
+                            if c.as_str() == "l" {
+                                return iced::widget::operation::focus(tab.url_id.clone()).chain(
+                                    iced::widget::operation::select_all(tab.url_id.clone()),
+                                );
+                            } else if c.as_str() == "f" {
+                                return iced::Task::done(Message::ToggleFindDialog);
+                            } else if c.as_str() == "h" {
+                                return iced::Task::done(Message::ToggleFindReplaceDialog);
+                            }
+                        }
+                        if matches!(key, Key::Named(iced::keyboard::key::Named::Enter)) {
+                            log::trace!("Key event: ctrl + Enter");
+                            return iced::Task::done(Message::SendRequest);
+                        }
+                    }
+                    KeyEvent::KeyPressed {
+                        key: Key::Named(iced::keyboard::key::Named::Enter),
+                        modifiers,
+                        ..
+                    } if modifiers.shift() => {
+                        return iced::widget::operation::is_focused("find_input").then(|f| {
+                            if f {
+                                iced::Task::done(Message::FindPrevious)
+                            } else {
+                                iced::Task::none()
+                            }
+                        });
+                    }
+                    KeyEvent::KeyPressed {
+                        key: Key::Named(iced::keyboard::key::Named::Enter),
+                        ..
+                    } => {
+                        let tasks = vec![
+                            iced::widget::operation::is_focused("find_input").then(|f| {
+                                if f {
+                                    iced::Task::done(Message::FindNext)
+                                } else {
+                                    iced::Task::none()
+                                }
+                            }),
+                            iced::widget::operation::is_focused("replace_input").then(|f| {
+                                if f {
+                                    iced::Task::done(Message::Replace)
+                                } else {
+                                    iced::Task::none()
+                                }
+                            }),
+                            iced::widget::operation::is_focused(tab.url_id.clone()).then(|f| {
+                                if f {
+                                    iced::Task::done(Message::UrlSubmitted)
+                                } else {
+                                    iced::Task::none()
+                                }
+                            }),
+                        ];
+                        return iced::Task::batch(tasks);
+                    }
+                    KeyEvent::KeyPressed {
+                        key: Key::Named(iced::keyboard::key::Named::Tab),
+                        modifiers,
+                        ..
+                    } if modifiers.shift() => {
+                        log::trace!("Key event: shift + tab");
+                        return iced::widget::operation::focus_previous();
+                    }
+                    KeyEvent::KeyPressed {
+                        key: Key::Named(iced::keyboard::key::Named::Tab),
+                        ..
+                    } => {
+                        log::trace!("Key event: Tab");
+                        return iced::widget::operation::focus_next();
+                    }
+                    KeyEvent::KeyPressed {
+                        key:
+                            Key::Named(
+                                named @ (iced::keyboard::key::Named::ArrowDown
+                                | iced::keyboard::key::Named::ArrowUp),
+                            ),
+                        ..
+                    } if app.url_suggest_open => {
+                        let down = named == iced::keyboard::key::Named::ArrowDown;
+                        return iced::widget::operation::is_focused(tab.url_id.clone()).then(
+                            move |f| {
+                                if f {
+                                    iced::Task::done(Message::UrlSuggestionMoved(down))
+                                } else {
+                                    iced::Task::none()
+                                }
+                            },
+                        );
+                    }
+                    KeyEvent::KeyPressed {
+                        key: Key::Named(iced::keyboard::key::Named::Escape),
+                        ..
+                    } => {
+                        if app.url_suggest_open {
+                            app.url_suggest_open = false;
+                            app.url_suggest_selected = None;
+                            return iced::Task::none();
+                        }
+                        if app.sidebar_editing_id.is_some() {
+                            app.sidebar_editing_id = None;
+                            app.sidebar_editing_name = String::new();
+                            return iced::Task::none();
+                        }
+                        if app.save_modal_open {
+                            app.save_modal_open = false;
+                            app.save_modal_name = String::new();
+                            app.save_modal_folder_id = None;
+                            return iced::Task::none();
+                        }
+                        if app.curl_import_open {
+                            app.curl_import_open = false;
+                            app.curl_import_error = None;
+                            return iced::Task::none();
+                        }
+                        if app.settings_open {
+                            return update(app, Message::SettingsClose);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            iced::Task::none()
+        }
+        Message::WindowMonitorChecked(id, monitor) => {
+            // No monitor under the window means the saved spot is on a screen that's gone
+            if monitor.is_none() && app.window_position.is_some() {
+                let position = iced::Point::new(WINDOW_MIN_VISIBLE, WINDOW_MIN_VISIBLE);
+                app.window_position = Some(position);
+                return iced::window::move_to(id, position);
+            }
+            iced::Task::none()
+        }
+        Message::RequestTabSelected(request_tab) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+
+            tab.active_request_tab = request_tab;
+            iced::Task::none()
+        }
+        Message::ApiKeyPositionChanged(position) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.api_key_position = position;
+            iced::Task::none()
+        }
+        Message::ApiKeyNameChanged(key) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.api_key_name = key;
+            iced::Task::none()
+        }
+        Message::ApiKeyChanged(key) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.api_key = key;
+            iced::Task::none()
+        }
+        Message::HeaderAdd => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.headers.push(RequestHeaders::new());
+            iced::Task::none()
+        }
+        Message::HeaderRemove(id) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if id < tab.headers.len() {
+                tab.headers.remove(id);
+            }
+            app.header_suggest_idx = None;
+            app.header_value_suggest_idx = None;
+            iced::Task::none()
+        }
+        Message::HeaderKeyChanged(id, key) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+
+            if let Some(header) = tab.headers.get_mut(id) {
+                header.key = key;
+            }
+            app.header_suggest_idx = Some(id);
+            app.header_value_suggest_idx = None;
+            iced::Task::none()
+        }
+        Message::HeaderSuggestionPicked(id, name) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(header) = tab.headers.get_mut(id) {
+                header.key = name.to_string();
+            }
+            app.header_suggest_idx = None;
+            // The value comes next, so its hints show right away
+            app.header_value_suggest_idx = Some(id);
+            iced::Task::none()
+        }
+        Message::HeaderValueSuggestionPicked(id, value) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(header) = tab.headers.get_mut(id) {
+                header.value = value.to_string();
+                header.missing_colon = false;
+            }
+            app.header_value_suggest_idx = None;
+            iced::Task::none()
+        }
+        Message::HeadersViewRaw => {
+            app.headers_raw_mode = true;
+            app.header_suggest_idx = None;
+            app.header_value_suggest_idx = None;
+            app.sync_raw_headers();
+            iced::Task::none()
+        }
+        Message::HeadersViewFormatted => {
+            app.headers_raw_mode = false;
+            iced::Task::none()
+        }
+        Message::HeadersRawAction(action) => {
+            let is_edit = matches!(action, text_editor::Action::Edit(_));
+            app.raw_headers_content.perform(action);
+            if is_edit {
+                let raw = app.raw_headers_content.text();
+                app.header_errors = validate_headers(&raw);
+                if let Some(tab) = app.current_tab_mut() {
+                    tab.headers = TabState::raw_to_headers(&raw);
+                }
+            }
+            iced::Task::none()
+        }
+        Message::HeaderValueChanged(id, value) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            if let Some(header) = tab.headers.get_mut(id) {
+                header.value = value;
+                header.missing_colon = false;
+            }
+            app.header_suggest_idx = None;
+            app.header_value_suggest_idx = Some(id);
+            iced::Task::none()
+        }
+        Message::HeaderToggled(id) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            match tab.headers.get_mut(id) {
+                Some(header) if !header.missing_colon => header.enabled = !header.enabled,
+                _ => return iced::Task::none(),
+            }
+            // Only that line of the raw text changes, the rest is kept as typed
+            if app.headers_raw_mode {
+                let raw = toggle_raw_header_line(&app.raw_headers_content.text(), id);
+                app.header_errors = validate_headers(&raw);
+                app.raw_headers_content = text_editor::Content::with_text(&raw);
+            }
+            iced::Task::none()
+        }
+        Message::StreamChunk(tab_id, chunk) => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            tab.stream_buffer.push_str(&chunk);
+
+            iced::Task::none()
+        }
+        Message::SseEvent(tab_id, event) => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            tab.sse_event_count += 1;
+            let mut entry = format!("[{}]", chrono::Local::now().format("%H:%M:%S"));
+            if !event.event.is_empty() {
+                entry.push_str(&format!(" event: {}", event.event));
+            }
+            if let Some(id) = &event.id {
+                entry.push_str(&format!(" id: {}", id));
+            }
+            entry.push('\n');
+            entry.push_str(&event.data);
+            entry.push_str("\n\n");
+            tab.stream_buffer.push_str(&entry);
+            iced::Task::none()
+        }
+        Message::StopStream => {
+            // The stream notices the flag within a moment and finishes like a normal body
+            if let Some(tab) = app.current_tab() {
+                tab.cancel_flag.store(true, Ordering::Relaxed);
+            }
+            iced::Task::none()
+        }
+        Message::DownloadProgress {
+            tab_id,
+            received,
+            total,
+        } => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            // Late progress after a cancel should not bring the overlay back
+            if tab.loading {
+                tab.download_progress = Some((received, total));
+            }
+            iced::Task::none()
+        }
+        Message::StreamToFileProgress {
+            tab_id,
+            received,
+            total,
+        } => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            if tab.loading {
+                tab.download_progress = Some((received, total));
+            }
+            iced::Task::none()
+        }
+        Message::RetryAttempt {
+            tab_id,
+            attempt,
+            max,
+        } => {
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            if tab.loading {
+                tab.retry_attempt = Some((attempt, max));
+            }
+            iced::Task::none()
+        }
+        Message::StreamDone {
+            tab_id,
+            wire_size,
+            total,
+        } => {
+            let is_active = app.is_active_tab(tab_id);
+            let cache_key = app.tab_mut_by_id(tab_id).and_then(|t| t.cache_key.take());
+            let cached = cache_key
+                .as_ref()
+                .and_then(|key| app.response_cache.get(key))
+                .cloned();
+            let Some(tab) = app.tab_mut_by_id(tab_id) else {
+                return iced::Task::none();
+            };
+            tab.dirty = true;
+            tab.response_wire_size = wire_size;
+            tab.response_time = Some(total);
+            if let Some(timing) = tab.response_timing.as_mut() {
+                timing.download = Some(total.saturating_sub(timing.ttfb));
+            }
+
+            tab.is_streaming = false;
+            tab.loading = false;
+            tab.loading_started = None;
+            let mut body = std::mem::take(&mut tab.stream_buffer);
+            // A 304 has no body, the cached one it confirmed stands in for it
+            tab.response_from_cache = false;
+            let mut new_cache_entry = None;
+            if tab.response_status.starts_with("304") {
+                if let Some(cached) = cached {
+                    if tab.response_content_type.is_empty() {
+                        tab.response_content_type = cached.content_type.clone();
+                    }
+                    body = cached.body;
+                    tab.response_from_cache = true;
+                }
+            } else if tab.response_status.starts_with('2') {
+                new_cache_entry = CachedResponse::from_response(
+                    &tab.response_header_list,
+                    &tab.response_content_type,
+                    &body,
+                );
+            }
+            tab.response_body = std::sync::Arc::from(body.as_str());
+            if text_extension_for(&tab.response_content_type) == "html" {
+                tab.response_html = parse_html_preview(&body);
+            }
+            let is_xml = text_extension_for(&tab.response_content_type) == "xml";
+            tab.response_json = if is_xml {
+                None
+            } else {
+                serde_json::from_str::<serde_json::Value>(&body)
+                    .ok()
+                    .map(std::sync::Arc::new)
+            };
+            tab.response_images = match &tab.response_json {
+                Some(json) if tab.render_json_images => json_image_handles(json),
+                _ => std::collections::HashMap::new(),
+            };
+            // A filter typed while the body streamed in applies to the whole of it
+            tab.refresh_response_display();
+            tab.refresh_raw_response();
+            if is_active {
+                app.response_body_content = tab.response_editor_content();
+                // Only the root starts open, a large document would be slow to lay out
+                app.json_tree_expanded.clear();
+                app.json_tree_expanded.insert("$".to_string());
+            }
+            if let Some(tab) = app.tab_mut_by_id(tab_id) {
+                tab.response_diff = tab
+                    .previous_response_body
+                    .as_deref()
+                    .map(|previous| diff_response_lines(previous, &body));
+            }
+            if let (Some(key), Some(entry)) = (cache_key, new_cache_entry) {
+                app.store_cached_response(key, entry);
+            }
+            // Text bodies arrive in chunks, so captures run once the whole body is in
+            app.apply_captures(tab_id, &body);
+            iced::Task::none()
+        }
+        Message::WsConnect => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            let url = tab.url.clone();
+
+            // Validate URL
+            if !url.starts_with("ws://") && !url.starts_with("wss://") {
+                app.add_ws_system_message("Error: WebSocket URL must start with ws:// or wss://");
+                return iced::Task::none();
+            }
+
+            tab.loading = true;
+            tab.ws_connection_id += 1;
+            app.add_ws_system_message(&format!("Connecting to {}...", url));
+
+            iced::Task::none()
+        }
+        Message::WsReconnect => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            // A new connection id replaces the subscription, which drops the old socket
+            tab.ws_connection = None;
+            tab.ws_connected = false;
+            app.add_ws_system_message("Reconnecting");
+            update(app, Message::WsConnect)
+        }
+        Message::WsDisconnect => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.ws_connection_id = 0;
+            tab.ws_connection = None;
+            tab.ws_connected = false;
+            tab.loading = false;
+            app.add_ws_system_message("Disconnected");
+            iced::Task::none()
+        }
+        Message::WsEvent(event) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            match event {
+                WsEvent::Connected(connection) => {
+                    tab.loading = false;
+                    tab.ws_connected = true;
+                    tab.ws_connection = Some(connection);
+                    app.add_ws_system_message("Connected successfully");
+                }
+                WsEvent::Disconnected(reason) => {
+                    tab.ws_connection = None;
+                    tab.loading = false;
+                    tab.ws_connected = false;
+                    app.add_ws_system_message(&format!("Disconnected: {}", reason));
+                }
+                WsEvent::MessageReceived(content) => {
+                    app.add_ws_received_message(&content);
+                }
+                WsEvent::Error(error) => {
+                    tab.loading = false;
+                    app.add_ws_system_message(&format!("Error: {}", error));
+                }
+            }
+            iced::Task::none()
+        }
+        Message::WsMessageInputChanged(text) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.ws_input = text;
+            iced::Task::none()
+        }
+        Message::WsSendMessage => {
+            // 1. Isolate the mutable borrow of 'app' (via 'tab') in a block
+            let message = {
+                let Some(tab) = app.current_tab_mut() else {
+                    return iced::Task::none();
+                };
+
+                let msg = tab.ws_input.clone();
+                if msg.is_empty() {
+                    return iced::Task::none();
+                }
+
+                tab.ws_input.clear();
+
+                // If you need to send the message, do it while you have the tab
+                if let Some(conn) = tab.ws_connection.as_mut() {
+                    let _ = conn.send(msg.clone());
+                }
+
+                msg // Return the msg so we can use it outside the block
+            }; // <--- 'tab' goes out of scope here, 'app' is no longer borrowed
+
+            // 2. Now 'app' is free to be borrowed again here
+            app.add_ws_sent_message(&message);
+
+            iced::Task::none()
+        }
+        Message::WsClearMessages => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+            tab.ws_messages_content = text_editor::Content::new();
+            tab.ws_count_sent = 0;
+            tab.ws_count_received = 0;
+
+            iced::Task::none()
+        }
+        Message::WsToggleAutoScroll => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+
+            tab.ws_auto_scroll = !tab.ws_auto_scroll;
+            iced::Task::none()
+        }
+        Message::WsMessageEditorAction(action) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+
+            tab.ws_messages_content.perform(action);
+            iced::Task::none()
+        }
+        Message::WsMessageTypeSelected(msg_type) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+
+            tab.ws_message_type = msg_type;
+            iced::Task::none()
+        }
+        Message::WsBinaryMessageTypeSelected(msg_type) => {
+            let Some(tab) = app.current_tab_mut() else {
+                return iced::Task::none();
+            };
+
+            tab.ws_binary_message_type = msg_type;
+            iced::Task::none()
+        }
+        Message::StateLoaded(maybe_session, metadata) => {
+            if let Some(session) = maybe_session {
+                app.json_theme = json_theme_from_str(&session.json_theme, &app.user_json_themes);
+                app.app_theme = theme_from_str(&session.app_theme);
+                app.next_tab_id = session.next_tab_id;
+                app.cookie_jar = session.cookie_jar;
+                app.http_settings = session.http_settings;
+                app.rebuild_http_client();
+                if let Some((width, height)) = session.window_size {
+                    app.window_size = iced::Size::new(width, height);
+                }
+                // A move reported since launch is newer than what was saved
+                if app.window_position.is_none() {
+                    app.window_position =
+                        session.window_position.map(|(x, y)| iced::Point::new(x, y));
+                }
+                app.layout = session.layout;
+                app.show_line_numbers = session.show_line_numbers;
+                app.wrap_lines = session.wrap_lines;
+                if let Some(ratio) = session.pane_ratio {
+                    app.pane_ratio = ratio.clamp(MIN_PANE_RATIO, 1.0 - MIN_PANE_RATIO);
+                }
+                app.last_dir = session.last_dir;
+                app.captured_vars = session.captured_vars;
+                app.active_tab = session.active_tab.min(metadata.len().saturating_sub(1));
+            }
+
+            // build tab list — all Unloaded first
+            app.tabs = metadata
+                .into_iter()
+                .map(|m| TabLoadState::Unloaded(m))
+                .collect();
+
+            // no saved tabs — create a fresh one
+            if app.tabs.is_empty() {
+                app.tabs
+                    .push(TabLoadState::Loaded(Box::new(TabState::new(0))));
+                app.next_tab_id = 1;
+                app.active_tab = 0;
+                return iced::Task::none();
+            }
+
+            // load active tab immediately
+            let active = app.active_tab;
+            if let Some(TabLoadState::Unloaded(meta)) = app.tabs.get(active) {
+                let id = meta.id;
+                app.tabs[active] = TabLoadState::Loading(meta.clone());
+                return iced::Task::perform(SavedState::load(id), move |s| {
+                    Message::TabBodyLoaded { id, saved: s }
+                });
+            }
+            iced::Task::none()
+        }
+        Message::SaveComplete => {
+            log::debug!("SaveComplete received");
+            iced::Task::none()
+        }
+        Message::CollectionLoaded(maybe_collection) => {
+            if let Some(collection) = maybe_collection {
+                app.collection = collection;
+            }
+            iced::Task::none()
+        }
+        Message::ToggleLineNumbers => {
+            app.show_line_numbers = !app.show_line_numbers;
+            iced::Task::none()
+        }
+        Message::ToggleWrapLines => {
+            app.wrap_lines = !app.wrap_lines;
+            iced::Task::none()
+        }
+        Message::ToggleLayout => {
+            app.layout = match app.layout {
+                Layout::Horizontal => Layout::Vertical,
+                Layout::Vertical => Layout::Horizontal,
+            };
+            iced::Task::none()
+        }
+        Message::PaneDividerPressed => {
+            app.pane_drag = Some((app.cursor_position, app.pane_ratio));
+            iced::Task::none()
+        }
+        Message::PaneDividerReset => {
+            app.pane_drag = None;
+            app.pane_ratio = DEFAULT_PANE_RATIO;
+            iced::Task::none()
+        }
+        Message::ToggleSidebar => {
+            app.sidebar_open = !app.sidebar_open;
+            iced::Task::none()
+        }
+        Message::SidebarItemSelected(id) => {
+            app.sidebar_selected_id = Some(id);
+            iced::Task::none()
+        }
+        Message::CollectionFolderAdd(parent_id) => {
+            let id = app.next_collection_id();
+            let folder = CollectionItem::Folder(CollectionFolder {
+                id,
+                name: "New Folder".to_string(),
+                expanded: true,
+                children: Vec::new(),
+            });
+            CrabiPie::collection_insert_into(&mut app.collection.items, parent_id, folder);
+            // immediately enter rename mode
+            app.sidebar_editing_id = Some(id);
+            app.sidebar_editing_name = "New Folder".to_string();
+            app.collection_save_task()
+        }
+        Message::CollectionItemToggleExpand(id) => {
+            CrabiPie::collection_toggle_expand(&mut app.collection.items, id);
+            iced::Task::none()
+        }
+        Message::CollectionRequestOpen(id) => {
+            app.sidebar_selected_id = Some(id);
+            let Some(req) = CrabiPie::collection_find_request(&app.collection.items, id) else {
+                return iced::Task::none();
+            };
+            let mut saved = req.saved_state.clone();
+            // Tab ids must stay unique, responses are routed by them
+            saved.id = app.next_tab_id;
+            app.next_tab_id += 1;
+            let mut tab = TabState::from_saved(saved);
+            tab.metadata.collection_request_id = Some(id);
+            tab.dirty = true;
+            app.tabs.push(TabLoadState::Loaded(Box::new(tab)));
+            // Goes through TabSelected so the current tab keeps its editor text
+            update(app, Message::TabSelected(app.tabs.len() - 1))
+        }
+        Message::CollectionRequestFocus(id) => {
+            // An open tab may hold unsaved edits, switch to it instead of reloading
+            match app
+                .tabs
+                .iter()
+                .position(|t| t.metadata().collection_request_id == Some(id))
+            {
+                Some(index) => {
+                    app.sidebar_selected_id = Some(id);
+                    update(app, Message::TabSelected(index))
+                }
+                None => update(app, Message::CollectionRequestOpen(id)),
+            }
+        }
+        Message::CollectionItemMove(id, offset) => {
+            if CrabiPie::collection_move_item(&mut app.collection.items, id, offset) {
+                app.collection_save_task()
+            } else {
+                iced::Task::none()
+            }
+        }
+        Message::CollectionItemRename(id) => {
+            // find current name
+            fn find_name(items: &[CollectionItem], id: usize) -> Option<String> {
+                for item in items {
+                    if item.id() == id {
+                        return Some(item.name().to_string());
+                    }
+                    if let CollectionItem::Folder(f) = item {
+                        if let Some(n) = find_name(&f.children, id) {
+                            return Some(n);
+                        }
+                    }
+                }
+                None
+            }
+            if let Some(name) = find_name(&app.collection.items, id) {
+                app.sidebar_editing_id = Some(id);
+                app.sidebar_editing_name = name;
+            }
+            iced::Task::none()
+        }
+        Message::CollectionItemRenameInput(text) => {
+            app.sidebar_editing_name = text;
+            iced::Task::none()
+        }
+        Message::CollectionItemRenameConfirm(id) => {
+            let new_name = app.sidebar_editing_name.trim().to_string();
+            if !new_name.is_empty() {
+                CrabiPie::collection_rename_item(&mut app.collection.items, id, new_name);
+            }
+            app.sidebar_editing_id = None;
+            app.sidebar_editing_name = String::new();
+            app.collection_save_task()
+        }
+        Message::CollectionItemRenameCancel => {
+            app.sidebar_editing_id = None;
+            app.sidebar_editing_name = String::new();
+            iced::Task::none()
+        }
+        Message::CollectionItemDelete(id) => {
+            CrabiPie::collection_remove_item(&mut app.collection.items, id);
+            app.collection_save_task()
+        }
+        Message::CollectionItemDuplicate(id) => {
+            let new_id = app.next_collection_id();
+            CrabiPie::collection_duplicate_item(&mut app.collection.items, id, new_id);
+            app.collection_save_task()
+        }
+        Message::OpenSaveModal => {
+            // Scope the borrow so it ends immediately after we get the title
+            let title = {
+                let Some(tab) = app.current_tab() else {
+                    return iced::Task::none();
+                };
+                tab.title.clone()
+            }; // The borrow of 'app' ends right here because 'tab' is gone
+
+            // Now 'app' is free to be modified mutably
+            app.save_modal_open = true;
+            app.save_modal_name = title;
+            app.save_modal_folder_id = None;
+
+            iced::Task::none()
+        }
+        Message::SaveModalNameChanged(name) => {
+            app.save_modal_name = name;
+            iced::Task::none()
+        }
+        Message::SaveModalFolderSelected(folder_id) => {
+            app.save_modal_folder_id = folder_id;
+            iced::Task::none()
+        }
+        Message::SaveModalConfirm => {
+            // 1. Get the data from the tab first (Immutable borrow phase)
+            let (method, saved_state) = {
+                let Some(tab) = app.current_tab() else {
+                    return iced::Task::none();
+                };
+                (
+                    tab.method.clone(),
+                    tab.to_saved(&app.json_theme.to_string(), &app.app_theme.to_string()),
+                )
+            }; // The immutable borrow of 'app' ends here
+
+            // 2. Now 'app' is free! You can now borrow it mutably.
+            let id = app.next_collection_id(); // Works now!
+
+            let req = CollectionItem::Request(CollectionRequest {
+                id,
+                name: app.save_modal_name.trim().to_string(),
+                method,
+                saved_state,
+            });
+
+            let folder_id = app.save_modal_folder_id;
+
+            // Perform the insertion
+            CrabiPie::collection_insert_into(&mut app.collection.items, folder_id, req);
+
+            // 3. Cleanup
+            app.save_modal_open = false;
+            app.save_modal_name = String::new();
+            app.save_modal_folder_id = None;
+            if let Some(tab) = app.current_tab_mut() {
+                tab.unsaved_edits = false;
+            }
+
+            app.collection_save_task()
+        }
+        Message::SaveModalCancel => {
+            app.save_modal_open = false;
+            app.save_modal_name = String::new();
+            app.save_modal_folder_id = None;
+            iced::Task::none()
+        }
+        Message::CollectionSaved => iced::Task::none(),
+        Message::PostmanImport => {
+            let dialog = app.file_dialog();
+            iced::Task::perform(
+                async move {
+                    match dialog
+                        .set_title("Import Postman Collection")
+                        .add_filter("JSON", &["json"])
+                        .pick_file()
+                        .await
+                    {
+                        Some(file_handle) => {
+                            let content = tokio::fs::read_to_string(file_handle.path())
+                                .await
+                                .map_err(|e| format!("Failed to read file: {}", e))?;
+                            Ok::<_, String>(remember_dir(
+                                file_handle.path(),
+                                Message::PostmanImported(from_postman(&content)?),
+                            ))
+                        }
+                        None => Ok(Message::NoOp),
+                    }
+                },
+                |result| match result {
+                    Ok(message) => message,
+                    Err(err) => Message::PostmanImportFailed(err),
+                },
+            )
+        }
+        Message::PostmanImported(import) => {
+            fn count_requests(items: &[CollectionItem]) -> usize {
+                items
+                    .iter()
+                    .map(|item| match item {
+                        CollectionItem::Folder(f) => count_requests(&f.children),
+                        CollectionItem::Request(_) => 1,
+                    })
+                    .sum()
+            }
+
+            let mut children = import.items;
+            app.collection_assign_ids(&mut children);
+            let imported = count_requests(&children);
+            let id = app.next_collection_id();
+            let folder = CollectionItem::Folder(CollectionFolder {
+                id,
+                name: import.name.clone(),
+                expanded: true,
+                children,
+            });
+            CrabiPie::collection_insert_into(&mut app.collection.items, None, folder);
+            // Variables already set here win, they may hold values for this machine
+            for (name, value) in import.variables {
+                app.captured_vars.entry(name).or_insert(value);
+            }
+
+            let mut tasks = vec![
+                app.collection_save_task(),
+                iced::Task::done(Message::ShowToast(Toast::success(format!(
+                    "Imported {} request{} from {}",
+                    imported,
+                    if imported == 1 { "" } else { "s" },
+                    import.name
+                )))),
+            ];
+            if !import.skipped.is_empty() {
+                let mut report = import
+                    .skipped
+                    .iter()
+                    .take(POSTMAN_SKIPPED_SHOWN)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if import.skipped.len() > POSTMAN_SKIPPED_SHOWN {
+                    report.push_str(&format!(
+                        "\n… and {} more",
+                        import.skipped.len() - POSTMAN_SKIPPED_SHOWN
+                    ));
+                }
+                tasks.push(iced::Task::done(Message::ShowToast(Toast::error(format!(
+                    "Not everything could be imported:\n{}",
+                    report
+                )))));
+            }
+            iced::Task::batch(tasks)
+        }
+        Message::PostmanImportFailed(err) => iced::Task::done(Message::ShowToast(Toast::error(
+            format!("Couldn't import Postman collection: {}", err),
+        ))),
+        Message::HistoryLoaded(maybe_history) => {
+            if let Some(history) = maybe_history {
+                app.history = history;
+            }
+            iced::Task::none()
+        }
+        Message::HistoryToggle => {
+            app.history_open = !app.history_open;
+            iced::Task::none()
+        }
+        Message::HistorySelected(idx) => {
+            let Some(entry) = app.history.get(idx) else {
+                return iced::Task::none();
+            };
+            let mut saved_state = entry.saved_state.clone();
+            // Load into the current tab without taking over its identity
+            if let Some(tab) = app.current_tab() {
+                saved_state.id = tab.id;
+                saved_state.title = tab.title.clone();
+            }
+            iced::Task::done(Message::RequestLoaded(saved_state))
+        }
+        Message::ClearHistory => {
+            app.history.clear();
+            app.history_save_task()
+        }
+        Message::HistorySaved => iced::Task::none(),
+        Message::RecentUrlsLoaded(maybe_urls) => {
+            if let Some(urls) = maybe_urls {
+                app.recent_urls = urls;
+            }
+            iced::Task::none()
+        }
+        Message::RecentUrlsSaved => iced::Task::none(),
+    }
+}
+
+fn view(app: &CrabiPie) -> Element<'_, Message> {
+    let tab_content = match app.current_tab() {
+        None => iced::widget::container(iced::widget::text("Loading..."))
+            .center(iced::Fill)
+            .into(),
+        Some(tab) => {
+            if tab.request_type == RequestType::WebSocket {
+                app.render_websocket_panel()
+            } else {
+                app.render_active_tab_content()
+            }
+        }
+    };
+
+    let main_content = column![
+        app.render_title_row(),
+        app.render_tabs(),
+        rule::horizontal(1.0),
+        tab_content,
+        app.render_status_bar(),
+    ]
+    .height(Length::Fill);
+
+    let main_content: Element<'_, Message> = if app.find_dialog_open {
+        let overlay = container(app.view_find_replace())
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .width(Length::Fill)
+            .height(Length::Fill);
+        iced::widget::stack![main_content, overlay].into()
+    } else {
+        main_content.into()
+    };
+
+    // Wrap with sidebar if open
+    let body: Element<'_, Message> = if app.sidebar_open {
+        row![app.render_sidebar(), rule::vertical(1.0), main_content,]
+            .height(Length::Fill)
+            .into()
+    } else {
+        main_content
+    };
+
+    // Save modal overlay
+    let body: Element<'_, Message> = if app.save_modal_open {
+        let overlay = container(app.render_save_modal())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .style(|_| container::Style {
+                background: Some(iced::Background::Color(iced::Color::from_rgba(
+                    0.0, 0.0, 0.0, 0.5,
+                ))),
+                ..Default::default()
+            });
+        iced::widget::stack![body, overlay].into()
+    } else {
+        body
+    };
+
+    let body: Element<'_, Message> = if app.settings_open {
+        let overlay = container(app.render_settings_modal())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .style(|_| container::Style {
+                background: Some(iced::Background::Color(iced::Color::from_rgba(
+                    0.0, 0.0, 0.0, 0.5,
+                ))),
+                ..Default::default()
+            });
+        iced::widget::stack![body, overlay].into()
+    } else {
+        body
+    };
+
+    // No dimmed backdrop, the editors behind it show the live preview
+    let body: Element<'_, Message> = if app.theme_editor.is_some() {
+        let overlay = container(app.render_theme_editor_modal())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center);
+        iced::widget::stack![body, overlay].into()
+    } else {
+        body
+    };
+
+    let body: Element<'_, Message> = if app.curl_import_open {
+        let overlay = container(app.render_curl_import_modal())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .style(|_| container::Style {
+                background: Some(iced::Background::Color(iced::Color::from_rgba(
+                    0.0, 0.0, 0.0, 0.5,
+                ))),
+                ..Default::default()
+            });
+        iced::widget::stack![body, overlay].into()
+    } else {
+        body
+    };
+
+    let body: Element<'_, Message> = if app.pending_load.is_some() {
+        let overlay = container(app.render_discard_changes_modal())
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .style(|_| container::Style {
+                background: Some(iced::Background::Color(iced::Color::from_rgba(
+                    0.0, 0.0, 0.0, 0.5,
+                ))),
+                ..Default::default()
+            });
+        iced::widget::stack![body, overlay].into()
+    } else {
+        body
+    };
+
+    let body: Element<'_, Message> = if app.cookie_jar_open {
+        iced::widget::stack![body, app.render_cookie_jar_modal()].into()
+    } else {
+        body
+    };
+
+    // Toasts sit above everything, including the modals
+    let body: Element<'_, Message> = if app.toasts.is_empty() {
+        body
+    } else {
+        iced::widget::stack![body, app.render_toasts()].into()
+    };
+
+    container(body).padding(10).height(Length::Fill).into()
+}
+
+const DEFAULT_WINDOW_SIZE: iced::Size = iced::Size::new(1500.0, 800.0);
+const TOAST_DURATION: std::time::Duration = std::time::Duration::from_secs(4);
+// The last part of TOAST_DURATION, spent fading out
+const TOAST_FADE: std::time::Duration = std::time::Duration::from_secs(1);
+const MAX_TOASTS: usize = 5;
+const SPINNER_DEGREES_PER_SEC: f32 = 720.0;
+const DIFF_CONTEXT_LINES: usize = 3;
+// Past this the diff settles for a coarser result rather than hold up the UI
+const DIFF_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(200);
+const LOAD_TEST_MAX_REQUESTS: usize = 10_000;
+const LOAD_TEST_MAX_CONCURRENCY: usize = 100;
+const PAGINATION_MAX_PAGES: usize = 1_000;
+const DIFF_MAX_LINES: usize = 2000;
+const JSON_TREE_MAX_ROWS: usize = 2000;
+// Skipped Postman items listed in the import toast, the rest are counted
+const POSTMAN_SKIPPED_SHOWN: usize = 5;
+// Limits for images rendered in the tree, decoded size per image
+const JSON_IMAGE_MAX_COUNT: usize = 20;
+const JSON_IMAGE_MAX_BYTES: usize = 2 * 1024 * 1024;
+const JSON_IMAGE_MIN_BASE64: usize = 64;
+const JSON_IMAGE_THUMB_HEIGHT: f32 = 96.0;
+const OAUTH_EXPIRY_MARGIN: std::time::Duration = std::time::Duration::from_secs(10);
+// How much of a restored window is kept on screen, in logical pixels
+const WINDOW_MIN_VISIBLE: f32 = 100.0;
+// Below this width the request and response panes switch to stacked
+const STACKED_LAYOUT_WIDTH: f32 = 1000.0;
+const DEFAULT_PANE_RATIO: f32 = 0.5;
+// Neither pane is dragged smaller than this share
+const MIN_PANE_RATIO: f32 = 0.15;
+const SIDEBAR_WIDTH: f32 = 240.0;
+// Title row, tab bar and request row sit above the panes
+const PANES_TOP_OFFSET: f32 = 140.0;
+
+fn main() -> iced::Result {
+    // Silent unless RUST_LOG is set, e.g. RUST_LOG=CrabiPie=debug
+    env_logger::init();
+    let icon_bytes = include_bytes!("../CrabiPie.ico");
+    let (window_size, window_position) = SessionState::load_window_geometry();
+    iced::application(CrabiPie::new, update, view)
+        .theme(|app: &CrabiPie| app.app_theme.clone())
+        .subscription(|app| app.subscription())
+        .title(|app: &CrabiPie| app.title())
+        .window(iced::window::Settings {
+            size: window_size.unwrap_or(DEFAULT_WINDOW_SIZE),
+            position: window_position
+                .map(iced::window::Position::Specific)
+                .unwrap_or_default(),
+            icon: iced::window::icon::from_file_data(icon_bytes, None).ok(),
+            ..Default::default()
+        })
+        .exit_on_close_request(false)
+        .run()
+}
+
+#[derive(Debug, Hash, Clone)]
+struct WebSocketRecipe {
+    ws_connection_id: usize,
+    url: String,
+}
+
+impl iced::advanced::subscription::Recipe for WebSocketRecipe {
+    type Output = Message;
+
+    fn hash(&self, state: &mut iced::advanced::subscription::Hasher) {
+        use std::hash::Hash;
+        self.ws_connection_id.hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: std::pin::Pin<
+            Box<dyn futures::Stream<Item = iced::advanced::subscription::Event> + Send>,
+        >,
+    ) -> std::pin::Pin<Box<dyn futures::Stream<Item = Self::Output> + Send>> {
+        let url = self.url.clone();
+
+        Box::pin(websocket_stream(url))
+    }
+}
+
+// Create the actual stream function
+fn websocket_stream(url: String) -> impl futures::Stream<Item = Message> {
+    use futures::sink::SinkExt;
+    use futures::stream::StreamExt;
+
+    futures::stream::unfold((WebSocketState::Disconnected, 0 as u8), move |state| {
+        let url = url.clone();
+        async move {
+            match state {
+                (WebSocketState::Disconnected, retry_count) => {
+                    // Stop retrying after 3 attempts
+                    if retry_count >= 3 {
+                        return Some((
+                                Message::WsEvent(WsEvent::Error(
+                                    "Failed to connect after 3 attempts. Please check the URL and try again.".to_string()
+                                )),
+                                (WebSocketState::Failed, retry_count)
+                            ));
+                    }
+
+                    // Try to connect
+                    match CrabiPie::connect_ws(&url).await {
+                        Ok(websocket) => {
+                            let (sender, receiver) = mpsc::channel(100);
+                            let connection = WsConnection(sender);
+
+                            Some((
+                                Message::WsEvent(WsEvent::Connected(connection)),
+                                (
+                                    WebSocketState::Connected {
+                                        websocket,
+                                        receiver,
+                                    },
+                                    0,
+                                ),
+                            ))
+                        }
+                        Err(e) => {
+                            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                            Some((
+                                Message::WsEvent(WsEvent::Error(format!(
+                                    "Connection attempt {} failed: {}",
+                                    retry_count + 1,
+                                    e
+                                ))),
+                                (WebSocketState::Disconnected, retry_count + 1),
+                            ))
+                        }
+                    }
+                }
+                (WebSocketState::Failed, retry_count) => {
+                    // Stop the stream - no more retries
+                    None
+                }
+                (
+                    WebSocketState::Connected {
+                        mut websocket,
+                        mut receiver,
+                    },
+                    _,
+                ) => {
+                    tokio::select! {
+                        result = websocket.next() => {
+                            match result {
+                                Some(Ok(reqwest_websocket::Message::Text(text))) => {
+                                    Some((
+                                        Message::WsEvent(WsEvent::MessageReceived(text)),
+                                        (WebSocketState::Connected { websocket, receiver }, 0)
+                                    ))
+                                }
+                                Some(Err(e)) => {
+                                    Some((
+                                        Message::WsEvent(WsEvent::Disconnected(format!("{}", e))),
+                                        (WebSocketState::Failed, 0) // Don't retry on disconnection
+                                    ))
+                                }
+                                None => {
+                                    Some((
+                                        Message::WsEvent(WsEvent::Disconnected("Connection closed".to_string())),
+                                        (WebSocketState::Failed, 0)
+                                    ))
+                                }
+                                _ => {
+                                    Some((
+                                        Message::WsEvent(WsEvent::MessageReceived("[Other]".to_string())),
+                                        (WebSocketState::Connected { websocket, receiver }, 0)
+                                    ))
+                                }
+                            }
+                        }
+
+                        Some(message) = receiver.next() => { //ui related
+                            match websocket.send(reqwest_websocket::Message::Text(message)).await {
+                                Ok(_) => Some((
+                                    Message::NoOp,
+                                    (WebSocketState::Connected { websocket, receiver }, 0)
+                                )),
+                                Err(e) => {
+                                    Some((
+                                        Message::WsEvent(WsEvent::Disconnected(format!("Send error: {}", e))),
+                                        (WebSocketState::Failed, 0)
+                                    ))
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+// State enum for the stream
+enum WebSocketState {
+    Disconnected,
+    Connected {
+        websocket: reqwest_websocket::WebSocket,
+        receiver: mpsc::Receiver<String>,
+    },
+    Failed,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum RequestType {
+    HTTP,
+    WebSocket,
+    GraphQL,
+    GRPC,
+}
+
+impl RequestType {
+    const ALL: [RequestType; 4] = [
+        RequestType::HTTP,
+        RequestType::WebSocket,
+        RequestType::GraphQL,
+        RequestType::GRPC,
+    ];
+}
+
+impl std::fmt::Display for RequestType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum HttpMethod {
+    GET,
+    POST,
+    PUT,
+    DELETE,
+    PATCH,
+    HEAD,
+    OPTIONS,
+}
+
+impl HttpMethod {
+    const ALL: [HttpMethod; 7] = [
+        HttpMethod::GET,
+        HttpMethod::POST,
+        HttpMethod::PUT,
+        HttpMethod::DELETE,
+        HttpMethod::PATCH,
+        HttpMethod::HEAD,
+        HttpMethod::OPTIONS,
+    ];
+
+    fn color(&self) -> iced::Color {
+        match self {
+            HttpMethod::GET => iced::Color::from_rgb(0.27, 0.73, 0.27),
+            HttpMethod::POST => iced::Color::from_rgb(0.98, 0.65, 0.14),
+            HttpMethod::PUT => iced::Color::from_rgb(0.14, 0.59, 0.98),
+            HttpMethod::DELETE => iced::Color::from_rgb(0.95, 0.26, 0.21),
+            HttpMethod::PATCH => iced::Color::from_rgb(0.61, 0.15, 0.69),
+            HttpMethod::HEAD => iced::Color::from_rgb(0.0, 0.74, 0.83),
+            HttpMethod::OPTIONS => iced::Color::from_rgb(0.91, 0.12, 0.55),
+        }
+    }
+}
+
+impl std::fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+// Methods whose requests can have a body. DELETE only sends one when the tab opts
+// in, see TabState::sends_body
+fn method_has_body(method: HttpMethod) -> bool {
+    match method {
+        HttpMethod::POST | HttpMethod::PUT | HttpMethod::PATCH | HttpMethod::DELETE => true,
+        HttpMethod::GET | HttpMethod::HEAD | HttpMethod::OPTIONS => false,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum ContentType {
+    Json,
+    FormData,
+    XWWWFormUrlEncoded,
+    Binary,
+}
+
+impl std::fmt::Display for ContentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentType::Json => write!(f, "JSON"),
+            ContentType::FormData => write!(f, "Form Data"),
+            ContentType::XWWWFormUrlEncoded => write!(f, "x-www-form"),
+            ContentType::Binary => write!(f, "Raw file"),
+        }
+    }
+}
+
+impl ContentType {
+    const ALL: [ContentType; 4] = [
+        ContentType::Json,
+        ContentType::FormData,
+        ContentType::XWWWFormUrlEncoded,
+        ContentType::Binary,
+    ];
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum FormViewType {
+    Raw,
+    Formatted,
+}
+
+impl std::fmt::Display for FormViewType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "View {:?}", self)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum FormFieldType {
+    Text,
+    File,
+}
+
+impl std::fmt::Display for FormFieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FormFieldType {
+    const ALL: [FormFieldType; 2] = [FormFieldType::Text, FormFieldType::File];
+}
+
+#[derive(Debug, Clone)]
+struct ListSnapshot {
+    query_params: Vec<QueryParam>,
+    form_data: Vec<FormField>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FormField {
+    enabled: bool,
+    key: String,
+    value: String,
+    files: Vec<String>,
+    field_type: FormFieldType,
+    // Content-Type of this part in a multipart body, reqwest picks one when None
+    #[serde(default)]
+    content_type: Option<String>,
+}
+
+impl FormField {
+    fn new() -> Self {
+        Self {
+            enabled: true,
+            key: String::new(),
+            value: String::new(),
+            files: Vec::new(),
+            field_type: FormFieldType::Text,
+            content_type: None,
+        }
+    }
+
+    // The part's type if it's one reqwest accepts, an invalid one is left out
+    fn part_content_type(&self) -> Option<&str> {
+        self.content_type
+            .as_deref()
+            .map(str::trim)
+            .filter(|ct| !ct.is_empty() && reqwest::multipart::Part::text("").mime_str(ct).is_ok())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum AuthType {
+    None,
+    Bearer,
+    Basic,
+    ApiKey,
+    OAuth2ClientCredentials,
+}
+
+impl std::fmt::Display for AuthType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthType::None => write!(f, "No Auth"),
+            AuthType::Bearer => write!(f, "Bearer Token"),
+            AuthType::Basic => write!(f, "Basic Auth"),
+            AuthType::ApiKey => write!(f, "Api Key"),
+            AuthType::OAuth2ClientCredentials => write!(f, "OAuth 2.0 (Client Credentials)"),
+        }
+    }
+}
+
+impl AuthType {
+    const ALL: [AuthType; 5] = [
+        AuthType::None,
+        AuthType::Bearer,
+        AuthType::Basic,
+        AuthType::ApiKey,
+        AuthType::OAuth2ClientCredentials,
+    ];
+}
+
+#[derive(Debug, Clone)]
+struct OAuthToken {
+    access_token: String,
+    // Seconds from now, None when the server didn't say
+    expires_in: Option<u64>,
+}
+
+// Client credentials grant (RFC 6749 section 4.4), credentials go in the form body
+async fn request_oauth_token(
+    client: reqwest::Client,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+    scope: String,
+) -> Result<OAuthToken, String> {
+    let mut form = vec![
+        ("grant_type", "client_credentials".to_string()),
+        ("client_id", client_id),
+        ("client_secret", client_secret),
+    ];
+    if !scope.is_empty() {
+        form.push(("scope", scope));
+    }
+    let response = client
+        .post(&token_url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to read token response: {}", e))?;
+    let json: Option<serde_json::Value> = serde_json::from_str(&body).ok();
+
+    if !status.is_success() {
+        // Servers report `error` and usually `error_description` on failure
+        let reason = json
+            .as_ref()
+            .and_then(|j| {
+                j.get("error_description")
+                    .or_else(|| j.get("error"))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| body.chars().take(200).collect());
+        return Err(format!("Token endpoint returned {}: {}", status, reason));
+    }
+
+    let json = json.ok_or("Token response is not JSON")?;
+    let access_token = json
+        .get("access_token")
+        .and_then(|v| v.as_str())
+        .ok_or("Token response has no access_token")?
+        .to_string();
+    Ok(OAuthToken {
+        access_token,
+        expires_in: json.get("expires_in").and_then(|v| v.as_u64()),
+    })
+}
+
+// The auth tab's fields under a name. OAuth2 client secrets are left out like
+// everywhere else, the rest is written to disk as is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuthPreset {
+    name: String,
+    auth_type: AuthType,
+    #[serde(default)]
+    bearer_token: String,
+    #[serde(default)]
+    basic_username: String,
+    #[serde(default)]
+    basic_password: String,
+    #[serde(default)]
+    api_key_name: String,
+    #[serde(default)]
+    api_key: String,
+    #[serde(default = "default_api_key_position")]
+    api_key_position: ApiKeyPosition,
+    #[serde(default)]
+    oauth_token_url: String,
+    #[serde(default)]
+    oauth_client_id: String,
+    #[serde(default)]
+    oauth_scope: String,
+}
+
+fn default_api_key_position() -> ApiKeyPosition {
+    ApiKeyPosition::Header
+}
+
+impl AuthPreset {
+    fn from_tab(name: String, tab: &TabState) -> Self {
+        Self {
+            name,
+            auth_type: tab.auth_type,
+            bearer_token: tab.bearer_token.clone(),
+            basic_username: tab.basic_username.clone(),
+            basic_password: tab.basic_password.clone(),
+            api_key_name: tab.api_key_name.clone(),
+            api_key: tab.api_key.clone(),
+            api_key_position: tab.api_key_position,
+            oauth_token_url: tab.oauth_token_url.clone(),
+            oauth_client_id: tab.oauth_client_id.clone(),
+            oauth_scope: tab.oauth_scope.clone(),
+        }
+    }
+
+    fn apply(self, tab: &mut TabState) {
+        tab.auth_type = self.auth_type;
+        tab.bearer_token = self.bearer_token;
+        tab.basic_username = self.basic_username;
+        tab.basic_password = self.basic_password;
+        tab.api_key_name = self.api_key_name;
+        tab.api_key = self.api_key;
+        tab.api_key_position = self.api_key_position;
+        if tab.oauth_token_url != self.oauth_token_url
+            || tab.oauth_client_id != self.oauth_client_id
+            || tab.oauth_scope != self.oauth_scope
+        {
+            tab.oauth_token_url = self.oauth_token_url;
+            tab.oauth_client_id = self.oauth_client_id;
+            tab.oauth_scope = self.oauth_scope;
+            tab.forget_oauth_token();
+        }
+    }
+}
+
+fn auth_presets_file_path() -> std::path::PathBuf {
+    state_dir().join("auth_presets.json")
+}
+
+async fn save_auth_presets(presets: Vec<AuthPreset>) {
+    if let Ok(json) = serde_json::to_string_pretty(&presets) {
+        tokio::fs::write(auth_presets_file_path(), json).await.ok();
+    }
+}
+
+async fn load_auth_presets() -> Option<Vec<AuthPreset>> {
+    let bytes = tokio::fs::read(auth_presets_file_path()).await.ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+enum ApiKeyPosition {
+    Header,
+    QueryParams,
+}
+
+impl std::fmt::Display for ApiKeyPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiKeyPosition::Header => write!(f, "Header"),
+            ApiKeyPosition::QueryParams => write!(f, "Query Params"),
+        }
+    }
+}
+
+impl ApiKeyPosition {
+    const ALL: [ApiKeyPosition; 2] = [ApiKeyPosition::Header, ApiKeyPosition::QueryParams];
+}
+
+// A signature over parts of the request, sent in its own header
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct RequestSigning {
+    enabled: bool,
+    algorithm: SigningAlgorithm,
+    encoding: SignatureEncoding,
+    // Header the signature is sent in
+    header_name: String,
+    sign_path: bool,
+    // A request header whose value is signed too, empty for none
+    sign_header: String,
+    sign_body: bool,
+}
+
+impl Default for RequestSigning {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            algorithm: SigningAlgorithm::HmacSha256,
+            encoding: SignatureEncoding::Hex,
+            header_name: "X-Signature".to_string(),
+            sign_path: false,
+            sign_header: String::new(),
+            sign_body: true,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum SigningAlgorithm {
+    HmacSha256,
+    HmacSha512,
+}
+
+impl std::fmt::Display for SigningAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SigningAlgorithm::HmacSha256 => write!(f, "HMAC-SHA256"),
+            SigningAlgorithm::HmacSha512 => write!(f, "HMAC-SHA512"),
+        }
+    }
+}
+
+impl SigningAlgorithm {
+    const ALL: [SigningAlgorithm; 2] = [SigningAlgorithm::HmacSha256, SigningAlgorithm::HmacSha512];
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum SignatureEncoding {
+    Hex,
+    Base64,
+}
+
+impl std::fmt::Display for SignatureEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureEncoding::Hex => write!(f, "Hex"),
+            SignatureEncoding::Base64 => write!(f, "Base64"),
+        }
+    }
+}
+
+impl SignatureEncoding {
+    const ALL: [SignatureEncoding; 2] = [SignatureEncoding::Hex, SignatureEncoding::Base64];
+}
+
+// A tab's signing settings with the secret already substituted, owned so
+// pagination_stream can sign every page it fetches
+#[derive(Debug, Clone)]
+struct RequestSigner {
+    signing: RequestSigning,
+    header_name: reqwest::header::HeaderName,
+    secret: String,
+}
+
+impl RequestSigner {
+    // Adds the signature header to the request that actually goes out, so the
+    // signed bytes are the sent bytes. The chosen parts are joined with newlines
+    // in a fixed order: path, header, body
+    fn sign(&self, request: &mut reqwest::Request) -> Result<(), String> {
+        use hmac::Mac;
+
+        let signing = &self.signing;
+        let path = match request.url().query() {
+            Some(query) => format!("{}?{}", request.url().path(), query),
+            None => request.url().path().to_string(),
+        };
+        let mut parts: Vec<&[u8]> = Vec::new();
+        if signing.sign_path {
+            parts.push(path.as_bytes());
+        }
+        let signed_header = signing.sign_header.trim();
+        if !signed_header.is_empty() {
+            parts.push(
+                request
+                    .headers()
+                    .get(signed_header)
+                    .map_or(&[][..], |value| value.as_bytes()),
+            );
+        }
+        if signing.sign_body {
+            match request.body() {
+                None => parts.push(&[]),
+                Some(body) => parts.push(body.as_bytes().ok_or_else(|| {
+                    "A streamed body such as form data can't be signed, turn off signing the body"
+                        .to_string()
+                })?),
+            }
+        }
+        let message = parts.join(&b'\n');
+
+        let digest = match signing.algorithm {
+            SigningAlgorithm::HmacSha256 => {
+                let mut mac = hmac::Hmac::<sha2::Sha256>::new_from_slice(self.secret.as_bytes())
+                    .map_err(|e| e.to_string())?;
+                mac.update(&message);
+                mac.finalize().into_bytes().to_vec()
+            }
+            SigningAlgorithm::HmacSha512 => {
+                let mut mac = hmac::Hmac::<sha2::Sha512>::new_from_slice(self.secret.as_bytes())
+                    .map_err(|e| e.to_string())?;
+                mac.update(&message);
+                mac.finalize().into_bytes().to_vec()
+            }
+        };
+        let value = match signing.encoding {
+            SignatureEncoding::Hex => digest.iter().map(|b| format!("{:02x}", b)).collect(),
+            SignatureEncoding::Base64 => base64::engine::general_purpose::STANDARD.encode(&digest),
+        };
+        let value = reqwest::header::HeaderValue::from_str(&value).map_err(|e| e.to_string())?;
+        request
+            .headers_mut()
+            .insert(self.header_name.clone(), value);
+        Ok(())
+    }
+}
+
+// reqwest doesn't report connection phases, so only what can be observed from
+// outside is kept: the resolver's lookup time, time to headers and body download
+#[derive(Debug, Clone, Copy, Default)]
+struct ResponseTiming {
+    // None when no lookup happened for this request, e.g. a pooled connection
+    dns: Option<std::time::Duration>,
+    // From sending until the response headers arrived, includes connect and TLS
+    ttfb: std::time::Duration,
+    download: Option<std::time::Duration>,
+}
+
+// Method, URL, headers and body of a request as it was sent, taken after auth,
+// cache validators and the signature were added
+#[derive(Debug, Clone)]
+struct SentRequest {
+    method: String,
+    url: reqwest::Url,
+    headers: Vec<(String, String)>,
+    // None for streamed bodies such as multipart files
+    body: Option<Vec<u8>>,
+}
+
+impl SentRequest {
+    fn of(request: &reqwest::Request) -> Self {
+        Self {
+            method: request.method().to_string(),
+            url: request.url().clone(),
+            headers: request
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+            body: request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(<[u8]>::to_vec),
+        }
+    }
+}
+
+// Lookup durations by host, with the instant each one finished
+static DNS_TIMINGS: std::sync::LazyLock<
+    std::sync::Mutex<std::collections::HashMap<String, (std::time::Instant, std::time::Duration)>>,
+> = std::sync::LazyLock::new(Default::default);
+
+// Resolves through the system resolver like reqwest's default, recording how long it took
+struct TimingResolver;
+
+impl reqwest::dns::Resolve for TimingResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let started = std::time::Instant::now();
+            let addrs: Vec<std::net::SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Ok(mut timings) = DNS_TIMINGS.lock() {
+                timings.insert(host, (std::time::Instant::now(), started.elapsed()));
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+// The lookup for host if it finished after since, i.e. it belongs to that request
+fn dns_time_since(host: &str, since: std::time::Instant) -> Option<std::time::Duration> {
+    let timings = DNS_TIMINGS.lock().ok()?;
+    timings
+        .get(host)
+        .filter(|(finished, _)| *finished >= since)
+        .map(|(_, duration)| *duration)
+}
+
+// A text body with the validators it came with, replayed when the server answers 304
+#[derive(Debug, Clone)]
+struct CachedResponse {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    content_type: String,
+    body: String,
+    stored_at: std::time::Instant,
+}
+
+impl CachedResponse {
+    // None when the response carries neither validator, there would be nothing to send back
+    fn from_response(headers: &[(String, String)], content_type: &str, body: &str) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        };
+        let etag = header("etag");
+        let last_modified = header("last-modified");
+        if etag.is_none() && last_modified.is_none() {
+            return None;
+        }
+        Some(Self {
+            etag,
+            last_modified,
+            content_type: content_type.to_string(),
+            body: body.to_string(),
+            stored_at: std::time::Instant::now(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct HttpResponse {
+    status: String,
+    headers: String,
+    // The same headers as name/value pairs, repeated names kept
+    header_list: Vec<(String, String)>,
+    // Status line and headers in arrival order, for the Raw tab
+    raw_head: String,
+    accepts_range: bool,
+    body: String,
+    is_binary: bool,
+    filename: String,
+    bytes: Vec<u8>,
+    content_type: String,
+    response_time: Option<tokio::time::Duration>,
+    timing: Option<ResponseTiming>,
+    set_cookies: Vec<String>,
+    content_encoding: String,
+    wire_size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct VideoState {
+    playing: bool,
+    position: f64,
+    duration: f64,
+    dragging: bool,
+    volume: f64,
+    // Volume to restore when unmuting, set while muted
+    muted_volume: Option<f64>,
+    speed: f64,
+    buffering: bool,
+}
+
+// Playback speeds offered for video responses
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct PlaybackRate(f64);
+
+impl PlaybackRate {
+    const ALL: [PlaybackRate; 4] = [
+        PlaybackRate(0.5),
+        PlaybackRate(1.0),
+        PlaybackRate(1.5),
+        PlaybackRate(2.0),
+    ];
+}
+
+impl std::fmt::Display for PlaybackRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryParam {
+    key: String,
+    value: String,
+    enabled: bool,
+}
+
+impl QueryParam {
+    fn new() -> Self {
+        Self {
+            key: String::new(),
+            value: String::new(),
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureRule {
+    name: String,
+    // JSONPath run against the response body
+    path: String,
+    enabled: bool,
+}
+
+impl CaptureRule {
+    fn new() -> Self {
+        Self {
+            name: String::new(),
+            path: String::new(),
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestHeaders {
+    key: String,
+    value: String,
+    enabled: bool,
+    // A raw line with no ':', kept whole in `key` so switching views doesn't lose
+    // it. It isn't sent until it gets a value
+    #[serde(skip)]
+    missing_colon: bool,
+}
+
+// Offered while typing a header name, matched by case-insensitive prefix
+const COMMON_HEADERS: [&str; 24] = [
+    "Accept",
+    "Accept-Charset",
+    "Accept-Encoding",
+    "Accept-Language",
+    "Authorization",
+    "Cache-Control",
+    "Connection",
+    "Content-Disposition",
+    "Content-Encoding",
+    "Content-Length",
+    "Content-Type",
+    "Cookie",
+    "Host",
+    "If-Match",
+    "If-Modified-Since",
+    "If-None-Match",
+    "Origin",
+    "Pragma",
+    "Range",
+    "Referer",
+    "User-Agent",
+    "X-API-Key",
+    "X-Forwarded-For",
+    "X-Requested-With",
+];
+
+// Values offered for a header while its value is typed, add a row to cover another header
+const HEADER_VALUE_HINTS: &[(&str, &[&str])] = &[
+    (
+        "Content-Type",
+        &[
+            "application/json",
+            "application/x-www-form-urlencoded",
+            "multipart/form-data",
+            "text/plain",
+            "text/html",
+            "application/xml",
+            "application/octet-stream",
+        ],
+    ),
+    (
+        "Accept",
+        &[
+            "*/*",
+            "application/json",
+            "text/html",
+            "application/xml",
+            "text/event-stream",
+        ],
+    ),
+    // Only codings http::BodyDecoder can undo, anything else would show as raw bytes
+    (
+        "Accept-Encoding",
+        &[
+            "gzip, deflate",
+            "gzip, deflate, br",
+            http::DEFAULT_ACCEPT_ENCODING,
+            "identity",
+        ],
+    ),
+    ("Accept-Language", &["en-US,en;q=0.9", "*"]),
+    ("Authorization", &["Bearer ", "Basic "]),
+    ("Cache-Control", &["no-cache", "no-store", "max-age=0"]),
+    ("Connection", &["keep-alive", "close"]),
+    ("Pragma", &["no-cache"]),
+    ("X-Requested-With", &["XMLHttpRequest"]),
+];
+
+// Hints for the header name that start with what's typed, all of them while it's empty
+fn header_value_suggestions(name: &str, typed: &str) -> Vec<&'static str> {
+    let Some((_, values)) = HEADER_VALUE_HINTS
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name.trim()))
+    else {
+        return Vec::new();
+    };
+    let typed = typed.trim_start().to_ascii_lowercase();
+    values
+        .iter()
+        .copied()
+        .filter(|value| {
+            let value = value.to_ascii_lowercase();
+            value.starts_with(&typed) && value != typed
+        })
+        .take(6)
+        .collect()
+}
+
+// Query strings may use `+` for spaces, invalid escapes are kept as typed
+// The url's pairs take the places of the rows it was built from, in order.
+// Disabled and blank rows never make it into the url, so they stay where they
+// were rather than being lost or moved to the end
+fn merge_query(rows: Vec<QueryParam>, url: &str) -> Vec<QueryParam> {
+    let mut parsed = parse_query(url).into_iter();
+    let mut merged = Vec::with_capacity(rows.len());
+    for row in rows {
+        if row.enabled && !row.key.is_empty() {
+            merged.extend(parsed.next());
+        } else if row.enabled || !row.key.is_empty() {
+            merged.push(row);
+        }
+    }
+    merged.extend(parsed);
+    merged
+}
+
+// The url's query as enabled rows. Repeated keys become separate rows, `?flag`
+// becomes a row with no value
+fn parse_query(url: &str) -> Vec<QueryParam> {
+    let Some(q_index) = url.find('?') else {
+        return Vec::new();
+    };
+    let query = url[q_index + 1..].split('#').next().unwrap_or("");
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            QueryParam {
+                key: decode_query_component(key),
+                value: decode_query_component(value),
+                enabled: true,
+            }
+        })
+        .collect()
+}
+
+fn decode_query_component(raw: &str) -> String {
+    let raw = raw.replace('+', " ");
+    match urlencoding::decode(&raw) {
+        Ok(decoded) => decoded.into_owned(),
+        Err(_) => raw,
+    }
+}
+
+fn header_name_suggestions(typed: &str) -> Vec<&'static str> {
+    let typed = typed.trim().to_ascii_lowercase();
+    if typed.is_empty() {
+        return Vec::new();
+    }
+    COMMON_HEADERS
+        .iter()
+        .copied()
+        .filter(|name| {
+            let name = name.to_ascii_lowercase();
+            name.starts_with(&typed) && name != typed
+        })
+        .take(6)
+        .collect()
+}
+
+impl RequestHeaders {
+    fn new() -> Self {
+        Self {
+            key: String::new(),
+            value: String::new(),
+            enabled: true,
+            missing_colon: false,
+        }
+    }
+
+    fn default() -> Vec<RequestHeaders> {
+        vec![
+            Self {
+                key: "User-Agent".to_string(),
+                value: "CrabiPie".to_string(),
+                enabled: true,
+                missing_colon: false,
+            },
+            Self {
+                key: "Connection".to_string(),
+                value: "keep-alive".to_string(),
+                enabled: true,
+                missing_colon: false,
+            },
+            Self {
+                key: "Accept-Encoding".to_string(),
+                value: "gzip, deflate, br".to_string(),
+                enabled: true,
+                missing_colon: false,
+            },
+        ]
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct HttpSettings {
+    follow_redirects: bool,
+    show_redirect_chain: bool,
+    max_redirects: usize,
+    // Retries after a connection error or 5xx, each wait doubling the backoff
+    retry_count: u32,
+    retry_backoff_ms: u64,
+    retry_non_idempotent: bool,
+    // Send If-None-Match / If-Modified-Since and reuse the body on a 304
+    cache_responses: bool,
+    // Used for both http and https when set
+    proxy_url: String,
+    proxy_username: String,
+    proxy_password: String,
+    // Client certificate for mutual TLS, a PEM certificate with its key or a PKCS#12 bundle
+    client_cert_path: String,
+    // Only for PEM, empty when the key is in the certificate file
+    client_key_path: String,
+    // PKCS#12 password, asked for again after a restart rather than saved
+    #[serde(skip)]
+    client_cert_password: String,
+    // Sent with every request, a same-named header of the request itself wins
+    pinned_headers: Vec<RequestHeaders>,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            follow_redirects: true,
+            show_redirect_chain: false,
+            max_redirects: 10,
+            retry_count: 0,
+            retry_backoff_ms: 500,
+            retry_non_idempotent: false,
+            cache_responses: false,
+            proxy_url: String::new(),
+            proxy_username: String::new(),
+            proxy_password: String::new(),
+            client_cert_path: String::new(),
+            client_key_path: String::new(),
+            client_cert_password: String::new(),
+            pinned_headers: Vec::new(),
+        }
+    }
+}
+
+fn build_http_client(
+    settings: &HttpSettings,
+    accept_invalid_certs: bool,
+) -> Result<reqwest::Client, String> {
+    // Showing the chain means following hops by hand, see send_following_redirects
+    let redirect_policy = if settings.follow_redirects && !settings.show_redirect_chain {
+        reqwest::redirect::Policy::limited(settings.max_redirects)
+    } else {
+        reqwest::redirect::Policy::none()
+    };
+
+    let mut builder = reqwest::Client::builder();
+    let proxy_url = settings.proxy_url.trim();
+    if !proxy_url.is_empty() {
+        let mut proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| format!("Invalid proxy URL `{}`: {}", proxy_url, e))?;
+        if !settings.proxy_username.is_empty() {
+            proxy = proxy.basic_auth(&settings.proxy_username, &settings.proxy_password);
+        }
+        builder = builder.proxy(proxy);
+    }
+    if let Some(identity) = load_client_identity(settings)? {
+        builder = builder.identity(identity);
+    }
+
+    builder
+        .pool_idle_timeout(std::time::Duration::from_secs(90))
+        .dns_resolver(std::sync::Arc::new(TimingResolver))
+        .redirect(redirect_policy)
+        .danger_accept_invalid_certs(accept_invalid_certs)
+        // Decoding happens in send_request so the on-the-wire size can be reported
+        .no_gzip()
+        .no_brotli()
+        .no_zstd()
+        .no_deflate()
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))
+}
+
+fn is_pkcs12_path(path: &str) -> bool {
+    let lower = path.to_ascii_lowercase();
+    lower.ends_with(".p12") || lower.ends_with(".pfx")
+}
+
+fn load_client_identity(settings: &HttpSettings) -> Result<Option<reqwest::Identity>, String> {
+    let cert_path = settings.client_cert_path.trim();
+    if cert_path.is_empty() {
+        return Ok(None);
+    }
+    let cert = std::fs::read(cert_path)
+        .map_err(|e| format!("Can't read client certificate `{}`: {}", cert_path, e))?;
+
+    if is_pkcs12_path(cert_path) {
+        return reqwest::Identity::from_pkcs12_der(&cert, &settings.client_cert_password)
+            .map(Some)
+            .map_err(|e| {
+                if settings.client_cert_password.is_empty() {
+                    "The client certificate needs its password, it isn't kept between sessions. Requests go without the certificate until it's entered".to_string()
+                } else {
+                    format!("Couldn't load client certificate: {}", e)
+                }
+            });
+    }
+
+    let key_path = settings.client_key_path.trim();
+    let key = if key_path.is_empty() {
+        cert.clone()
+    } else {
+        std::fs::read(key_path)
+            .map_err(|e| format!("Can't read client key `{}`: {}", key_path, e))?
+    };
+    // native-tls wants the key as unencrypted PKCS#8 (`BEGIN PRIVATE KEY`)
+    reqwest::Identity::from_pkcs8_pem(&cert, &key)
+        .map(Some)
+        .map_err(|e| format!("Couldn't load client certificate and key: {}", e))
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+enum RequestTab {
+    Body,
+    Headers,
+    Auth,
+    Query,
+    GraphQL,
+    Captures,
+}
+
+impl RequestTab {
+    // In tab bar order, Ctrl+1 is the first one. GraphQL has no tab of its own
+    const ALL: [RequestTab; 5] = [
+        RequestTab::Query,
+        RequestTab::Body,
+        RequestTab::Headers,
+        RequestTab::Auth,
+        RequestTab::Captures,
+    ];
+
+    // The next or previous tab in the bar, wrapping around at either end
+    fn cycled(self, forward: bool) -> Self {
+        let len = Self::ALL.len();
+        let index = Self::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        let next = if forward { index + 1 } else { index + len - 1 };
+        Self::ALL[next % len]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+enum ResponseTab {
+    Body,
+    Headers,
+    Raw,
+    Preview,
+    Cookies,
+    Diff,
+    Tree,
+    LoadTest,
+    Paginate,
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+enum Layout {
+    #[default]
+    Horizontal,
+    Vertical,
+}
+
+// Which editor the find dialog searches and highlights
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum FindTarget {
+    RequestBody,
+    ResponseBody,
+}
+
+#[derive(Debug, Clone)]
+pub enum WsEvent {
+    Connected(WsConnection),
+    Disconnected(String),
+    MessageReceived(String),
+    Error(String),
+}
+
+pub enum WsCommand {
+    Connect(String), // URL
+    Disconnect,
+    SendMessage(String),
+}
+
+#[derive(Debug, Clone)]
+struct WsMessage {
+    timestamp: String,
+    direction: MessageDirection,
+    content: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum MessageDirection {
+    Sent,
+    Received,
+    System,
+}
+
+#[derive(Debug, Clone)]
+struct WsMessageDisplay {
+    timestamp: String,
+    direction: MessageDirection,
+    content: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct WsConnection(mpsc::Sender<String>);
+
+impl WsConnection {
+    pub fn send(&mut self, message: String) {
+        self.0
+            .try_send(message)
+            .expect("Send message through WebSocket");
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+enum WsMessageType {
+    Text,
+    Json,
+    Binary,
+    HTML,
+}
+
+impl std::fmt::Display for WsMessageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl WsMessageType {
+    const ALL: [WsMessageType; 4] = [
+        WsMessageType::Text,
+        WsMessageType::Json,
+        WsMessageType::Binary,
+        WsMessageType::HTML,
+    ];
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+enum WsBinaryMessageType {
+    Base64,
+    HexaDecimal,
+}
+
+impl std::fmt::Display for WsBinaryMessageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl WsBinaryMessageType {
+    const ALL: [WsBinaryMessageType; 2] = [
+        WsBinaryMessageType::Base64,
+        WsBinaryMessageType::HexaDecimal,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AppPersistedState {
+    tabs: Vec<SavedState>,
+    active_tab: usize,
+    json_theme: String,
+    app_theme: String,
+    next_tab_id: usize,
+    cookie_jar: std::collections::HashMap<String, Vec<CookieEntry>>,
+}
+
+impl AppPersistedState {
+    async fn migrate_if_needed() {
+        let old_path = state_dir().join("session.json");
+        // if tabs key exists in old json → split into per-file format → delete old
+    }
+}
+
+// Wraps a file dialog's result so its folder becomes the next dialog's start
+fn remember_dir(path: &std::path::Path, then: Message) -> Message {
+    match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => Message::FileDialogDone {
+            dir: dir.to_path_buf(),
+            then: Box::new(then),
+        },
+        _ => then,
+    }
+}
+
+// rfd takes paths literally, so `~/Downloads` has to be resolved by hand
+fn downloads_dir() -> Option<std::path::PathBuf> {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()?;
+    let dir = std::path::PathBuf::from(home).join("Downloads");
+    dir.is_dir().then_some(dir)
+}
+
+fn state_file_path() -> std::path::PathBuf {
+    state_dir().join("session.json")
+}
+
+fn state_dir() -> std::path::PathBuf {
+    let base = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let dir = base.join(".crabipie");
+    std::fs::create_dir_all(&dir).ok();
+    dir
+}
+
+async fn save_app_state(state: AppPersistedState) {
+    if let Ok(json) = serde_json::to_string_pretty(&state) {
+        tokio::fs::write(state_file_path(), json).await.ok();
+    }
+}
+
+async fn load_app_state() -> (Option<SessionState>, Vec<TabMetadata>) {
+    let session = SessionState::load().await;
+    let metadata = TabMetadata::load_all().await;
+    (session, metadata)
+}
+fn theme_from_str(s: &str) -> iced::Theme {
+    match s {
+        "Light" => iced::Theme::Light,
+        "Dark" => iced::Theme::Dark,
+        "Dracula" => iced::Theme::Dracula,
+        "Nord" => iced::Theme::Nord,
+        "Solarized Light" => iced::Theme::SolarizedLight,
+        "Solarized Dark" => iced::Theme::SolarizedDark,
+        "Gruvbox Light" => iced::Theme::GruvboxLight,
+        "Gruvbox Dark" => iced::Theme::GruvboxDark,
+        "Catppuccin Latte" => iced::Theme::CatppuccinLatte,
+        "Catppuccin Frappe" => iced::Theme::CatppuccinFrappe,
+        "Catppuccin Macchiato" => iced::Theme::CatppuccinMacchiato,
+        "Catppuccin Mocha" => iced::Theme::CatppuccinMocha,
+        "Tokyo Night" => iced::Theme::TokyoNight,
+        "Tokyo Night Storm" => iced::Theme::TokyoNightStorm,
+        "Tokyo Night Light" => iced::Theme::TokyoNightLight,
+        "Kanagawa Wave" => iced::Theme::KanagawaWave,
+        "Kanagawa Dragon" => iced::Theme::KanagawaDragon,
+        "Kanagawa Lotus" => iced::Theme::KanagawaLotus,
+        "Moonfly" => iced::Theme::Moonfly,
+        "Nightfly" => iced::Theme::Nightfly,
+        "Oxocarbon" => iced::Theme::Oxocarbon,
+        "Ferra" => iced::Theme::Ferra,
+        _ => iced::Theme::CatppuccinMocha, // default fallback
+    }
+}
+
+fn json_theme_from_str(
+    s: &str,
+    user_themes: &[json_highlighter::CustomJsonTheme],
+) -> json_highlighter::JsonThemeWrapper {
+    if let Some(theme) = json_highlighter::JsonThemeWrapper::all(user_themes)
+        .into_iter()
+        .find(|theme| theme.to_string() == s)
+    {
+        return theme;
+    }
+    // Older sessions saved these names
+    match s {
+        "Base 16 Eighties" => {
+            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Eighties)
+        }
+        "Base 16 Mocha" => {
+            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Mocha)
+        }
+        "Base 16 Ocean" => {
+            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Ocean)
+        }
+        "Solarized Dark" => {
+            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::SolarizedDark)
+        }
+        "Inspired GitHub" => {
+            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::InspiredGitHub)
+        }
+        "Default Dark" => json_highlighter::JsonThemeWrapper::Custom(
+            json_highlighter::CustomJsonTheme::DEFAULT_DARK,
+        ),
+        "Default Light" => json_highlighter::JsonThemeWrapper::Custom(
+            json_highlighter::CustomJsonTheme::DEFAULT_LIGHT,
+        ),
+        "VSCode Dark" => json_highlighter::JsonThemeWrapper::Custom(
+            json_highlighter::CustomJsonTheme::VSCODE_DARK,
+        ),
+        _ => json_highlighter::JsonThemeWrapper::Custom(
+            json_highlighter::CustomJsonTheme::VSCODE_DARK,
+        ),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Collection {
+    name: String,
+    items: Vec<CollectionItem>,
+}
+
+impl Collection {
+    fn new() -> Self {
+        Self {
+            name: "My Collection".to_string(),
+            items: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum CollectionItem {
+    Folder(CollectionFolder),
+    Request(CollectionRequest),
+}
+
+impl CollectionItem {
+    fn id(&self) -> usize {
+        match self {
+            CollectionItem::Folder(f) => f.id,
+            CollectionItem::Request(r) => r.id,
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            CollectionItem::Folder(f) => &f.name,
+            CollectionItem::Request(r) => &r.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ToastKind {
+    Success,
+    Error,
+}
+
+// A notification in the bottom right corner, id and time are assigned by ShowToast
+#[derive(Debug, Clone)]
+struct Toast {
+    id: usize,
+    kind: ToastKind,
+    message: String,
+    shown_at: std::time::Instant,
+}
+
+impl Toast {
+    fn new(kind: ToastKind, message: String) -> Self {
+        Self {
+            id: 0,
+            kind,
+            message,
+            shown_at: std::time::Instant::now(),
+        }
+    }
+
+    fn success(message: String) -> Self {
+        Self::new(ToastKind::Success, message)
+    }
+
+    fn error(message: String) -> Self {
+        Self::new(ToastKind::Error, message)
+    }
+}
+
+// A Set-Cookie header broken into columns for the response Cookies tab
+#[derive(Debug, Clone)]
+struct ResponseCookie {
+    raw: String,
+    name: String,
+    value: String,
+    domain: String,
+    path: String,
+    expires: Option<String>,
+    // Secure, HttpOnly and SameSite, as sent
+    flags: Vec<String>,
+}
+
+impl ResponseCookie {
+    // `host` stands in for the domain when the server didn't set one, as browsers do
+    fn parse(raw: &str, host: &str) -> Self {
+        let entry = parse_set_cookie(raw);
+        let flags = raw
+            .split(';')
+            .skip(1)
+            .map(str::trim)
+            .filter(|attr| {
+                let name = attr.split('=').next().unwrap_or("").trim();
+                ["secure", "httponly", "samesite", "partitioned"]
+                    .contains(&name.to_ascii_lowercase().as_str())
+            })
+            .map(str::to_string)
+            .collect();
+        match entry {
+            Some(entry) => ResponseCookie {
+                raw: raw.to_string(),
+                name: entry.name,
+                value: entry.value,
+                domain: if entry.domain.is_empty() {
+                    host.to_string()
+                } else {
+                    entry.domain
+                },
+                path: entry.path,
+                expires: entry.expires,
+                flags,
+            },
+            // No `name=value`, show the header as is so nothing is hidden
+            None => ResponseCookie {
+                raw: raw.to_string(),
+                name: raw.to_string(),
+                value: String::new(),
+                domain: host.to_string(),
+                path: String::new(),
+                expires: None,
+                flags,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CookieEntry {
+    pub name: String,
+    pub value: String,
+    pub enabled: bool,
+    pub domain: String,
+    pub path: String,
+    pub expires: Option<String>,
+}
+
+impl CookieEntry {
+    // Unparseable dates are treated as not expired rather than dropping the cookie
+    fn is_expired(&self) -> bool {
+        self.expires
+            .as_deref()
+            .and_then(|e| chrono::DateTime::parse_from_rfc2822(e).ok())
+            .is_some_and(|at| at <= chrono::Utc::now())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CollectionFolder {
+    id: usize,
+    name: String,
+    expanded: bool,
+    children: Vec<CollectionItem>, // enables infinite nesting
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CollectionRequest {
+    id: usize,
+    name: String,
+    method: HttpMethod,
+    saved_state: SavedState,
+}
 
-            // Now 'app' is free to be modified mutably
-            app.save_modal_open = true;
-            app.save_modal_name = title;
-            app.save_modal_folder_id = None;
+fn collection_file_path() -> std::path::PathBuf {
+    let base = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| std::path::PathBuf::from("."));
+    let dir = base.join(".crabipie");
+    std::fs::create_dir_all(&dir).ok();
+    dir.join("collection.json")
+}
 
-            iced::Task::none()
-        }
-        Message::SaveModalNameChanged(name) => {
-            app.save_modal_name = name;
-            iced::Task::none()
-        }
-        Message::SaveModalFolderSelected(folder_id) => {
-            app.save_modal_folder_id = folder_id;
-            iced::Task::none()
+// Draft and the theme to go back to if the theme editor is cancelled
+struct ThemeEditor {
+    draft: json_highlighter::CustomJsonTheme,
+    // Index into CustomJsonTheme::FIELDS of the color being edited
+    selected: usize,
+    hex_input: String,
+    previous: json_highlighter::JsonThemeWrapper,
+}
+
+// On disk the colors are `#rrggbb` so the file stays hand-editable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SavedJsonTheme {
+    name: String,
+    key: String,
+    string: String,
+    number: String,
+    boolean: String,
+    null: String,
+    punctuation: String,
+    text: String,
+}
+
+impl SavedJsonTheme {
+    fn from_theme(theme: &json_highlighter::CustomJsonTheme) -> Self {
+        Self {
+            name: theme.name.to_string(),
+            key: color_to_hex(theme.key),
+            string: color_to_hex(theme.string),
+            number: color_to_hex(theme.number),
+            boolean: color_to_hex(theme.boolean),
+            null: color_to_hex(theme.null),
+            punctuation: color_to_hex(theme.punctuation),
+            text: color_to_hex(theme.text),
         }
-        Message::SaveModalConfirm => {
-            // 1. Get the data from the tab first (Immutable borrow phase)
-            let (method, saved_state) = {
-                let Some(tab) = app.current_tab() else {
-                    return iced::Task::none();
-                };
-                (
-                    tab.method.clone(),
-                    tab.to_saved(&app.json_theme.to_string(), &app.app_theme.to_string()),
-                )
-            }; // The immutable borrow of 'app' ends here
+    }
 
-            // 2. Now 'app' is free! You can now borrow it mutably.
-            let id = app.next_collection_id(); // Works now!
+    fn to_theme(&self) -> Option<json_highlighter::CustomJsonTheme> {
+        Some(json_highlighter::CustomJsonTheme {
+            name: std::borrow::Cow::Owned(self.name.clone()),
+            key: color_from_hex(&self.key)?,
+            string: color_from_hex(&self.string)?,
+            number: color_from_hex(&self.number)?,
+            boolean: color_from_hex(&self.boolean)?,
+            null: color_from_hex(&self.null)?,
+            punctuation: color_from_hex(&self.punctuation)?,
+            text: color_from_hex(&self.text)?,
+        })
+    }
+}
 
-            let req = CollectionItem::Request(CollectionRequest {
-                id,
-                name: app.save_modal_name.trim().to_string(),
-                method,
-                saved_state,
-            });
+fn color_to_hex(color: iced::Color) -> String {
+    let [r, g, b, _] = color.into_rgba8();
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
 
-            let folder_id = app.save_modal_folder_id;
+fn color_from_hex(hex: &str) -> Option<iced::Color> {
+    let hex = hex.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some(iced::Color::from_rgb8(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+    ))
+}
 
-            // Perform the insertion
-            CrabiPie::collection_insert_into(&mut app.collection.items, folder_id, req);
+fn json_themes_file_path() -> std::path::PathBuf {
+    state_dir().join("json_themes.json")
+}
 
-            // 3. Cleanup
-            app.save_modal_open = false;
-            app.save_modal_name = String::new();
-            app.save_modal_folder_id = None;
+// Read before the first frame so the session's theme name can be resolved against them
+fn load_user_json_themes() -> Vec<json_highlighter::CustomJsonTheme> {
+    std::fs::read(json_themes_file_path())
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<Vec<SavedJsonTheme>>(&bytes).ok())
+        .map(|saved| saved.iter().filter_map(SavedJsonTheme::to_theme).collect())
+        .unwrap_or_default()
+}
 
-            app.collection_save_task()
-        }
-        Message::SaveModalCancel => {
-            app.save_modal_open = false;
-            app.save_modal_name = String::new();
-            app.save_modal_folder_id = None;
-            iced::Task::none()
-        }
-        Message::CollectionSaved => iced::Task::none(),
+async fn save_user_json_themes(themes: Vec<json_highlighter::CustomJsonTheme>) {
+    let saved: Vec<SavedJsonTheme> = themes.iter().map(SavedJsonTheme::from_theme).collect();
+    if let Ok(json) = serde_json::to_string_pretty(&saved) {
+        tokio::fs::write(json_themes_file_path(), json).await.ok();
     }
 }
 
-fn view(app: &CrabiPie) -> Element<'_, Message> {
-    let tab_content = match app.current_tab() {
-        None => iced::widget::container(iced::widget::text("Loading..."))
-            .center(iced::Fill)
-            .into(),
-        Some(tab) => {
-            if tab.request_type == RequestType::WebSocket {
-                app.render_websocket_panel()
-            } else {
-                app.render_active_tab_content()
-            }
-        }
-    };
+async fn save_collection(collection: Collection) {
+    if let Ok(json) = serde_json::to_string_pretty(&collection) {
+        tokio::fs::write(collection_file_path(), json).await.ok();
+    }
+}
 
-    let main_content = column![
-        app.render_title_row(),
-        app.render_tabs(),
-        rule::horizontal(1.0),
-        tab_content
-    ]
-    .height(Length::Fill);
+async fn load_collection() -> Option<Collection> {
+    let bytes = tokio::fs::read(collection_file_path()).await.ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
 
-    let main_content: Element<'_, Message> = if app.find_dialog_open {
-        let overlay = container(app.view_find_replace())
-            .align_x(Alignment::Center)
-            .align_y(Alignment::Center)
-            .width(Length::Fill)
-            .height(Length::Fill);
-        iced::widget::stack![main_content, overlay].into()
-    } else {
-        main_content.into()
-    };
+// Oldest entries are dropped once the history grows past this
+const HISTORY_LIMIT: usize = 100;
+const RECENT_URLS_LIMIT: usize = 200;
+const URL_SUGGESTIONS_SHOWN: usize = 8;
+const RESPONSE_CACHE_LIMIT: usize = 50;
+// Undo steps kept per tab for the param and form field lists
+const LIST_UNDO_LIMIT: usize = 50;
 
-    // Wrap with sidebar if open
-    let body: Element<'_, Message> = if app.sidebar_open {
-        row![app.render_sidebar(), rule::vertical(1.0), main_content,]
-            .height(Length::Fill)
-            .into()
-    } else {
-        main_content
-    };
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    sent_at: String,
+    saved_state: SavedState,
+}
 
-    // Save modal overlay
-    let body: Element<'_, Message> = if app.save_modal_open {
-        let overlay = container(app.render_save_modal())
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .align_x(Alignment::Center)
-            .align_y(Alignment::Center)
-            .style(|_| container::Style {
-                background: Some(iced::Background::Color(iced::Color::from_rgba(
-                    0.0, 0.0, 0.0, 0.5,
-                ))),
-                ..Default::default()
-            });
-        iced::widget::stack![body, overlay].into()
-    } else {
-        body
-    };
+fn history_file_path() -> std::path::PathBuf {
+    state_dir().join("history.json")
+}
 
-    let body: Element<'_, Message> = if app.cookie_jar_open {
-        iced::widget::stack![body, app.render_cookie_jar_modal()].into()
-    } else {
-        body
-    };
+async fn save_history(history: Vec<HistoryEntry>) {
+    if let Ok(json) = serde_json::to_string(&history) {
+        tokio::fs::write(history_file_path(), json).await.ok();
+    }
+}
 
-    container(body).padding(10).height(Length::Fill).into()
+async fn load_history() -> Option<Vec<HistoryEntry>> {
+    let bytes = tokio::fs::read(history_file_path()).await.ok()?;
+    serde_json::from_slice(&bytes).ok()
 }
 
-fn main() -> iced::Result {
-    let icon_bytes = include_bytes!("../CrabiPie.ico");
-    iced::application(CrabiPie::new, update, view)
-        .theme(|app: &CrabiPie| app.app_theme.clone())
-        .subscription(|app| app.subscription())
-        .title(|app: &CrabiPie| app.title())
-        .window(iced::window::Settings {
-            size: iced::Size::new(1500.0, 800.0),
-            icon: iced::window::icon::from_file_data(icon_bytes, None).ok(),
-            ..Default::default()
-        })
-        .exit_on_close_request(false)
-        .run()
+fn recent_urls_file_path() -> std::path::PathBuf {
+    state_dir().join("recent_urls.json")
 }
 
-#[derive(Debug, Hash, Clone)]
-struct WebSocketRecipe {
-    ws_connection_id: usize,
-    url: String,
+async fn save_recent_urls(urls: Vec<String>) {
+    if let Ok(json) = serde_json::to_string(&urls) {
+        tokio::fs::write(recent_urls_file_path(), json).await.ok();
+    }
 }
 
-impl iced::advanced::subscription::Recipe for WebSocketRecipe {
-    type Output = Message;
+async fn load_recent_urls() -> Option<Vec<String>> {
+    let bytes = tokio::fs::read(recent_urls_file_path()).await.ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
 
-    fn hash(&self, state: &mut iced::advanced::subscription::Hasher) {
-        use std::hash::Hash;
-        self.ws_connection_id.hash(state);
-    }
+// RFC 6265 domain-match, `api.example.com` matches a cookie for `example.com`
+fn domain_matches(host: &str, domain: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
 
-    fn stream(
-        self: Box<Self>,
-        _input: std::pin::Pin<
-            Box<dyn futures::Stream<Item = iced::advanced::subscription::Event> + Send>,
-        >,
-    ) -> std::pin::Pin<Box<dyn futures::Stream<Item = Self::Output> + Send>> {
-        let url = self.url.clone();
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
 
-        Box::pin(websocket_stream(url))
+// Why a body file can't be sent, None when it can. Opening a directory
+// succeeds on Linux, so it's checked to be a file first
+fn readable_file_error(path: &str) -> Option<String> {
+    match std::fs::metadata(path) {
+        Ok(meta) if !meta.is_file() => Some(format!("{} is not a file", path)),
+        Ok(_) => std::fs::File::open(path)
+            .err()
+            .map(|e| format!("Can't read {}: {}", path, e)),
+        Err(e) => Some(format!("Can't read {}: {}", path, e)),
     }
 }
 
-// Create the actual stream function
-fn websocket_stream(url: String) -> impl futures::Stream<Item = Message> {
-    use futures::sink::SinkExt;
-    use futures::stream::StreamExt;
+// Size of each path that can be read from disk, missing files are left out
+fn file_sizes<'a>(
+    paths: impl IntoIterator<Item = &'a String>,
+) -> std::collections::HashMap<String, u64> {
+    paths
+        .into_iter()
+        .filter_map(|path| Some((path.clone(), std::fs::metadata(path).ok()?.len())))
+        .collect()
+}
 
-    futures::stream::unfold((WebSocketState::Disconnected, 0 as u8), move |state| {
-        let url = url.clone();
-        async move {
-            match state {
-                (WebSocketState::Disconnected, retry_count) => {
-                    // Stop retrying after 3 attempts
-                    if retry_count >= 3 {
-                        return Some((
-                                Message::WsEvent(WsEvent::Error(
-                                    "Failed to connect after 3 attempts. Please check the URL and try again.".to_string()
-                                )),
-                                (WebSocketState::Failed, retry_count)
-                            ));
-                    }
+// Width that fits the longest line of an unwrapped editor, so it scrolls sideways
+// rather than clipping. Glyphs are counted wide, a little slack is harmless
+fn unwrapped_editor_width(text: &str) -> f32 {
+    let longest = text
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    (longest as f32 * UNWRAPPED_GLYPH_WIDTH + 20.0).max(200.0)
+}
 
-                    // Try to connect
-                    match CrabiPie::connect_ws(&url).await {
-                        Ok(websocket) => {
-                            let (sender, receiver) = mpsc::channel(100);
-                            let connection = WsConnection(sender);
+// Lowercase letters and digits joined by dashes, safe to use as a file name
+fn slugify(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+// The same checks send_request applies, a header failing them is left out of the request
+fn check_header(key: &str, value: &str) -> Result<(), String> {
+    reqwest::header::HeaderName::from_bytes(key.trim().as_bytes())
+        .map_err(|_| format!("invalid header name \"{}\"", key.trim()))?;
+    reqwest::header::HeaderValue::from_str(value.trim())
+        .map_err(|_| format!("invalid value for \"{}\"", key.trim()))?;
+    Ok(())
+}
+
+// Comments the nth header line out, or back in. Lines are counted the way
+// TabState::raw_to_headers counts them, so n matches an index into tab.headers
+fn toggle_raw_header_line(raw: &str, n: usize) -> String {
+    let mut seen = 0;
+    raw.lines()
+        .map(|line| {
+            let trimmed = line.trim();
+            let content = trimmed.strip_prefix('#').unwrap_or(trimmed);
+            if !content.contains(':') {
+                // Rows missing their ':' are counted, but have nothing to comment out
+                if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                    seen += 1;
+                }
+                return line.to_string();
+            }
+            seen += 1;
+            if seen != n + 1 {
+                return line.to_string();
+            }
+            match trimmed.strip_prefix('#') {
+                Some(rest) => rest.trim_start().to_string(),
+                None => format!("# {trimmed}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 1-based line numbers of raw header lines that can't be sent, disabled lines are skipped
+fn validate_headers(text: &str) -> Vec<(usize, String)> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let result = match line.split_once(':') {
+                Some((key, value)) => check_header(key, value),
+                None => Err("missing ':' between name and value".to_string()),
+            };
+            result.err().map(|err| (idx + 1, err))
+        })
+        .collect()
+}
+
+fn extract_domain(url: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+}
+
+const IMAGE_ZOOM_STEP: f32 = 1.25;
+const IMAGE_ZOOM_MIN: f32 = 0.1;
+const IMAGE_ZOOM_MAX: f32 = 8.0;
+// Per character, at the editor's default text size
+const UNWRAPPED_GLYPH_WIDTH: f32 = 10.0;
+
+// Pixel size read straight from the file header, None for formats we don't know
+fn image_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |i: usize| Some(u16::from_be_bytes(bytes.get(i..i + 2)?.try_into().ok()?) as u32);
+    let le16 = |i: usize| Some(u16::from_le_bytes(bytes.get(i..i + 2)?.try_into().ok()?) as u32);
+    let be32 = |i: usize| Some(u32::from_be_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+    let le32 = |i: usize| Some(u32::from_le_bytes(bytes.get(i..i + 4)?.try_into().ok()?));
+    let le24 = |i: usize| Some(le32(i)? & 0x00ff_ffff);
+
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    if bytes.starts_with(b"GIF8") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    if bytes.starts_with(b"BM") {
+        // Height is negative for top-down bitmaps
+        return Some((le32(18)?, (le32(22)? as i32).unsigned_abs()));
+    }
+    if bytes.starts_with(b"RIFF") && bytes.get(8..12) == Some(b"WEBP") {
+        return match bytes.get(12..16)? {
+            b"VP8 " => Some((le16(26)? & 0x3fff, le16(28)? & 0x3fff)),
+            b"VP8L" => {
+                let bits = le32(21)?;
+                Some(((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+            }
+            b"VP8X" => Some((le24(24)? + 1, le24(27)? + 1)),
+            _ => None,
+        };
+    }
+    if bytes.starts_with(&[0xff, 0xd8]) {
+        // Walk the segments until a start-of-frame marker, which holds the size
+        let mut i = 2;
+        while *bytes.get(i)? == 0xff {
+            let marker = *bytes.get(i + 1)?;
+            let is_sof = matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc);
+            if is_sof {
+                return Some((be16(i + 7)?, be16(i + 5)?));
+            }
+            i += 2 + be16(i + 2)? as usize;
+        }
+    }
+    None
+}
+
+// Bytes added to the hex view per "Load more", a full dump of a big file is megabytes of text
+const HEX_DUMP_PAGE: usize = 16 * 1024;
+// Bytes of response text loaded into the editor at a time
+const RESPONSE_DISPLAY_PAGE: usize = 256 * 1024;
+
+// How much of `text` fits in `limit` bytes, cut back to the last line break so the
+// editor doesn't end halfway through a line. Only a break past the `shown` bytes
+// already loaded counts, otherwise one long line would stop the cut moving forward
+fn response_display_cut(text: &str, shown: usize, limit: usize) -> usize {
+    if text.len() <= limit {
+        return text.len();
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if end <= shown {
+        return shown;
+    }
+    text[shown..end].rfind('\n').map_or(end, |i| shown + i + 1)
+}
+
+#[derive(Debug, Clone, Default)]
+struct SseEvent {
+    event: String,
+    data: String,
+    id: Option<String>,
+}
 
-                            Some((
-                                Message::WsEvent(WsEvent::Connected(connection)),
-                                (
-                                    WebSocketState::Connected {
-                                        websocket,
-                                        receiver,
-                                    },
-                                    0,
-                                ),
-                            ))
-                        }
-                        Err(e) => {
-                            tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                            Some((
-                                Message::WsEvent(WsEvent::Error(format!(
-                                    "Connection attempt {} failed: {}",
-                                    retry_count + 1,
-                                    e
-                                ))),
-                                (WebSocketState::Disconnected, retry_count + 1),
-                            ))
-                        }
-                    }
-                }
-                (WebSocketState::Failed, retry_count) => {
-                    // Stop the stream - no more retries
-                    None
+// Splits a text/event-stream body into events, fed one chunk at a time
+#[derive(Default)]
+struct SseParser {
+    // Text after the last line break, the rest of that line is still to come
+    partial_line: String,
+    pending: SseEvent,
+    has_data: bool,
+}
+
+impl SseParser {
+    fn feed(&mut self, chunk: &str) -> Vec<SseEvent> {
+        self.partial_line.push_str(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.partial_line.find('\n') {
+            let line: String = self.partial_line.drain(..=end).collect();
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            // A blank line ends the event, one without data is dropped
+            if line.is_empty() {
+                let event = std::mem::take(&mut self.pending);
+                if std::mem::take(&mut self.has_data) {
+                    events.push(event);
                 }
-                (
-                    WebSocketState::Connected {
-                        mut websocket,
-                        mut receiver,
-                    },
-                    _,
-                ) => {
-                    tokio::select! {
-                        result = websocket.next() => {
-                            match result {
-                                Some(Ok(reqwest_websocket::Message::Text(text))) => {
-                                    Some((
-                                        Message::WsEvent(WsEvent::MessageReceived(text)),
-                                        (WebSocketState::Connected { websocket, receiver }, 0)
-                                    ))
-                                }
-                                Some(Err(e)) => {
-                                    Some((
-                                        Message::WsEvent(WsEvent::Disconnected(format!("{}", e))),
-                                        (WebSocketState::Failed, 0) // Don't retry on disconnection
-                                    ))
-                                }
-                                None => {
-                                    Some((
-                                        Message::WsEvent(WsEvent::Disconnected("Connection closed".to_string())),
-                                        (WebSocketState::Failed, 0)
-                                    ))
-                                }
-                                _ => {
-                                    Some((
-                                        Message::WsEvent(WsEvent::MessageReceived("[Other]".to_string())),
-                                        (WebSocketState::Connected { websocket, receiver }, 0)
-                                    ))
-                                }
-                            }
-                        }
+                continue;
+            }
+            // Lines starting with a colon are comments, often used as keep-alives
+            if line.starts_with(':') {
+                continue;
+            }
 
-                        Some(message) = receiver.next() => { //ui related
-                            match websocket.send(reqwest_websocket::Message::Text(message)).await {
-                                Ok(_) => Some((
-                                    Message::NoOp,
-                                    (WebSocketState::Connected { websocket, receiver }, 0)
-                                )),
-                                Err(e) => {
-                                    Some((
-                                        Message::WsEvent(WsEvent::Disconnected(format!("Send error: {}", e))),
-                                        (WebSocketState::Failed, 0)
-                                    ))
-                                }
-                            }
-                        }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "data" => {
+                    if self.has_data {
+                        self.pending.data.push('\n');
                     }
+                    self.pending.data.push_str(value);
+                    self.has_data = true;
                 }
+                "event" => self.pending.event = value.to_string(),
+                "id" => self.pending.id = Some(value.to_string()),
+                // `retry` and unknown fields don't change what is shown
+                _ => {}
             }
         }
-    })
+        events
+    }
 }
 
-// State enum for the stream
-enum WebSocketState {
-    Disconnected,
-    Connected {
-        websocket: reqwest_websocket::WebSocket,
-        receiver: mpsc::Receiver<String>,
-    },
-    Failed,
+// Classic `hexdump -C` layout: offset, 16 bytes split in two groups, printable ASCII
+fn format_hex_dump(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() / 16 * 78 + 78);
+    for (row, chunk) in bytes.chunks(16).enumerate() {
+        out.push_str(&format!("{:08x}  ", row * 16));
+        for i in 0..16 {
+            match chunk.get(i) {
+                Some(b) => out.push_str(&format!("{:02x} ", b)),
+                None => out.push_str("   "),
+            }
+            if i == 7 {
+                out.push(' ');
+            }
+        }
+        out.push_str(" |");
+        out.extend(chunk.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+    out
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
-enum RequestType {
-    HTTP,
-    WebSocket,
-    GraphQL,
-    GRPC,
+// A tiny subset of HTML for the response preview, anything else collapses into its text
+#[derive(Debug, Clone, Default, PartialEq)]
+struct HtmlSpan {
+    text: String,
+    bold: bool,
+    italic: bool,
+    code: bool,
+    link: Option<String>,
 }
 
-impl RequestType {
-    const ALL: [RequestType; 4] = [
-        RequestType::HTTP,
-        RequestType::WebSocket,
-        RequestType::GraphQL,
-        RequestType::GRPC,
-    ];
+#[derive(Debug, Clone, PartialEq)]
+enum HtmlBlockKind {
+    Heading(u8),
+    Paragraph,
+    ListItem { depth: usize, marker: String },
+    Preformatted,
+    Quote,
+    Rule,
 }
 
-impl std::fmt::Display for RequestType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+#[derive(Debug, Clone, PartialEq)]
+struct HtmlBlock {
+    kind: HtmlBlockKind,
+    spans: Vec<HtmlSpan>,
+}
+
+fn html_flush_block(current: &mut Option<HtmlBlock>, blocks: &mut Vec<HtmlBlock>) {
+    let Some(mut block) = current.take() else {
+        return;
+    };
+    if block.kind != HtmlBlockKind::Preformatted {
+        if let Some(last) = block.spans.last_mut() {
+            last.text.truncate(last.text.trim_end().len());
+        }
+    }
+    block.spans.retain(|s| !s.text.is_empty());
+    if !block.spans.is_empty() {
+        blocks.push(block);
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
-enum HttpMethod {
-    GET,
-    POST,
-    PUT,
-    DELETE,
-    PATCH,
+fn html_attr(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let start = lower.find(&format!("{}=", name))? + name.len() + 1;
+    let value = &tag[start..];
+    let value = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value
+            .split(|c: char| c.is_whitespace() || c == '>')
+            .next()?,
+    };
+    Some(decode_html_entities(value))
 }
 
-impl HttpMethod {
-    const ALL: [HttpMethod; 5] = [
-        HttpMethod::GET,
-        HttpMethod::POST,
-        HttpMethod::PUT,
-        HttpMethod::DELETE,
-        HttpMethod::PATCH,
-    ];
+fn decode_html_entities(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let decoded = match entity {
+            Some("amp") => Some('&'),
+            Some("lt") => Some('<'),
+            Some("gt") => Some('>'),
+            Some("quot") => Some('"'),
+            Some("apos") => Some('\''),
+            Some("nbsp") => Some('\u{a0}'),
+            Some(e) if e.starts_with("#x") || e.starts_with("#X") => {
+                u32::from_str_radix(&e[2..], 16)
+                    .ok()
+                    .and_then(char::from_u32)
+            }
+            Some(e) if e.starts_with('#') => e[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match (decoded, entity) {
+            (Some(c), Some(e)) => {
+                out.push(c);
+                rest = &rest[e.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
 }
 
-impl std::fmt::Display for HttpMethod {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !last_space {
+                out.push(' ');
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
     }
+    out
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
-enum ContentType {
-    Json,
-    FormData,
-    XWWWFormUrlEncoded,
+// Lenient on purpose, real pages are rarely well formed. Anything unknown is dropped
+// but its text is kept, script/style/title contents are skipped entirely
+fn parse_html_preview(html: &str) -> Vec<HtmlBlock> {
+    let mut blocks = Vec::new();
+    let mut current: Option<HtmlBlock> = None;
+    let (mut bold, mut italic, mut code, mut pre) = (0usize, 0usize, 0usize, 0usize);
+    let mut link: Option<String> = None;
+    // One entry per open list, Some(count) for <ol>
+    let mut lists: Vec<Option<usize>> = Vec::new();
+    let new_block = |kind| {
+        Some(HtmlBlock {
+            kind,
+            spans: Vec::new(),
+        })
+    };
+
+    let mut rest = html;
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            rest = rest.find("-->").map_or("", |i| &rest[i + 3..]);
+            continue;
+        }
+        let is_tag = rest.starts_with('<')
+            && rest[1..]
+                .starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?'));
+        if is_tag {
+            let Some(len) = xml_tag_end(rest) else {
+                break;
+            };
+            let tag = &rest[..len];
+            rest = &rest[len..];
+            let closing = tag.starts_with("</");
+            let name = xml_tag_name(tag).to_ascii_lowercase();
+
+            if !closing && matches!(name.as_str(), "script" | "style" | "title") {
+                // Lowercasing keeps byte offsets, so the index is valid in rest
+                let close = format!("</{}", name);
+                rest = rest
+                    .to_ascii_lowercase()
+                    .find(&close)
+                    .map_or("", |i| &rest[i..]);
+                continue;
+            }
+
+            match (name.as_str(), closing) {
+                ("b" | "strong", false) => bold += 1,
+                ("b" | "strong", true) => bold = bold.saturating_sub(1),
+                ("i" | "em", false) => italic += 1,
+                ("i" | "em", true) => italic = italic.saturating_sub(1),
+                ("code", false) => code += 1,
+                ("code", true) => code = code.saturating_sub(1),
+                ("a", false) => link = html_attr(tag, "href"),
+                ("a", true) => link = None,
+                ("br", _) => {
+                    if let Some(block) = current.as_mut() {
+                        block.spans.push(HtmlSpan {
+                            text: "\n".to_string(),
+                            ..Default::default()
+                        });
+                    }
+                }
+                ("hr", _) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    blocks.push(HtmlBlock {
+                        kind: HtmlBlockKind::Rule,
+                        spans: Vec::new(),
+                    });
+                }
+                ("ul", false) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    lists.push(None);
+                }
+                ("ol", false) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    lists.push(Some(0));
+                }
+                ("ul" | "ol", true) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    lists.pop();
+                }
+                ("li", false) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    let depth = lists.len().saturating_sub(1);
+                    let marker = match lists.last_mut() {
+                        Some(Some(count)) => {
+                            *count += 1;
+                            format!("{}.", count)
+                        }
+                        _ => "•".to_string(),
+                    };
+                    current = new_block(HtmlBlockKind::ListItem { depth, marker });
+                }
+                ("pre", false) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    pre += 1;
+                    current = new_block(HtmlBlockKind::Preformatted);
+                }
+                ("pre", true) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    pre = pre.saturating_sub(1);
+                }
+                ("blockquote", false) => {
+                    html_flush_block(&mut current, &mut blocks);
+                    current = new_block(HtmlBlockKind::Quote);
+                }
+                (heading, false) if heading.len() == 2 && heading.starts_with('h') => {
+                    match heading[1..].parse::<u8>() {
+                        Ok(level @ 1..=6) => {
+                            html_flush_block(&mut current, &mut blocks);
+                            current = new_block(HtmlBlockKind::Heading(level));
+                        }
+                        _ => {}
+                    }
+                }
+                (
+                    "p" | "div" | "tr" | "table" | "section" | "article" | "header" | "footer"
+                    | "nav" | "main" | "body" | "li" | "blockquote" | "h1" | "h2" | "h3" | "h4"
+                    | "h5" | "h6" | "dt" | "dd",
+                    _,
+                ) => html_flush_block(&mut current, &mut blocks),
+                _ => {}
+            }
+        } else {
+            // The first char may be multi-byte, so it's skipped by its own length
+            let skip = rest.chars().next().map_or(0, char::len_utf8);
+            let len = rest[skip..].find('<').map_or(rest.len(), |i| i + skip);
+            let decoded = decode_html_entities(&rest[..len]);
+            rest = &rest[len..];
+
+            let block = current.get_or_insert_with(|| HtmlBlock {
+                kind: HtmlBlockKind::Paragraph,
+                spans: Vec::new(),
+            });
+            let mut text = if pre > 0 {
+                decoded
+            } else {
+                collapse_whitespace(&decoded)
+            };
+            let starts_line = block.spans.last().is_none_or(|s| s.text.ends_with('\n'));
+            if pre == 0 && starts_line {
+                text = text.trim_start().to_string();
+            }
+            if text.is_empty() {
+                continue;
+            }
+
+            let span = HtmlSpan {
+                text,
+                bold: bold > 0,
+                italic: italic > 0,
+                code: code > 0 || pre > 0,
+                link: link.clone(),
+            };
+            match block.spans.last_mut() {
+                Some(last)
+                    if last.bold == span.bold
+                        && last.italic == span.italic
+                        && last.code == span.code
+                        && last.link == span.link =>
+                {
+                    last.text.push_str(&span.text)
+                }
+                _ => block.spans.push(span),
+            }
+        }
+    }
+    html_flush_block(&mut current, &mut blocks);
+    blocks
 }
 
-impl std::fmt::Display for ContentType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ContentType::Json => write!(f, "JSON"),
-            ContentType::FormData => write!(f, "Form Data"),
-            ContentType::XWWWFormUrlEncoded => write!(f, "x-www-form"),
+enum XmlToken<'a> {
+    Open(&'a str),
+    Close(&'a str),
+    // Self-closing elements, comments, declarations and text, printed as they are
+    Leaf(&'a str),
+    Text(&'a str),
+}
+
+// Index just past the `>` closing the tag at the start of s, ignoring any inside quotes
+fn xml_tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(i + 1),
+            _ => {}
         }
     }
+    None
 }
 
-impl ContentType {
-    const ALL: [ContentType; 3] = [
-        ContentType::Json,
-        ContentType::FormData,
-        ContentType::XWWWFormUrlEncoded,
-    ];
+fn xml_tag_name(tag: &str) -> &str {
+    tag.trim_start_matches(['<', '/'])
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or("")
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
-enum FormViewType {
-    Raw,
-    Formatted,
+// Re-indents XML by two spaces per level, an element holding only text stays on one line.
+// Returns None when the tags don't nest properly so the caller can keep the original
+// Text the response editor shows for an unfiltered body
+fn pretty_response_text(
+    body: &str,
+    content_type: &str,
+    json: Option<&serde_json::Value>,
+) -> String {
+    if text_extension_for(content_type) == "xml" {
+        prettify_xml(body).unwrap_or_else(|| body.to_string())
+    } else if let Some(json) = json {
+        // serde_json's preserve_order keeps the fields in the order the server sent them
+        serde_json::to_string_pretty(json).unwrap_or_else(|_| body.to_string())
+    } else {
+        body.to_string()
+    }
 }
 
-impl std::fmt::Display for FormViewType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "View {:?}", self)
+// Re-serializes a JSON body, indented or on one line. With preserve_order the
+// keys stay in the order they were written
+fn reformat_json(text: &str, pretty: bool) -> Result<String, String> {
+    let json: serde_json::Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if pretty {
+        serde_json::to_string_pretty(&json)
+    } else {
+        serde_json::to_string(&json)
     }
+    .map_err(|e| e.to_string())
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
-enum FormFieldType {
-    Text,
-    File,
+fn prettify_xml(input: &str) -> Option<String> {
+    let mut tokens = Vec::new();
+    let mut rest = input.trim();
+    while !rest.is_empty() {
+        let (token, len) = if rest.starts_with("<!--") {
+            let len = rest.find("-->")? + 3;
+            (XmlToken::Leaf(&rest[..len]), len)
+        } else if rest.starts_with("<![CDATA[") {
+            let len = rest.find("]]>")? + 3;
+            (XmlToken::Text(&rest[..len]), len)
+        } else if rest.starts_with('<') {
+            let len = xml_tag_end(rest)?;
+            let tag = &rest[..len];
+            let token = if tag.starts_with("<?") || tag.starts_with("<!") || tag.ends_with("/>") {
+                XmlToken::Leaf(tag)
+            } else if tag.starts_with("</") {
+                XmlToken::Close(tag)
+            } else {
+                XmlToken::Open(tag)
+            };
+            (token, len)
+        } else {
+            let len = rest.find('<').unwrap_or(rest.len());
+            (XmlToken::Text(rest[..len].trim()), len)
+        };
+        if !matches!(token, XmlToken::Text("")) {
+            tokens.push(token);
+        }
+        rest = &rest[len..];
+    }
+    if !tokens
+        .iter()
+        .any(|t| matches!(t, XmlToken::Open(_) | XmlToken::Leaf(_)))
+    {
+        return None;
+    }
+
+    let mut out = Vec::new();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let indent = "  ".repeat(stack.len());
+        match tokens[i] {
+            XmlToken::Open(tag) => {
+                let name = xml_tag_name(tag);
+                let inline = match (tokens.get(i + 1), tokens.get(i + 2)) {
+                    (Some(XmlToken::Close(close)), _) if xml_tag_name(close) == name => {
+                        Some((String::new(), *close, 2))
+                    }
+                    (Some(XmlToken::Text(text)), Some(XmlToken::Close(close)))
+                        if xml_tag_name(close) == name =>
+                    {
+                        Some((text.to_string(), *close, 3))
+                    }
+                    _ => None,
+                };
+                match inline {
+                    Some((text, close, consumed)) => {
+                        out.push(format!("{}{}{}{}", indent, tag, text, close));
+                        i += consumed;
+                        continue;
+                    }
+                    None => {
+                        out.push(format!("{}{}", indent, tag));
+                        stack.push(name);
+                    }
+                }
+            }
+            XmlToken::Close(tag) => {
+                if stack.pop()? != xml_tag_name(tag) {
+                    return None;
+                }
+                out.push(format!("{}{}", "  ".repeat(stack.len()), tag));
+            }
+            XmlToken::Leaf(raw) | XmlToken::Text(raw) => out.push(format!("{}{}", indent, raw)),
+        }
+        i += 1;
+    }
+
+    stack.is_empty().then(|| out.join("\n"))
 }
 
-impl std::fmt::Display for FormFieldType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+// Color for a status line such as `404 Not Found`. "Error", "Cancelled" and
+// anything else without a leading code are gray, readable on light and dark themes
+fn status_color(status: &str) -> iced::Color {
+    let code = status
+        .split_whitespace()
+        .next()
+        .and_then(|s| s.parse::<u16>().ok());
+
+    match code {
+        Some(200..=299) => iced::Color::from_rgb(0.2, 0.8, 0.2), // green
+        Some(300..=399) => iced::Color::from_rgb(0.2, 0.6, 0.9), // blue
+        Some(400..=499) => iced::Color::from_rgb(0.9, 0.6, 0.2), // orange
+        Some(500..=599) => iced::Color::from_rgb(0.9, 0.2, 0.2), // red
+        _ => iced::Color::from_rgb(0.55, 0.55, 0.55),
     }
 }
 
-impl FormFieldType {
-    const ALL: [FormFieldType; 2] = [FormFieldType::Text, FormFieldType::File];
+// Replaces `{{name}}` with captured values, unknown names are left as they are.
+// Query params are percent-encoded into the URL, so the encoded form counts too
+fn substitute_vars(input: &str, vars: &std::collections::BTreeMap<String, String>) -> String {
+    if vars.is_empty() || !(input.contains("{{") || input.contains("%7B%7B")) {
+        return input.to_string();
+    }
+    let mut output = input.to_string();
+    for (name, value) in vars {
+        output = output.replace(&format!("{{{{{}}}}}", name), value);
+        let encoded = urlencoding::encode(&format!("{{{{{}}}}}", name)).into_owned();
+        output = output.replace(&encoded, &urlencoding::encode(value));
+    }
+    output
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct FormField {
-    enabled: bool,
-    key: String,
-    value: String,
-    files: Vec<String>,
-    field_type: FormFieldType,
+struct LoadTestRun {
+    total: usize,
+    // Kept sorted, so percentiles are a lookup
+    latencies: Vec<std::time::Duration>,
+    statuses: std::collections::BTreeMap<u16, usize>,
+    errors: usize,
+    last_error: Option<String>,
+    started_at: std::time::Instant,
+    finished_at: Option<std::time::Instant>,
+    stopped: bool,
+    // Aborts the run when dropped
+    handle: Option<iced::task::Handle>,
 }
 
-impl FormField {
-    fn new() -> Self {
+impl LoadTestRun {
+    fn new(total: usize, handle: iced::task::Handle) -> Self {
         Self {
-            enabled: true,
-            key: String::new(),
-            value: String::new(),
-            files: Vec::new(),
-            field_type: FormFieldType::Text,
+            total,
+            latencies: Vec::new(),
+            statuses: std::collections::BTreeMap::new(),
+            errors: 0,
+            last_error: None,
+            started_at: std::time::Instant::now(),
+            finished_at: None,
+            stopped: false,
+            handle: Some(handle),
         }
     }
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
-enum AuthType {
-    None,
-    Bearer,
-    ApiKey,
-}
 
-impl std::fmt::Display for AuthType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AuthType::None => write!(f, "No Auth"),
-            AuthType::Bearer => write!(f, "Bearer Token"),
-            AuthType::ApiKey => write!(f, "Api Key"),
+    fn record(&mut self, result: Result<(u16, std::time::Duration), String>) {
+        match result {
+            Ok((status, latency)) => {
+                let at = self.latencies.partition_point(|l| *l <= latency);
+                self.latencies.insert(at, latency);
+                *self.statuses.entry(status).or_default() += 1;
+            }
+            Err(e) => {
+                self.errors += 1;
+                self.last_error = Some(e);
+            }
+        }
+        if self.done() >= self.total {
+            self.finished_at = Some(std::time::Instant::now());
+            self.handle = None;
         }
     }
-}
 
-impl AuthType {
-    const ALL: [AuthType; 3] = [AuthType::None, AuthType::Bearer, AuthType::ApiKey];
-}
+    fn done(&self) -> usize {
+        self.latencies.len() + self.errors
+    }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
-enum ApiKeyPosition {
-    Header,
-    QueryParams,
-}
+    fn is_running(&self) -> bool {
+        self.handle.is_some()
+    }
 
-impl std::fmt::Display for ApiKeyPosition {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ApiKeyPosition::Header => write!(f, "Header"),
-            ApiKeyPosition::QueryParams => write!(f, "Query Params"),
+    fn elapsed(&self) -> std::time::Duration {
+        self.finished_at
+            .unwrap_or_else(std::time::Instant::now)
+            .duration_since(self.started_at)
+    }
+
+    // A percentile as shown in the stats and summary, "-" before any response
+    fn percentile_text(&self, p: f64) -> String {
+        self.percentile(p)
+            .map(CrabiPie::format_duration)
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    // Nearest rank over the requests that got a response
+    fn percentile(&self, p: f64) -> Option<std::time::Duration> {
+        let last = self.latencies.len().checked_sub(1)?;
+        self.latencies
+            .get(((last as f64) * p).round() as usize)
+            .copied()
+    }
+
+    // 4xx and 5xx count as failures along with requests that got no response
+    fn successes(&self) -> usize {
+        self.statuses
+            .iter()
+            .filter(|(status, _)| **status < 400)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    fn failures(&self) -> usize {
+        self.done() - self.successes()
+    }
+
+    fn summary(&self) -> String {
+        let mut lines = vec![
+            format!(
+                "{} / {} requests in {:.2}s",
+                self.done(),
+                self.total,
+                self.elapsed().as_secs_f64()
+            ),
+            format!(
+                "min {}, median {}, p95 {}, max {}",
+                self.percentile_text(0.0),
+                self.percentile_text(0.5),
+                self.percentile_text(0.95),
+                self.percentile_text(1.0)
+            ),
+            format!("succeeded {}, failed {}", self.successes(), self.failures()),
+        ];
+        lines.extend(
+            self.statuses
+                .iter()
+                .map(|(status, count)| format!("{}: {}", status, count)),
+        );
+        if self.errors > 0 {
+            lines.push(format!("errors: {}", self.errors));
         }
+        lines.join("\n")
     }
 }
 
-impl ApiKeyPosition {
-    const ALL: [ApiKeyPosition; 2] = [ApiKeyPosition::Header, ApiKeyPosition::QueryParams];
+// Everything a send takes along once it leaves the app
+struct SendJob {
+    tab_id: usize,
+    request: reqwest::RequestBuilder,
+    // Substituted URL, names a download the server doesn't name itself
+    url: String,
+    cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    max_retries: u32,
+    retry_backoff: std::time::Duration,
+    // With the chain shown the client never redirects, hops are followed here instead
+    manual_redirects: bool,
+    max_redirects: usize,
+    // Streams the body into this file instead of the response view
+    download_to: Option<std::path::PathBuf>,
 }
 
-#[derive(Debug, Clone, Default)]
-struct HttpResponse {
+// Status line, headers and timing of a response, shared by every message about it
+struct ResponseHead {
+    tab_id: usize,
     status: String,
+    // Shown text, preceded by the redirects that led here
     headers: String,
-    accepts_range: bool,
-    body: String,
-    is_binary: bool,
-    filename: String,
-    bytes: Vec<u8>,
+    header_list: Vec<(String, String)>,
+    raw_head: String,
     content_type: String,
-    response_time: Option<tokio::time::Duration>,
+    // Content-Encoding, decoded as the body arrives so the wire size stays known
+    encoding: String,
     set_cookies: Vec<String>,
+    filename: String,
+    accepts_range: bool,
+    start_time: tokio::time::Instant,
+    ttfb: std::time::Duration,
+    dns: Option<std::time::Duration>,
 }
 
-#[derive(Debug, Clone)]
-pub struct VideoState {
-    playing: bool,
-    position: f64,
-    duration: f64,
-    dragging: bool,
-    volume: f64,
-    buffering: bool,
-}
+impl ResponseHead {
+    fn read(
+        tab_id: usize,
+        resp: &reqwest::Response,
+        redirect_chain: &[String],
+        url: &str,
+        start_time: tokio::time::Instant,
+    ) -> Self {
+        let hm = resp.headers();
+        let header_list = http::collect_header_list(hm);
+        let headers = if redirect_chain.is_empty() {
+            http::format_header_list(&header_list)
+        } else {
+            format!(
+                "Redirects:\n{}\n\n{}",
+                redirect_chain.join("\n"),
+                http::format_header_list(&header_list)
+            )
+        };
+        Self {
+            tab_id,
+            status: format!(
+                "{} {}",
+                resp.status().as_u16(),
+                resp.status().canonical_reason().unwrap_or("")
+            ),
+            headers,
+            header_list,
+            raw_head: http::format_raw_head(resp),
+            content_type: hm
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+                .to_string(),
+            encoding: http::content_encoding(hm),
+            set_cookies: hm
+                .get_all("set-cookie")
+                .iter()
+                .filter_map(|v| v.to_str().ok().map(|s| s.to_string()))
+                .collect(),
+            filename: http::filename_from_response(hm, url),
+            accepts_range: hm
+                .get("accept-ranges")
+                .and_then(|h| h.to_str().ok())
+                .is_some(),
+            ttfb: start_time.elapsed(),
+            dns: resp
+                .url()
+                .host_str()
+                .and_then(|host| dns_time_since(host, start_time.into_std())),
+            start_time,
+        }
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct QueryParam {
-    key: String,
-    value: String,
-    enabled: bool,
+    fn timing(&self, download: Option<std::time::Duration>) -> Option<ResponseTiming> {
+        Some(ResponseTiming {
+            dns: self.dns,
+            ttfb: self.ttfb,
+            download,
+        })
+    }
+
+    // The head alone, callers add the body and what else they know
+    fn response(&self) -> HttpResponse {
+        HttpResponse {
+            status: self.status.clone(),
+            headers: self.headers.clone(),
+            header_list: self.header_list.clone(),
+            raw_head: self.raw_head.clone(),
+            content_type: self.content_type.clone(),
+            set_cookies: self.set_cookies.clone(),
+            filename: self.filename.clone(),
+            content_encoding: self.encoding.clone(),
+            ..Default::default()
+        }
+    }
+
+    // Reading the body went wrong, the error is shown as the body
+    fn failed(&self, body: String) -> Message {
+        Message::ResponseReceived(
+            self.tab_id,
+            HttpResponse {
+                body,
+                response_time: Some(self.start_time.elapsed()),
+                ..self.response()
+            },
+        )
+    }
 }
 
-impl QueryParam {
-    fn new() -> Self {
-        Self {
-            key: String::new(),
-            value: String::new(),
-            enabled: true,
+// Sends with retries, then hands the response to the stream for how its body
+// is shown: written to a file, buffered as binary, or streamed as text or events
+fn send_stream(job: SendJob) -> impl futures::Stream<Item = Message> {
+    async_stream::stream! {
+        let SendJob {
+            tab_id,
+            mut request,
+            url,
+            cancel_flag,
+            max_retries,
+            retry_backoff,
+            manual_redirects,
+            max_redirects,
+            download_to,
+        } = job;
+        let mut attempt = 0;
+        let (start_time, sent) = loop {
+            // Keep a copy for the next attempt, streamed bodies cannot be replayed
+            let retry = if attempt < max_retries { request.try_clone() } else { None };
+            let start_time = tokio::time::Instant::now();
+            let sent = http::send_once(request, manual_redirects, max_redirects).await;
+            let retryable = match &sent {
+                Ok((resp, _)) => resp.status().is_server_error(),
+                Err(failure) => failure.retryable,
+            };
+            let Some(next) = retry.filter(|_| retryable) else {
+                break (start_time, sent);
+            };
+            request = next;
+
+            attempt += 1;
+            yield Message::RetryAttempt { tab_id, attempt, max: max_retries };
+            // Sleep in slices so a cancel does not wait out the whole backoff
+            let deadline = tokio::time::Instant::now() + retry_backoff * 2u32.saturating_pow(attempt - 1);
+            while !cancel_flag.load(Ordering::Relaxed) && tokio::time::Instant::now() < deadline {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+            if cancel_flag.load(Ordering::Relaxed) {
+                return;
+            }
+        };
+        let (resp, redirect_chain) = match sent {
+            Ok(sent) => sent,
+            Err(failure) => {
+                yield Message::SendFailed(tab_id, failure.message);
+                return;
+            }
+        };
+        let head = ResponseHead::read(tab_id, &resp, &redirect_chain, &url, start_time);
+        let decoder = http::BodyDecoder::new(&head.encoding);
+
+        // Send and Download: written to disk as it arrives, never held in memory
+        if let Some(path) = download_to {
+            for await message in download_body_stream(head, resp, decoder, path, cancel_flag) {
+                yield message;
+            }
+            return;
+        }
+
+        // Binary / video: buffered. Unknown types come here too and are sniffed
+        // once the body is in
+        if http::is_binary_content_type(&head.content_type)
+            || !http::is_text_content_type(&head.content_type)
+        {
+            for await message in binary_body_stream(head, resp, decoder, cancel_flag) {
+                yield message;
+            }
+            return;
+        }
+
+        // Text: a "headers ready" snapshot first so the UI can show the status
+        // right away, the body follows in chunks
+        yield Message::ResponseReceived(tab_id, HttpResponse {
+            response_time: Some(head.ttfb),
+            timing: head.timing(None),
+            ..head.response()
+        });
+        // Server-sent events never finish, so they are parsed and shown as they arrive
+        if head.content_type.starts_with("text/event-stream") {
+            for await message in sse_body_stream(head, resp, decoder, cancel_flag) {
+                yield message;
+            }
+        } else {
+            for await message in text_body_stream(head, resp, decoder, cancel_flag) {
+                yield message;
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RequestHeaders {
-    key: String,
-    value: String,
-    enabled: bool,
+fn download_body_stream(
+    head: ResponseHead,
+    resp: reqwest::Response,
+    mut decoder: http::BodyDecoder,
+    path: std::path::PathBuf,
+    cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) -> impl futures::Stream<Item = Message> {
+    async_stream::stream! {
+        use futures_util::StreamExt;
+        use tokio::io::AsyncWriteExt;
+
+        let tab_id = head.tab_id;
+        let encoding = &head.encoding;
+        let total = resp.content_length();
+        let mut byte_stream = resp.bytes_stream();
+        let mut last_report = tokio::time::Instant::now();
+        let mut wire_size = 0;
+        let mut written: u64 = 0;
+        let mut failure = None;
+
+        match tokio::fs::File::create(&path).await {
+            Err(e) => failure = Some(format!("Failed to create {}: {e}", path.display())),
+            Ok(mut file) => {
+                yield Message::StreamToFileProgress { tab_id, received: 0, total };
+                loop {
+                    if cancel_flag.load(Ordering::Relaxed) {
+                        drop(file);
+                        tokio::fs::remove_file(&path).await.ok();
+                        return;
+                    }
+                    // Wake up now and then so a cancel is noticed on a stalled download
+                    let next = match tokio::time::timeout(
+                        tokio::time::Duration::from_millis(250),
+                        byte_stream.next(),
+                    ).await {
+                        Err(_) => continue,
+                        Ok(None) => break,
+                        Ok(Some(next)) => next,
+                    };
+                    let decoded = match next {
+                        Ok(chunk) => {
+                            wire_size += chunk.len();
+                            decoder.feed(chunk.as_ref())
+                                .map_err(|e| format!("Error decoding {encoding} body: {e}"))
+                        }
+                        Err(e) => Err(format!("Error reading body after {wire_size} bytes: {e}")),
+                    };
+                    let result = match decoded {
+                        Ok(decoded) => file.write_all(&decoded).await
+                            .map(|_| decoded.len() as u64)
+                            .map_err(|e| format!("Failed to write {}: {e}", path.display())),
+                        Err(e) => Err(e),
+                    };
+                    match result {
+                        Ok(n) => written += n,
+                        Err(e) => {
+                            failure = Some(e);
+                            break;
+                        }
+                    }
+                    if last_report.elapsed() >= tokio::time::Duration::from_millis(100) {
+                        last_report = tokio::time::Instant::now();
+                        yield Message::StreamToFileProgress {
+                            tab_id,
+                            received: wire_size as u64,
+                            total,
+                        };
+                    }
+                }
+                if failure.is_none() {
+                    let rest = decoder.finish()
+                        .map_err(|e| format!("Error decoding {encoding} body: {e}"));
+                    let flushed = match rest {
+                        Ok(rest) => match file.write_all(&rest).await {
+                            Ok(()) => {
+                                written += rest.len() as u64;
+                                file.flush().await
+                                    .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+                            }
+                            Err(e) => Err(format!("Failed to write {}: {e}", path.display())),
+                        },
+                        Err(e) => Err(e),
+                    };
+                    failure = flushed.err();
+                }
+                if failure.is_some() {
+                    drop(file);
+                    tokio::fs::remove_file(&path).await.ok();
+                }
+            }
+        }
+
+        // Reported like a text response so the summary lands in the body view
+        yield Message::ResponseReceived(tab_id, HttpResponse {
+            content_type: "text/plain".to_string(),
+            response_time: Some(head.ttfb),
+            timing: head.timing(Some(head.start_time.elapsed().saturating_sub(head.ttfb))),
+            ..head.response()
+        });
+        let summary = match failure {
+            Some(e) => format!("Download failed, partial file removed.\n\n{e}"),
+            None => format!(
+                "Saved {} to {}\n\nContent-Type: {}",
+                CrabiPie::human_size(written as usize),
+                path.display(),
+                head.content_type
+            ),
+        };
+        yield Message::StreamChunk(tab_id, summary);
+        yield Message::StreamDone { tab_id, wire_size, total: head.start_time.elapsed() };
+    }
 }
 
-impl RequestHeaders {
-    fn new() -> Self {
-        Self {
-            key: String::new(),
-            value: String::new(),
-            enabled: true,
+// Streamed so large downloads report progress and can be cancelled, media the
+// server can serve in ranges is left to the player instead
+fn binary_body_stream(
+    head: ResponseHead,
+    resp: reqwest::Response,
+    mut decoder: http::BodyDecoder,
+    cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) -> impl futures::Stream<Item = Message> {
+    async_stream::stream! {
+        use futures_util::StreamExt;
+
+        let tab_id = head.tab_id;
+        let ct = &head.content_type;
+        let encoding = &head.encoding;
+        if head.accepts_range && (ct.starts_with("video/") || ct.starts_with("audio/")) {
+            yield Message::ResponseReceived(tab_id, HttpResponse {
+                is_binary: true,
+                response_time: Some(head.start_time.elapsed()),
+                timing: head.timing(None),
+                accepts_range: true,
+                ..head.response()
+            });
+            return;
+        }
+
+        let total = resp.content_length();
+        let mut bytes: Vec<u8> = Vec::new();
+        let mut wire_size = 0;
+        let mut byte_stream = resp.bytes_stream();
+        let mut last_report = tokio::time::Instant::now();
+
+        yield Message::DownloadProgress { tab_id, received: 0, total };
+        while let Some(chunk_result) = byte_stream.next().await {
+            if cancel_flag.load(Ordering::Relaxed) {
+                return;
+            }
+            match chunk_result {
+                Ok(chunk) => {
+                    wire_size += chunk.len();
+                    match decoder.feed(chunk.as_ref()) {
+                        Ok(decoded) => bytes.extend_from_slice(&decoded),
+                        Err(e) => {
+                            yield head.failed(format!("Error decoding {encoding} body: {e}"));
+                            return;
+                        }
+                    }
+                    if last_report.elapsed() >= tokio::time::Duration::from_millis(100) {
+                        last_report = tokio::time::Instant::now();
+                        yield Message::DownloadProgress {
+                            tab_id,
+                            received: wire_size as u64,
+                            total,
+                        };
+                    }
+                }
+                Err(e) => {
+                    yield head.failed(format!("Error reading binary data after {wire_size} bytes: {e}"));
+                    return;
+                }
+            }
         }
+
+        match decoder.finish() {
+            Ok(rest) => bytes.extend_from_slice(&rest),
+            Err(e) => {
+                yield head.failed(format!("Error decoding {encoding} body: {e}"));
+                return;
+            }
+        }
+
+        // Text after all, handed over the way a streamed text body would be
+        if !http::detect_binary(ct, &bytes) {
+            yield Message::ResponseReceived(tab_id, HttpResponse {
+                response_time: Some(head.ttfb),
+                timing: head.timing(None),
+                ..head.response()
+            });
+            let text = http::CharsetDecoder::for_content_type(ct).decode(&bytes, true);
+            yield Message::StreamChunk(tab_id, text);
+            yield Message::StreamDone { tab_id, wire_size, total: head.start_time.elapsed() };
+            return;
+        }
+
+        let body = format!("Binary file ({} bytes)\n\nContent-Type: {}", bytes.len(), ct);
+        yield Message::ResponseReceived(tab_id, HttpResponse {
+            body,
+            is_binary: true,
+            bytes,
+            response_time: Some(head.start_time.elapsed()),
+            timing: head.timing(Some(head.start_time.elapsed().saturating_sub(head.ttfb))),
+            accepts_range: head.accepts_range,
+            wire_size,
+            ..head.response()
+        });
     }
+}
 
-    fn default() -> Vec<RequestHeaders> {
-        vec![
-            Self {
-                key: "User-Agent".to_string(),
-                value: "CrabiPie".to_string(),
-                enabled: true,
-            },
-            Self {
-                key: "Connection".to_string(),
-                value: "keep-alive".to_string(),
-                enabled: true,
-            },
-            Self {
-                key: "Accept-Encoding".to_string(),
-                value: "gzip, deflate, br".to_string(),
-                enabled: true,
-            },
-        ]
+fn sse_body_stream(
+    head: ResponseHead,
+    resp: reqwest::Response,
+    mut decoder: http::BodyDecoder,
+    cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) -> impl futures::Stream<Item = Message> {
+    async_stream::stream! {
+        use futures_util::StreamExt;
+
+        let tab_id = head.tab_id;
+        let mut wire_size = 0;
+        let mut byte_stream = resp.bytes_stream();
+        // Always UTF-8, a character split across chunks waits for the rest
+        // of it and an invalid byte becomes U+FFFD instead of stalling the stream
+        let mut text_decoder = http::CharsetDecoder::for_content_type("text/event-stream");
+        let mut parser = SseParser::default();
+        loop {
+            if cancel_flag.load(Ordering::Relaxed) {
+                yield Message::StreamChunk(tab_id, "…[stopped]\n".to_string());
+                break;
+            }
+            // Wake up now and then so Stop works while the server is quiet
+            let next = match tokio::time::timeout(
+                tokio::time::Duration::from_millis(250),
+                byte_stream.next(),
+            ).await {
+                Err(_) => continue,
+                Ok(None) => break,
+                Ok(Some(next)) => next,
+            };
+            match next {
+                Ok(bytes) => {
+                    wire_size += bytes.len();
+                    let text = match decoder.feed(bytes.as_ref()) {
+                        Ok(decoded) => text_decoder.decode(&decoded, false),
+                        Err(e) => {
+                            yield Message::StreamChunk(tab_id, format!("\n[decode error: {e}]"));
+                            break;
+                        }
+                    };
+                    for event in parser.feed(&text) {
+                        yield Message::SseEvent(tab_id, event);
+                    }
+                }
+                Err(e) => {
+                    yield Message::StreamChunk(tab_id, format!("\n[stream error: {e}]"));
+                    break;
+                }
+            }
+        }
+        yield Message::StreamDone { tab_id, wire_size, total: head.start_time.elapsed() };
     }
 }
 
-static HTTP_CLIENT: once_cell::sync::Lazy<reqwest::Client> = once_cell::sync::Lazy::new(|| {
-    reqwest::Client::builder()
-        .pool_idle_timeout(std::time::Duration::from_secs(90))
-        .build()
-        .expect("failed to build http client")
-});
+// Decoded with the charset from Content-Type, a character split across chunks
+// waits in the decoder for the rest of it
+fn text_body_stream(
+    head: ResponseHead,
+    resp: reqwest::Response,
+    mut decoder: http::BodyDecoder,
+    cancel_flag: std::sync::Arc<std::sync::atomic::AtomicBool>,
+) -> impl futures::Stream<Item = Message> {
+    async_stream::stream! {
+        use futures_util::StreamExt;
+
+        let tab_id = head.tab_id;
+        let mut wire_size = 0;
+        let mut byte_stream = resp.bytes_stream();
+        let mut text_decoder = http::CharsetDecoder::for_content_type(&head.content_type);
+        while let Some(chunk_result) = byte_stream.next().await {
+            if cancel_flag.load(Ordering::Relaxed) {
+                yield Message::StreamChunk(tab_id, "…[cancelled]".to_string());
+                break;
+            }
+            match chunk_result {
+                Ok(bytes) => {
+                    wire_size += bytes.len();
+                    match decoder.feed(bytes.as_ref()) {
+                        Ok(decoded) => {
+                            let text = text_decoder.decode(&decoded, false);
+                            if !text.is_empty() {
+                                yield Message::StreamChunk(tab_id, text);
+                            }
+                        }
+                        Err(e) => {
+                            yield Message::StreamChunk(tab_id, format!("\n[decode error: {e}]"));
+                            break;
+                        }
+                    }
+                }
+                Err(e) => {
+                    yield Message::StreamChunk(tab_id, format!("\n[stream error: {e}]"));
+                    break;
+                }
+            }
+        }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-enum RequestTab {
-    Body,
-    Headers,
-    Auth,
-    Query,
-    GraphQL,
+        // Flush whatever the decoders still hold, an incomplete tail becomes U+FFFD
+        let rest = decoder.finish().unwrap_or_default();
+        let tail = text_decoder.decode(&rest, true);
+        if !tail.is_empty() {
+            yield Message::StreamChunk(tab_id, tail);
+        }
+
+        yield Message::StreamDone { tab_id, wire_size, total: head.start_time.elapsed() };
+    }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-enum ResponseTab {
-    Body,
-    Headers,
+// Sends `count` copies of `request`, at most `concurrency` at a time. The time
+// covers the whole body so slow downloads show up in the latencies
+fn load_test_stream(
+    client: reqwest::Client,
+    request: reqwest::Request,
+    count: usize,
+    concurrency: usize,
+) -> impl futures::Stream<Item = Result<(u16, std::time::Duration), String>> {
+    use futures_util::StreamExt;
+    futures_util::stream::iter(0..count)
+        .map(move |_| {
+            let client = client.clone();
+            let request = request.try_clone();
+            async move {
+                let request = request.ok_or("The request body can't be repeated")?;
+                let start = std::time::Instant::now();
+                let response = client.execute(request).await.map_err(|e| e.to_string())?;
+                let status = response.status().as_u16();
+                response.bytes().await.map_err(|e| e.to_string())?;
+                Ok::<_, String>((status, start.elapsed()))
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
 }
 
-#[derive(Debug, Clone)]
-pub enum WsEvent {
-    Connected(WsConnection),
-    Disconnected(String),
-    MessageReceived(String),
-    Error(String),
+// Where an auto-paginate run finds the items of a page and the link to the next one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Pagination {
+    // JSONPath to the items, empty takes the whole body
+    items_path: String,
+    // JSONPath to the next URL or cursor, empty follows the Link header's rel="next"
+    next_path: String,
+    // Query parameter the cursor goes in, empty when next is a URL
+    cursor_param: String,
+    max_pages: usize,
 }
 
-pub enum WsCommand {
-    Connect(String), // URL
-    Disconnect,
-    SendMessage(String),
+impl Default for Pagination {
+    fn default() -> Self {
+        Self {
+            items_path: "$.items".to_string(),
+            next_path: String::new(),
+            cursor_param: String::new(),
+            max_pages: 10,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
-struct WsMessage {
-    timestamp: String,
-    direction: MessageDirection,
-    content: String,
+enum PaginationEvent {
+    Page(Vec<serde_json::Value>),
+    Done { reason: String, failed: bool },
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum MessageDirection {
-    Sent,
-    Received,
-    System,
+struct PaginationRun {
+    max_pages: usize,
+    pages: usize,
+    items: Vec<serde_json::Value>,
+    stop_reason: Option<String>,
+    failed: bool,
+    // Aborts the run when dropped
+    handle: Option<iced::task::Handle>,
 }
 
-#[derive(Debug, Clone)]
-struct WsMessageDisplay {
-    timestamp: String,
-    direction: MessageDirection,
-    content: String,
-}
+impl PaginationRun {
+    fn new(max_pages: usize, handle: iced::task::Handle) -> Self {
+        Self {
+            max_pages,
+            pages: 0,
+            items: Vec::new(),
+            stop_reason: None,
+            failed: false,
+            handle: Some(handle),
+        }
+    }
 
-#[derive(Debug, Clone)]
-pub struct WsConnection(mpsc::Sender<String>);
+    fn is_running(&self) -> bool {
+        self.handle.is_some()
+    }
 
-impl WsConnection {
-    pub fn send(&mut self, message: String) {
-        self.0
-            .try_send(message)
-            .expect("Send message through WebSocket");
+    fn summary(&self) -> String {
+        format!(
+            "{} {} fetched, {} {}",
+            self.pages,
+            if self.pages == 1 { "page" } else { "pages" },
+            self.items.len(),
+            if self.items.len() == 1 {
+                "item"
+            } else {
+                "items"
+            }
+        )
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-enum WsMessageType {
-    Text,
-    Json,
-    Binary,
-    HTML,
+// Sends `request`, then follows the next link of each page until there is
+// none, `max_pages` pages are in or a page fails
+fn pagination_stream(
+    client: reqwest::Client,
+    request: reqwest::Request,
+    config: Pagination,
+    signer: Option<RequestSigner>,
+) -> impl futures::Stream<Item = PaginationEvent> {
+    async_stream::stream! {
+        let first_url = request.url().clone();
+        let mut next = Some(first_url.clone());
+        let mut visited = std::collections::HashSet::new();
+        let mut pages = 0;
+        let (reason, failed) = loop {
+            let Some(url) = next.take() else {
+                break ("No next page, all pages fetched".to_string(), false);
+            };
+            if pages >= config.max_pages {
+                break (format!("Stopped at the {}-page limit", config.max_pages), false);
+            }
+            if !visited.insert(url.to_string()) {
+                break (format!("The next link points back at {}", url), true);
+            }
+            let Some(mut page_request) = request.try_clone() else {
+                break ("The request body can't be repeated".to_string(), true);
+            };
+            let same_origin = url.origin() == first_url.origin();
+            if !same_origin {
+                http::strip_credentials(page_request.headers_mut());
+            }
+            *page_request.url_mut() = url.clone();
+            // The signature is a credential too, so other origins don't get one
+            if let Some(signer) = signer.as_ref().filter(|_| same_origin)
+                && let Err(e) = signer.sign(&mut page_request)
+            {
+                break (format!("Page {}: {}", pages + 1, e), true);
+            }
+            let response = match client.execute(page_request).await {
+                Ok(response) => response,
+                Err(e) => break (format!("Page {}: {}", pages + 1, e), true),
+            };
+            let status = response.status();
+            if !status.is_success() {
+                break (format!("Page {} returned {}", pages + 1, status), true);
+            }
+            let link_next = link_header_next(response.headers());
+            // The client leaves decoding to us, see build_http_client
+            let encoding = http::content_encoding(response.headers());
+            let bytes = match response.bytes().await {
+                Ok(bytes) => match http::decode_body(&encoding, &bytes) {
+                    Ok(bytes) => bytes,
+                    Err(e) => break (format!("Page {}: can't decode {} body: {}", pages + 1, encoding, e), true),
+                },
+                Err(e) => break (format!("Page {}: {}", pages + 1, e), true),
+            };
+            let json = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+                Ok(json) => json,
+                Err(e) => break (format!("Page {} is not JSON: {}", pages + 1, e), true),
+            };
+            let items = match pagination_items(&json, &config.items_path) {
+                Ok(items) => items,
+                Err(e) => break (format!("Items path: {}", e), true),
+            };
+            pages += 1;
+            yield PaginationEvent::Page(items);
+
+            next = if config.next_path.trim().is_empty() {
+                link_next.and_then(|link| url.join(&link).ok())
+            } else {
+                let value = match json.query(config.next_path.trim()) {
+                    Ok(matches) => matches.first().copied().cloned(),
+                    Err(e) => break (format!("Next path: {}", e), true),
+                };
+                let value = match value {
+                    Some(serde_json::Value::String(s)) => Some(s),
+                    Some(serde_json::Value::Number(n)) => Some(n.to_string()),
+                    _ => None,
+                }
+                .filter(|s| !s.trim().is_empty());
+                let cursor_param = config.cursor_param.trim();
+                match value {
+                    Some(cursor) if !cursor_param.is_empty() => {
+                        // The cursor replaces its parameter on the first page's URL
+                        let mut next_url = first_url.clone();
+                        let pairs: Vec<(String, String)> = first_url
+                            .query_pairs()
+                            .filter(|(key, _)| key != cursor_param)
+                            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                            .collect();
+                        next_url
+                            .query_pairs_mut()
+                            .clear()
+                            .extend_pairs(pairs)
+                            .append_pair(cursor_param, &cursor);
+                        Some(next_url)
+                    }
+                    Some(link) => url.join(&link).ok(),
+                    None => None,
+                }
+            };
+        };
+        yield PaginationEvent::Done { reason, failed };
+    }
 }
 
-impl std::fmt::Display for WsMessageType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+// A lone match that is an array gives its elements, so `$.items` and
+// `$.items[*]` gather the same thing
+fn pagination_items(
+    json: &serde_json::Value,
+    path: &str,
+) -> Result<Vec<serde_json::Value>, String> {
+    let path = path.trim();
+    if path.is_empty() {
+        return Ok(match json {
+            serde_json::Value::Array(items) => items.clone(),
+            other => vec![other.clone()],
+        });
     }
+    let matches = json.query(path).map_err(|e| e.to_string())?;
+    Ok(match matches.as_slice() {
+        [serde_json::Value::Array(items)] => items.clone(),
+        many => many.iter().map(|v| (*v).clone()).collect(),
+    })
 }
 
-impl WsMessageType {
-    const ALL: [WsMessageType; 4] = [
-        WsMessageType::Text,
-        WsMessageType::Json,
-        WsMessageType::Binary,
-        WsMessageType::HTML,
-    ];
+// Target of the rel="next" entry of the Link headers, e.g.
+// `<https://api.example.com/items?page=2>; rel="next"`
+fn link_header_next(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get_all(reqwest::header::LINK)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .find_map(|link| {
+            let (target, params) = link.split_once(';')?;
+            let is_next = params.split(';').any(|param| {
+                param.split_once('=').is_some_and(|(key, value)| {
+                    key.trim().eq_ignore_ascii_case("rel")
+                        && value
+                            .trim()
+                            .trim_matches('"')
+                            .split_whitespace()
+                            .any(|rel| rel.eq_ignore_ascii_case("next"))
+                })
+            });
+            let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+            is_next.then(|| target.to_string())
+        })
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
-enum WsBinaryMessageType {
-    Base64,
-    HexaDecimal,
+#[derive(Debug, Clone)]
+enum DiffLine {
+    Context(String),
+    Added(String),
+    Removed(String),
+    // Unchanged lines left out between two changes
+    Skipped(usize),
 }
 
-impl std::fmt::Display for WsBinaryMessageType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+impl DiffLine {
+    fn to_unified(&self) -> String {
+        match self {
+            DiffLine::Context(line) => format!("  {}", line),
+            DiffLine::Added(line) => format!("+ {}", line),
+            DiffLine::Removed(line) => format!("- {}", line),
+            DiffLine::Skipped(count) => format!("… {} unchanged lines", count),
+        }
     }
 }
 
-impl WsBinaryMessageType {
-    const ALL: [WsBinaryMessageType; 2] = [
-        WsBinaryMessageType::Base64,
-        WsBinaryMessageType::HexaDecimal,
-    ];
+// Line diff with a few lines of context around each change, empty when both
+// sides match. JSON is prettified first so a reformatted body isn't all changes
+fn diff_response_lines(previous: &str, current: &str) -> Vec<DiffLine> {
+    let normalize = |body: &str| {
+        serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok())
+            .unwrap_or_else(|| body.to_string())
+    };
+    let (old, new) = (normalize(previous), normalize(current));
+    let diff = similar::TextDiff::configure()
+        .timeout(DIFF_TIMEOUT)
+        .diff_lines(&old, &new);
+
+    let mut lines = Vec::new();
+    let mut shown_until = 0;
+    for group in diff.grouped_ops(DIFF_CONTEXT_LINES) {
+        let Some(first) = group.first() else {
+            continue;
+        };
+        let start = first.old_range().start;
+        if start > shown_until {
+            lines.push(DiffLine::Skipped(start - shown_until));
+        }
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let line = change.value().trim_end_matches(['\r', '\n']).to_string();
+                lines.push(match change.tag() {
+                    similar::ChangeTag::Equal => DiffLine::Context(line),
+                    similar::ChangeTag::Insert => DiffLine::Added(line),
+                    similar::ChangeTag::Delete => DiffLine::Removed(line),
+                });
+            }
+        }
+        shown_until = group.last().map_or(start, |op| op.old_range().end);
+    }
+    let total = old.lines().count();
+    if !lines.is_empty() && total > shown_until {
+        lines.push(DiffLine::Skipped(total - shown_until));
+    }
+    lines
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct AppPersistedState {
-    tabs: Vec<SavedState>,
-    active_tab: usize,
-    json_theme: String,
-    app_theme: String,
-    next_tab_id: usize,
-    cookie_jar: std::collections::HashMap<String, Vec<CookieEntry>>,
+// Narrows a JSON response to what a JSONPath expression selects. The full body
+// comes back, with the reason, when the filter is empty or can't be applied
+fn filter_json_response(body: &str, filter: &str) -> (String, Option<String>) {
+    let filter = filter.trim();
+    if filter.is_empty() {
+        return (body.to_string(), None);
+    }
+    let json: serde_json::Value = match serde_json::from_str(body) {
+        Ok(json) => json,
+        Err(e) => return (body.to_string(), Some(format!("Response is not JSON: {e}"))),
+    };
+    match json.query(filter) {
+        Ok(matches) => {
+            // A single match is shown as is, several are gathered into an array
+            let selected = match matches.as_slice() {
+                [single] => (*single).clone(),
+                many => serde_json::Value::Array(many.iter().map(|v| (*v).clone()).collect()),
+            };
+            let display =
+                serde_json::to_string_pretty(&selected).unwrap_or_else(|_| body.to_string());
+            (display, None)
+        }
+        Err(e) => (body.to_string(), Some(e.to_string())),
+    }
 }
 
-impl AppPersistedState {
-    async fn migrate_if_needed() {
-        let old_path = state_dir().join("session.json");
-        // if tabs key exists in old json → split into per-file format → delete old
+// File extension to suggest when saving a text response of this content type
+fn text_extension_for(content_type: &str) -> &'static str {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    if mime.ends_with("/json") || mime.ends_with("+json") {
+        "json"
+    } else if mime == "text/html" || mime == "application/xhtml+xml" {
+        "html"
+    } else if mime.ends_with("/xml") || mime.ends_with("+xml") {
+        "xml"
+    } else {
+        "txt"
     }
 }
 
-fn state_file_path() -> std::path::PathBuf {
-    state_dir().join("session.json")
+// Wraps a value in single quotes for a POSIX shell, `'` becomes `'\''`
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
 }
 
-fn state_dir() -> std::path::PathBuf {
-    let base = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .map(std::path::PathBuf::from)
-        .unwrap_or_else(|_| std::path::PathBuf::from("."));
-    let dir = base.join(".crabipie");
-    std::fs::create_dir_all(&dir).ok();
-    dir
-}
+// Splits a shell command into words, honouring quotes and `\` line continuations
+fn split_shell_words(input: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => {
+                    in_word = true;
+                    current.push(c);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
 
-async fn save_app_state(state: AppPersistedState) {
-    if let Ok(json) = serde_json::to_string_pretty(&state) {
-        tokio::fs::write(state_file_path(), json).await.ok();
+    if in_word {
+        words.push(current);
     }
+    Ok(words)
 }
 
-async fn load_app_state() -> (Option<SessionState>, Vec<TabMetadata>) {
-    let session = SessionState::load().await;
-    let metadata = TabMetadata::load_all().await;
-    (session, metadata)
-}
-fn theme_from_str(s: &str) -> iced::Theme {
-    match s {
-        "Light" => iced::Theme::Light,
-        "Dark" => iced::Theme::Dark,
-        "Dracula" => iced::Theme::Dracula,
-        "Nord" => iced::Theme::Nord,
-        "Solarized Light" => iced::Theme::SolarizedLight,
-        "Solarized Dark" => iced::Theme::SolarizedDark,
-        "Gruvbox Light" => iced::Theme::GruvboxLight,
-        "Gruvbox Dark" => iced::Theme::GruvboxDark,
-        "Catppuccin Latte" => iced::Theme::CatppuccinLatte,
-        "Catppuccin Frappe" => iced::Theme::CatppuccinFrappe,
-        "Catppuccin Macchiato" => iced::Theme::CatppuccinMacchiato,
-        "Catppuccin Mocha" => iced::Theme::CatppuccinMocha,
-        "Tokyo Night" => iced::Theme::TokyoNight,
-        "Tokyo Night Storm" => iced::Theme::TokyoNightStorm,
-        "Tokyo Night Light" => iced::Theme::TokyoNightLight,
-        "Kanagawa Wave" => iced::Theme::KanagawaWave,
-        "Kanagawa Dragon" => iced::Theme::KanagawaDragon,
-        "Kanagawa Lotus" => iced::Theme::KanagawaLotus,
-        "Moonfly" => iced::Theme::Moonfly,
-        "Nightfly" => iced::Theme::Nightfly,
-        "Oxocarbon" => iced::Theme::Oxocarbon,
-        "Ferra" => iced::Theme::Ferra,
-        _ => iced::Theme::CatppuccinMocha, // default fallback
+// Short curl options that take a value, these may also be written as `-XPOST`
+const CURL_VALUE_FLAGS: [&str; 8] = ["-X", "-H", "-d", "-F", "-u", "-A", "-b", "-e"];
+
+fn parse_curl(input: &str) -> Result<SavedState, String> {
+    let mut args = split_shell_words(input)?.into_iter();
+    if args.next().as_deref() != Some("curl") {
+        return Err("Command must start with `curl`".to_string());
     }
-}
 
-fn json_theme_from_str(s: &str) -> json_highlighter::JsonThemeWrapper {
-    match s {
-        "Base 16 Eighties" => {
-            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Eighties)
-        }
-        "Base 16 Mocha" => {
-            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Mocha)
-        }
-        "Base 16 Ocean" => {
-            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::Base16Ocean)
+    let mut state = SavedState::default();
+    state.headers.clear();
+    state.form_data.clear();
+
+    let mut url = None;
+    let mut method = None;
+    let mut data: Vec<String> = Vec::new();
+    let mut head = false;
+
+    while let Some(arg) = args.next() {
+        let (flag, mut attached) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => match arg.get(..2) {
+                Some(short) if arg.len() > 2 && CURL_VALUE_FLAGS.contains(&short) => {
+                    (short.to_string(), Some(arg[2..].to_string()))
+                }
+                _ => (arg.clone(), None),
+            },
+        };
+        let mut value = || {
+            attached
+                .take()
+                .or_else(|| args.next())
+                .ok_or(format!("Missing value for `{}`", flag))
+        };
+
+        match flag.as_str() {
+            "-X" | "--request" => method = Some(value()?),
+            "-H" | "--header" => {
+                let header = value()?;
+                let (key, value) = header
+                    .split_once(':')
+                    .ok_or(format!("Invalid header `{}`", header))?;
+                state.headers.push(RequestHeaders {
+                    key: key.trim().to_string(),
+                    value: value.trim().to_string(),
+                    enabled: true,
+                    missing_colon: false,
+                });
+            }
+            "-A" | "--user-agent" | "-b" | "--cookie" | "-e" | "--referer" => {
+                let key = match flag.as_str() {
+                    "-A" | "--user-agent" => "User-Agent",
+                    "-b" | "--cookie" => "Cookie",
+                    _ => "Referer",
+                };
+                state.headers.push(RequestHeaders {
+                    key: key.to_string(),
+                    value: value()?,
+                    enabled: true,
+                    missing_colon: false,
+                });
+            }
+            "-d" | "--data" | "--data-raw" | "--data-binary" | "--data-ascii"
+            | "--data-urlencode" => data.push(value()?),
+            "-F" | "--form" => {
+                let field = value()?;
+                let (key, value) = field
+                    .split_once('=')
+                    .ok_or(format!("Invalid form field `{}`", field))?;
+                // `name=value;type=application/json` sets the part's Content-Type
+                let (value, content_type) = match value.rsplit_once(";type=") {
+                    Some((value, ct)) => (value, Some(ct.to_string())),
+                    None => (value, None),
+                };
+                let form_field = match value.strip_prefix('@') {
+                    Some(path) => FormField {
+                        enabled: true,
+                        key: key.to_string(),
+                        value: String::new(),
+                        files: vec![path.to_string()],
+                        field_type: FormFieldType::File,
+                        content_type,
+                    },
+                    None => FormField {
+                        enabled: true,
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        files: Vec::new(),
+                        field_type: FormFieldType::Text,
+                        content_type,
+                    },
+                };
+                state.form_data.push(form_field);
+                state.content_type = ContentType::FormData;
+            }
+            "-u" | "--user" => {
+                let credentials = value()?;
+                let (username, password) =
+                    credentials.split_once(':').unwrap_or((&credentials, ""));
+                state.auth_type = AuthType::Basic;
+                state.basic_username = username.to_string();
+                state.basic_password = password.to_string();
+            }
+            "-I" | "--head" => head = true,
+            "--url" => url = Some(value()?),
+            // Options with a value we have no use for
+            "-o" | "--output" | "-m" | "--max-time" | "--connect-timeout" | "-w"
+            | "--write-out" | "-x" | "--proxy" => {
+                value()?;
+            }
+            // Flags such as --compressed, -L, -s, -k
+            f if f.starts_with('-') => {}
+            _ if url.is_none() => url = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument `{}`", arg)),
         }
-        "Solarized Dark" => {
-            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::SolarizedDark)
+    }
+
+    state.url = url.ok_or("No URL found in cURL command")?;
+    state.method = match method {
+        Some(method) => HttpMethod::ALL
+            .into_iter()
+            .find(|m| m.to_string().eq_ignore_ascii_case(&method))
+            .ok_or(format!("Unsupported method `{}`", method))?,
+        None if head => HttpMethod::HEAD,
+        None if !data.is_empty() || state.content_type == ContentType::FormData => HttpMethod::POST,
+        None => HttpMethod::GET,
+    };
+
+    if !data.is_empty() {
+        let body = data.join("&");
+        let content_type = state
+            .headers
+            .iter()
+            .find(|h| h.key.eq_ignore_ascii_case("content-type"))
+            .map(|h| h.value.to_ascii_lowercase());
+        // curl sends -d as x-www-form-urlencoded unless told otherwise
+        let urlencoded = match content_type {
+            Some(ct) => ct.contains("x-www-form-urlencoded"),
+            None => !body.trim_start().starts_with(['{', '[']),
+        };
+
+        if urlencoded {
+            state.content_type = ContentType::XWWWFormUrlEncoded;
+            state.form_data = body
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    let decode = |s: &str| {
+                        urlencoding::decode(&s.replace('+', " "))
+                            .map(|d| d.into_owned())
+                            .unwrap_or_else(|_| s.to_string())
+                    };
+                    FormField {
+                        enabled: true,
+                        key: decode(key),
+                        value: decode(value),
+                        files: Vec::new(),
+                        field_type: FormFieldType::Text,
+                        content_type: None,
+                    }
+                })
+                .collect();
+        } else {
+            state.content_type = ContentType::Json;
+            state.body = body;
         }
-        "Inspired GitHub" => {
-            json_highlighter::JsonThemeWrapper::Builtin(iced::highlighter::Theme::InspiredGitHub)
+    }
+
+    if let Ok(parsed) = url::Url::parse(&state.url) {
+        let params: Vec<QueryParam> = parsed
+            .query_pairs()
+            .map(|(key, value)| QueryParam {
+                key: key.into_owned(),
+                value: value.into_owned(),
+                enabled: true,
+            })
+            .collect();
+        if !params.is_empty() {
+            state.query_params = params;
         }
-        "Default Dark" => json_highlighter::JsonThemeWrapper::Custom(
-            json_highlighter::CustomJsonTheme::DEFAULT_DARK,
-        ),
-        "Default Light" => json_highlighter::JsonThemeWrapper::Custom(
-            json_highlighter::CustomJsonTheme::DEFAULT_LIGHT,
-        ),
-        "VSCode Dark" => json_highlighter::JsonThemeWrapper::Custom(
-            json_highlighter::CustomJsonTheme::VSCODE_DARK,
-        ),
-        _ => json_highlighter::JsonThemeWrapper::Custom(
-            json_highlighter::CustomJsonTheme::VSCODE_DARK,
-        ),
     }
+
+    if state.headers.is_empty() {
+        state.headers.push(RequestHeaders::new());
+    }
+    if state.form_data.is_empty() {
+        state.form_data.push(FormField::new());
+    }
+
+    Ok(state)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Collection {
+// A Postman collection mapped onto collection items, ids are assigned on insert
+#[derive(Debug, Clone)]
+struct PostmanImport {
     name: String,
     items: Vec<CollectionItem>,
+    // Collection variables, Postman's {{name}} placeholders work here unchanged
+    variables: Vec<(String, String)>,
+    // Items that couldn't be mapped, or only in part
+    skipped: Vec<String>,
 }
 
-impl Collection {
-    fn new() -> Self {
-        Self {
-            name: "My Collection".to_string(),
-            items: Vec::new(),
-        }
+fn from_postman(json: &str) -> Result<PostmanImport, String> {
+    let root: serde_json::Value =
+        serde_json::from_str(json).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let info = root
+        .get("info")
+        .ok_or("Not a Postman collection, `info` is missing")?;
+    let schema = info.get("schema").and_then(|s| s.as_str()).unwrap_or("");
+    if !schema.contains("v2.1") {
+        return Err("Only Postman collection v2.1 files are supported".to_string());
     }
+    let name = info
+        .get("name")
+        .and_then(|n| n.as_str())
+        .unwrap_or("Postman import")
+        .to_string();
+
+    let mut skipped = Vec::new();
+    let items = postman_items(root.get("item"), root.get("auth"), "", &mut skipped);
+    let variables = root
+        .get("variable")
+        .and_then(|v| v.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|var| {
+            let key = var.get("key")?.as_str()?;
+            Some((key.to_string(), postman_str(var.get("value"))))
+        })
+        .collect();
+    Ok(PostmanImport {
+        name,
+        items,
+        variables,
+        skipped,
+    })
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-enum CollectionItem {
-    Folder(CollectionFolder),
-    Request(CollectionRequest),
-}
-
-impl CollectionItem {
-    fn id(&self) -> usize {
-        match self {
-            CollectionItem::Folder(f) => f.id,
-            CollectionItem::Request(r) => r.id,
-        }
+// Postman's values are usually strings, but numbers and booleans turn up too
+fn postman_str(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
     }
+}
 
-    fn name(&self) -> &str {
-        match self {
-            CollectionItem::Folder(f) => &f.name,
-            CollectionItem::Request(r) => &r.name,
+// `path` names the enclosing folders, for the skipped report
+fn postman_items(
+    items: Option<&serde_json::Value>,
+    auth: Option<&serde_json::Value>,
+    path: &str,
+    skipped: &mut Vec<String>,
+) -> Vec<CollectionItem> {
+    let mut out = Vec::new();
+    for item in items.and_then(|i| i.as_array()).into_iter().flatten() {
+        let name = item
+            .get("name")
+            .and_then(|n| n.as_str())
+            .unwrap_or("Untitled")
+            .to_string();
+        let label = if path.is_empty() {
+            name.clone()
+        } else {
+            format!("{} / {}", path, name)
+        };
+        // Folders and requests without auth of their own use the parent's
+        let auth = item.get("auth").or(auth);
+        if item.get("item").is_some() {
+            let children = postman_items(item.get("item"), auth, &label, skipped);
+            out.push(CollectionItem::Folder(CollectionFolder {
+                id: 0,
+                name,
+                expanded: false,
+                children,
+            }));
+        } else if let Some(request) = item.get("request") {
+            let auth = request.get("auth").or(auth);
+            match postman_request(&name, &label, request, auth, skipped) {
+                Ok(saved_state) => out.push(CollectionItem::Request(CollectionRequest {
+                    id: 0,
+                    name,
+                    method: saved_state.method,
+                    saved_state,
+                })),
+                Err(err) => skipped.push(format!("{}: {}", label, err)),
+            }
+        } else {
+            skipped.push(format!("{}: no request", label));
         }
     }
+    out
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CookieEntry {
-    pub name: String,
-    pub value: String,
-    pub enabled: bool,
-    pub domain: String,
-    pub path: String,
-    pub expires: Option<String>,
-}
+fn postman_request(
+    name: &str,
+    label: &str,
+    request: &serde_json::Value,
+    auth: Option<&serde_json::Value>,
+    skipped: &mut Vec<String>,
+) -> Result<SavedState, String> {
+    let mut state = SavedState::default();
+    state.headers.clear();
+    state.form_data.clear();
+    state.title = name.to_string();
+    state.name = name.to_string();
+
+    let method = request
+        .get("method")
+        .and_then(|m| m.as_str())
+        .unwrap_or("GET");
+    state.method = HttpMethod::ALL
+        .into_iter()
+        .find(|m| m.to_string().eq_ignore_ascii_case(method))
+        .ok_or(format!("unsupported method `{}`", method))?;
+    // A request may be given as just its URL
+    let url = if request.is_string() {
+        Some(request)
+    } else {
+        request.get("url")
+    };
+    state.url = match url {
+        Some(serde_json::Value::String(raw)) => raw.clone(),
+        _ => url
+            .and_then(|u| u.get("raw"))
+            .and_then(|raw| raw.as_str())
+            .ok_or("no URL")?
+            .to_string(),
+    };
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct CollectionFolder {
-    id: usize,
-    name: String,
-    expanded: bool,
-    children: Vec<CollectionItem>, // enables infinite nesting
-}
+    let enabled = |entry: &serde_json::Value| {
+        !entry
+            .get("disabled")
+            .and_then(|d| d.as_bool())
+            .unwrap_or(false)
+    };
+    let params: Vec<QueryParam> = match url.and_then(|u| u.get("query")).and_then(|q| q.as_array())
+    {
+        Some(query) => query
+            .iter()
+            .map(|p| QueryParam {
+                key: postman_str(p.get("key")),
+                value: postman_str(p.get("value")),
+                enabled: enabled(p),
+            })
+            .collect(),
+        // Placeholders like {{baseUrl}} keep the URL from parsing, so split it by hand
+        None => state
+            .url
+            .split_once('?')
+            .map(|(_, query)| query)
+            .unwrap_or("")
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                QueryParam {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    enabled: true,
+                }
+            })
+            .collect(),
+    };
+    if !params.is_empty() {
+        state.query_params = params;
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct CollectionRequest {
-    id: usize,
-    name: String,
-    method: HttpMethod,
-    saved_state: SavedState,
-}
+    state.headers = request
+        .get("header")
+        .and_then(|h| h.as_array())
+        .into_iter()
+        .flatten()
+        .map(|h| RequestHeaders {
+            key: postman_str(h.get("key")),
+            value: postman_str(h.get("value")),
+            enabled: enabled(h),
+            missing_colon: false,
+        })
+        .collect();
+    state.description = match request.get("description") {
+        Some(serde_json::Value::Object(d)) => postman_str(d.get("content")),
+        other => postman_str(other),
+    };
 
-fn collection_file_path() -> std::path::PathBuf {
-    let base = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .map(std::path::PathBuf::from)
-        .unwrap_or_else(|_| std::path::PathBuf::from("."));
-    let dir = base.join(".crabipie");
-    std::fs::create_dir_all(&dir).ok();
-    dir.join("collection.json")
-}
+    if let Some(body) = request.get("body") {
+        let fields = |mode: &str| -> Vec<FormField> {
+            body.get(mode)
+                .and_then(|f| f.as_array())
+                .into_iter()
+                .flatten()
+                .map(|f| {
+                    let is_file = f.get("type").and_then(|t| t.as_str()) == Some("file");
+                    let files = match f.get("src") {
+                        Some(serde_json::Value::Array(srcs)) => {
+                            srcs.iter().map(|s| postman_str(Some(s))).collect()
+                        }
+                        Some(serde_json::Value::String(src)) => vec![src.clone()],
+                        _ => Vec::new(),
+                    };
+                    FormField {
+                        enabled: enabled(f),
+                        key: postman_str(f.get("key")),
+                        value: if is_file {
+                            String::new()
+                        } else {
+                            postman_str(f.get("value"))
+                        },
+                        files: if is_file { files } else { Vec::new() },
+                        field_type: if is_file {
+                            FormFieldType::File
+                        } else {
+                            FormFieldType::Text
+                        },
+                        content_type: f
+                            .get("contentType")
+                            .and_then(|ct| ct.as_str())
+                            .map(str::to_string),
+                    }
+                })
+                .collect()
+        };
+        match body.get("mode").and_then(|m| m.as_str()) {
+            Some("raw") => {
+                state.content_type = ContentType::Json;
+                state.body = postman_str(body.get("raw"));
+            }
+            Some("urlencoded") => {
+                state.content_type = ContentType::XWWWFormUrlEncoded;
+                state.form_data = fields("urlencoded");
+            }
+            Some("formdata") => {
+                state.content_type = ContentType::FormData;
+                state.form_data = fields("formdata");
+            }
+            Some("graphql") => {
+                let graphql = body.get("graphql");
+                state.request_type = RequestType::GraphQL;
+                state.graphql_query = postman_str(graphql.and_then(|g| g.get("query")));
+                state.graphql_variables = postman_str(graphql.and_then(|g| g.get("variables")));
+            }
+            Some("file") => {
+                state.content_type = ContentType::Binary;
+                state.binary_body_path = postman_str(body.get("file").and_then(|f| f.get("src")));
+            }
+            Some("none") | None => {}
+            Some(other) => skipped.push(format!(
+                "{}: body mode `{}` isn't supported, imported without a body",
+                label, other
+            )),
+        }
+    }
 
-async fn save_collection(collection: Collection) {
-    if let Ok(json) = serde_json::to_string_pretty(&collection) {
-        tokio::fs::write(collection_file_path(), json).await.ok();
+    if let Some(auth) = auth
+        && let Err(err) = postman_auth(auth, &mut state)
+    {
+        skipped.push(format!("{}: {}, imported without auth", label, err));
     }
-}
 
-async fn load_collection() -> Option<Collection> {
-    let bytes = tokio::fs::read(collection_file_path()).await.ok()?;
-    serde_json::from_slice(&bytes).ok()
+    if state.headers.is_empty() {
+        state.headers.push(RequestHeaders::new());
+    }
+    if state.form_data.is_empty() {
+        state.form_data.push(FormField::new());
+    }
+    Ok(state)
 }
 
-fn extract_domain(url: &str) -> Option<String> {
-    url::Url::parse(url)
-        .ok()
-        .and_then(|u| u.host_str().map(|h| h.to_string()))
+// Postman keeps auth settings as key/value lists under the auth type's name
+fn postman_auth(auth: &serde_json::Value, state: &mut SavedState) -> Result<(), String> {
+    let kind = auth
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("noauth");
+    let param = |key: &str| {
+        auth.get(kind)
+            .and_then(|params| params.as_array())
+            .and_then(|params| {
+                params
+                    .iter()
+                    .find(|p| p.get("key").and_then(|k| k.as_str()) == Some(key))
+            })
+            .map(|p| postman_str(p.get("value")))
+            .unwrap_or_default()
+    };
+    match kind {
+        "noauth" => state.auth_type = AuthType::None,
+        "bearer" => {
+            state.auth_type = AuthType::Bearer;
+            state.bearer_token = param("token");
+        }
+        "basic" => {
+            state.auth_type = AuthType::Basic;
+            state.basic_username = param("username");
+            state.basic_password = param("password");
+        }
+        "apikey" => {
+            state.auth_type = AuthType::ApiKey;
+            state.api_key_name = param("key");
+            state.api_key = param("value");
+            state.api_key_position = if param("in") == "query" {
+                ApiKeyPosition::QueryParams
+            } else {
+                ApiKeyPosition::Header
+            };
+        }
+        // The client secret isn't saved with requests, it's asked for on the Auth tab
+        "oauth2" if param("grant_type") == "client_credentials" => {
+            state.auth_type = AuthType::OAuth2ClientCredentials;
+            state.oauth_token_url = param("accessTokenUrl");
+            state.oauth_client_id = param("clientId");
+            state.oauth_scope = param("scope");
+        }
+        "oauth2" => return Err("only the client credentials OAuth2 grant is supported".into()),
+        other => return Err(format!("auth type `{}` isn't supported", other)),
+    }
+    Ok(())
 }
 
 fn parse_set_cookie(raw: &str) -> Option<CookieEntry> {
@@ -6023,18 +16581,30 @@ fn parse_set_cookie(raw: &str) -> Option<CookieEntry> {
     let mut path = "/".to_string();
     let mut domain = String::new();
     let mut expires = None;
+    let mut max_age = None;
 
     for part in parts {
-        let p = part.trim();
-        if let Some(v) = p.strip_prefix("path=") {
-            path = v.to_string();
-        } else if let Some(v) = p.strip_prefix("domain=") {
-            domain = v.to_string();
-        } else if let Some(v) = p.strip_prefix("expires=") {
-            expires = Some(v.to_string());
+        // Attribute names are case-insensitive, servers usually send `Path=` and `Max-Age=`
+        let Some((attr, v)) = part.trim().split_once('=') else {
+            continue;
+        };
+        let v = v.trim();
+        match attr.trim().to_ascii_lowercase().as_str() {
+            "path" => path = v.to_string(),
+            "domain" => domain = v.trim_start_matches('.').to_ascii_lowercase(),
+            "expires" => expires = Some(v.to_string()),
+            "max-age" => max_age = v.parse::<i64>().ok(),
+            _ => {}
         }
     }
 
+    // Max-Age wins over Expires, store it as an absolute date like Expires.
+    // Capped at 400 days the way browsers do, which also keeps the math in range.
+    if let Some(secs) = max_age {
+        let at = chrono::Utc::now() + chrono::Duration::seconds(secs.clamp(-1, 400 * 24 * 3600));
+        expires = Some(at.to_rfc2822());
+    }
+
     Some(CookieEntry {
         name: name.trim().to_string(),
         value: value.trim().to_string(),
@@ -6172,6 +16742,183 @@ fn parse_graphql_schema(json: &str) -> Result<GraphqlSchema, String> {
     Ok(schema)
 }
 
+// Path of an object member. Keys that aren't plain names, such as ones with a
+// dot or a space, go in brackets so the path still works as a filter
+fn json_child_path(path: &str, key: &str) -> String {
+    let plain = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        format!("{path}.{key}")
+    } else {
+        format!(
+            "{path}['{}']",
+            key.replace('\\', "\\\\").replace('\'', "\\'")
+        )
+    }
+}
+
+// One row per visible node of the response Tree tab. Paths follow JSONPath, so an
+// open node can be pasted straight into the response filter
+fn json_tree_rows<'a>(
+    value: &'a serde_json::Value,
+    path: &str,
+    key: Option<String>,
+    depth: u16,
+    expanded: &std::collections::HashSet<String>,
+    images: &std::collections::HashMap<String, iced::widget::image::Handle>,
+    rows: &mut Vec<Element<'a, Message>>,
+) {
+    if rows.len() >= JSON_TREE_MAX_ROWS {
+        return;
+    }
+    let indent = Space::new().width(depth as f32 * 16.0);
+    let key_label = key.map(|k| text(format!("{k}:")).size(13).font(iced::Font::MONOSPACE));
+    let gray = |_: &iced::Theme| text::Style {
+        color: Some(iced::Color::from_rgb(0.5, 0.5, 0.5)),
+    };
+
+    let children: Vec<(String, String, &serde_json::Value)> = match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (json_child_path(path, k), k.clone(), v))
+            .collect(),
+        serde_json::Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("{path}[{i}]"), i.to_string(), v))
+            .collect(),
+        leaf => {
+            let mut r = row![indent, Space::new().width(20.0)].spacing(4);
+            if let Some(label) = key_label {
+                r = r.push(label);
+            }
+            if let Some(handle) = images.get(path) {
+                let thumbnail = iced::widget::image(handle.clone()).height(JSON_IMAGE_THUMB_HEIGHT);
+                rows.push(r.push(thumbnail).into());
+                return;
+            }
+            let value_text = text(leaf.to_string())
+                .size(13)
+                .font(iced::Font::MONOSPACE)
+                .style(move |theme: &iced::Theme| text::Style {
+                    color: Some(match leaf {
+                        serde_json::Value::String(_) => theme.extended_palette().success.base.color,
+                        serde_json::Value::Number(_) => theme.extended_palette().primary.base.color,
+                        _ => theme.extended_palette().danger.base.color,
+                    }),
+                });
+            rows.push(r.push(value_text).align_y(Alignment::Center).into());
+            return;
+        }
+    };
+
+    let is_expanded = expanded.contains(path);
+    let summary = match value {
+        serde_json::Value::Object(_) => format!("{{{}}}", children.len()),
+        _ => format!("[{}]", children.len()),
+    };
+    let toggle: Element<'a, Message> = if children.is_empty() {
+        Space::new().width(20.0).into()
+    } else {
+        container(
+            button(text(if is_expanded { "▼" } else { "▶" }).size(10))
+                .style(button::text)
+                .padding(0)
+                .on_press(Message::ToggleJsonNode(path.to_string())),
+        )
+        .width(20.0)
+        .center_x(20.0)
+        .into()
+    };
+    let mut r = row![indent, toggle].spacing(4);
+    if let Some(label) = key_label {
+        r = r.push(label);
+    }
+    rows.push(
+        r.push(
+            text(summary)
+                .size(13)
+                .font(iced::Font::MONOSPACE)
+                .style(gray),
+        )
+        .align_y(Alignment::Center)
+        .into(),
+    );
+
+    if is_expanded {
+        for (child_path, child_key, child) in children {
+            json_tree_rows(
+                child,
+                &child_path,
+                Some(child_key),
+                depth + 1,
+                expanded,
+                images,
+                rows,
+            );
+        }
+    }
+}
+
+// Strings holding an image, as a data URL or bare base64, keyed by tree path.
+// Capped so a media-heavy response doesn't decode hundreds of images
+fn json_image_handles(
+    value: &serde_json::Value,
+) -> std::collections::HashMap<String, iced::widget::image::Handle> {
+    fn walk(
+        value: &serde_json::Value,
+        path: String,
+        found: &mut std::collections::HashMap<String, iced::widget::image::Handle>,
+    ) {
+        if found.len() >= JSON_IMAGE_MAX_COUNT {
+            return;
+        }
+        match value {
+            serde_json::Value::Object(map) => {
+                for (k, v) in map {
+                    walk(v, json_child_path(&path, k), found);
+                }
+            }
+            serde_json::Value::Array(items) => {
+                for (i, v) in items.iter().enumerate() {
+                    walk(v, format!("{path}[{i}]"), found);
+                }
+            }
+            serde_json::Value::String(s) => {
+                if let Some(bytes) = decode_image_string(s) {
+                    found.insert(path, iced::widget::image::Handle::from_bytes(bytes));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut found = std::collections::HashMap::new();
+    walk(value, "$".to_string(), &mut found);
+    found
+}
+
+// The bytes of a string that decodes to an image the preview understands
+fn decode_image_string(s: &str) -> Option<Vec<u8>> {
+    let data = match s.strip_prefix("data:image/") {
+        Some(rest) => rest.split_once(";base64,")?.1,
+        // Short strings are ids and hashes far more often than images
+        None if s.len() >= JSON_IMAGE_MIN_BASE64 => s,
+        None => return None,
+    };
+    if data.len() > JSON_IMAGE_MAX_BYTES / 3 * 4 + 4 {
+        return None;
+    }
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .ok()?;
+    image_dimensions(&bytes)?;
+    Some(bytes)
+}
+
 fn render_schema_tree<'a>(
     types: &'a [GraphqlType],
     current_type: &'a GraphqlType,
@@ -6451,6 +17198,34 @@ fn proximity_order(active: usize, len: usize) -> Vec<usize> {
     indices
 }
 
+// Writes to the clipboard and clears the tab's copied checkmark after a moment
+fn copy_with_feedback(text: String) -> iced::Task<Message> {
+    iced::Task::batch([
+        iced::clipboard::write(text),
+        iced::Task::perform(
+            async {
+                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+            },
+            |_| Message::ResetCopied,
+        ),
+    ])
+}
+
+// Quiet time after the last keystroke before the JSON body is parsed
+const BODY_LINT_DELAY_MS: u64 = 400;
+// Same for the proxy, redirect and certificate password settings
+const CLIENT_REBUILD_DELAY_MS: u64 = 500;
+
+// Bodies larger than this ask for confirmation before being put in the editor
+const BODY_FILE_WARN_SIZE: u64 = 5 * 1024 * 1024;
+
+async fn read_body_file(path: String) -> Result<String, String> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    String::from_utf8(bytes).map_err(|e| format!("Invalid UTF-8 in file: {}", e))
+}
+
 const BODY_DEFAULT: &str = r#"{
   "title": "foo",
   "body": "bar",
@@ -6458,6 +17233,70 @@ const BODY_DEFAULT: &str = r#"{
   "foo": "bar"
 }"#;
 
+// Starter bodies for the "Insert template" list, as (name, body). The list
+// shown follows the request mode, see body_templates
+const JSON_BODY_TEMPLATES: &[(&str, &str)] = &[
+    ("Empty object", "{}"),
+    ("Empty array", "[]"),
+    ("Example object", BODY_DEFAULT),
+    (
+        "JSON-RPC call",
+        r#"{
+  "jsonrpc": "2.0",
+  "method": "",
+  "params": {},
+  "id": 1
+}"#,
+    ),
+];
+
+const FORM_BODY_TEMPLATES: &[(&str, &str)] = &[
+    ("Login", "username: \npassword: "),
+    (
+        "OAuth2 token",
+        "grant_type: client_credentials\nclient_id: \nclient_secret: \nscope: ",
+    ),
+    ("Contact", "name: \nemail: \nmessage: "),
+];
+
+const GRAPHQL_QUERY_TEMPLATES: &[(&str, &str)] = &[
+    (
+        "Query",
+        r#"query Name {
+  field {
+    id
+  }
+}"#,
+    ),
+    (
+        "Query with variables",
+        r#"query Name($id: ID!) {
+  node(id: $id) {
+    id
+  }
+}"#,
+    ),
+    (
+        "Mutation",
+        r#"mutation Name($input: Input!) {
+  action(input: $input) {
+    id
+  }
+}"#,
+    ),
+];
+
+fn body_templates(tab: &TabState) -> &'static [(&'static str, &'static str)] {
+    if tab.request_type == RequestType::GraphQL {
+        return GRAPHQL_QUERY_TEMPLATES;
+    }
+    match tab.content_type {
+        ContentType::Json => JSON_BODY_TEMPLATES,
+        ContentType::FormData | ContentType::XWWWFormUrlEncoded => FORM_BODY_TEMPLATES,
+        ContentType::Binary => &[],
+    }
+}
+
 const RAW_FORM_PLACEHOLDER: &str = r#"Rows are separated by newline.
 Keys and values are separated by :
 Prepend # to the rows that you want to add but keep it disabled.
@@ -6494,3 +17333,175 @@ const INTROSPECTION_QUERY: &str = r#"
   }
 }
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(params: &[QueryParam]) -> Vec<(&str, &str)> {
+        params
+            .iter()
+            .map(|p| (p.key.as_str(), p.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn query_round_trip_keeps_encoded_values_and_repeated_keys() {
+        let url = "https://example.com/search?q=a%26b&q=c+d&tag=%2B1&a=b=c&flag#top";
+        let params = parse_query(url);
+        // Only the first '=' splits, the rest belong to the value
+        assert_eq!(
+            pairs(&params),
+            [
+                ("q", "a&b"),
+                ("q", "c d"),
+                ("tag", "+1"),
+                ("a", "b=c"),
+                ("flag", "")
+            ]
+        );
+
+        let query = CrabiPie::build_query_string(&params);
+        assert_eq!(query, "?q=a%26b&q=c%20d&tag=%2B1&a=b%3Dc&flag");
+        let rebuilt = format!("https://example.com/search{query}");
+        assert_eq!(pairs(&parse_query(&rebuilt)), pairs(&params));
+    }
+
+    #[test]
+    fn prettify_and_minify_keep_key_order() {
+        let body = r#"{"zeta": 1, "alpha": {"y": true, "b": null}, "mid": [3, 2]}"#;
+        let minified = reformat_json(body, false).unwrap();
+        assert_eq!(
+            minified,
+            r#"{"zeta":1,"alpha":{"y":true,"b":null},"mid":[3,2]}"#
+        );
+
+        let pretty = reformat_json(&minified, true).unwrap();
+        let keys: Vec<&str> = pretty
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix('"')?.split('"').next())
+            .collect();
+        assert_eq!(keys, ["zeta", "alpha", "y", "b", "mid"]);
+        assert_eq!(reformat_json(&pretty, false).unwrap(), minified);
+    }
+
+    #[test]
+    fn reformat_json_reports_invalid_input() {
+        assert!(reformat_json("{\"a\": }", true).is_err());
+    }
+
+    #[test]
+    fn merge_query_keeps_disabled_rows_in_place() {
+        let mut rows = parse_query("http://localhost/?a=1&off=x&b=2&c=3");
+        rows[1].enabled = false;
+        let merged = merge_query(rows, "http://localhost/?a=10&b=2&d=4");
+        assert_eq!(
+            pairs(&merged),
+            [("a", "10"), ("off", "x"), ("b", "2"), ("d", "4")]
+        );
+        assert!(!merged[1].enabled);
+    }
+
+    #[test]
+    fn json_paths_bracket_keys_that_arent_plain_names() {
+        assert_eq!(json_child_path("$", "name"), "$.name");
+        assert_eq!(json_child_path("$.a", "first.last"), "$.a['first.last']");
+        assert_eq!(json_child_path("$", "two words"), "$['two words']");
+        assert_eq!(json_child_path("$", "it's"), r"$['it\'s']");
+        assert_eq!(json_child_path("$", "1st"), "$['1st']");
+
+        let json: serde_json::Value = serde_json::json!({"a.b": {"c d": 1}});
+        let path = json_child_path(&json_child_path("$", "a.b"), "c d");
+        let (filtered, error) = filter_json_response(&json.to_string(), &path);
+        assert_eq!(error, None);
+        assert_eq!(filtered.trim(), "1");
+    }
+
+    #[test]
+    fn query_round_trip_leaves_out_disabled_rows() {
+        let mut params = parse_query("http://localhost/?a=1&b=2");
+        params[0].enabled = false;
+        assert_eq!(CrabiPie::build_query_string(&params), "?b=2");
+    }
+
+    fn sse_summary(events: &[SseEvent]) -> Vec<(&str, &str, Option<&str>)> {
+        events
+            .iter()
+            .map(|e| (e.event.as_str(), e.data.as_str(), e.id.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn sse_parser_joins_lines_split_across_chunks() {
+        let mut parser = SseParser::default();
+        let mut events = parser.feed(": keep-alive\r\nevent: tick\r\nid: 7\r\nda");
+        assert!(events.is_empty());
+        events.extend(parser.feed("ta: one\r\ndata:two\r\n\r\n"));
+        // No data, so nothing is emitted for this one
+        events.extend(parser.feed("event: empty\n\n"));
+        events.extend(parser.feed("data\n\ndata: last\n"));
+        assert_eq!(
+            sse_summary(&events),
+            vec![("tick", "one\ntwo", Some("7")), ("", "", None)]
+        );
+        // The last event only ends at its blank line
+        assert_eq!(sse_summary(&parser.feed("\n")), vec![("", "last", None)]);
+    }
+
+    #[test]
+    fn sse_stream_keeps_going_past_an_invalid_byte() {
+        let mut decoder = http::CharsetDecoder::for_content_type("text/event-stream");
+        let mut parser = SseParser::default();
+        let mut events = Vec::new();
+        // The second chunk starts in the middle of "é"
+        for chunk in [&b"data: a\xffb\n\ndata: caf\xc3"[..], &b"\xa9\n\n"[..]] {
+            events.extend(parser.feed(&decoder.decode(chunk, false)));
+        }
+        assert_eq!(
+            sse_summary(&events),
+            vec![("", "a\u{fffd}b", None), ("", "café", None)]
+        );
+    }
+
+    #[test]
+    fn raw_header_toggle_counts_lines_like_raw_to_headers() {
+        let raw = "Accept: */*\n\n# a comment\n#X-Off: 1\nBroken line\n   \nX-On: 2";
+        let rows = TabState::raw_to_headers(raw);
+        let keys: Vec<_> = rows.iter().map(|h| h.key.as_str()).collect();
+        assert_eq!(keys, ["Accept", "X-Off", "Broken line", "X-On"]);
+
+        for (n, row) in rows.iter().enumerate() {
+            let toggled = TabState::raw_to_headers(&toggle_raw_header_line(raw, n));
+            if row.missing_colon {
+                assert_eq!(toggle_raw_header_line(raw, n), raw);
+                continue;
+            }
+            // Only the nth row flips, every other row is left as it was
+            assert_eq!(toggled.len(), rows.len());
+            for (i, (before, after)) in rows.iter().zip(&toggled).enumerate() {
+                assert_eq!(before.key, after.key);
+                assert_eq!(
+                    before.enabled != after.enabled,
+                    i == n,
+                    "row {i} after toggling {n}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn durations_take_the_unit_of_the_rounded_value() {
+        let format =
+            |micros: u64| CrabiPie::format_duration(std::time::Duration::from_micros(micros));
+        assert_eq!(format(999), "999µs");
+        assert_eq!(format(1_000), "1.0ms");
+        assert_eq!(format(9_960), "10ms");
+        assert_eq!(format(230_000), "230ms");
+        assert_eq!(format(999_600), "1.00s");
+        assert_eq!(format(3_250_000), "3.25s");
+        assert_eq!(format(9_996_000), "10.0s");
+        assert_eq!(format(59_970_000), "1m 00s");
+        assert_eq!(format(125_000_000), "2m 05s");
+        assert_eq!(format(3_599_600_000), "1h 00m");
+    }
+}